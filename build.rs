@@ -0,0 +1,39 @@
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 在编译时记录 git 提交哈希和构建时间，供 /version 接口使用
+fn main() {
+    // 允许在没有 .git 目录的环境（如 Docker 构建）中通过环境变量传入
+    let git_commit = env::var("GIT_COMMIT_HASH").ok().or_else(git_commit_hash);
+    println!(
+        "cargo:rustc-env=GIT_COMMIT_HASH={}",
+        git_commit.unwrap_or_else(|| "unknown".to_string())
+    );
+
+    let build_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp);
+
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
+
+fn git_commit_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if hash.is_empty() {
+        None
+    } else {
+        Some(hash)
+    }
+}
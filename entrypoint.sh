@@ -10,8 +10,9 @@ mkdir -p /var/lib/mongodb
 SESSION_KEY=$(openssl rand -hex 32)
 
 # 设置默认值，如果环境变量未设置则使用默认值
+# 未设置 ADMIN_PASS 时由程序在首次创建管理员时生成随机密码并输出到日志
 ADMIN_USER="${ADMIN_USER:-admin}"
-ADMIN_PASS="${ADMIN_PASS:-password123}"
+ADMIN_PASS="${ADMIN_PASS:-}"
 
 # 创建 .env 文件
 cat > /app/.env << EOF
@@ -0,0 +1,260 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{REFERER, USER_AGENT};
+use actix_web::web::Bytes;
+use actix_web::Error;
+use crate::client_ip::client_ip;
+use std::env;
+use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+// Access log output format, selected via ACCESS_LOG_FORMAT
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessLogFormat {
+    Combined, // Apache/nginx combined style line
+    Json,     // One JSON object per request (structured fields)
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessLogConfig {
+    pub format: AccessLogFormat,
+    pub sample_rate: f64,              // 0.0 - 1.0, fraction of requests to log
+    pub exclude_prefixes: Vec<String>, // Path prefixes never logged (static files, health checks)
+}
+
+impl AccessLogConfig {
+    // 从环境变量读取配置:
+    // ACCESS_LOG_FORMAT=combined|json (默认 combined)
+    // ACCESS_LOG_SAMPLE_RATE=0.0-1.0 (默认 1.0)
+    // ACCESS_LOG_EXCLUDE=/static/,/health (逗号分隔的路径前缀)
+    pub fn from_env() -> Self {
+        let format = match env::var("ACCESS_LOG_FORMAT")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "json" => AccessLogFormat::Json,
+            _ => AccessLogFormat::Combined,
+        };
+
+        let sample_rate = env::var("ACCESS_LOG_SAMPLE_RATE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .map(|v| v.clamp(0.0, 1.0))
+            .unwrap_or(1.0);
+
+        let exclude_prefixes = env::var("ACCESS_LOG_EXCLUDE")
+            .unwrap_or_else(|_| "/static/,/health".to_string())
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        AccessLogConfig {
+            format,
+            sample_rate,
+            exclude_prefixes,
+        }
+    }
+
+    fn should_log(&self, path: &str) -> bool {
+        if self.exclude_prefixes.iter().any(|p| path.starts_with(p.as_str())) {
+            return false;
+        }
+        self.sample_rate >= 1.0 || rand::random::<f64>() < self.sample_rate
+    }
+}
+
+// Access log middleware
+pub struct AccessLog {
+    config: Rc<AccessLogConfig>,
+}
+
+impl AccessLog {
+    pub fn new(config: AccessLogConfig) -> Self {
+        AccessLog {
+            config: Rc::new(config),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<LoggedBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AccessLogService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogService {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct AccessLogService<S> {
+    service: Rc<S>,
+    config: Rc<AccessLogConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<LoggedBody<B>>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let entry = if self.config.should_log(req.path()) {
+            let headers = req.headers();
+            Some(AccessLogEntry {
+                format: self.config.format,
+                start: Instant::now(),
+                client_ip: client_ip(req.request()),
+                method: req.method().to_string(),
+                path: match req.query_string() {
+                    "" => req.path().to_string(),
+                    query => format!("{}?{}", req.path(), query),
+                },
+                version: format!("{:?}", req.version()),
+                referer: header_str(headers.get(REFERER)),
+                user_agent: header_str(headers.get(USER_AGENT)),
+                status: 0,
+                bytes: 0,
+            })
+        } else {
+            None
+        };
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            // 日志在响应体发送完毕(或被丢弃)时输出，耗时包含处理函数和响应体生成
+            Ok(res.map_body(|head, body| {
+                let entry = entry.map(|mut entry| {
+                    entry.status = head.status.as_u16();
+                    entry
+                });
+                LoggedBody {
+                    body: Box::pin(body),
+                    entry,
+                }
+            }))
+        })
+    }
+}
+
+fn header_str(value: Option<&actix_web::http::header::HeaderValue>) -> String {
+    value
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-")
+        .to_string()
+}
+
+struct AccessLogEntry {
+    format: AccessLogFormat,
+    start: Instant,
+    client_ip: String,
+    method: String,
+    path: String,
+    version: String,
+    referer: String,
+    user_agent: String,
+    status: u16,
+    bytes: u64,
+}
+
+impl AccessLogEntry {
+    fn emit(&self) {
+        let duration_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+
+        match self.format {
+            AccessLogFormat::Json => {
+                tracing::info!(
+                    target: "access_log",
+                    client_ip = %self.client_ip,
+                    method = %self.method,
+                    path = %self.path,
+                    version = %self.version,
+                    status = self.status,
+                    bytes = self.bytes,
+                    duration_ms = duration_ms,
+                    referer = %self.referer,
+                    user_agent = %self.user_agent,
+                );
+            }
+            AccessLogFormat::Combined => {
+                tracing::info!(
+                    target: "access_log",
+                    "{} - - [{}] \"{} {} {}\" {} {} \"{}\" \"{}\" {:.3}ms",
+                    self.client_ip,
+                    chrono::Local::now().format("%d/%b/%Y:%H:%M:%S %z"),
+                    self.method,
+                    self.path,
+                    self.version,
+                    self.status,
+                    self.bytes,
+                    self.referer,
+                    self.user_agent,
+                    duration_ms
+                );
+            }
+        }
+    }
+}
+
+// Response body wrapper that counts bytes and emits the log entry once the body is done
+pub struct LoggedBody<B> {
+    body: Pin<Box<B>>,
+    entry: Option<AccessLogEntry>,
+}
+
+impl<B> Drop for LoggedBody<B> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            entry.emit();
+        }
+    }
+}
+
+impl<B: MessageBody> MessageBody for LoggedBody<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        match this.body.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(entry) = this.entry.as_mut() {
+                    entry.bytes += chunk.len() as u64;
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            other => other,
+        }
+    }
+}
@@ -1,29 +1,70 @@
 use actix_session::Session;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use futures::stream::TryStreamExt;
 use mongodb::{
-    bson::doc,
+    bson::{doc, Document},
     options::{FindOneOptions, FindOptions},
     Database,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::cache;
+use crate::collect_handlers::{RestartError, ResumeError};
 use crate::index_manager::{IndexManager, CollectionIndexInfo, SingleIndexInfo};
-use crate::models::{Binding, Collection, Config, Type, Vod};
+use crate::logging;
+use crate::models::{
+    exclude_deleted_vods, parse_actor_list, parse_year_num, AuditLogEntry, Binding, BrokenReport, Collection, CollectionRun, Config, Type,
+    Vod, MAX_COLLECT_CONCURRENCY, MAX_COLLECT_TIMEOUT_SECS, MIN_COLLECT_CONCURRENCY,
+    MIN_COLLECT_TIMEOUT_SECS,
+};
+use crate::pagination::{build_pagination, pagination_window};
+use crate::resolve::resolve_object_id;
 use crate::scheduled_task::ScheduledTaskManager;
+use crate::site_data::SiteDataManager;
+use crate::vod_export;
+use crate::vod_import;
 
 // Helper function to check if user is authenticated
 fn check_auth(session: &Session) -> Result<(), HttpResponse> {
-    match session.get::<String>("user_id") {
-        Ok(Some(_)) => Ok(()),
-        _ => Err(HttpResponse::Unauthorized().json(json!({
+    match crate::auth::session_user_id(session) {
+        Some(_) => Ok(()),
+        None => Err(HttpResponse::Unauthorized().json(json!({
             "error": "Unauthorized",
             "message": "Please login to access this resource"
         }))),
     }
 }
 
+// 修改会原样输出到前台的配置（自定义 HTML）需要管理员组（group_id = 1）账号
+async fn check_admin_group(db: &Database, session: &Session) -> Result<(), HttpResponse> {
+    check_auth(session)?;
+    let user_id = crate::auth::session_user_id(session)
+        .and_then(|id| mongodb::bson::oid::ObjectId::parse_str(id).ok());
+    let is_admin = match user_id {
+        Some(user_id) => db
+            .collection::<crate::models::User>("users")
+            .find_one(doc! {"_id": user_id}, None)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|user| user.group_id == 1),
+        None => false,
+    };
+    if is_admin {
+        Ok(())
+    } else {
+        Err(HttpResponse::Forbidden().json(json!({
+            "success": false,
+            "message": "只有管理员组账号可以修改自定义 HTML 配置"
+        })))
+    }
+}
+
+fn is_custom_html_key(config_key: &str) -> bool {
+    crate::site_data::CUSTOM_HTML_CONFIG_KEYS.contains(&config_key)
+}
+
 // --- DTOs for Admin API ---
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TypeRequest {
@@ -38,6 +79,7 @@ pub struct TypeRequest {
     pub type_title: Option<String>,
     pub subarea: Option<String>,
     pub subyear: Option<String>,
+    pub is_adult: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +89,41 @@ pub struct BindingRequest {
     pub local_type_id: i32,
 }
 
+// 校验通过的绑定：source_flag / external_id 已去掉首尾空白，id 为 "{source_flag}_{external_id}"
+struct ValidBinding<'a> {
+    id: String,
+    source_flag: &'a str,
+    external_id: &'a str,
+}
+
+// 单条和批量绑定共用的校验。local_type_name 为 local_type_id 对应的分类名称，分类不存在时为 None
+fn validate_binding_request<'a>(req: &'a BindingRequest, local_type_name: Option<&str>) -> Result<ValidBinding<'a>, String> {
+    let source_flag = req.source_flag.trim();
+    let external_id = req.external_id.trim();
+    if source_flag.is_empty() || external_id.is_empty() {
+        return Err("source_flag 和 external_id 不能为空".to_string());
+    }
+    if local_type_name.is_none() {
+        return Err(format!("分类 {} 不存在", req.local_type_id));
+    }
+    Ok(ValidBinding {
+        id: format!("{}_{}", source_flag, external_id),
+        source_flag,
+        external_id,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteTypeQuery {
+    pub force: Option<bool>, // Delete even if bindings still point to the type
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FixBindingsRequest {
+    pub action: String, // "delete" or "reassign"
+    pub to_type_id: Option<i32>, // Required for "reassign"
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigRequest {
     pub config_key: String,
@@ -69,14 +146,55 @@ pub struct CollectionRequest {
     pub collect_filter: String,
     #[serde(default)]
     pub collect_filter_from: String,
+    #[serde(default)]
+    pub default_area: String,
+    #[serde(default)]
+    pub default_lang: String,
+    #[serde(default)]
+    pub field_mapping: std::collections::HashMap<String, String>,
     pub collect_opt: i32,
     pub collect_sync_pic_opt: i32,
     pub collect_remove_ad: i32,
     pub collect_convert_webp: i32,
     pub collect_download_retry: i32,
+    #[serde(default = "crate::models::default_collect_timeout")]
+    pub collect_timeout_secs: i32,
+    #[serde(default = "crate::models::default_collect_concurrency")]
+    pub collect_concurrency: i32,
+    #[serde(default)]
+    pub collect_window: String,
+    #[serde(default)]
+    pub collect_proxy: String,
+    #[serde(default)]
+    pub collect_user_agent: String,
+    #[serde(default)]
+    pub collect_referer: String,
     pub collect_status: i32,
 }
 
+// 校验采集源请求中取值有范围限制的字段
+fn validate_collection_request(req: &CollectionRequest) -> Result<(), String> {
+    if !(MIN_COLLECT_TIMEOUT_SECS..=MAX_COLLECT_TIMEOUT_SECS).contains(&req.collect_timeout_secs) {
+        return Err(format!(
+            "collect_timeout_secs 必须在 {}-{} 秒之间",
+            MIN_COLLECT_TIMEOUT_SECS, MAX_COLLECT_TIMEOUT_SECS
+        ));
+    }
+    if !(MIN_COLLECT_CONCURRENCY..=MAX_COLLECT_CONCURRENCY).contains(&req.collect_concurrency) {
+        return Err(format!(
+            "collect_concurrency 必须在 {}-{} 之间",
+            MIN_COLLECT_CONCURRENCY, MAX_COLLECT_CONCURRENCY
+        ));
+    }
+    crate::collect_handlers::validate_field_mapping(&req.field_mapping)?;
+    crate::models::parse_collect_window(&req.collect_window)?;
+    crate::collect_handlers::parse_collect_filter(&req.collect_filter)?;
+    crate::collect_client::parse_collect_proxy(&req.collect_proxy)?;
+    crate::collect_client::validate_header_value("collect_user_agent", &req.collect_user_agent)?;
+    crate::collect_client::validate_header_value("collect_referer", &req.collect_referer)?;
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VodRequest {
     pub vod_name: String,
@@ -91,6 +209,56 @@ pub struct VodRequest {
     pub vod_lang: Option<String>,
     pub vod_year: Option<String>,
     pub vod_content: Option<String>,
+    pub vod_extra_types: Option<Vec<i32>>, // 附加分类，更新时不传则保持不变
+}
+
+impl VodRequest {
+    // 新建视频（create_vod、批量导入）：播放量和评分置零，播放源为空
+    pub fn to_new_vod(&self) -> Vod {
+        let mut new_vod = Vod {
+            id: None,
+            vod_name: self.vod_name.clone(),
+            type_id: self.type_id,
+            vod_status: self.vod_status,
+            vod_class: self.vod_class.clone(),
+            vod_pic: self.vod_pic.clone(),
+            vod_actor: self.vod_actor.clone(),
+            vod_director: self.vod_director.clone(),
+            vod_remarks: self.vod_remarks.clone(),
+            vod_pubdate: mongodb::bson::DateTime::now(),
+            vod_created_at: Some(mongodb::bson::DateTime::now()),
+            vod_area: self.vod_area.clone(),
+            vod_lang: self.vod_lang.clone(),
+            vod_year: self.vod_year.clone(),
+            vod_year_num: None,
+            vod_content: self.vod_content.clone(),
+            vod_hits: Some(0),
+            vod_hits_day: Some(0),
+            vod_hits_week: Some(0),
+            vod_hits_month: Some(0),
+            vod_score: Some("0.0".to_string()),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: self.extra_types().unwrap_or_default(),
+            vod_play_urls: vec![], // Empty initially
+        };
+        new_vod.normalize();
+        new_vod
+    }
+
+    // 附加分类去重，并去掉与主分类相同的ID
+    fn extra_types(&self) -> Option<Vec<i32>> {
+        self.vod_extra_types.as_ref().map(|ids| {
+            let mut extra = Vec::new();
+            for &id in ids {
+                if id != self.type_id && !extra.contains(&id) {
+                    extra.push(id);
+                }
+            }
+            extra
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -98,6 +266,20 @@ pub struct BatchDeleteRequest {
     pub ids: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetupRequest {
+    pub admin_username: Option<String>,
+    pub admin_password: String,
+}
+
+// 按原分类或视频ID列表批量修改分类
+#[derive(Debug, Deserialize)]
+pub struct RebindVodsRequest {
+    pub from_type_id: Option<i32>,
+    pub vod_ids: Option<Vec<String>>,
+    pub to_type_id: i32,
+}
+
 // --- Category Management API ---
 
 // GET /api/admin/types
@@ -143,6 +325,156 @@ pub async fn get_collections(db: web::Data<Database>, session: Session) -> impl
     }
 }
 
+// GET /api/admin/collections/{id}
+pub async fn get_collection(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let collection = db.collection::<Collection>("collections");
+    // 无效的ID与不存在的采集源一样返回 404
+    let collection_id = match mongodb::bson::oid::ObjectId::parse_str(path.into_inner().trim()) {
+        Ok(id) => id,
+        Err(_) => return HttpResponse::NotFound().body("Collection not found"),
+    };
+
+    match collection.find_one(doc! {"_id": collection_id}, None).await {
+        Ok(Some(c)) => HttpResponse::Ok().json(c),
+        Ok(None) => HttpResponse::NotFound().body("Collection not found"),
+        Err(e) => {
+            eprintln!("Failed to fetch collection: {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch collection")
+        }
+    }
+}
+
+// POST /api/admin/collections/{id}/enable
+pub async fn enable_collection(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    set_collection_status(path.into_inner(), db, session, 1).await
+}
+
+// POST /api/admin/collections/{id}/disable
+pub async fn disable_collection(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    set_collection_status(path.into_inner(), db, session, 0).await
+}
+
+// 只修改采集源启用状态，不需要提交完整的采集源配置
+async fn set_collection_status(
+    id: String,
+    db: web::Data<Database>,
+    session: Session,
+    status: i32,
+) -> HttpResponse {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let collection = db.collection::<Collection>("collections");
+    let collection_id = match resolve_object_id(&id, "collection") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    match collection
+        .update_one(
+            doc! {"_id": collection_id},
+            doc! {"$set": {"collect_status": status, "updated_at": mongodb::bson::DateTime::now()}},
+            None,
+        )
+        .await
+    {
+        Ok(result) if result.matched_count > 0 => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": if status == 1 { "采集源已启用" } else { "采集源已禁用" },
+            "collect_status": status
+        })),
+        Ok(_) => HttpResponse::NotFound().body("Collection not found"),
+        Err(e) => {
+            eprintln!("Failed to update collection status: {}", e);
+            HttpResponse::InternalServerError().body("Failed to update collection status")
+        }
+    }
+}
+
+// POST /api/admin/collections/{id}/clone
+// 复制采集源为新的禁用状态采集源，名称追加「(副本)」，重名时追加序号
+pub async fn clone_collection(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let collection = db.collection::<Collection>("collections");
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let source = match collection.find_one(doc! {"_id": collection_id}, None).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return HttpResponse::NotFound().body("Collection not found"),
+        Err(e) => {
+            eprintln!("Failed to fetch collection: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to fetch collection");
+        }
+    };
+
+    // 找一个未被占用的名称：xxx(副本)、xxx(副本2)、xxx(副本3)...
+    let mut clone_name = format!("{}(副本)", source.collect_name);
+    let mut counter = 2;
+    loop {
+        match collection
+            .count_documents(doc! {"collect_name": &clone_name}, None)
+            .await
+        {
+            Ok(0) => break,
+            Ok(_) => {
+                clone_name = format!("{}(副本{})", source.collect_name, counter);
+                counter += 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to check collection name: {}", e);
+                return HttpResponse::InternalServerError().body("Failed to clone collection");
+            }
+        }
+    }
+
+    let now = mongodb::bson::DateTime::now();
+    let cloned = Collection {
+        id: None,
+        collect_name: clone_name.clone(),
+        collect_status: 0, // 副本默认禁用
+        created_at: now,
+        updated_at: now,
+        ..source
+    };
+
+    match collection.insert_one(cloned, None).await {
+        Ok(result) => HttpResponse::Created().json(json!({
+            "success": true,
+            "message": "Collection cloned",
+            "id": result.inserted_id.as_object_id().map(|id| id.to_hex()),
+            "collect_name": clone_name
+        })),
+        Err(e) => {
+            eprintln!("Failed to clone collection: {}", e);
+            HttpResponse::InternalServerError().body("Failed to clone collection")
+        }
+    }
+}
+
 // POST /api/admin/collections
 pub async fn create_collection(
     db: web::Data<Database>,
@@ -152,6 +484,9 @@ pub async fn create_collection(
     if let Err(response) = check_auth(&session) {
         return response;
     }
+    if let Err(message) = validate_collection_request(&collection_req) {
+        return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
+    }
     let collection = db.collection::<Collection>("collections");
 
     let new_collection = Collection {
@@ -165,11 +500,20 @@ pub async fn create_collection(
         collect_param: collection_req.collect_param.clone(),
         collect_filter: collection_req.collect_filter.clone(),
         collect_filter_from: collection_req.collect_filter_from.clone(),
+        default_area: collection_req.default_area.clone(),
+        default_lang: collection_req.default_lang.clone(),
+        field_mapping: collection_req.field_mapping.clone(),
         collect_opt: collection_req.collect_opt,
         collect_sync_pic_opt: collection_req.collect_sync_pic_opt,
         collect_remove_ad: collection_req.collect_remove_ad,
         collect_convert_webp: collection_req.collect_convert_webp,
         collect_download_retry: collection_req.collect_download_retry,
+        collect_timeout_secs: collection_req.collect_timeout_secs,
+        collect_concurrency: collection_req.collect_concurrency,
+        collect_window: collection_req.collect_window.trim().to_string(),
+        collect_proxy: collection_req.collect_proxy.trim().to_string(),
+        collect_user_agent: collection_req.collect_user_agent.trim().to_string(),
+        collect_referer: collection_req.collect_referer.trim().to_string(),
         collect_status: collection_req.collect_status,
         created_at: mongodb::bson::DateTime::now(),
         updated_at: mongodb::bson::DateTime::now(),
@@ -198,12 +542,9 @@ pub async fn start_collection_collect(
         return response;
     }
 
-    let collection_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest()
-                .json(json!({"success": false, "message": "Invalid collection ID"}))
-        }
+        Err(response) => return response,
     };
 
     // 获取采集源配置
@@ -263,19 +604,21 @@ pub async fn start_collection_collect(
     let task_id = uuid::Uuid::new_v4().to_string();
     let task_id_clone = task_id.clone();
 
+    // 同一采集源已在采集时不再启动新任务，返回已有任务ID
+    if let Err(existing_task_id) =
+        crate::collect_handlers::reserve_collect_task(&collection.collect_name, &task_id).await
+    {
+        return HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "该采集源已有正在运行的采集任务",
+            "task_id": existing_task_id,
+            "already_running": true
+        }));
+    }
+
     // 启动后台采集任务
-    tokio::spawn(async move {
-        if let Err(e) = crate::collect_handlers::start_batch_collect(
-            &db,
-            collection,
-            hours_param,
-            task_id_clone,
-        )
-        .await
-        {
-            eprintln!("Batch collect failed: {}", e);
-        }
-    });
+    crate::collect_handlers::spawn_collect_task(db.get_ref().clone(), collection, hours_param, task_id_clone)
+        .await;
 
     HttpResponse::Ok().json(json!({
         "success": true,
@@ -300,10 +643,13 @@ pub async fn update_collection(
     if let Err(response) = check_auth(&session) {
         return response;
     }
+    if let Err(message) = validate_collection_request(&collection_req) {
+        return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
+    }
     let collection = db.collection::<Collection>("collections");
-    let collection_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
         Ok(id) => id,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid collection ID"),
+        Err(response) => return response,
     };
 
     let update_doc = doc! {
@@ -317,11 +663,24 @@ pub async fn update_collection(
             "collect_param": &collection_req.collect_param,
             "collect_filter": &collection_req.collect_filter,
             "collect_filter_from": &collection_req.collect_filter_from,
+            "default_area": &collection_req.default_area,
+            "default_lang": &collection_req.default_lang,
+            "field_mapping": collection_req
+                .field_mapping
+                .iter()
+                .map(|(ours, theirs)| (ours.clone(), mongodb::bson::Bson::String(theirs.clone())))
+                .collect::<Document>(),
             "collect_opt": collection_req.collect_opt,
             "collect_sync_pic_opt": collection_req.collect_sync_pic_opt,
             "collect_remove_ad": collection_req.collect_remove_ad,
             "collect_convert_webp": collection_req.collect_convert_webp,
             "collect_download_retry": collection_req.collect_download_retry,
+            "collect_timeout_secs": collection_req.collect_timeout_secs,
+            "collect_concurrency": collection_req.collect_concurrency,
+            "collect_window": collection_req.collect_window.trim(),
+            "collect_proxy": collection_req.collect_proxy.trim(),
+            "collect_user_agent": collection_req.collect_user_agent.trim(),
+            "collect_referer": collection_req.collect_referer.trim(),
             "collect_status": collection_req.collect_status,
             "updated_at": mongodb::bson::DateTime::now(),
         }
@@ -365,6 +724,9 @@ pub async fn get_collect_progress(path: web::Path<String>, session: Session) ->
             total_pages: 0,
             success: 0,
             failed: 0,
+            skipped: 0,
+            filtered: 0,
+            filtered_sources: 0,
             log: "任务不存在".to_string(),
         });
 
@@ -413,6 +775,95 @@ pub async fn stop_collect_task(path: web::Path<String>, session: Session) -> imp
     }
 }
 
+// POST /api/admin/collect/restart/{task_id}
+// 强制结束卡住的采集任务并按相同参数重新启动，返回新任务ID
+pub async fn restart_collect_task(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let task_id = path.into_inner();
+
+    match crate::collect_handlers::restart_task(&db, &task_id).await {
+        Ok(new_task_id) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "采集任务已重启",
+            "old_task_id": task_id,
+            "task_id": new_task_id
+        })),
+        Err(RestartError::TaskNotFound) => HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "任务不存在或不支持重启（仅支持手动启动的采集任务）"
+        })),
+        Err(RestartError::CollectionNotFound) => HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "采集源已被删除，旧任务已停止"
+        })),
+        Err(RestartError::AlreadyRunning(existing_task_id)) => HttpResponse::Conflict().json(json!({
+            "success": false,
+            "message": "旧任务已停止，但该采集源已有其他正在运行的采集任务",
+            "task_id": existing_task_id
+        })),
+        Err(RestartError::Database(e)) => {
+            eprintln!("Failed to restart collect task: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "旧任务已停止，但读取采集源失败"
+            }))
+        }
+    }
+}
+
+// POST /api/admin/collect/resume/{task_id}
+// 继续失败或中断的采集任务：重试记录的失败页，并从第一个未完成的页继续，任务ID不变
+pub async fn resume_collect_task(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let task_id = path.into_inner();
+
+    match crate::collect_handlers::resume_task(&db, &task_id).await {
+        Ok(()) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "采集任务已继续",
+            "task_id": task_id
+        })),
+        Err(ResumeError::StateNotFound) => HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "没有该任务的采集进度记录，无法继续采集"
+        })),
+        Err(ResumeError::CollectionNotFound) => HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "采集源已被删除，无法继续采集"
+        })),
+        Err(ResumeError::NothingToResume) => HttpResponse::Conflict().json(json!({
+            "success": false,
+            "message": "该任务所有页面都已采集完成，无需继续"
+        })),
+        Err(ResumeError::AlreadyRunning(existing_task_id)) => HttpResponse::Conflict().json(json!({
+            "success": false,
+            "message": "该采集源已有正在运行的采集任务",
+            "task_id": existing_task_id
+        })),
+        Err(ResumeError::Database(e)) => {
+            eprintln!("Failed to resume collect task: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "读取采集进度失败"
+            }))
+        }
+    }
+}
+
 // DELETE /api/admin/collections/{id}
 pub async fn delete_collection(
     path: web::Path<String>,
@@ -423,9 +874,9 @@ pub async fn delete_collection(
         return response;
     }
     let collection = db.collection::<Collection>("collections");
-    let collection_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
         Ok(id) => id,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid collection ID"),
+        Err(response) => return response,
     };
 
     match collection
@@ -458,11 +909,100 @@ pub struct VodsQuery {
     pub type_id: Option<i32>,
     pub status: Option<i32>,
     pub search: Option<String>,
+    pub sort: Option<String>, // pubdate(默认) | pubdate_asc | created | hits | score | name
+    pub after_id: Option<String>, // 游标分页：传入上一页的 next_cursor，空字符串表示从头开始
+}
+
+// 解析游标分页的 after_id，空字符串表示从第一条开始
+fn parse_after_id(after_id: &str) -> Result<Option<mongodb::bson::oid::ObjectId>, String> {
+    let after_id = after_id.trim();
+    if after_id.is_empty() {
+        return Ok(None);
+    }
+    mongodb::bson::oid::ObjectId::parse_str(after_id)
+        .map(Some)
+        .map_err(|_| "Invalid after_id".to_string())
+}
+
+// 后台视频列表的排序方式，未知取值按更新时间倒序
+fn admin_vods_sort(sort: Option<&str>) -> Document {
+    match sort.unwrap_or("") {
+        "pubdate_asc" => doc! {"vod_pubdate": 1},
+        "created" => doc! {"vod_created_at": -1, "vod_pubdate": -1},
+        "hits" => doc! {"vod_hits": -1, "vod_pubdate": -1},
+        "score" => doc! {"vod_score_num": -1, "vod_pubdate": -1},
+        "name" => doc! {"vod_name": 1},
+        _ => doc! {"vod_pubdate": -1},
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncompleteVodsQuery {
+    pub issue: String, // no_pic | no_play | no_content
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CachePurgeRequest {
+    pub scope: String,       // home | counts | site_data | all
+    pub key: Option<String>, // 只清除该区域中的单个条目，例如 counts 区域的 "vods"
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BrokenReportsQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    pub user_id: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionRunsQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    pub collection_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentFailuresQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestImageRequest {
+    pub image_url: String,
+    #[serde(default)]
+    pub convert_webp: bool,
+    pub quality: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParsePlayRequest {
+    pub vod_play_from: String,
+    pub vod_play_url: Option<String>,
+    #[serde(default)]
+    pub normalize_episode_names: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogLevelRequest {
+    pub level: String,
+    pub duration_secs: Option<u64>,
 }
 
 // GET /api/admin/vods
 pub async fn get_vods_admin(
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
     query: web::Query<VodsQuery>,
     session: Session,
 ) -> impl Responder {
@@ -470,12 +1010,44 @@ pub async fn get_vods_admin(
         return response;
     }
 
-    let page = query.page.unwrap_or(1).max(1);
-    let limit = query.limit.unwrap_or(20).min(100);
-    let skip = (page - 1) * limit;
+    if let Some(Err(msg)) = query.after_id.as_deref().map(parse_after_id) {
+        return HttpResponse::BadRequest().json(json!({
+            "code": 0,
+            "msg": msg,
+            "limit": query.limit.unwrap_or(20).min(100),
+            "videos": []
+        }));
+    }
 
-    // 构建查询条件
-    let mut filter_doc = doc! {};
+    match query_admin_vods(&db, &site_data_manager, &query).await {
+        Ok(data) => HttpResponse::Ok().json(data),
+        Err(e) => {
+            eprintln!("Failed to fetch vods: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "code": 0,
+                "msg": "Failed to fetch videos",
+                "page": query.page.unwrap_or(1).max(1),
+                "limit": query.limit.unwrap_or(20).min(100),
+                "total": 0,
+                "videos": []
+            }))
+        }
+    }
+}
+
+// 按 VodsQuery 查询后台视频列表，返回与 GET /api/admin/vods 相同结构的 JSON；
+// 后台视频管理页（/admin/vods）首次渲染时也使用这里的结果
+pub async fn query_admin_vods(
+    db: &Database,
+    site_data_manager: &SiteDataManager,
+    query: &VodsQuery,
+) -> Result<serde_json::Value, mongodb::error::Error> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let skip = (page - 1) * limit;
+
+    // 构建查询条件
+    let mut filter_doc = doc! {};
 
     // 分类筛选
     if let Some(type_id) = query.type_id {
@@ -494,18 +1066,125 @@ pub async fn get_vods_admin(
         }
     }
 
+    // 回收站中的视频只在 GET /api/admin/vods/trash 中列出
+    exclude_deleted_vods(&mut filter_doc);
+
     let collection = db.collection::<Vod>("vods");
+
+    // 传入 after_id 时改用游标分页：按 _id 升序取 _id 之后的一页，不用 skip，深翻页不再逐条跳过前面的文档。
+    // 与 execute_batch_delete_inner 的分批方式相同；此模式忽略 sort 参数，也不统计总数
+    if let Some(after_id) = query.after_id.as_deref() {
+        // GET /api/admin/vods 已校验格式，这里无效值按从头开始处理
+        if let Ok(Some(after_id)) = parse_after_id(after_id) {
+            filter_doc.insert("_id", doc! {"$gt": after_id});
+        }
+        // 多取一条判断是否还有下一页
+        let find_options = FindOptions::builder()
+            .sort(doc! {"_id": 1})
+            .limit(limit as i64 + 1)
+            .build();
+        let mut vods: Vec<Vod> = collection
+            .find(filter_doc, find_options)
+            .await?
+            .try_collect()
+            .await
+            .unwrap_or_else(|_| vec![]);
+
+        let has_more = vods.len() > limit as usize;
+        vods.truncate(limit as usize);
+        let next_cursor = if has_more {
+            vods.last().and_then(|vod| vod.id).map(|id| id.to_hex())
+        } else {
+            None
+        };
+
+        return Ok(json!({
+            "code": 1,
+            "msg": "success",
+            "limit": limit,
+            "has_more": has_more,
+            "next_cursor": next_cursor,
+            "videos": vods
+        }));
+    }
+
     let find_options = FindOptions::builder()
-        .sort(doc! {"vod_pubdate": -1})
+        .sort(crate::pagination::stable_sort(admin_vods_sort(query.sort.as_deref())))
         .skip(skip as u64)
         .limit(limit as i64)
         .build();
 
     // 获取总数
+    let total = collection.count_documents(filter_doc.clone(), None).await?;
+
+    // 获取分页数据
+    let vods: Vec<Vod> = collection
+        .find(filter_doc, find_options)
+        .await?
+        .try_collect()
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+    let total_pages = total.div_ceil(limit as u64);
+    let window = pagination_window(site_data_manager).await;
+
+    Ok(json!({
+        "code": 1,
+        "msg": "success",
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "total_pages": total_pages,
+        "has_more": (page as u64) < total_pages,
+        "pages": build_pagination(page as u64, total_pages, window),
+        "videos": vods
+    }))
+}
+
+// GET /api/admin/vods/incomplete?issue=no_pic|no_play|no_content
+pub async fn get_incomplete_vods(
+    db: web::Data<Database>,
+    query: web::Query<IncompleteVodsQuery>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).min(100);
+    let skip = (page - 1) * limit;
+
+    // null 同时匹配字段为 null 和字段不存在
+    let mut filter_doc = match query.issue.as_str() {
+        "no_pic" => doc! {"$or": [{"vod_pic": null}, {"vod_pic": ""}]},
+        "no_play" => doc! {"$or": [{"vod_play_urls": {"$size": 0}}, {"vod_play_urls": null}]},
+        "no_content" => doc! {"$or": [{"vod_content": null}, {"vod_content": ""}]},
+        _ => {
+            return HttpResponse::BadRequest().json(json!({
+                "code": 0,
+                "msg": "Invalid issue, expected no_pic, no_play or no_content",
+                "page": page,
+                "limit": limit,
+                "total": 0,
+                "videos": []
+            }));
+        }
+    };
+
+    exclude_deleted_vods(&mut filter_doc);
+
+    let collection = db.collection::<Vod>("vods");
+    let find_options = FindOptions::builder()
+        .sort(crate::pagination::stable_sort(doc! {"vod_pubdate": -1}))
+        .skip(skip as u64)
+        .limit(limit as i64)
+        .build();
+
     let total = match collection.count_documents(filter_doc.clone(), None).await {
         Ok(count) => count,
         Err(e) => {
-            eprintln!("Failed to count vods: {}", e);
+            eprintln!("Failed to count incomplete vods: {}", e);
             return HttpResponse::InternalServerError().json(json!({
                 "code": 0,
                 "msg": "Failed to count videos",
@@ -517,7 +1196,6 @@ pub async fn get_vods_admin(
         }
     };
 
-    // 获取分页数据
     match collection.find(filter_doc, find_options).await {
         Ok(cursor) => {
             let vods: Vec<Vod> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
@@ -532,7 +1210,7 @@ pub async fn get_vods_admin(
             }))
         }
         Err(e) => {
-            eprintln!("Failed to fetch vods: {}", e);
+            eprintln!("Failed to fetch incomplete vods: {}", e);
             HttpResponse::InternalServerError().json(json!({
                 "code": 0,
                 "msg": "Failed to fetch videos",
@@ -556,28 +1234,7 @@ pub async fn create_vod(
     }
     let collection = db.collection::<Vod>("vods");
 
-    let new_vod = Vod {
-        id: None,
-        vod_name: vod_req.vod_name.clone(),
-        type_id: vod_req.type_id,
-        vod_status: vod_req.vod_status,
-        vod_class: vod_req.vod_class.clone(),
-        vod_pic: vod_req.vod_pic.clone(),
-        vod_actor: vod_req.vod_actor.clone(),
-        vod_director: vod_req.vod_director.clone(),
-        vod_remarks: vod_req.vod_remarks.clone(),
-        vod_pubdate: mongodb::bson::DateTime::now(),
-        vod_area: vod_req.vod_area.clone(),
-        vod_lang: vod_req.vod_lang.clone(),
-        vod_year: vod_req.vod_year.clone(),
-        vod_content: vod_req.vod_content.clone(),
-        vod_hits: Some(0),
-        vod_hits_day: Some(0),
-        vod_hits_week: Some(0),
-        vod_hits_month: Some(0),
-        vod_score: Some("0.0".to_string()),
-        vod_play_urls: vec![], // Empty initially
-    };
+    let new_vod = vod_req.to_new_vod();
 
     match collection.insert_one(new_vod, None).await {
         Ok(_) => HttpResponse::Created().json(json!({
@@ -605,27 +1262,33 @@ pub async fn update_vod(
         return response;
     }
     let collection = db.collection::<Vod>("vods");
-    let vod_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
+    let vod_id = match resolve_object_id(&path.into_inner(), "video") {
         Ok(id) => id,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid video ID"),
+        Err(response) => return response,
     };
 
-    let update_doc = doc! {
-        "$set": {
-            "vod_name": &vod_req.vod_name,
-            "type_id": vod_req.type_id,
-            "vod_status": vod_req.vod_status,
-            "vod_class": &vod_req.vod_class,
-            "vod_pic": &vod_req.vod_pic,
-            "vod_actor": &vod_req.vod_actor,
-            "vod_director": &vod_req.vod_director,
-            "vod_remarks": &vod_req.vod_remarks,
-            "vod_area": &vod_req.vod_area,
-            "vod_lang": &vod_req.vod_lang,
-            "vod_year": &vod_req.vod_year,
-            "vod_content": &vod_req.vod_content,
-        }
+    let mut set_fields = doc! {
+        "vod_name": &vod_req.vod_name,
+        "type_id": vod_req.type_id,
+        "vod_status": vod_req.vod_status,
+        "vod_class": &vod_req.vod_class,
+        "vod_pic": &vod_req.vod_pic,
+        "vod_actor": &vod_req.vod_actor,
+        "vod_actor_list": parse_actor_list(vod_req.vod_actor.as_deref().unwrap_or("")),
+        "vod_director": &vod_req.vod_director,
+        "vod_remarks": &vod_req.vod_remarks,
+        "vod_area": &vod_req.vod_area,
+        "vod_lang": &vod_req.vod_lang,
+        "vod_year": &vod_req.vod_year,
+        "vod_year_num": vod_req.vod_year.as_deref().and_then(parse_year_num),
+        "vod_content": &vod_req.vod_content,
+        // 与采集更新一致刷新更新时间，导出的 since 过滤和前台“最近更新”都依赖它
+        "vod_pubdate": mongodb::bson::DateTime::now(),
     };
+    if let Some(extra_types) = vod_req.extra_types() {
+        set_fields.insert("vod_extra_types", extra_types);
+    }
+    let update_doc = doc! { "$set": set_fields };
 
     match collection
         .update_one(doc! {"_id": vod_id}, update_doc, None)
@@ -654,26 +1317,64 @@ pub async fn update_vod(
     }
 }
 
-// DELETE /api/admin/vods/{id}
+#[derive(Deserialize)]
+pub struct DeleteVodQuery {
+    #[serde(default)]
+    pub permanent: bool, // true 时直接从数据库删除，否则移入回收站
+}
+
+// 移入回收站、永久删除或批量导入后清除依赖视频数量的缓存
+pub async fn purge_vod_count_caches(site_data_manager: &SiteDataManager) {
+    cache::purge(cache::SCOPE_COUNTS, Some(cache::VODS_BY_STATUS_KEY)).await;
+    cache::purge(cache::SCOPE_HOME, None).await;
+    if let Err(e) = site_data_manager.refresh().await {
+        eprintln!("Cache refresh failed: {}", e);
+    }
+}
+
+// DELETE /api/admin/vods/{id}?permanent=true
+// 默认把视频移入回收站（设置 vod_deleted_at），可通过 POST /api/admin/vods/{id}/restore 恢复；
+// permanent=true 时永久删除，回收站中的视频也可以这样彻底清除
 pub async fn delete_vod(
     path: web::Path<String>,
+    query: web::Query<DeleteVodQuery>,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
     let collection = db.collection::<Vod>("vods");
-    let vod_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
+    let vod_id = match resolve_object_id(&path.into_inner(), "video") {
         Ok(id) => id,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid video ID"),
+        Err(response) => return response,
     };
 
-    match collection.delete_one(doc! {"_id": vod_id}, None).await {
-        Ok(result) => {
-            if result.deleted_count > 0 {
+    let result = if query.permanent {
+        collection
+            .delete_one(doc! {"_id": vod_id}, None)
+            .await
+            .map(|result| result.deleted_count)
+    } else {
+        let filter = doc! {"_id": vod_id, "vod_deleted_at": {"$exists": false}};
+        collection
+            .update_one(filter, doc! {"$set": {"vod_deleted_at": mongodb::bson::DateTime::now()}}, None)
+            .await
+            .map(|result| result.matched_count)
+    };
+
+    match result {
+        Ok(count) => {
+            if count > 0 {
+                purge_vod_count_caches(&site_data_manager).await;
+                let message = if query.permanent {
+                    "Video permanently deleted"
+                } else {
+                    "Video moved to trash"
+                };
                 HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Video deleted successfully"}))
+                    .json(json!({"success": true, "message": message, "permanent": query.permanent}))
             } else {
                 HttpResponse::NotFound()
                     .json(json!({"success": false, "message": "Video not found"}))
@@ -687,9 +1388,12 @@ pub async fn delete_vod(
     }
 }
 
-// DELETE /api/admin/vods/batch
+// DELETE /api/admin/vods?permanent=true（请求体为 ids 列表）
+// 与 delete_vod 相同，默认移入回收站，permanent=true 时永久删除
 pub async fn batch_delete_vods(
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    query: web::Query<DeleteVodQuery>,
     batch_req: web::Json<BatchDeleteRequest>,
     session: Session,
 ) -> impl Responder {
@@ -717,16 +1421,35 @@ pub async fn batch_delete_vods(
         }));
     }
 
-    // Delete all valid videos
-    match collection
-        .delete_many(doc! {"_id": {"$in": object_ids}}, None)
-        .await
-    {
-        Ok(result) => {
+    let result = if query.permanent {
+        collection
+            .delete_many(doc! {"_id": {"$in": object_ids}}, None)
+            .await
+            .map(|result| result.deleted_count)
+    } else {
+        // 已在回收站中的视频保留原来的删除时间
+        let filter = doc! {"_id": {"$in": object_ids}, "vod_deleted_at": {"$exists": false}};
+        collection
+            .update_many(filter, doc! {"$set": {"vod_deleted_at": mongodb::bson::DateTime::now()}}, None)
+            .await
+            .map(|result| result.modified_count)
+    };
+
+    match result {
+        Ok(deleted_count) => {
+            if deleted_count > 0 {
+                purge_vod_count_caches(&site_data_manager).await;
+            }
+            let message = if query.permanent {
+                "Videos permanently deleted"
+            } else {
+                "Videos moved to trash"
+            };
             let response = json!({
                 "success": true,
-                "message": "Videos deleted successfully",
-                "deleted_count": result.deleted_count,
+                "message": message,
+                "permanent": query.permanent,
+                "deleted_count": deleted_count,
                 "invalid_ids": invalid_ids.len(),
                 "invalid_id_list": invalid_ids
             });
@@ -743,970 +1466,2844 @@ pub async fn batch_delete_vods(
     }
 }
 
-// --- Website Configuration Management API ---
-
-// GET /api/admin/configs
-pub async fn get_configs(db: web::Data<Database>, session: Session) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
-    let collection = db.collection::<Config>("configs");
-    let find_options = FindOptions::builder().sort(doc! {"config_sort": 1}).build();
-
-    match collection.find(None, find_options).await {
-        Ok(cursor) => {
-            let configs: Vec<Config> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
-            HttpResponse::Ok().json(configs)
-        }
-        Err(e) => {
-            eprintln!("Failed to fetch configs: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch configs")
-        }
-    }
-}
-
-// GET /api/admin/configs/{key}
-pub async fn get_config_by_key(
+// POST /api/admin/vods/{id}/restore
+// 把回收站中的视频恢复为正常状态
+pub async fn restore_vod(
     path: web::Path<String>,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Config>("configs");
-    let config_key = path.into_inner();
+    let vod_id = match resolve_object_id(&path.into_inner(), "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
 
-    match collection
-        .find_one(doc! {"config_key": &config_key}, None)
+    let filter = doc! {"_id": vod_id, "vod_deleted_at": {"$exists": true}};
+    match db
+        .collection::<Vod>("vods")
+        .update_one(filter, doc! {"$unset": {"vod_deleted_at": ""}}, None)
         .await
     {
-        Ok(Some(config)) => HttpResponse::Ok().json(config),
-        Ok(None) => HttpResponse::NotFound().body("Config not found"),
+        Ok(result) if result.matched_count > 0 => {
+            purge_vod_count_caches(&site_data_manager).await;
+            HttpResponse::Ok().json(json!({"success": true, "message": "Video restored"}))
+        }
+        Ok(_) => HttpResponse::NotFound()
+            .json(json!({"success": false, "message": "Video not found in trash"})),
         Err(e) => {
-            eprintln!("Failed to fetch config: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch config")
+            eprintln!("Failed to restore video: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to restore video"}))
         }
     }
 }
 
-// POST /api/admin/configs
-pub async fn create_config(
+#[derive(Deserialize)]
+pub struct TrashQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+// GET /api/admin/vods/trash
+// 回收站列表，按删除时间倒序
+pub async fn get_vod_trash(
     db: web::Data<Database>,
-    config_req: web::Json<ConfigRequest>,
+    query: web::Query<TrashQuery>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Config>("configs");
 
-    let new_config = Config {
-        id: None,
-        config_key: config_req.config_key.clone(),
-        config_value: config_req.config_value.clone(),
-        config_desc: config_req.config_desc.clone(),
-        config_type: config_req.config_type.clone(),
-        config_group: config_req.config_group.clone(),
-        config_sort: config_req.config_sort,
-        updated_at: mongodb::bson::DateTime::now(),
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let skip = (page - 1) * limit;
+
+    let collection = db.collection::<Vod>("vods");
+    let filter = doc! {"vod_deleted_at": {"$exists": true}};
+    let find_options = FindOptions::builder()
+        .sort(crate::pagination::stable_sort(doc! {"vod_deleted_at": -1}))
+        .skip(skip as u64)
+        .limit(limit as i64)
+        .build();
+
+    let total = match collection.count_documents(filter.clone(), None).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count trashed vods: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "code": 0,
+                "msg": "Failed to count videos",
+                "page": page,
+                "limit": limit,
+                "total": 0,
+                "videos": []
+            }));
+        }
     };
 
-    match collection.insert_one(new_config, None).await {
-        Ok(_) => {
-            HttpResponse::Created().json(json!({"success": true, "message": "Config created"}))
+    match collection.find(filter, find_options).await {
+        Ok(cursor) => {
+            let vods: Vec<Vod> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+            HttpResponse::Ok().json(json!({
+                "code": 1,
+                "msg": "success",
+                "page": page,
+                "limit": limit,
+                "total": total,
+                "total_pages": total.div_ceil(limit as u64),
+                "videos": vods
+            }))
         }
         Err(e) => {
-            if e.to_string().contains("E11000 duplicate key error") {
-                HttpResponse::Conflict()
-                    .json(json!({"success": false, "message": "Config key already exists"}))
-            } else {
-                eprintln!("Failed to create config: {}", e);
-                HttpResponse::InternalServerError()
-                    .json(json!({"success": false, "message": "Failed to create config"}))
-            }
+            eprintln!("Failed to fetch trashed vods: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "code": 0,
+                "msg": "Failed to fetch videos",
+                "page": page,
+                "limit": limit,
+                "total": 0,
+                "videos": []
+            }))
         }
     }
 }
 
-// PUT /api/admin/configs/{key}
-pub async fn update_config(
-    path: web::Path<String>,
+// POST /api/admin/vods/import
+// 请求体为 VodRequest 结构的 JSON 数组，或 multipart/form-data 上传的 CSV 文件（字段名 file，首行为字段名）。
+// 格式错误直接返回 400；解析成功后在后台逐批写入，进度和结果通过 GET /api/admin/vods/import/progress/{task_id} 查询
+pub async fn import_vods(
+    req: HttpRequest,
+    payload: web::Payload,
     db: web::Data<Database>,
-    config_req: web::Json<ConfigRequest>,
+    site_data_manager: web::Data<SiteDataManager>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Config>("configs");
-    let config_key = path.into_inner();
 
-    let update_doc = doc! {
-        "$set": {
-            "config_value": &config_req.config_value,
-            "config_desc": &config_req.config_desc,
-            "config_type": &config_req.config_type,
-            "config_group": &config_req.config_group,
-            "config_sort": config_req.config_sort,
-            "updated_at": mongodb::bson::DateTime::now(),
+    let is_multipart = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_lowercase().starts_with("multipart/form-data"))
+        .unwrap_or(false);
+    let rows = if is_multipart {
+        let multipart = actix_multipart::Multipart::new(req.headers(), payload);
+        match vod_import::read_csv_upload(multipart).await {
+            Ok(body) => vod_import::parse_csv_rows(&body),
+            Err(e) => Err(e),
+        }
+    } else {
+        match vod_import::read_body(payload).await {
+            Ok(body) => vod_import::parse_json_rows(&body),
+            Err(e) => Err(e),
         }
     };
 
-    match collection
-        .update_one(doc! {"config_key": &config_key}, update_doc, None)
-        .await
-    {
-        Ok(result) => {
-            if result.matched_count > 0 {
-                HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Config updated successfully"}))
-            } else {
-                HttpResponse::NotFound()
-                    .json(json!({"success": false, "message": "Config not found"}))
-            }
+    let rows = match rows {
+        Ok(rows) if rows.is_empty() => {
+            return HttpResponse::BadRequest()
+                .json(json!({"success": false, "message": "没有可导入的数据"}));
         }
-        Err(e) => {
-            eprintln!("Failed to update config: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to update config"}))
+        Ok(rows) => rows,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
         }
+    };
+
+    let total = rows.len();
+    let task_id = vod_import::start_import(
+        db.get_ref().clone(),
+        site_data_manager.get_ref().clone(),
+        rows,
+    )
+    .await;
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": "导入任务已启动",
+        "task_id": task_id,
+        "total": total
+    }))
+}
+
+// GET /api/admin/vods/import/progress/{task_id}
+// 任务结束后 progress 中的 inserted / skipped / errors 即为导入结果
+pub async fn get_vod_import_progress(path: web::Path<String>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    match vod_import::get_import_progress(&path.into_inner()).await {
+        Some(progress) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "progress": progress
+        })),
+        None => HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "任务不存在"
+        })),
     }
 }
 
-// DELETE /api/admin/configs/{key}
-pub async fn delete_config(
-    path: web::Path<String>,
+#[derive(Deserialize)]
+pub struct ExportVodsQuery {
+    pub format: Option<String>,
+    pub type_id: Option<i32>,
+    pub since: Option<String>,
+}
+
+// GET /api/admin/vods/export?format=maccms&type_id=1&since=1700000000
+// 流式下载视频库（不含回收站）：默认每行一个视频文档，format=maccms 时为 provide-vod 结构，可作为其他站点的采集源
+pub async fn export_vods(
+    query: web::Query<ExportVodsQuery>,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Config>("configs");
-    let config_key = path.into_inner();
 
-    match collection
-        .delete_one(doc! {"config_key": &config_key}, None)
-        .await
-    {
-        Ok(result) => {
-            if result.deleted_count > 0 {
-                HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Config deleted successfully"}))
-            } else {
-                HttpResponse::NotFound()
-                    .json(json!({"success": false, "message": "Config not found"}))
-            }
+    let format = match vod_export::ExportFormat::parse(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
         }
+    };
+    let since = match query.since.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(value) => match vod_export::parse_since(value) {
+            Ok(since) => Some(since),
+            Err(message) => {
+                return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
+            }
+        },
+        None => None,
+    };
+
+    let filter = vod_export::export_filter(query.type_id, since);
+    let types = site_data_manager.get_all_categories().await;
+    let stream = match vod_export::export_stream(db.get_ref(), filter, format, types).await {
+        Ok(stream) => stream,
         Err(e) => {
-            eprintln!("Failed to delete config: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to delete config"}))
+            eprintln!("Failed to export vods: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "导出失败"}));
         }
-    }
+    };
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            actix_web::http::header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"maccms-vods-{}.{}\"",
+                chrono::Local::now().format("%Y%m%d%H%M%S"),
+                format.file_extension()
+            ),
+        ))
+        .streaming(stream)
 }
 
-// POST /api/admin/types
-pub async fn create_type(
+// POST /api/admin/vods/rebind
+pub async fn rebind_vods(
     db: web::Data<Database>,
-    type_req: web::Json<TypeRequest>,
+    site_data_manager: web::Data<SiteDataManager>,
+    req: web::Json<RebindVodsRequest>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Type>("types");
 
-    // In a real system, you'd generate type_id and handle type_mid, etc.
-    // For simplicity, let's assume type_id is auto-incremented or managed externally for now.
-    // Or, query max type_id and increment.
-    let new_type_id = match collection
-        .find_one(
-            None,
-            FindOneOptions::builder().sort(doc! {"type_id": -1}).build(),
-        )
+    // 目标分类必须存在
+    match db
+        .collection::<Type>("types")
+        .find_one(doc! {"type_id": req.to_type_id}, None)
         .await
     {
-        Ok(Some(last_type)) => last_type.type_id + 1,
-        _ => 1, // Start from 1 if no types exist
-    };
-
-    let new_type = Type {
-        id: None, // MongoDB will generate ObjectId
-        type_id: new_type_id,
-        type_name: type_req.type_name.clone(),
-        type_pid: type_req.type_pid,
-        type_en: type_req.type_en.clone(),
-        type_sort: type_req.type_sort.unwrap_or(0),
-        type_status: type_req.type_status.unwrap_or(1),
-        type_mid: type_req.type_mid,
-        type_key: type_req.type_key.clone(),
-        type_des: type_req.type_des.clone(),
-        type_title: type_req.type_title.clone(),
-        type_tpl: None,
-        type_tpl_list: None,
-        type_tpl_detail: None,
-        type_tpl_play: None,
-        type_tpl_down: None,
-        subarea: type_req.subarea.clone(),
-        subyear: type_req.subyear.clone(),
-    };
-
-    match collection.insert_one(new_type, None).await {
-        Ok(_) => HttpResponse::Created().json(json!({"success": true, "message": "Type created"})),
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return HttpResponse::BadRequest().json(json!({
+                "success": false,
+                "message": "目标分类不存在"
+            }));
+        }
         Err(e) => {
-            eprintln!("Failed to create type: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to create type"}))
+            eprintln!("Failed to fetch target type: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "查询目标分类失败"
+            }));
         }
     }
-}
 
-// PUT /api/admin/types/{id}
-pub async fn update_type(
-    path: web::Path<String>,
-    db: web::Data<Database>,
-    type_req: web::Json<TypeRequest>,
-    session: Session,
-) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
-    let collection = db.collection::<Type>("types");
-    let type_id: i32 = match path.into_inner().parse() {
-        Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest()
-                .json(json!({"success": false, "message": "Invalid type ID"}))
+    let mut invalid_ids = Vec::new();
+    let filter = match (req.from_type_id, req.vod_ids.as_ref()) {
+        (Some(from_type_id), None) => {
+            if from_type_id == req.to_type_id {
+                return HttpResponse::BadRequest().json(json!({
+                    "success": false,
+                    "message": "原分类与目标分类相同"
+                }));
+            }
+            doc! {"type_id": from_type_id}
+        }
+        (None, Some(vod_ids)) => {
+            let mut object_ids = Vec::new();
+            for id_str in vod_ids {
+                match mongodb::bson::oid::ObjectId::parse_str(id_str) {
+                    Ok(id) => object_ids.push(id),
+                    Err(_) => invalid_ids.push(id_str.clone()),
+                }
+            }
+            if object_ids.is_empty() {
+                return HttpResponse::BadRequest().json(json!({
+                    "success": false,
+                    "message": "No valid video IDs provided",
+                    "invalid_ids": invalid_ids
+                }));
+            }
+            doc! {"_id": {"$in": object_ids}, "type_id": {"$ne": req.to_type_id}}
+        }
+        _ => {
+            return HttpResponse::BadRequest().json(json!({
+                "success": false,
+                "message": "请指定 from_type_id 或 vod_ids 其中之一"
+            }));
         }
     };
 
-    let mut update_fields = doc! {
-        "type_name": &type_req.type_name,
-        "type_pid": type_req.type_pid,
-    };
-
-    if let Some(ref type_en) = type_req.type_en {
-        update_fields.insert("type_en", type_en);
-    }
-    if let Some(type_sort) = type_req.type_sort {
-        update_fields.insert("type_sort", type_sort);
-    }
-    if let Some(type_status) = type_req.type_status {
-        update_fields.insert("type_status", type_status);
-    }
-    if let Some(type_mid) = type_req.type_mid {
-        update_fields.insert("type_mid", type_mid);
-    }
-    if let Some(ref type_key) = type_req.type_key {
-        update_fields.insert("type_key", type_key);
-    }
-    if let Some(ref type_des) = type_req.type_des {
-        update_fields.insert("type_des", type_des);
-    }
-    if let Some(ref type_title) = type_req.type_title {
-        update_fields.insert("type_title", type_title);
-    }
-    if let Some(ref subarea) = type_req.subarea {
-        update_fields.insert("subarea", subarea);
-    }
-    if let Some(ref subyear) = type_req.subyear {
-        update_fields.insert("subyear", subyear);
-    }
-
-    let update_doc = doc! {
-        "$set": update_fields
-    };
-
-    match collection
-        .update_one(doc! {"type_id": type_id}, update_doc, None)
+    match db
+        .collection::<Vod>("vods")
+        .update_many(filter, doc! {"$set": {"type_id": req.to_type_id}}, None)
         .await
     {
         Ok(result) => {
-            if result.matched_count > 0 {
-                HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Type updated successfully"}))
-            } else {
-                HttpResponse::NotFound()
-                    .json(json!({"success": false, "message": "Type not found"}))
+            // 分类下的视频数量发生变化，刷新站点数据和首页缓存
+            if result.modified_count > 0 {
+                if let Err(e) = site_data_manager.refresh().await {
+                    eprintln!("Cache refresh failed: {}", e);
+                }
+                cache::purge(cache::SCOPE_HOME, None).await;
             }
+
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "message": format!("已将 {} 个视频移动到分类 {}", result.modified_count, req.to_type_id),
+                "matched_count": result.matched_count,
+                "modified_count": result.modified_count,
+                "invalid_id_list": invalid_ids
+            }))
         }
         Err(e) => {
-            eprintln!("Failed to update type: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to update type"}))
+            eprintln!("Failed to rebind videos: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "修改视频分类失败",
+                "error": e.to_string()
+            }))
         }
     }
 }
 
-// DELETE /api/admin/types/{id}
-pub async fn delete_type(
-    path: web::Path<String>,
-    db: web::Data<Database>,
-    session: Session,
-) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
+// 未登录的 setup 请求创建首个管理员前需要占用的锁：setup_locks 中 _id 唯一，同一时间只有一个请求能拿到；
+// 进程在初始化途中退出时，锁在 SETUP_LOCK_TTL_SECS 秒后视为过期，可以重新占用
+const SETUP_LOCK_ID: &str = "admin_setup";
+const SETUP_LOCK_TTL_SECS: i64 = 300;
+
+async fn count_admins(db: &Database) -> Result<u64, mongodb::error::Error> {
+    db.collection::<crate::models::User>("users")
+        .count_documents(doc! {"group_id": 1}, None)
+        .await
+}
+
+// 占用 setup 锁：不存在或已过期时写入成功；锁被占用时 upsert 插入同一 _id 触发唯一键冲突，返回 false
+async fn claim_setup_lock(db: &Database) -> Result<bool, mongodb::error::Error> {
+    let now = mongodb::bson::DateTime::now();
+    let stale_before =
+        mongodb::bson::DateTime::from_millis(now.timestamp_millis() - SETUP_LOCK_TTL_SECS * 1000);
+    match db
+        .collection::<mongodb::bson::Document>("setup_locks")
+        .update_one(
+            doc! {"_id": SETUP_LOCK_ID, "claimed_at": {"$lt": stale_before}},
+            doc! {"$set": {"claimed_at": now}},
+            mongodb::options::UpdateOptions::builder().upsert(true).build(),
+        )
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) if e.to_string().contains("E11000 duplicate key error") => Ok(false),
+        Err(e) => Err(e),
     }
-    let collection = db.collection::<Type>("types");
-    let type_id: i32 = match path.into_inner().parse() {
-        Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest()
-                .json(json!({"success": false, "message": "Invalid type ID"}))
-        }
-    };
+}
 
-    match collection.delete_one(doc! {"type_id": type_id}, None).await {
-        Ok(result) => {
-            if result.deleted_count > 0 {
-                HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Type deleted successfully"}))
-            } else {
-                HttpResponse::NotFound()
-                    .json(json!({"success": false, "message": "Type not found"}))
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to delete type: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to delete type"}))
-        }
+async fn release_setup_lock(db: &Database) {
+    if let Err(e) = db
+        .collection::<mongodb::bson::Document>("setup_locks")
+        .delete_one(doc! {"_id": SETUP_LOCK_ID}, None)
+        .await
+    {
+        eprintln!("Failed to release setup lock: {}", e);
     }
 }
 
-// --- Binding Management API ---
-// DELETE /api/admin/bindings/{id}
-pub async fn delete_binding(
+// POST /api/admin/setup
+// 全新安装：初始化网站配置和基础分类（不含演示视频），并创建管理员账户。
+// 站点已有配置或分类时拒绝执行；已存在管理员账户时需要先登录，提交的同名管理员账户会改为提交的密码。
+// 查询失败时返回 500，不会按「没有管理员」跳过登录校验；未登录时持有 setup 锁执行，并发请求只有一个能创建管理员。
+pub async fn setup_site(
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    req: web::Json<SetupRequest>,
     session: Session,
-    path: web::Path<String>,
 ) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
+    if crate::init_data::is_site_initialized(&db).await {
+        return HttpResponse::Conflict().json(json!({
+            "success": false,
+            "message": "站点已初始化，无需重复执行"
+        }));
     }
-    let collection = db.collection::<Binding>("bindings");
-    let binding_id = path.into_inner();
 
-    match collection.delete_one(doc! {"_id": binding_id}, None).await {
-        Ok(result) => {
-            if result.deleted_count > 0 {
-                HttpResponse::Ok()
-                    .json(json!({"success": true, "message": "Binding deleted successfully"}))
-            } else {
-                HttpResponse::NotFound()
-                    .json(json!({"success": false, "message": "Binding not found"}))
-            }
-        }
+    let admin_count = match count_admins(&db).await {
+        Ok(count) => count,
         Err(e) => {
-            eprintln!("Failed to delete binding: {}", e);
-            HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to delete binding"}))
+            eprintln!("Failed to count admin users: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "查询管理员账户失败"
+            }));
         }
+    };
+    if admin_count > 0 {
+        if let Err(response) = check_auth(&session) {
+            return response;
+        }
+        return run_setup(&db, &site_data_manager, &req).await;
     }
-}
-// GET /api/admin/bindings
-pub async fn get_bindings(db: web::Data<Database>, session: Session) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
-    let collection = db.collection::<Binding>("bindings");
 
-    match collection.find(None, None).await {
-        Ok(cursor) => {
-            let bindings: Vec<Binding> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
-            HttpResponse::Ok().json(bindings)
+    match claim_setup_lock(&db).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Conflict().json(json!({
+                "success": false,
+                "message": "其他初始化请求正在执行，请稍后再试"
+            }));
         }
         Err(e) => {
-            eprintln!("Failed to fetch bindings: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch bindings")
+            eprintln!("Failed to claim setup lock: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "初始化站点失败"
+            }));
         }
     }
-}
-
-// GET /api/admin/collections/{id}/binding-status
-pub async fn get_collection_binding_status(
-    path: web::Path<String>,
-    db: web::Data<Database>,
-    session: Session,
-) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
 
-    let collection_id = match mongodb::bson::oid::ObjectId::parse_str(&path.into_inner()) {
-        Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest()
-                .json(json!({"success": false, "message": "Invalid collection ID"}))
+    // 拿到锁后再确认一次：之前持有锁的请求可能已经创建了管理员并完成初始化
+    let response = match count_admins(&db).await {
+        Ok(0) if !crate::init_data::is_site_initialized(&db).await => {
+            run_setup(&db, &site_data_manager, &req).await
+        }
+        Ok(_) => HttpResponse::Conflict().json(json!({
+            "success": false,
+            "message": "站点已初始化，无需重复执行"
+        })),
+        Err(e) => {
+            eprintln!("Failed to count admin users: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "查询管理员账户失败"
+            }))
         }
     };
+    release_setup_lock(&db).await;
+    response
+}
 
-    // 获取采集源配置
-    let collection = match db
-        .collection::<Collection>("collections")
-        .find_one(doc! {"_id": collection_id}, None)
+// 创建或更新管理员账户并写入基础数据；调用方负责登录校验或持有 setup 锁
+async fn run_setup(
+    db: &Database,
+    site_data_manager: &SiteDataManager,
+    req: &SetupRequest,
+) -> HttpResponse {
+    let users = db.collection::<crate::models::User>("users");
+    let admin_username = req
+        .admin_username
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("admin")
+        .to_string();
+    if req.admin_password.chars().count() < 8 {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "管理员密码至少需要8个字符"
+        }));
+    }
+
+    // 已存在的同名管理员改为提交的密码（能执行到这里说明已登录）；同名的非管理员账户不做修改
+    let (admin_created, admin_password_updated) = match users
+        .find_one(doc! {"user_name": &admin_username}, None)
         .await
     {
-        Ok(Some(c)) => c,
+        Ok(Some(user)) if user.group_id == 1 => {
+            let hashed = match bcrypt::hash(&req.admin_password, bcrypt::DEFAULT_COST) {
+                Ok(hashed) => hashed,
+                Err(e) => {
+                    eprintln!("Failed to hash admin password: {}", e);
+                    return HttpResponse::InternalServerError().json(json!({
+                        "success": false,
+                        "message": "设置管理员密码失败"
+                    }));
+                }
+            };
+            if let Err(e) = users
+                .update_one(
+                    doc! {"_id": user.id, "group_id": 1},
+                    doc! {"$set": {"user_pwd": hashed}},
+                    None,
+                )
+                .await
+            {
+                eprintln!("Failed to update admin password: {}", e);
+                return HttpResponse::InternalServerError().json(json!({
+                    "success": false,
+                    "message": "设置管理员密码失败"
+                }));
+            }
+            (false, true)
+        }
+        Ok(Some(_)) => {
+            return HttpResponse::Conflict().json(json!({
+                "success": false,
+                "message": "该用户名已被非管理员账户使用"
+            }));
+        }
         Ok(None) => {
-            return HttpResponse::NotFound()
-                .json(json!({"success": false, "message": "Collection not found"}))
+            if let Err(e) =
+                crate::auth::create_admin_user(db, &admin_username, &req.admin_password).await
+            {
+                eprintln!("Failed to create admin user: {}", e);
+                return HttpResponse::InternalServerError().json(json!({
+                    "success": false,
+                    "message": "创建管理员账户失败"
+                }));
+            }
+            (true, false)
         }
         Err(e) => {
-            eprintln!("Failed to fetch collection: {}", e);
-            return HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to fetch collection"}));
+            eprintln!("Failed to query admin user: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "查询管理员账户失败"
+            }));
         }
     };
 
-    // 检查是否有绑定的分类
-    let bindings_collection = db.collection::<Binding>("bindings");
-    let bindings_count = match bindings_collection
-        .count_documents(doc! {"source_flag": &collection.collect_name}, None)
-        .await
-    {
-        Ok(count) => count,
-        Err(e) => {
-            eprintln!("Failed to count bindings: {}", e);
-            return HttpResponse::InternalServerError()
-                .json(json!({"success": false, "message": "Failed to check bindings"}));
-        }
-    };
+    if let Err(e) = crate::init_data::init_essential_data(db).await.map_err(|e| e.to_string()) {
+        eprintln!("Setup failed: {}", e);
+        return HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("初始化站点数据失败: {}", e)
+        }));
+    }
 
-    let has_bindings = bindings_count > 0;
+    if let Err(e) = site_data_manager.refresh().await {
+        eprintln!("Cache refresh failed: {}", e);
+    }
 
     HttpResponse::Ok().json(json!({
         "success": true,
-        "has_bindings": has_bindings,
-        "bindings_count": bindings_count,
-        "source_flag": collection.collect_name,
-        "message": if has_bindings {
-            format!("已绑定 {} 个分类", bindings_count)
-        } else {
-            "请先绑定分类".to_string()
-        }
+        "message": "站点初始化完成",
+        "admin_username": admin_username,
+        "admin_created": admin_created,
+        "admin_password_updated": admin_password_updated
     }))
 }
 
-// POST /api/admin/bindings
-pub async fn create_or_update_binding(
-    db: web::Data<Database>,
-    binding_req: web::Json<BindingRequest>,
-    session: Session,
-) -> impl Responder {
+// --- Website Configuration Management API ---
+
+// GET /api/admin/configs
+pub async fn get_configs(db: web::Data<Database>, session: Session) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    let collection = db.collection::<Binding>("bindings");
-
-    let binding_id = format!("{}_{}", binding_req.source_flag, binding_req.external_id);
-
-    // Fetch local type name for the binding
-    let type_collection = db.collection::<Type>("types");
-    let local_type_name = match type_collection
-        .find_one(doc! {"type_id": binding_req.local_type_id}, None)
-        .await
-    {
-        Ok(Some(t)) => t.type_name,
-        _ => "Unknown Type".to_string(), // Default if type not found
-    };
-
-    let now = mongodb::bson::DateTime::now();
-    let new_binding = Binding {
-        id: binding_id.clone(),
-        source_flag: binding_req.source_flag.clone(),
-        external_id: binding_req.external_id.clone(),
-        local_type_id: binding_req.local_type_id,
-        local_type_name: local_type_name.clone(),
-        created_at: now,
-        updated_at: now,
-    };
+    let collection = db.collection::<Config>("configs");
+    let find_options = FindOptions::builder().sort(doc! {"config_sort": 1}).build();
 
-    match collection.insert_one(new_binding, None).await {
-        Ok(_) => HttpResponse::Created()
-            .json(json!({"success": true, "message": "Binding created/updated"})),
+    match collection.find(None, find_options).await {
+        Ok(cursor) => {
+            let configs: Vec<Config> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+            HttpResponse::Ok().json(configs)
+        }
         Err(e) => {
-            // If it's a duplicate key error, try to update instead (upsert behavior)
-            if e.to_string().contains("E11000 duplicate key error") {
-                let update_doc = doc! {"$set": {
-                    "source_flag": &binding_req.source_flag,
-                    "external_id": &binding_req.external_id,
-                    "local_type_id": binding_req.local_type_id,
-                    "local_type_name": local_type_name.clone(),
-                    "updated_at": mongodb::bson::DateTime::now()
-                }};
-                match collection
-                    .update_one(doc! {"_id": binding_id}, update_doc, None)
-                    .await
-                {
-                    Ok(_) => HttpResponse::Ok()
-                        .json(json!({"success": true, "message": "Binding updated"})),
-                    Err(e) => {
-                        eprintln!("Failed to update binding: {}", e);
-                        HttpResponse::InternalServerError()
-                            .json(json!({"success": false, "message": "Failed to update binding"}))
-                    }
-                }
-            } else {
-                eprintln!("Failed to create binding: {}", e);
-                HttpResponse::InternalServerError()
-                    .json(json!({"success": false, "message": "Failed to create binding"}))
-            }
+            eprintln!("Failed to fetch configs: {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch configs")
         }
     }
 }
 
-// --- Index Management API ---
-
-// POST /api/admin/indexes/create
-pub async fn create_indexes(db: web::Data<Database>, session: Session) -> impl Responder {
+// GET /api/admin/configs/{key}
+pub async fn get_config_by_key(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
+    let collection = db.collection::<Config>("configs");
+    let config_key = path.into_inner();
 
-    let index_manager = IndexManager::new(db.get_ref().clone());
-
-    match index_manager.create_all_indexes().await {
-        Ok(_) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "索引创建完成"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("索引创建失败: {}", e)
-        })),
+    match collection
+        .find_one(doc! {"config_key": &config_key}, None)
+        .await
+    {
+        Ok(Some(config)) => HttpResponse::Ok().json(config),
+        Ok(None) => HttpResponse::NotFound().body("Config not found"),
+        Err(e) => {
+            eprintln!("Failed to fetch config: {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch config")
+        }
     }
 }
 
-// GET /api/admin/indexes/status
-pub async fn get_index_status(db: web::Data<Database>, session: Session) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
-
-    let index_manager = IndexManager::new(db.get_ref().clone());
-
-    match index_manager.verify_indexes().await {
-        Ok(_) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "所有索引状态正常"
-        })),
-        Err(e) => HttpResponse::Ok().json(json!({
-            "success": false,
-            "message": format!("索引验证失败: {}", e)
-        })),
+// 保存前校验需要特定格式的配置值
+fn validate_config_value(config_key: &str, config_value: &str) -> Result<(), String> {
+    match config_key {
+        "collect_max_episodes_per_source" => {
+            crate::collect_handlers::parse_max_episodes_per_source(config_value).map(|_| ())
+        }
+        "collect_max_vod_doc_kb" => {
+            crate::collect_handlers::parse_max_vod_doc_kb(config_value).map(|_| ())
+        }
+        "collect_page_retries" => {
+            crate::collect_handlers::parse_collect_page_retries(config_value).map(|_| ())
+        }
+        "collect_max_consecutive_failures" => {
+            crate::collect_handlers::parse_max_consecutive_failures(config_value).map(|_| ())
+        }
+        "task_poll_interval_ms" => {
+            crate::task_poll::parse_task_poll_interval_ms(config_value).map(|_| ())
+        }
+        "task_auto_refresh" => crate::task_poll::parse_task_auto_refresh(config_value).map(|_| ()),
+        "feed_excluded_type_ids" => crate::site_data::parse_type_id_list(config_value).map(|_| ()),
+        "home_sections" => crate::site_data::parse_type_id_list(config_value).map(|_| ()),
+        "home_section_size" => crate::web_handlers::parse_home_section_size(config_value).map(|_| ()),
+        "api_daily_quota" => crate::api_quota::parse_api_daily_quota(config_value).map(|_| ()),
+        "api_rate_limit" => crate::api_rate_limit::parse_api_rate_limit(config_value).map(|_| ()),
+        "sitemap_cache_ttl" => crate::sitemap::parse_sitemap_cache_ttl(config_value).map(|_| ()),
+        "cache_ttl_seconds" => crate::site_data::parse_cache_ttl_seconds(config_value).map(|_| ()),
+        "login_max_attempts" => crate::login_lockout::parse_login_max_attempts(config_value).map(|_| ()),
+        "login_lockout_minutes" => {
+            crate::login_lockout::parse_login_lockout_minutes(config_value).map(|_| ())
+        }
+        "watch_progress_interval" => {
+            crate::watch_history::parse_watch_progress_interval(config_value).map(|_| ())
+        }
+        "canonical_host" => crate::canonical_host::parse_canonical_host(config_value).map(|_| ()),
+        "force_https" => crate::canonical_host::parse_force_https(config_value).map(|_| ()),
+        key if is_custom_html_key(key) => crate::site_data::validate_custom_html(config_value),
+        _ => Ok(()),
     }
 }
 
-// GET /api/admin/indexes/list
-pub async fn list_indexes(db: web::Data<Database>, session: Session) -> impl Responder {
+// POST /api/admin/configs
+pub async fn create_config(
+    db: web::Data<Database>,
+    config_req: web::Json<ConfigRequest>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
+    if is_custom_html_key(&config_req.config_key) {
+        if let Err(response) = check_admin_group(&db, &session).await {
+            return response;
+        }
+    }
+    if let Err(message) = validate_config_value(&config_req.config_key, &config_req.config_value) {
+        return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
+    }
+    let collection = db.collection::<Config>("configs");
 
-    let index_manager = IndexManager::new(db.get_ref().clone());
+    let new_config = Config {
+        id: None,
+        config_key: config_req.config_key.clone(),
+        config_value: config_req.config_value.clone(),
+        config_desc: config_req.config_desc.clone(),
+        config_type: config_req.config_type.clone(),
+        config_group: config_req.config_group.clone(),
+        config_sort: config_req.config_sort,
+        updated_at: mongodb::bson::DateTime::now(),
+    };
 
-    // 使用IndexManager的show_index_status方法获取索引信息
-    match index_manager.show_index_status().await {
+    match collection.insert_one(new_config, None).await {
         Ok(_) => {
-            // 返回简单的成功响应，详细状态在控制台输出
-            HttpResponse::Ok().json(json!({
-                "success": true,
-                "message": "索引状态已输出到控制台"
-            }))
+            HttpResponse::Created().json(json!({"success": true, "message": "Config created"}))
+        }
+        Err(e) => {
+            if e.to_string().contains("E11000 duplicate key error") {
+                HttpResponse::Conflict()
+                    .json(json!({"success": false, "message": "Config key already exists"}))
+            } else {
+                eprintln!("Failed to create config: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(json!({"success": false, "message": "Failed to create config"}))
+            }
         }
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("获取索引状态失败: {}", e)
-        })),
     }
 }
 
-// GET /api/admin/indexes/data
-pub async fn get_indexes_data(db: web::Data<Database>, session: Session) -> impl Responder {
+// PUT /api/admin/configs/{key}
+pub async fn update_config(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    config_req: web::Json<ConfigRequest>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-
-    let index_manager = IndexManager::new(db.get_ref().clone());
-    match index_manager.get_all_indexes().await {
-        Ok(indexes) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "data": indexes
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("获取索引数据失败: {}", e)
-        })),
+    let collection = db.collection::<Config>("configs");
+    let config_key = path.into_inner();
+    if is_custom_html_key(&config_key) {
+        if let Err(response) = check_admin_group(&db, &session).await {
+            return response;
+        }
+    }
+    if let Err(message) = validate_config_value(&config_key, &config_req.config_value) {
+        return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
     }
-}
 
-// GET /api/admin/statistics
-pub async fn get_statistics(db: web::Data<Database>, session: Session) -> impl Responder {
+    let update_doc = doc! {
+        "$set": {
+            "config_value": &config_req.config_value,
+            "config_desc": &config_req.config_desc,
+            "config_type": &config_req.config_type,
+            "config_group": &config_req.config_group,
+            "config_sort": config_req.config_sort,
+            "updated_at": mongodb::bson::DateTime::now(),
+        }
+    };
+
+    match collection
+        .update_one(doc! {"config_key": &config_key}, update_doc, None)
+        .await
+    {
+        Ok(result) => {
+            if result.matched_count > 0 {
+                if config_key == "collect_dedup_key" {
+                    // 唯一索引需要与去重方式一致，重建索引可能较慢，放到后台执行
+                    let index_manager = IndexManager::new(db.get_ref().clone());
+                    tokio::spawn(async move {
+                        if let Err(e) = index_manager.sync_vod_dedup_index().await {
+                            eprintln!("Failed to sync vod dedup index: {}", e);
+                        }
+                    });
+                }
+                HttpResponse::Ok()
+                    .json(json!({"success": true, "message": "Config updated successfully"}))
+            } else {
+                HttpResponse::NotFound()
+                    .json(json!({"success": false, "message": "Config not found"}))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to update config: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to update config"}))
+        }
+    }
+}
+
+// DELETE /api/admin/configs/{key}
+pub async fn delete_config(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-
-    let mut stats = json!({
-        "success": true,
-        "data": {
-            "vods": 0,
-            "types": 0,
-            "collections": 0,
-            "bindings": 0,
-            "configs": 0,
-            "users": 0
+    let collection = db.collection::<Config>("configs");
+    let config_key = path.into_inner();
+    if is_custom_html_key(&config_key) {
+        if let Err(response) = check_admin_group(&db, &session).await {
+            return response;
         }
-    });
+    }
 
-    // 获取视频数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("vods")
-        .count_documents(None, None)
+    match collection
+        .delete_one(doc! {"config_key": &config_key}, None)
         .await
     {
-        stats["data"]["vods"] = count.into();
+        Ok(result) => {
+            if result.deleted_count > 0 {
+                HttpResponse::Ok()
+                    .json(json!({"success": true, "message": "Config deleted successfully"}))
+            } else {
+                HttpResponse::NotFound()
+                    .json(json!({"success": false, "message": "Config not found"}))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to delete config: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to delete config"}))
+        }
     }
+}
 
-    // 获取分类数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("types")
-        .count_documents(None, None)
-        .await
-    {
-        stats["data"]["types"] = count.into();
+// POST /api/admin/types
+pub async fn create_type(
+    db: web::Data<Database>,
+    type_req: web::Json<TypeRequest>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
     }
+    let collection = db.collection::<Type>("types");
 
-    // 获取采集源数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("collections")
-        .count_documents(None, None)
+    // In a real system, you'd generate type_id and handle type_mid, etc.
+    // For simplicity, let's assume type_id is auto-incremented or managed externally for now.
+    // Or, query max type_id and increment.
+    let new_type_id = match collection
+        .find_one(
+            None,
+            FindOneOptions::builder().sort(doc! {"type_id": -1}).build(),
+        )
         .await
     {
-        stats["data"]["collections"] = count.into();
+        Ok(Some(last_type)) => last_type.type_id + 1,
+        _ => 1, // Start from 1 if no types exist
+    };
+
+    let new_type = Type {
+        id: None, // MongoDB will generate ObjectId
+        type_id: new_type_id,
+        type_name: type_req.type_name.clone(),
+        type_pid: type_req.type_pid,
+        type_en: type_req.type_en.clone(),
+        type_sort: type_req.type_sort.unwrap_or(0),
+        type_status: type_req.type_status.unwrap_or(1),
+        type_mid: type_req.type_mid,
+        type_key: type_req.type_key.clone(),
+        type_des: type_req.type_des.clone(),
+        type_title: type_req.type_title.clone(),
+        type_tpl: None,
+        type_tpl_list: None,
+        type_tpl_detail: None,
+        type_tpl_play: None,
+        type_tpl_down: None,
+        subarea: type_req.subarea.clone(),
+        subyear: type_req.subyear.clone(),
+        is_adult: type_req.is_adult.unwrap_or(false),
+    };
+
+    match collection.insert_one(new_type, None).await {
+        Ok(_) => HttpResponse::Created().json(json!({"success": true, "message": "Type created"})),
+        Err(e) => {
+            eprintln!("Failed to create type: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to create type"}))
+        }
     }
+}
 
-    // 获取绑定数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("bindings")
-        .count_documents(None, None)
-        .await
-    {
-        stats["data"]["bindings"] = count.into();
+// PUT /api/admin/types/{id}
+pub async fn update_type(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    type_req: web::Json<TypeRequest>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
     }
+    let collection = db.collection::<Type>("types");
+    let type_id: i32 = match path.into_inner().parse() {
+        Ok(id) => id,
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .json(json!({"success": false, "message": "Invalid type ID"}))
+        }
+    };
 
-    // 获取配置数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("configs")
-        .count_documents(None, None)
-        .await
-    {
-        stats["data"]["configs"] = count.into();
+    let mut update_fields = doc! {
+        "type_name": &type_req.type_name,
+        "type_pid": type_req.type_pid,
+    };
+
+    if let Some(ref type_en) = type_req.type_en {
+        update_fields.insert("type_en", type_en);
+    }
+    if let Some(type_sort) = type_req.type_sort {
+        update_fields.insert("type_sort", type_sort);
+    }
+    if let Some(type_status) = type_req.type_status {
+        update_fields.insert("type_status", type_status);
+    }
+    if let Some(type_mid) = type_req.type_mid {
+        update_fields.insert("type_mid", type_mid);
+    }
+    if let Some(ref type_key) = type_req.type_key {
+        update_fields.insert("type_key", type_key);
+    }
+    if let Some(ref type_des) = type_req.type_des {
+        update_fields.insert("type_des", type_des);
+    }
+    if let Some(ref type_title) = type_req.type_title {
+        update_fields.insert("type_title", type_title);
+    }
+    if let Some(ref subarea) = type_req.subarea {
+        update_fields.insert("subarea", subarea);
+    }
+    if let Some(ref subyear) = type_req.subyear {
+        update_fields.insert("subyear", subyear);
+    }
+    if let Some(is_adult) = type_req.is_adult {
+        update_fields.insert("is_adult", is_adult);
     }
 
-    // 获取用户数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("users")
-        .count_documents(None, None)
+    let update_doc = doc! {
+        "$set": update_fields
+    };
+
+    match collection
+        .update_one(doc! {"type_id": type_id}, update_doc, None)
         .await
     {
-        stats["data"]["users"] = count.into();
+        Ok(result) => {
+            if result.matched_count > 0 {
+                // 年龄验证等前台逻辑读取的是站点数据缓存，更新后立即刷新
+                if let Err(e) = site_data_manager.refresh().await {
+                    eprintln!("Cache refresh failed: {}", e);
+                }
+                HttpResponse::Ok()
+                    .json(json!({"success": true, "message": "Type updated successfully"}))
+            } else {
+                HttpResponse::NotFound()
+                    .json(json!({"success": false, "message": "Type not found"}))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to update type: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to update type"}))
+        }
     }
-
-    HttpResponse::Ok().json(stats)
 }
 
-// === 定时任务管理 API ===
-
-// GET /api/admin/scheduled-task/status
-pub async fn get_scheduled_task_status(
-    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+// DELETE /api/admin/types/{id}
+pub async fn delete_type(
+    path: web::Path<String>,
+    query: web::Query<DeleteTypeQuery>,
+    db: web::Data<Database>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
+    let collection = db.collection::<Type>("types");
+    let type_id: i32 = match path.into_inner().parse() {
+        Ok(id) => id,
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .json(json!({"success": false, "message": "Invalid type ID"}))
+        }
+    };
 
-    match task_manager.get_task_status().await {
-        Ok(status) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "data": status
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("获取定时任务状态失败: {}", e)
-        })),
+    // 仍有采集绑定指向该分类时拒绝删除，否则采集的视频会落到不存在的分类
+    if !query.force.unwrap_or(false) {
+        match db
+            .collection::<Binding>("bindings")
+            .count_documents(doc! {"local_type_id": type_id}, None)
+            .await
+        {
+            Ok(0) => {}
+            Ok(count) => {
+                return HttpResponse::Conflict().json(json!({
+                    "success": false,
+                    "message": format!("该分类仍有 {} 条采集绑定，请先调整绑定，或使用 force=true 强制删除", count),
+                    "binding_count": count
+                }))
+            }
+            Err(e) => {
+                eprintln!("Failed to count bindings: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(json!({"success": false, "message": "Failed to check bindings"}));
+            }
+        }
+    }
+
+    match collection.delete_one(doc! {"type_id": type_id}, None).await {
+        Ok(result) => {
+            if result.deleted_count > 0 {
+                HttpResponse::Ok()
+                    .json(json!({"success": true, "message": "Type deleted successfully"}))
+            } else {
+                HttpResponse::NotFound()
+                    .json(json!({"success": false, "message": "Type not found"}))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to delete type: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to delete type"}))
+        }
     }
 }
 
-// POST /api/admin/scheduled-task/start
-pub async fn start_scheduled_task(
-    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+// --- Binding Management API ---
+// DELETE /api/admin/bindings/{id}
+pub async fn delete_binding(
+    db: web::Data<Database>,
     session: Session,
+    path: web::Path<String>,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    match task_manager.start_scheduled_task().await {
-        Ok(_) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "定时采集任务已启动"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("启动定时任务失败: {}", e)
+    let collection = db.collection::<Binding>("bindings");
+    let binding_id = path.into_inner();
+
+    match collection.delete_one(doc! {"_id": binding_id}, None).await {
+        Ok(result) => {
+            if result.deleted_count > 0 {
+                HttpResponse::Ok()
+                    .json(json!({"success": true, "message": "Binding deleted successfully"}))
+            } else {
+                HttpResponse::NotFound()
+                    .json(json!({"success": false, "message": "Binding not found"}))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to delete binding: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to delete binding"}))
+        }
+    }
+}
+// GET /api/admin/bindings
+pub async fn get_bindings(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let collection = db.collection::<Binding>("bindings");
+
+    match collection.find(None, None).await {
+        Ok(cursor) => {
+            let bindings: Vec<Binding> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+            HttpResponse::Ok().json(bindings)
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch bindings: {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch bindings")
+        }
+    }
+}
+
+// 查找 local_type_id 已不存在的绑定
+async fn find_broken_bindings(db: &Database) -> Result<Vec<Binding>, mongodb::error::Error> {
+    let type_ids: Vec<mongodb::bson::Bson> = db
+        .collection::<Type>("types")
+        .distinct("type_id", None, None)
+        .await?;
+
+    db.collection::<Binding>("bindings")
+        .find(
+            doc! {"local_type_id": {"$nin": type_ids}},
+            FindOptions::builder().sort(doc! {"source_flag": 1, "external_id": 1}).build(),
+        )
+        .await?
+        .try_collect()
+        .await
+}
+
+// GET /api/admin/bindings/validate
+// 检查所有绑定的 local_type_id 是否仍存在，返回失效绑定列表
+pub async fn validate_bindings(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let total = match db.collection::<Binding>("bindings").count_documents(None, None).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count bindings: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to count bindings"}));
+        }
+    };
+
+    match find_broken_bindings(&db).await {
+        Ok(broken) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": if broken.is_empty() {
+                "所有绑定均指向存在的分类".to_string()
+            } else {
+                format!("{} 条绑定指向不存在的分类", broken.len())
+            },
+            "total": total,
+            "broken_count": broken.len(),
+            "broken": broken
+        })),
+        Err(e) => {
+            eprintln!("Failed to validate bindings: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to validate bindings"}))
+        }
+    }
+}
+
+// POST /api/admin/bindings/validate
+// 修复失效绑定：action=delete 删除，action=reassign 改绑到 to_type_id
+pub async fn fix_broken_bindings(
+    db: web::Data<Database>,
+    req: web::Json<FixBindingsRequest>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    // reassign 需要先确认目标分类存在
+    let target = match req.action.as_str() {
+        "delete" => None,
+        "reassign" => {
+            let to_type_id = match req.to_type_id {
+                Some(id) => id,
+                None => {
+                    return HttpResponse::BadRequest()
+                        .json(json!({"success": false, "message": "reassign 需要指定 to_type_id"}))
+                }
+            };
+            match db.collection::<Type>("types").find_one(doc! {"type_id": to_type_id}, None).await {
+                Ok(Some(t)) => Some(t),
+                Ok(None) => {
+                    return HttpResponse::BadRequest()
+                        .json(json!({"success": false, "message": "目标分类不存在"}))
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch type: {}", e);
+                    return HttpResponse::InternalServerError()
+                        .json(json!({"success": false, "message": "Failed to fetch type"}));
+                }
+            }
+        }
+        _ => {
+            return HttpResponse::BadRequest()
+                .json(json!({"success": false, "message": "action 只能是 delete 或 reassign"}))
+        }
+    };
+
+    let broken = match find_broken_bindings(&db).await {
+        Ok(broken) => broken,
+        Err(e) => {
+            eprintln!("Failed to validate bindings: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to validate bindings"}));
+        }
+    };
+    if broken.is_empty() {
+        return HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "没有需要修复的绑定",
+            "fixed": 0,
+            "ids": []
+        }));
+    }
+
+    let ids: Vec<String> = broken.into_iter().map(|b| b.id).collect();
+    let collection = db.collection::<Binding>("bindings");
+    let filter = doc! {"_id": {"$in": &ids}};
+    let result = match &target {
+        None => collection.delete_many(filter, None).await.map(|r| r.deleted_count),
+        Some(t) => collection
+            .update_many(
+                filter,
+                doc! {"$set": {
+                    "local_type_id": t.type_id,
+                    "local_type_name": &t.type_name,
+                    "updated_at": mongodb::bson::DateTime::now()
+                }},
+                None,
+            )
+            .await
+            .map(|r| r.modified_count),
+    };
+
+    match result {
+        Ok(fixed) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": match &target {
+                None => format!("已删除 {} 条失效绑定", fixed),
+                Some(t) => format!("已将 {} 条失效绑定改绑到「{}」", fixed, t.type_name),
+            },
+            "fixed": fixed,
+            "ids": ids
+        })),
+        Err(e) => {
+            eprintln!("Failed to fix bindings: {}", e);
+            HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to fix bindings"}))
+        }
+    }
+}
+
+// GET /api/admin/collections/{id}/binding-status
+pub async fn get_collection_binding_status(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    // 获取采集源配置
+    let collection = match db
+        .collection::<Collection>("collections")
+        .find_one(doc! {"_id": collection_id}, None)
+        .await
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(json!({"success": false, "message": "Collection not found"}))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch collection: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to fetch collection"}));
+        }
+    };
+
+    // 检查是否有绑定的分类
+    let bindings_collection = db.collection::<Binding>("bindings");
+    let bindings_count = match bindings_collection
+        .count_documents(doc! {"source_flag": &collection.collect_name}, None)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count bindings: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to check bindings"}));
+        }
+    };
+
+    let has_bindings = bindings_count > 0;
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "has_bindings": has_bindings,
+        "bindings_count": bindings_count,
+        "source_flag": collection.collect_name,
+        "message": if has_bindings {
+            format!("已绑定 {} 个分类", bindings_count)
+        } else {
+            "请先绑定分类".to_string()
+        }
+    }))
+}
+
+// GET /api/admin/collections/{id}/binding-coverage
+// 对比采集源当前的分类列表与已有绑定，返回已绑定和未绑定的分类
+pub async fn get_collection_binding_coverage(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let collection = match db
+        .collection::<Collection>("collections")
+        .find_one(doc! {"_id": collection_id}, None)
+        .await
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(json!({"success": false, "message": "Collection not found"}))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch collection: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to fetch collection"}));
+        }
+    };
+
+    let categories =
+        match crate::collect_handlers::fetch_source_categories(&collection).await {
+            Ok(categories) => categories,
+            Err(e) => {
+                eprintln!("Failed to fetch source categories: {}", e);
+                return HttpResponse::BadGateway().json(json!({
+                    "success": false,
+                    "message": format!("获取采集源分类失败: {}", e)
+                }));
+            }
+        };
+
+    let bindings: Vec<Binding> = match db
+        .collection::<Binding>("bindings")
+        .find(doc! {"source_flag": &collection.collect_name}, None)
+        .await
+    {
+        Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+        Err(e) => {
+            eprintln!("Failed to fetch bindings: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to check bindings"}));
+        }
+    };
+    let bindings_by_external: std::collections::HashMap<&str, &Binding> = bindings
+        .iter()
+        .map(|b| (b.external_id.as_str(), b))
+        .collect();
+
+    let mut covered = Vec::new();
+    let mut uncovered = Vec::new();
+    for category in &categories {
+        match bindings_by_external.get(category.type_id.to_string().as_str()) {
+            Some(binding) => covered.push(json!({
+                "type_id": category.type_id,
+                "type_name": category.type_name,
+                "local_type_id": binding.local_type_id,
+                "local_type_name": binding.local_type_name
+            })),
+            None => uncovered.push(json!({
+                "type_id": category.type_id,
+                "type_name": category.type_name
+            })),
+        }
+    }
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "source_flag": collection.collect_name,
+        "total": categories.len(),
+        "covered_count": covered.len(),
+        "uncovered_count": uncovered.len(),
+        "covered": covered,
+        "uncovered": uncovered
+    }))
+}
+
+// GET /api/admin/collections/{id}/categories
+// 按采集源ID在服务端获取上游分类列表（短时缓存），前端不需要拿到采集地址
+pub async fn get_collection_categories(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let collection_id = match resolve_object_id(&path.into_inner(), "collection") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let collection = match db
+        .collection::<Collection>("collections")
+        .find_one(doc! {"_id": collection_id}, None)
+        .await
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(json!({"success": false, "message": "Collection not found"}))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch collection: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to fetch collection"}));
+        }
+    };
+
+    match crate::collect_handlers::fetch_source_categories(&collection).await {
+        Ok(categories) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "source_flag": collection.collect_name,
+            "total": categories.len(),
+            "categories": categories
         })),
+        Err(e) => {
+            eprintln!("Failed to fetch source categories for {}: {}", collection.collect_name, e);
+            HttpResponse::BadGateway().json(json!({
+                "success": false,
+                "message": format!("获取采集源分类失败: {}", e)
+            }))
+        }
+    }
+}
+
+// POST /api/admin/bindings
+pub async fn create_or_update_binding(
+    db: web::Data<Database>,
+    binding_req: web::Json<BindingRequest>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let collection = db.collection::<Binding>("bindings");
+
+    // Fetch local type name for the binding
+    let type_collection = db.collection::<Type>("types");
+    let local_type_name = match type_collection
+        .find_one(doc! {"type_id": binding_req.local_type_id}, None)
+        .await
+    {
+        Ok(t) => t.map(|t| t.type_name),
+        Err(e) => {
+            eprintln!("Failed to fetch type: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to fetch type"}));
+        }
+    };
+
+    let valid = match validate_binding_request(&binding_req, local_type_name.as_deref()) {
+        Ok(valid) => valid,
+        Err(message) => return HttpResponse::BadRequest().json(json!({"success": false, "message": message})),
+    };
+    let binding_id = valid.id.clone();
+    let local_type_name = local_type_name.unwrap_or_default();
+
+    let now = mongodb::bson::DateTime::now();
+    let new_binding = Binding {
+        id: binding_id.clone(),
+        source_flag: valid.source_flag.to_string(),
+        external_id: valid.external_id.to_string(),
+        local_type_id: binding_req.local_type_id,
+        local_type_name: local_type_name.clone(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    match collection.insert_one(new_binding, None).await {
+        Ok(_) => HttpResponse::Created()
+            .json(json!({"success": true, "message": "Binding created/updated"})),
+        Err(e) => {
+            // If it's a duplicate key error, try to update instead (upsert behavior)
+            if e.to_string().contains("E11000 duplicate key error") {
+                let update_doc = doc! {"$set": {
+                    "source_flag": valid.source_flag,
+                    "external_id": valid.external_id,
+                    "local_type_id": binding_req.local_type_id,
+                    "local_type_name": local_type_name.clone(),
+                    "updated_at": mongodb::bson::DateTime::now()
+                }};
+                match collection
+                    .update_one(doc! {"_id": binding_id}, update_doc, None)
+                    .await
+                {
+                    Ok(_) => HttpResponse::Ok()
+                        .json(json!({"success": true, "message": "Binding updated"})),
+                    Err(e) => {
+                        eprintln!("Failed to update binding: {}", e);
+                        HttpResponse::InternalServerError()
+                            .json(json!({"success": false, "message": "Failed to update binding"}))
+                    }
+                }
+            } else {
+                eprintln!("Failed to create binding: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(json!({"success": false, "message": "Failed to create binding"}))
+            }
+        }
+    }
+}
+
+// 单次批量绑定的最大条数
+const MAX_BATCH_BINDINGS: usize = 1000;
+
+// POST /api/admin/bindings/batch
+// 一次写入多条绑定：逐条校验后用一条 update 命令（upsert）提交，按原始下标返回每条结果
+pub async fn batch_create_bindings(
+    db: web::Data<Database>,
+    binding_reqs: web::Json<Vec<BindingRequest>>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let binding_reqs = binding_reqs.into_inner();
+    if binding_reqs.is_empty() || binding_reqs.len() > MAX_BATCH_BINDINGS {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": format!("绑定数量需在 1 到 {} 之间", MAX_BATCH_BINDINGS)
+        }));
+    }
+
+    // 一次加载全部分类，用于校验 local_type_id 并取分类名称
+    let type_names: std::collections::HashMap<i32, String> = match db.collection::<Type>("types").find(None, None).await {
+        Ok(cursor) => cursor
+            .try_collect::<Vec<Type>>()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| (t.type_id, t.type_name))
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to load types: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "Failed to load types"}));
+        }
+    };
+
+    let now = mongodb::bson::DateTime::now();
+    let mut results: Vec<serde_json::Value> = Vec::with_capacity(binding_reqs.len());
+    let mut seen_ids: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut updates: Vec<Document> = Vec::new();
+    let mut update_indices: Vec<usize> = Vec::new(); // updates[i] 对应的请求下标
+
+    for (index, req) in binding_reqs.iter().enumerate() {
+        let validated = validate_binding_request(req, type_names.get(&req.local_type_id).map(String::as_str))
+            .and_then(|valid| match seen_ids.get(&valid.id) {
+                Some(first) => Err(format!("与第 {} 条重复", first)),
+                None => Ok(valid),
+            });
+
+        match validated {
+            Err(message) => results.push(json!({
+                "index": index,
+                "id": format!("{}_{}", req.source_flag.trim(), req.external_id.trim()),
+                "status": "failed",
+                "message": message
+            })),
+            Ok(ValidBinding { id: binding_id, source_flag, external_id }) => {
+                seen_ids.insert(binding_id.clone(), index);
+                updates.push(doc! {
+                    "q": {"_id": &binding_id},
+                    "u": {
+                        "$set": {
+                            "source_flag": source_flag,
+                            "external_id": external_id,
+                            "local_type_id": req.local_type_id,
+                            "local_type_name": &type_names[&req.local_type_id],
+                            "updated_at": now
+                        },
+                        "$setOnInsert": {"created_at": now}
+                    },
+                    "upsert": true
+                });
+                update_indices.push(index);
+                // 先记为 updated，命令返回后再改成 created / failed
+                results.push(json!({"index": index, "id": binding_id, "status": "updated"}));
+            }
+        }
+    }
+
+    if !updates.is_empty() {
+        let command = doc! {"update": "bindings", "updates": updates, "ordered": false};
+        let reply = match db.run_command(command, None).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                eprintln!("Failed to write bindings: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(json!({"success": false, "message": "Failed to write bindings"}));
+            }
+        };
+
+        // upserted / writeErrors 中的 index 是 updates 数组的下标
+        let entry_index = |entry: &Document| -> Option<usize> {
+            let i = entry.get_i32("index").ok()? as usize;
+            update_indices.get(i).copied()
+        };
+        if let Ok(upserted) = reply.get_array("upserted") {
+            for entry in upserted.iter().filter_map(|b| b.as_document()) {
+                if let Some(index) = entry_index(entry) {
+                    results[index]["status"] = json!("created");
+                }
+            }
+        }
+        if let Ok(write_errors) = reply.get_array("writeErrors") {
+            for entry in write_errors.iter().filter_map(|b| b.as_document()) {
+                if let Some(index) = entry_index(entry) {
+                    results[index]["status"] = json!("failed");
+                    results[index]["message"] = json!(entry.get_str("errmsg").unwrap_or("write failed"));
+                }
+            }
+        }
+    }
+
+    let count = |status: &str| results.iter().filter(|r| r["status"] == status).count();
+    let (created, updated, failed) = (count("created"), count("updated"), count("failed"));
+    let failed_indices: Vec<usize> = results
+        .iter()
+        .filter(|r| r["status"] == "failed")
+        .filter_map(|r| r["index"].as_u64().map(|i| i as usize))
+        .collect();
+
+    HttpResponse::Ok().json(json!({
+        "success": failed == 0,
+        "message": format!("新增 {} 条，更新 {} 条，失败 {} 条", created, updated, failed),
+        "created": created,
+        "updated": updated,
+        "failed": failed,
+        "failed_indices": failed_indices,
+        "results": results
+    }))
+}
+
+// --- Site Bundle (export / import) ---
+
+// 整站配置包：网站配置、分类、采集源和分类绑定（不含视频），用于把整套设置迁移到新服务器
+const SITE_BUNDLE_VERSION: i32 = 1;
+pub const MAX_SITE_BUNDLE_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SiteBundle {
+    pub version: i32,
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    #[serde(default)]
+    pub configs: Vec<Config>,
+    #[serde(default)]
+    pub types: Vec<Type>,
+    #[serde(default)]
+    pub collections: Vec<Collection>,
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SiteBundleImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+async fn load_all<T>(db: &Database, name: &str) -> Result<Vec<T>, mongodb::error::Error>
+where
+    T: serde::de::DeserializeOwned + Unpin + Send + Sync,
+{
+    db.collection::<T>(name).find(None, None).await?.try_collect().await
+}
+
+// GET /api/admin/export/bundle
+pub async fn export_site_bundle(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let loaded = async {
+        Ok::<_, mongodb::error::Error>((
+            load_all::<Config>(&db, "configs").await?,
+            load_all::<Type>(&db, "types").await?,
+            load_all::<Collection>(&db, "collections").await?,
+            load_all::<Binding>(&db, "bindings").await?,
+        ))
+    }
+    .await;
+    let (mut configs, mut types, mut collections, mut bindings) = match loaded {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to export site bundle: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "导出失败"}));
+        }
+    };
+
+    // ObjectId 与数据库实例绑定，导入时按业务键匹配
+    configs.iter_mut().for_each(|c| c.id = None);
+    types.iter_mut().for_each(|t| t.id = None);
+    collections.iter_mut().for_each(|c| c.id = None);
+    // 代理账号密码不写入配置包，导入后需重新填写
+    collections
+        .iter_mut()
+        .for_each(|c| c.collect_proxy = crate::collect_client::redact_proxy_credentials(&c.collect_proxy));
+    configs.sort_by_key(|c| (c.config_group.clone(), c.config_sort, c.config_key.clone()));
+    types.sort_by_key(|t| t.type_id);
+    collections.sort_by(|a, b| a.collect_name.cmp(&b.collect_name));
+    bindings.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let now = chrono::Local::now();
+    let bundle = SiteBundle {
+        version: SITE_BUNDLE_VERSION,
+        exported_at: Some(now.to_rfc3339()),
+        configs,
+        types,
+        collections,
+        bindings,
+    };
+
+    HttpResponse::Ok()
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"maccms-bundle-{}.json\"", now.format("%Y%m%d%H%M%S")),
+        ))
+        .json(bundle)
+}
+
+// 导入前已存在的业务键，用于校验引用和统计新增/更新数量
+struct ExistingSiteData {
+    config_keys: std::collections::HashSet<String>,
+    type_names: std::collections::HashMap<i32, String>,
+    type_parents: std::collections::HashMap<i32, i32>,
+    collect_names: std::collections::HashSet<String>,
+    binding_ids: std::collections::HashSet<String>,
+}
+
+impl ExistingSiteData {
+    async fn load(db: &Database) -> Result<Self, mongodb::error::Error> {
+        let types = load_all::<Type>(db, "types").await?;
+        Ok(ExistingSiteData {
+            config_keys: load_all::<Config>(db, "configs")
+                .await?
+                .into_iter()
+                .map(|c| c.config_key)
+                .collect(),
+            type_parents: types.iter().map(|t| (t.type_id, t.type_pid)).collect(),
+            type_names: types.into_iter().map(|t| (t.type_id, t.type_name)).collect(),
+            collect_names: load_all::<Collection>(db, "collections")
+                .await?
+                .into_iter()
+                .map(|c| c.collect_name)
+                .collect(),
+            binding_ids: load_all::<Binding>(db, "bindings")
+                .await?
+                .into_iter()
+                .map(|b| b.id)
+                .collect(),
+        })
+    }
+}
+
+#[derive(Default)]
+struct BundleReport {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+// 校验整个配置包并规范化绑定（重算 _id 和分类名称）；有错误时不写入任何数据
+fn validate_site_bundle(
+    bundle: &mut SiteBundle,
+    existing: &ExistingSiteData,
+    allow_custom_html: bool,
+) -> BundleReport {
+    let mut report = BundleReport::default();
+
+    if bundle.version != SITE_BUNDLE_VERSION {
+        report
+            .errors
+            .push(format!("不支持的配置包版本 {}，当前版本为 {}", bundle.version, SITE_BUNDLE_VERSION));
+        return report;
+    }
+
+    let mut config_keys = std::collections::HashSet::new();
+    for config in &bundle.configs {
+        let key = config.config_key.trim();
+        if key.is_empty() {
+            report.errors.push("configs: 存在空的 config_key".to_string());
+        } else if !config_keys.insert(key.to_string()) {
+            report.errors.push(format!("configs: {} 重复", key));
+        } else if is_custom_html_key(key) && !allow_custom_html {
+            report
+                .errors
+                .push(format!("configs: {} 只有管理员组账号可以导入", key));
+        } else if let Err(message) = validate_config_value(key, &config.config_value) {
+            report.errors.push(format!("configs: {}: {}", key, message));
+        }
+    }
+
+    // 分类名称：配置包中的优先，其余沿用数据库中已有的分类
+    let mut type_names = existing.type_names.clone();
+    let mut bundle_type_ids = std::collections::HashSet::new();
+    for t in &bundle.types {
+        if t.type_name.trim().is_empty() {
+            report.errors.push(format!("types: 分类 {} 名称为空", t.type_id));
+        }
+        if !bundle_type_ids.insert(t.type_id) {
+            report.errors.push(format!("types: 分类 {} 重复", t.type_id));
+        }
+        type_names.insert(t.type_id, t.type_name.clone());
+    }
+    for t in &bundle.types {
+        if t.type_pid == t.type_id {
+            report.errors.push(format!("types: 分类 {} 的上级分类不能是自己", t.type_id));
+        } else if t.type_pid != 0 && !type_names.contains_key(&t.type_pid) {
+            report.errors.push(format!(
+                "types: 分类 {} 的上级分类 {} 不存在",
+                t.type_id, t.type_pid
+            ));
+        }
+    }
+    // 上级分类链不能成环（含与数据库中已有分类组成的环），否则分类树和面包屑会无限递归
+    let mut type_parents = existing.type_parents.clone();
+    type_parents.extend(bundle.types.iter().map(|t| (t.type_id, t.type_pid)));
+    for t in &bundle.types {
+        if t.type_pid == t.type_id {
+            continue;
+        }
+        let mut visited = std::collections::HashSet::new();
+        let mut current = t.type_pid;
+        while current != 0 && visited.insert(current) {
+            if current == t.type_id {
+                report
+                    .errors
+                    .push(format!("types: 分类 {} 的上级分类链存在循环", t.type_id));
+                break;
+            }
+            current = type_parents.get(&current).copied().unwrap_or(0);
+        }
+    }
+
+    let mut collect_names = existing.collect_names.clone();
+    let mut bundle_collect_names = std::collections::HashSet::new();
+    for c in &bundle.collections {
+        let name = c.collect_name.trim();
+        if name.is_empty() || c.collect_url.trim().is_empty() {
+            report
+                .errors
+                .push("collections: collect_name 和 collect_url 不能为空".to_string());
+            continue;
+        }
+        if !bundle_collect_names.insert(name.to_string()) {
+            report.errors.push(format!("collections: {} 重复", name));
+        }
+        if !(MIN_COLLECT_TIMEOUT_SECS..=MAX_COLLECT_TIMEOUT_SECS).contains(&c.collect_timeout_secs) {
+            report.errors.push(format!(
+                "collections: {} 的超时时间需在 {}-{} 秒之间",
+                name, MIN_COLLECT_TIMEOUT_SECS, MAX_COLLECT_TIMEOUT_SECS
+            ));
+        }
+        if !(MIN_COLLECT_CONCURRENCY..=MAX_COLLECT_CONCURRENCY).contains(&c.collect_concurrency) {
+            report.errors.push(format!(
+                "collections: {} 的并发页数需在 {}-{} 之间",
+                name, MIN_COLLECT_CONCURRENCY, MAX_COLLECT_CONCURRENCY
+            ));
+        }
+        if let Err(message) = crate::models::parse_collect_window(&c.collect_window) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        if let Err(message) = crate::collect_handlers::parse_collect_filter(&c.collect_filter) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        if let Err(message) = crate::collect_handlers::validate_field_mapping(&c.field_mapping) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        if let Err(message) = crate::collect_client::parse_collect_proxy(&c.collect_proxy) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        if let Err(message) = crate::collect_client::validate_header_value("collect_user_agent", &c.collect_user_agent) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        if let Err(message) = crate::collect_client::validate_header_value("collect_referer", &c.collect_referer) {
+            report.errors.push(format!("collections: {}: {}", name, message));
+        }
+        collect_names.insert(name.to_string());
+    }
+
+    let mut binding_ids = std::collections::HashSet::new();
+    for binding in &mut bundle.bindings {
+        let source_flag = binding.source_flag.trim().to_string();
+        let external_id = binding.external_id.trim().to_string();
+        if source_flag.is_empty() || external_id.is_empty() {
+            report
+                .errors
+                .push("bindings: source_flag 和 external_id 不能为空".to_string());
+            continue;
+        }
+        binding.id = format!("{}_{}", source_flag, external_id);
+        if !binding_ids.insert(binding.id.clone()) {
+            report.errors.push(format!("bindings: {} 重复", binding.id));
+        }
+        match type_names.get(&binding.local_type_id) {
+            Some(name) => binding.local_type_name = name.clone(),
+            None => report.errors.push(format!(
+                "bindings: {} 绑定的分类 {} 不存在",
+                binding.id, binding.local_type_id
+            )),
+        }
+        // 采集源可能稍后再添加，不阻止导入
+        if !collect_names.contains(&source_flag) {
+            report
+                .warnings
+                .push(format!("bindings: {} 对应的采集源 {} 不存在", binding.id, source_flag));
+        }
+        binding.source_flag = source_flag;
+        binding.external_id = external_id;
+    }
+
+    report
+}
+
+// 按业务键统计新增和更新的数量
+fn bundle_summary(bundle: &SiteBundle, existing: &ExistingSiteData) -> serde_json::Value {
+    let count = |total: usize, existed: usize| json!({"created": total - existed, "updated": existed});
+    json!({
+        "configs": count(
+            bundle.configs.len(),
+            bundle.configs.iter().filter(|c| existing.config_keys.contains(c.config_key.trim())).count()
+        ),
+        "types": count(
+            bundle.types.len(),
+            bundle.types.iter().filter(|t| existing.type_names.contains_key(&t.type_id)).count()
+        ),
+        "collections": count(
+            bundle.collections.len(),
+            bundle.collections.iter().filter(|c| existing.collect_names.contains(c.collect_name.trim())).count()
+        ),
+        "bindings": count(
+            bundle.bindings.len(),
+            bundle.bindings.iter().filter(|b| existing.binding_ids.contains(&b.id)).count()
+        ),
+    })
+}
+
+// 按依赖顺序写入：配置 -> 分类 -> 采集源 -> 绑定；已存在的记录按业务键整条替换，配置包中没有的记录保持不变
+async fn write_site_bundle(db: &Database, bundle: SiteBundle) -> Vec<String> {
+    let mut errors = Vec::new();
+    let now = mongodb::bson::DateTime::now();
+    let upsert = || mongodb::options::ReplaceOptions::builder().upsert(true).build();
+
+    let configs = db.collection::<Config>("configs");
+    for mut config in bundle.configs {
+        config.config_key = config.config_key.trim().to_string();
+        config.updated_at = now;
+        if let Err(e) = configs
+            .replace_one(doc! {"config_key": &config.config_key}, &config, upsert())
+            .await
+        {
+            errors.push(format!("configs: {} 写入失败: {}", config.config_key, e));
+        }
+    }
+
+    let types = db.collection::<Type>("types");
+    for t in bundle.types {
+        if let Err(e) = types.replace_one(doc! {"type_id": t.type_id}, &t, upsert()).await {
+            errors.push(format!("types: 分类 {} 写入失败: {}", t.type_id, e));
+        }
+    }
+
+    let collections = db.collection::<Collection>("collections");
+    for mut c in bundle.collections {
+        c.collect_name = c.collect_name.trim().to_string();
+        c.updated_at = now;
+        if let Err(e) = collections
+            .replace_one(doc! {"collect_name": &c.collect_name}, &c, upsert())
+            .await
+        {
+            errors.push(format!("collections: {} 写入失败: {}", c.collect_name, e));
+        }
+    }
+
+    let bindings = db.collection::<Binding>("bindings");
+    for mut binding in bundle.bindings {
+        binding.updated_at = now;
+        if let Err(e) = bindings
+            .replace_one(doc! {"_id": &binding.id}, &binding, upsert())
+            .await
+        {
+            errors.push(format!("bindings: {} 写入失败: {}", binding.id, e));
+        }
+    }
+
+    errors
+}
+
+// POST /api/admin/import/bundle?dry_run=true
+pub async fn import_site_bundle(
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    query: web::Query<SiteBundleImportQuery>,
+    bundle: web::Json<SiteBundle>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let mut bundle = bundle.into_inner();
+    let existing = match ExistingSiteData::load(&db).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            eprintln!("Failed to load site data for bundle import: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(json!({"success": false, "message": "读取现有数据失败"}));
+        }
+    };
+
+    let has_custom_html = bundle.configs.iter().any(|c| is_custom_html_key(c.config_key.trim()));
+    let allow_custom_html = !has_custom_html || check_admin_group(&db, &session).await.is_ok();
+    let report = validate_site_bundle(&mut bundle, &existing, allow_custom_html);
+    let summary = bundle_summary(&bundle, &existing);
+
+    if !report.errors.is_empty() {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": format!("配置包校验失败（{} 个问题），未写入任何数据", report.errors.len()),
+            "dry_run": query.dry_run,
+            "summary": summary,
+            "errors": report.errors,
+            "warnings": report.warnings
+        }));
+    }
+
+    if query.dry_run {
+        return HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "校验通过（试运行，未写入数据）",
+            "dry_run": true,
+            "summary": summary,
+            "errors": [],
+            "warnings": report.warnings
+        }));
+    }
+
+    let write_errors = write_site_bundle(&db, bundle).await;
+    if let Err(e) = site_data_manager.refresh().await {
+        eprintln!("Cache refresh failed: {}", e);
+    }
+
+    HttpResponse::Ok().json(json!({
+        "success": write_errors.is_empty(),
+        "message": if write_errors.is_empty() {
+            "导入完成".to_string()
+        } else {
+            format!("导入完成，{} 条记录写入失败", write_errors.len())
+        },
+        "dry_run": false,
+        "summary": summary,
+        "errors": write_errors,
+        "warnings": report.warnings
+    }))
+}
+
+// --- Index Management API ---
+
+// POST /api/admin/indexes/create
+pub async fn create_indexes(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let index_manager = IndexManager::new(db.get_ref().clone());
+
+    match index_manager.create_all_indexes().await {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "索引创建完成"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("索引创建失败: {}", e)
+        })),
+    }
+}
+
+// GET /api/admin/indexes/status
+pub async fn get_index_status(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let index_manager = IndexManager::new(db.get_ref().clone());
+
+    match index_manager.verify_indexes().await {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "所有索引状态正常"
+        })),
+        Err(e) => HttpResponse::Ok().json(json!({
+            "success": false,
+            "message": format!("索引验证失败: {}", e)
+        })),
+    }
+}
+
+// GET /api/admin/indexes/list
+pub async fn list_indexes(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let index_manager = IndexManager::new(db.get_ref().clone());
+
+    // 使用IndexManager的show_index_status方法获取索引信息
+    match index_manager.show_index_status().await {
+        Ok(_) => {
+            // 返回简单的成功响应，详细状态在控制台输出
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "message": "索引状态已输出到控制台"
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("获取索引状态失败: {}", e)
+        })),
+    }
+}
+
+// GET /api/admin/indexes/data
+pub async fn get_indexes_data(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let index_manager = IndexManager::new(db.get_ref().clone());
+    match index_manager.get_all_indexes().await {
+        Ok(indexes) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "data": indexes
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("获取索引数据失败: {}", e)
+        })),
+    }
+}
+
+// GET /api/admin/statistics
+pub async fn get_statistics(
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let ttl = cache::count_cache_ttl(&site_data_manager).await;
+
+    let counts = cache::SiteCounts::load(&db, ttl).await;
+
+    let stats = json!({
+        "success": true,
+        "data": {
+            "vods": counts.vods,
+            "types": counts.types,
+            "collections": counts.collections,
+            "bindings": counts.bindings,
+            "configs": counts.configs,
+            "users": counts.users,
+            "vods_by_status": {
+                "published": counts.vods_published,
+                "hidden": counts.vods_hidden
+            },
+            "vods_trashed": counts.vods_trashed
+        }
+    });
+
+    HttpResponse::Ok().json(stats)
+}
+
+// === 定时任务管理 API ===
+
+// GET /api/admin/scheduled-task/status
+pub async fn get_scheduled_task_status(
+    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    match task_manager.get_task_status().await {
+        Ok(status) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "data": status
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("获取定时任务状态失败: {}", e)
+        })),
+    }
+}
+
+// POST /api/admin/scheduled-task/start
+pub async fn start_scheduled_task(
+    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    match task_manager.start_scheduled_task().await {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "定时采集任务已启动"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("启动定时任务失败: {}", e)
+        })),
+    }
+}
+
+// POST /api/admin/scheduled-task/stop
+pub async fn stop_scheduled_task(
+    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    match task_manager.stop_scheduled_task().await {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "定时采集任务已停止"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("停止定时任务失败: {}", e)
+        })),
+    }
+}
+
+// PUT /api/admin/scheduled-task/config
+#[derive(Debug, Deserialize)]
+pub struct ScheduledTaskConfigRequest {
+    pub enabled: Option<bool>, // 修改全局配置时必填
+    pub interval_hours: Option<i32>,
+    pub cron: Option<String>, // 空字符串表示改回按间隔运行
+    // 传入时只设置该采集源的间隔：interval_hours 为该源自己的间隔，不传或为 null 表示删除覆盖、跟随全局配置
+    pub collection_id: Option<String>,
+}
+
+pub async fn update_scheduled_task_config(
+    db: web::Data<Database>,
+    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+    session: Session,
+    config: web::Json<ScheduledTaskConfigRequest>,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    let config = config.into_inner();
+    if let Some(hours) = config.interval_hours {
+        if !(crate::scheduled_task::MIN_INTERVAL_HOURS..=crate::scheduled_task::MAX_INTERVAL_HOURS).contains(&hours) {
+            return HttpResponse::BadRequest().json(json!({
+                "success": false,
+                "message": format!(
+                    "间隔时间需在 {}-{} 小时之间",
+                    crate::scheduled_task::MIN_INTERVAL_HOURS,
+                    crate::scheduled_task::MAX_INTERVAL_HOURS
+                )
+            }));
+        }
+    }
+
+    if let Some(collection_id) = config.collection_id.as_deref() {
+        let collection_id = match resolve_object_id(collection_id, "collection") {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
+        match db.collection::<Collection>("collections").count_documents(doc! {"_id": collection_id}, None).await {
+            Ok(0) => {
+                return HttpResponse::NotFound().json(json!({"success": false, "message": "采集源不存在"}));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(json!({
+                    "success": false,
+                    "message": format!("查询采集源失败: {}", e)
+                }));
+            }
+        }
+        return match task_manager.update_source_config(collection_id, config.interval_hours).await {
+            Ok(()) => HttpResponse::Ok().json(json!({
+                "success": true,
+                "message": match config.interval_hours {
+                    Some(hours) => format!("采集源定时间隔已设置为 {} 小时", hours),
+                    None => "采集源已改回跟随全局定时配置".to_string(),
+                }
+            })),
+            Err(e) => HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": format!("更新配置失败: {}", e)
+            })),
+        };
+    }
+
+    let Some(enabled) = config.enabled else {
+        return HttpResponse::BadRequest().json(json!({"success": false, "message": "缺少 enabled"}));
+    };
+    if let Some(expr) = config.cron.as_deref().filter(|e| !e.trim().is_empty()) {
+        if let Err(message) = crate::scheduled_task::parse_cron(expr) {
+            return HttpResponse::BadRequest().json(json!({"success": false, "message": message}));
+        }
+    }
+    match task_manager.update_config(enabled, config.interval_hours, config.cron).await {
+        Ok(true) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "定时任务配置已更新"
+        })),
+        Ok(false) => HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "配置更新失败"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("更新配置失败: {}", e)
+        })),
+    }
+}
+
+// GET /api/admin/scheduled-task/logs
+pub async fn get_scheduled_task_logs(
+    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+    session: Session,
+    query: web::Query<ScheduledTaskLogsQuery>,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+    match task_manager.get_task_logs(query.limit).await {
+        Ok(logs) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "data": logs
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": format!("获取任务日志失败: {}", e)
+        })),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduledTaskLogsQuery {
+    pub limit: Option<i32>,
+}
+
+// --- Batch Delete Source API ---
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchDeleteSourceRequest {
+    pub source_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchDeleteProgress {
+    pub status: String,
+    pub processed_count: u64,
+    pub deleted_count: u64,
+    pub total_count: u64,
+    pub log: String,
+}
+
+impl Default for BatchDeleteProgress {
+    fn default() -> Self {
+        Self {
+            status: "unknown".to_string(),
+            processed_count: 0,
+            deleted_count: 0,
+            total_count: 0,
+            log: "未知状态".to_string(),
+        }
+    }
+}
+
+// 类型别名简化复杂类型
+type BatchDeleteProgressMap = std::collections::HashMap<
+    String,
+    (BatchDeleteProgress, String, Option<tokio::task::JoinHandle<()>>),
+>;
+type BatchDeleteProgressStore = tokio::sync::RwLock<BatchDeleteProgressMap>;
+
+// 全局批量删除任务进度存储
+static BATCH_DELETE_PROGRESS: std::sync::OnceLock<BatchDeleteProgressStore> = std::sync::OnceLock::new();
+
+// 初始化批量删除任务进度存储
+fn get_batch_delete_progress_store() -> &'static BatchDeleteProgressStore {
+    BATCH_DELETE_PROGRESS.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
+}
+
+// 获取批量删除任务进度
+pub async fn get_batch_delete_progress(task_id: &str) -> Option<BatchDeleteProgress> {
+    let store = get_batch_delete_progress_store();
+    let progress_map = store.read().await;
+    progress_map
+        .get(task_id)
+        .map(|(progress, _, _)| progress.clone())
+}
+
+// 批量删除任务失败时间（任务进度只在内存中，最近失败列表据此排序）
+static BATCH_DELETE_FAILED_AT: std::sync::OnceLock<
+    tokio::sync::RwLock<std::collections::HashMap<String, mongodb::bson::DateTime>>,
+> = std::sync::OnceLock::new();
+
+fn get_batch_delete_failed_at_store(
+) -> &'static tokio::sync::RwLock<std::collections::HashMap<String, mongodb::bson::DateTime>> {
+    BATCH_DELETE_FAILED_AT.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
+}
+
+// 记录批量删除任务的失败时间。最近失败列表最多只能翻到 MAX_FAILURE_DEPTH 条，
+// 超出时淘汰最早的记录，进度已不在内存中的任务也一并清理
+async fn record_batch_delete_failure(task_id: &str) {
+    let known: std::collections::HashSet<String> =
+        get_batch_delete_progress_store().read().await.keys().cloned().collect();
+
+    let mut failed_at = get_batch_delete_failed_at_store().write().await;
+    failed_at.retain(|id, _| known.contains(id));
+    failed_at.insert(task_id.to_string(), mongodb::bson::DateTime::now());
+
+    let excess = failed_at.len().saturating_sub(MAX_FAILURE_DEPTH as usize);
+    if excess > 0 {
+        let mut by_time: Vec<(String, mongodb::bson::DateTime)> =
+            failed_at.iter().map(|(id, at)| (id.clone(), *at)).collect();
+        by_time.sort_by_key(|(_, at)| *at);
+        for (id, _) in by_time.into_iter().take(excess) {
+            failed_at.remove(&id);
+        }
+    }
+}
+
+// 更新批量删除任务进度
+async fn update_batch_delete_progress(task_id: &str, progress: BatchDeleteProgress, task_name: String) {
+    if progress.status == "failed" {
+        record_batch_delete_failure(task_id).await;
+    }
+
+    let store = get_batch_delete_progress_store();
+    let mut progress_map = store.write().await;
+    if let Some((current_progress, current_name, handle)) = progress_map.get_mut(task_id) {
+        *current_progress = progress;
+        *current_name = task_name;
+        // 保持原有的handle不变，不需要克隆
+    } else {
+        progress_map.insert(task_id.to_string(), (progress, task_name, None));
+    }
+}
+
+// 停止批量删除任务
+pub async fn stop_batch_delete_task(task_id: &str) -> bool {
+    let store = get_batch_delete_progress_store();
+    let mut progress_map = store.write().await;
+
+    if let Some((mut progress, task_name, handle)) = progress_map.remove(task_id) {
+        // 取消任务
+        if let Some(task_handle) = handle {
+            task_handle.abort();
+        }
+
+        // 标记任务为已停止
+        progress.status = "stopped".to_string();
+        progress.log = "任务已手动停止".to_string();
+
+        // 将任务重新插入，但状态为已停止且清除句柄
+        progress_map.insert(task_id.to_string(), (progress, task_name, None));
+
+        true
+    } else {
+        false
+    }
+}
+
+// 获取所有运行中的批量删除任务
+pub async fn get_all_batch_delete_tasks() -> Vec<serde_json::Value> {
+    let store = get_batch_delete_progress_store();
+    let progress_map = store.read().await;
+
+    let mut tasks = Vec::new();
+    for (task_id, (progress, task_name, _)) in progress_map.iter() {
+        tasks.push(json!({
+            "task_id": task_id,
+            "task_name": task_name,
+            "status": progress.status,
+            "processed_count": progress.processed_count,
+            "deleted_count": progress.deleted_count,
+            "total_count": progress.total_count,
+            "log": progress.log
+        }));
+    }
+
+    tasks
+}
+
+// 启动批量删除任务
+pub async fn start_batch_delete_source(
+    db: web::Data<Database>,
+    source_name: String,
+) -> String {
+    let task_id = uuid::Uuid::new_v4().to_string();
+    let task_id_clone = task_id.clone();
+
+    let collection = db.collection::<Vod>("vods");
+    const BATCH_SIZE: i64 = 2000;
+
+    // 获取总视频数量
+    let total_count = match collection.count_documents(None, None).await {
+        Ok(count) => count as i64,
+        Err(e) => {
+            eprintln!("Failed to count vods: {}", e);
+
+            // 初始化失败状态
+            let failed_progress = BatchDeleteProgress {
+                status: "failed".to_string(),
+                processed_count: 0,
+                deleted_count: 0,
+                total_count: 0,
+                log: "无法获取视频总数".to_string(),
+            };
+
+            update_batch_delete_progress(&task_id, failed_progress, format!("批量删除播放源: {}", source_name)).await;
+            return task_id;
+        }
+    };
+
+    let total_count_u64 = total_count as u64;
+
+    // 初始化进度
+    let initial_progress = BatchDeleteProgress {
+        status: "running".to_string(),
+        processed_count: 0,
+        deleted_count: 0,
+        total_count: total_count_u64,
+        log: "开始批量删除播放源任务".to_string(),
+    };
+
+    update_batch_delete_progress(&task_id, initial_progress, format!("批量删除播放源: {}", source_name)).await;
+
+    // 启动后台任务
+    let db_clone = db.clone();
+    let source_name_clone = source_name.clone();
+    let task_handle = tokio::spawn(async move {
+        if let Err(e) = execute_batch_delete_inner(db_clone, &task_id_clone, &source_name_clone, BATCH_SIZE).await {
+            eprintln!("Batch delete failed: {}", e);
+
+            let failed_progress = BatchDeleteProgress {
+                status: "failed".to_string(),
+                processed_count: 0,
+                deleted_count: 0,
+                total_count: total_count_u64,
+                log: format!("批量删除失败: {}", e),
+            };
+            update_batch_delete_progress(&task_id_clone, failed_progress, format!("批量删除播放源: {}", source_name_clone)).await;
+        }
+    });
+
+    // 将任务句柄存储到进度Map中
+    let store = get_batch_delete_progress_store();
+    let mut progress_map = store.write().await;
+    if let Some((_, _, handle_ref)) = progress_map.get_mut(&task_id) {
+        *handle_ref = Some(task_handle);
+    } else {
+        progress_map.insert(task_id.clone(), (
+            BatchDeleteProgress {
+                status: "running".to_string(),
+                processed_count: 0,
+                deleted_count: 0,
+                total_count: total_count_u64,
+                log: "开始批量删除播放源任务".to_string(),
+            },
+            format!("批量删除播放源: {}", source_name),
+            Some(task_handle)
+        ));
+    }
+
+    task_id
+}
+
+// 执行批量删除的核心逻辑
+async fn execute_batch_delete_inner(
+    db: web::Data<Database>,
+    task_id: &str,
+    source_name: &str,
+    batch_size: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let collection = db.collection::<Vod>("vods");
+
+    let mut processed_count = 0u64;
+    let mut deleted_count = 0u64;
+    let mut last_id: Option<mongodb::bson::oid::ObjectId> = None;
+
+    // 获取总视频数量
+    let total_count = collection.count_documents(None, None).await?;
+    let total_count_u64 = total_count as u64;
+
+    // 分批处理视频
+    loop {
+        // 构建查询，使用大于last_id来获取下一批
+        let mut filter = doc! {};
+
+        if let Some(last) = last_id {
+            filter.insert("_id", doc! {"$gt": last});
+        }
+
+        let find_options = FindOptions::builder()
+            .sort(doc! {"_id": 1})
+            .limit(batch_size)
+            .build();
+
+        let cursor = collection.find(filter, find_options).await?;
+        let mut vods_in_batch: Vec<Vod> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+
+        if vods_in_batch.is_empty() {
+            // 更新最终状态
+            let completed_progress = BatchDeleteProgress {
+                status: "completed".to_string(),
+                processed_count: total_count_u64,
+                deleted_count,
+                total_count: total_count_u64,
+                log: format!("批量删除完成：处理了 {} 个视频，删除了 {} 个播放源", processed_count, deleted_count),
+            };
+            update_batch_delete_progress(task_id, completed_progress, format!("批量删除播放源: {}", source_name)).await;
+            break;
+        }
+
+        // 更新last_id为当前批次的最后一个视频ID
+        if let Some(last_vod) = vods_in_batch.last() {
+            last_id = last_vod.id;
+        }
+
+        // 处理这一批视频
+        for vod in &mut vods_in_batch {
+            if let Some(vod_id) = vod.id {
+                let mut has_changed = false;
+
+                // 检查vod_play_urls数组中是否有匹配的source_name
+                let mut new_play_urls = Vec::new();
+
+                for play_source in &vod.vod_play_urls {
+                    if play_source.source_name != source_name {
+                        new_play_urls.push(play_source.to_owned());
+                    } else {
+                        deleted_count += 1;
+                        has_changed = true;
+                    }
+                }
+
+                if has_changed {
+                    vod.vod_play_urls = new_play_urls;
+                }
+
+                // 如果有更改，更新数据库
+                if has_changed {
+                    // 使用mongodb::bson::to_document来序列化vod结构
+                    let mut update_doc = mongodb::bson::to_document(&vod)?;
+                    // 移除_id字段，因为我们不能更新主键
+                    update_doc.remove("_id");
+
+                    let update_doc = doc! {
+                        "$set": update_doc
+                    };
+
+                    // 这里我们可以选择不等待update_one，增加并发性
+                    if let Err(e) = collection.update_one(doc! {"_id": vod_id}, update_doc, None).await {
+                        eprintln!("Failed to update vod {}: {}", vod_id, e);
+                        // 继续处理，不因为单个错误而停止
+                    }
+                }
+            }
+
+            processed_count += 1;
+
+            // 每处理100个视频更新一次进度
+            if processed_count % 100 == 0 {
+                let progress = BatchDeleteProgress {
+                    status: "running".to_string(),
+                    processed_count,
+                    deleted_count,
+                    total_count: total_count_u64,
+                    log: format!("正在处理中... 已处理 {}/{} 个视频", processed_count, total_count_u64),
+                };
+                update_batch_delete_progress(task_id, progress, format!("批量删除播放源: {}", source_name)).await;
+            }
+        }
+
+        // 如果这一批没有达到BATCH_SIZE，说明已经处理完了所有数据
+        if vods_in_batch.len() < batch_size as usize {
+            // 更新最终状态
+            let completed_progress = BatchDeleteProgress {
+                status: "completed".to_string(),
+                processed_count: total_count_u64,
+                deleted_count,
+                total_count: total_count_u64,
+                log: format!("批量删除完成：处理了 {} 个视频，删除了 {} 个播放源", processed_count, deleted_count),
+            };
+            update_batch_delete_progress(task_id, completed_progress, format!("批量删除播放源: {}", source_name)).await;
+            break;
+        }
     }
+
+    Ok(())
 }
 
-// POST /api/admin/scheduled-task/stop
-pub async fn stop_scheduled_task(
-    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
+// POST /api/admin/batch-delete-source
+pub async fn batch_delete_source(
+    db: web::Data<Database>,
+    request: web::Json<BatchDeleteSourceRequest>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    match task_manager.stop_scheduled_task().await {
-        Ok(_) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "定时采集任务已停止"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
+
+    let source_name = request.source_name.trim();
+    if source_name.is_empty() {
+        return HttpResponse::BadRequest().json(json!({
             "success": false,
-            "message": format!("停止定时任务失败: {}", e)
-        })),
+            "message": "播放源名称不能为空"
+        }));
     }
-}
 
-// PUT /api/admin/scheduled-task/config
-#[derive(Debug, Deserialize)]
-pub struct ScheduledTaskConfigRequest {
-    pub enabled: bool,
-    pub interval_hours: Option<i32>,
-}
+    // 检查是否存在正在运行的任务
+    let running_tasks = get_all_batch_delete_tasks().await;
+    let has_running = running_tasks
+        .iter()
+        .any(|task| task["status"] == "running");
 
-pub async fn update_scheduled_task_config(
-    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
-    session: Session,
-    config: web::Json<ScheduledTaskConfigRequest>,
-) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
-    match task_manager.update_config(config.enabled, config.interval_hours).await {
-        Ok(true) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "定时任务配置已更新"
-        })),
-        Ok(false) => HttpResponse::BadRequest().json(json!({
-            "success": false,
-            "message": "配置更新失败"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
+    if has_running {
+        return HttpResponse::BadRequest().json(json!({
             "success": false,
-            "message": format!("更新配置失败: {}", e)
-        })),
+            "message": "已有正在运行的批量删除任务，请等待完成后重试"
+        }));
     }
+
+    // 启动后台任务
+    let task_id = start_batch_delete_source(db, source_name.to_string()).await;
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": "批量删除任务已启动",
+        "task_id": task_id,
+        "source_name": source_name
+    }))
 }
 
-// GET /api/admin/scheduled-task/logs
-pub async fn get_scheduled_task_logs(
-    task_manager: web::Data<std::sync::Arc<ScheduledTaskManager>>,
-    session: Session,
-    query: web::Query<ScheduledTaskLogsQuery>,
-) -> impl Responder {
+// GET /api/admin/batch-delete/progress/{task_id}
+pub async fn get_batch_delete_progress_handler(path: web::Path<String>, session: Session) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
-    match task_manager.get_task_logs(query.limit).await {
-        Ok(logs) => HttpResponse::Ok().json(json!({
-            "success": true,
-            "data": logs
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "success": false,
-            "message": format!("获取任务日志失败: {}", e)
-        })),
-    }
-}
 
-#[derive(Debug, Deserialize)]
-pub struct ScheduledTaskLogsQuery {
-    pub limit: Option<i32>,
-}
-
-// --- Batch Delete Source API ---
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BatchDeleteSourceRequest {
-    pub source_name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BatchDeleteProgress {
-    pub status: String,
-    pub processed_count: u64,
-    pub deleted_count: u64,
-    pub total_count: u64,
-    pub log: String,
-}
+    let task_id = path.into_inner();
 
-impl Default for BatchDeleteProgress {
-    fn default() -> Self {
-        Self {
-            status: "unknown".to_string(),
+    let progress = get_batch_delete_progress(&task_id).await
+        .unwrap_or_else(|| BatchDeleteProgress {
+            status: "not_found".to_string(),
             processed_count: 0,
             deleted_count: 0,
             total_count: 0,
-            log: "未知状态".to_string(),
-        }
-    }
-}
+            log: "任务不存在".to_string(),
+        });
 
-// 类型别名简化复杂类型
-type BatchDeleteProgressMap = std::collections::HashMap<
-    String,
-    (BatchDeleteProgress, String, Option<tokio::task::JoinHandle<()>>),
->;
-type BatchDeleteProgressStore = tokio::sync::RwLock<BatchDeleteProgressMap>;
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "progress": progress
+    }))
+}
 
-// 全局批量删除任务进度存储
-static BATCH_DELETE_PROGRESS: std::sync::OnceLock<BatchDeleteProgressStore> = std::sync::OnceLock::new();
+// GET /api/admin/batch-delete/running-tasks
+pub async fn get_running_batch_delete_tasks_handler(session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
 
-// 初始化批量删除任务进度存储
-fn get_batch_delete_progress_store() -> &'static BatchDeleteProgressStore {
-    BATCH_DELETE_PROGRESS.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
-}
+    let tasks = get_all_batch_delete_tasks().await;
 
-// 获取批量删除任务进度
-pub async fn get_batch_delete_progress(task_id: &str) -> Option<BatchDeleteProgress> {
-    let store = get_batch_delete_progress_store();
-    let progress_map = store.read().await;
-    progress_map
-        .get(task_id)
-        .map(|(progress, _, _)| progress.clone())
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "tasks": tasks
+    }))
 }
 
-// 更新批量删除任务进度
-async fn update_batch_delete_progress(task_id: &str, progress: BatchDeleteProgress, task_name: String) {
-    let store = get_batch_delete_progress_store();
-    let mut progress_map = store.write().await;
-    if let Some((current_progress, current_name, handle)) = progress_map.get_mut(task_id) {
-        *current_progress = progress;
-        *current_name = task_name;
-        // 保持原有的handle不变，不需要克隆
-    } else {
-        progress_map.insert(task_id.to_string(), (progress, task_name, None));
+// POST /api/admin/batch-delete/stop/{task_id}
+pub async fn stop_batch_delete_task_handler(path: web::Path<String>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
     }
-}
-
-// 停止批量删除任务
-pub async fn stop_batch_delete_task(task_id: &str) -> bool {
-    let store = get_batch_delete_progress_store();
-    let mut progress_map = store.write().await;
 
-    if let Some((mut progress, task_name, handle)) = progress_map.remove(task_id) {
-        // 取消任务
-        if let Some(task_handle) = handle {
-            task_handle.abort();
-        }
-
-        // 标记任务为已停止
-        progress.status = "stopped".to_string();
-        progress.log = "任务已手动停止".to_string();
+    let task_id = path.into_inner();
 
-        // 将任务重新插入，但状态为已停止且清除句柄
-        progress_map.insert(task_id.to_string(), (progress, task_name, None));
+    let stopped = stop_batch_delete_task(&task_id).await;
 
-        true
+    if stopped {
+        HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "批量删除任务已成功停止"
+        }))
     } else {
-        false
+        HttpResponse::NotFound().json(json!({
+            "success": false,
+            "message": "任务不存在或已经停止"
+        }))
     }
 }
 
-// 获取所有运行中的批量删除任务
-pub async fn get_all_batch_delete_tasks() -> Vec<serde_json::Value> {
-    let store = get_batch_delete_progress_store();
-    let progress_map = store.read().await;
+// --- Maintenance: delete vods without play sources ---
 
-    let mut tasks = Vec::new();
-    for (task_id, (progress, task_name, _)) in progress_map.iter() {
-        tasks.push(json!({
-            "task_id": task_id,
-            "task_name": task_name,
-            "status": progress.status,
-            "processed_count": progress.processed_count,
-            "deleted_count": progress.deleted_count,
-            "total_count": progress.total_count,
-            "log": progress.log
-        }));
-    }
+#[derive(Debug, Deserialize)]
+pub struct DeleteEmptyVodsRequest {
+    pub type_id: Option<i32>, // 只清理该分类下的视频
+    #[serde(default)]
+    pub dry_run: bool, // 只统计不删除
+}
 
-    tasks
+// 没有任何播放源的视频（采集源中的占位条目）
+fn empty_vods_filter(type_id: Option<i32>) -> mongodb::bson::Document {
+    let mut filter = doc! {"vod_play_urls": {"$size": 0}};
+    if let Some(type_id) = type_id {
+        filter.insert("type_id", type_id);
+    }
+    filter
 }
 
-// 启动批量删除任务
-pub async fn start_batch_delete_source(
+// 启动删除空视频任务，进度与批量删除播放源任务共用同一存储
+pub async fn start_delete_empty_vods(
     db: web::Data<Database>,
-    source_name: String,
+    type_id: Option<i32>,
+    dry_run: bool,
 ) -> String {
-    let task_id = uuid::Uuid::new_v4().to_string();
-    let task_id_clone = task_id.clone();
-
-    let collection = db.collection::<Vod>("vods");
-    const BATCH_SIZE: i64 = 2000;
-
-    // 获取总视频数量
-    let total_count = match collection.count_documents(None, None).await {
-        Ok(count) => count as i64,
-        Err(e) => {
-            eprintln!("Failed to count vods: {}", e);
-
-            // 初始化失败状态
-            let failed_progress = BatchDeleteProgress {
-                status: "failed".to_string(),
-                processed_count: 0,
-                deleted_count: 0,
-                total_count: 0,
-                log: "无法获取视频总数".to_string(),
-            };
-
-            update_batch_delete_progress(&task_id, failed_progress, format!("批量删除播放源: {}", source_name)).await;
-            return task_id;
-        }
+    let task_id = uuid::Uuid::new_v4().to_string();
+    let task_id_clone = task_id.clone();
+    let task_name = if dry_run {
+        "清理无播放源视频（预览）".to_string()
+    } else {
+        "清理无播放源视频".to_string()
     };
+    const BATCH_SIZE: i64 = 2000;
 
-    let total_count_u64 = total_count as u64;
-
-    // 初始化进度
     let initial_progress = BatchDeleteProgress {
         status: "running".to_string(),
         processed_count: 0,
         deleted_count: 0,
-        total_count: total_count_u64,
-        log: "开始批量删除播放源任务".to_string(),
+        total_count: 0,
+        log: "开始清理无播放源视频".to_string(),
     };
+    update_batch_delete_progress(&task_id, initial_progress, task_name.clone()).await;
 
-    update_batch_delete_progress(&task_id, initial_progress, format!("批量删除播放源: {}", source_name)).await;
-
-    // 启动后台任务
     let db_clone = db.clone();
-    let source_name_clone = source_name.clone();
+    let task_name_clone = task_name.clone();
     let task_handle = tokio::spawn(async move {
-        if let Err(e) = execute_batch_delete_inner(db_clone, &task_id_clone, &source_name_clone, BATCH_SIZE).await {
-            eprintln!("Batch delete failed: {}", e);
+        if let Err(e) =
+            execute_delete_empty_vods_inner(db_clone, &task_id_clone, &task_name_clone, type_id, dry_run, BATCH_SIZE)
+                .await
+        {
+            eprintln!("Delete empty vods failed: {}", e);
 
             let failed_progress = BatchDeleteProgress {
                 status: "failed".to_string(),
-                processed_count: 0,
-                deleted_count: 0,
-                total_count: total_count_u64,
-                log: format!("批量删除失败: {}", e),
+                log: format!("清理失败: {}", e),
+                ..get_batch_delete_progress(&task_id_clone).await.unwrap_or_default()
             };
-            update_batch_delete_progress(&task_id_clone, failed_progress, format!("批量删除播放源: {}", source_name_clone)).await;
+            update_batch_delete_progress(&task_id_clone, failed_progress, task_name_clone).await;
         }
     });
 
@@ -1715,246 +4312,1002 @@ pub async fn start_batch_delete_source(
     let mut progress_map = store.write().await;
     if let Some((_, _, handle_ref)) = progress_map.get_mut(&task_id) {
         *handle_ref = Some(task_handle);
-    } else {
-        progress_map.insert(task_id.clone(), (
-            BatchDeleteProgress {
-                status: "running".to_string(),
-                processed_count: 0,
-                deleted_count: 0,
-                total_count: total_count_u64,
-                log: "开始批量删除播放源任务".to_string(),
-            },
-            format!("批量删除播放源: {}", source_name),
-            Some(task_handle)
-        ));
     }
 
     task_id
 }
 
-// 执行批量删除的核心逻辑
-async fn execute_batch_delete_inner(
+// 按 _id 分批扫描并删除无播放源的视频
+async fn execute_delete_empty_vods_inner(
     db: web::Data<Database>,
     task_id: &str,
-    source_name: &str,
+    task_name: &str,
+    type_id: Option<i32>,
+    dry_run: bool,
     batch_size: i64,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let collection = db.collection::<Vod>("vods");
+    let collection = db.collection::<mongodb::bson::Document>("vods");
 
+    let total_count = collection.count_documents(empty_vods_filter(type_id), None).await?;
     let mut processed_count = 0u64;
     let mut deleted_count = 0u64;
     let mut last_id: Option<mongodb::bson::oid::ObjectId> = None;
 
-    // 获取总视频数量
-    let total_count = collection.count_documents(None, None).await?;
-    let total_count_u64 = total_count as u64;
-
-    // 分批处理视频
     loop {
-        // 构建查询，使用大于last_id来获取下一批
-        let mut filter = doc! {};
-
+        let mut filter = empty_vods_filter(type_id);
         if let Some(last) = last_id {
             filter.insert("_id", doc! {"$gt": last});
         }
 
         let find_options = FindOptions::builder()
             .sort(doc! {"_id": 1})
+            .projection(doc! {"_id": 1})
             .limit(batch_size)
             .build();
 
         let cursor = collection.find(filter, find_options).await?;
-        let mut vods_in_batch: Vec<Vod> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+        let batch: Vec<mongodb::bson::Document> = cursor.try_collect().await?;
+        let ids: Vec<mongodb::bson::oid::ObjectId> = batch
+            .iter()
+            .filter_map(|d| d.get_object_id("_id").ok())
+            .collect();
 
-        if vods_in_batch.is_empty() {
-            // 更新最终状态
-            let completed_progress = BatchDeleteProgress {
-                status: "completed".to_string(),
-                processed_count: total_count_u64,
-                deleted_count,
-                total_count: total_count_u64,
-                log: format!("批量删除完成：处理了 {} 个视频，删除了 {} 个播放源", processed_count, deleted_count),
-            };
-            update_batch_delete_progress(task_id, completed_progress, format!("批量删除播放源: {}", source_name)).await;
+        if ids.is_empty() {
             break;
         }
+        last_id = ids.last().copied();
+        processed_count += ids.len() as u64;
+
+        if dry_run {
+            deleted_count += ids.len() as u64;
+        } else {
+            let result = collection
+                .delete_many(doc! {"_id": {"$in": &ids}}, None)
+                .await?;
+            deleted_count += result.deleted_count;
+        }
+
+        let progress = BatchDeleteProgress {
+            status: "running".to_string(),
+            processed_count,
+            deleted_count,
+            total_count,
+            log: format!("正在处理中... 已处理 {}/{} 个视频", processed_count, total_count),
+        };
+        update_batch_delete_progress(task_id, progress, task_name.to_string()).await;
+
+        if ids.len() < batch_size as usize {
+            break;
+        }
+    }
+
+    let log = if dry_run {
+        format!("预览完成：共有 {} 个无播放源视频可删除", deleted_count)
+    } else {
+        format!("清理完成：删除了 {} 个无播放源视频", deleted_count)
+    };
+    let completed_progress = BatchDeleteProgress {
+        status: "completed".to_string(),
+        processed_count,
+        deleted_count,
+        total_count,
+        log,
+    };
+    update_batch_delete_progress(task_id, completed_progress, task_name.to_string()).await;
+
+    if !dry_run && deleted_count > 0 {
+            cache::purge(cache::SCOPE_COUNTS, Some(cache::VODS_BY_STATUS_KEY)).await;
+    }
+
+    Ok(())
+}
+
+// POST /api/admin/maintenance/delete-empty-vods
+// 进度通过 /api/admin/batch-delete/progress/{task_id} 查询
+pub async fn delete_empty_vods(
+    db: web::Data<Database>,
+    request: web::Json<DeleteEmptyVodsRequest>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    // 检查是否存在正在运行的任务
+    let running_tasks = get_all_batch_delete_tasks().await;
+    let has_running = running_tasks
+        .iter()
+        .any(|task| task["status"] == "running");
+
+    if has_running {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "已有正在运行的批量删除任务，请等待完成后重试"
+        }));
+    }
+
+    let task_id = start_delete_empty_vods(db, request.type_id, request.dry_run).await;
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": if request.dry_run { "预览任务已启动" } else { "清理任务已启动" },
+        "task_id": task_id,
+        "dry_run": request.dry_run
+    }))
+}
+
+// 一致性检查每项最多返回的样例条数
+const VERIFY_SAMPLE_LIMIT: i64 = 100;
+
+// 执行聚合并收集结果
+async fn aggregate_docs(
+    db: &Database,
+    collection: &str,
+    pipeline: Vec<Document>,
+) -> Result<Vec<Document>, mongodb::error::Error> {
+    db.collection::<Document>(collection)
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect()
+        .await
+}
+
+// 各项数据一致性检查，只读
+async fn run_integrity_checks(db: &Database) -> Result<serde_json::Value, mongodb::error::Error> {
+    let type_ids: Vec<mongodb::bson::Bson> = db
+        .collection::<Type>("types")
+        .distinct("type_id", None, None)
+        .await?;
+
+    // 1. 绑定指向不存在的分类
+    let broken_bindings = find_broken_bindings(db).await?;
+
+    // 2. 视频指向不存在的分类，按分类ID分组
+    let orphan_vods = aggregate_docs(
+        db,
+        "vods",
+        vec![
+            doc! { "$match": { "type_id": { "$nin": type_ids } } },
+            doc! { "$group": { "_id": "$type_id", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1 } },
+        ],
+    )
+    .await?;
+    let orphan_vod_groups: Vec<serde_json::Value> = orphan_vods
+        .iter()
+        .map(|d| {
+            json!({
+                "type_id": d.get("_id").and_then(|v| v.as_i32()),
+                "count": d.get("count").and_then(|v| v.as_i32()).unwrap_or(0)
+            })
+        })
+        .collect();
+    let orphan_vod_count: i64 = orphan_vod_groups
+        .iter()
+        .map(|g| g["count"].as_i64().unwrap_or(0))
+        .sum();
+
+    // 3. 采集源名称重复（绑定按采集源名称关联，重名会互相串用绑定）
+    let duplicate_collections = aggregate_docs(
+        db,
+        "collections",
+        vec![
+            doc! { "$group": {
+                "_id": "$collect_name",
+                "count": { "$sum": 1 },
+                "ids": { "$push": "$_id" }
+            } },
+            doc! { "$match": { "count": { "$gt": 1 } } },
+            doc! { "$sort": { "_id": 1 } },
+        ],
+    )
+    .await?;
+    let duplicate_collection_names: Vec<serde_json::Value> = duplicate_collections
+        .iter()
+        .map(|d| {
+            let ids: Vec<String> = d
+                .get_array("ids")
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| id.as_object_id().map(|oid| oid.to_hex()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            json!({
+                "collect_name": d.get_str("_id").unwrap_or_default(),
+                "count": d.get("count").and_then(|v| v.as_i32()).unwrap_or(0),
+                "ids": ids
+            })
+        })
+        .collect();
+
+    // 4. 同一视频中出现重复的播放源名称
+    let duplicate_source_match = vec![
+        doc! { "$project": {
+            "vod_name": 1,
+            "source_names": { "$ifNull": ["$vod_play_urls.source_name", []] }
+        } },
+        doc! { "$match": { "$expr": {
+            "$ne": [{ "$size": "$source_names" }, { "$size": { "$setUnion": ["$source_names", []] } }]
+        } } },
+    ];
+    let mut count_pipeline = duplicate_source_match.clone();
+    count_pipeline.push(doc! { "$count": "total" });
+    let duplicate_source_count = aggregate_docs(db, "vods", count_pipeline)
+        .await?
+        .first()
+        .and_then(|d| d.get("total"))
+        .and_then(|v| v.as_i64().or_else(|| v.as_i32().map(i64::from)))
+        .unwrap_or(0);
+    let mut sample_pipeline = duplicate_source_match;
+    sample_pipeline.push(doc! { "$limit": VERIFY_SAMPLE_LIMIT });
+    let duplicate_source_samples: Vec<serde_json::Value> = aggregate_docs(db, "vods", sample_pipeline)
+        .await?
+        .iter()
+        .map(|d| {
+            let mut seen = std::collections::HashSet::new();
+            let mut duplicates: Vec<String> = Vec::new();
+            for name in d.get_array("source_names").map(|a| a.as_slice()).unwrap_or(&[]) {
+                if let Some(name) = name.as_str() {
+                    if !seen.insert(name) && !duplicates.iter().any(|n| n == name) {
+                        duplicates.push(name.to_string());
+                    }
+                }
+            }
+            json!({
+                "vod_id": d.get_object_id("_id").map(|id| id.to_hex()).unwrap_or_default(),
+                "vod_name": d.get_str("vod_name").unwrap_or_default(),
+                "duplicate_sources": duplicates
+            })
+        })
+        .collect();
+
+    let anomaly_count = broken_bindings.len() as i64
+        + orphan_vod_count
+        + duplicate_collection_names.len() as i64
+        + duplicate_source_count;
+
+    Ok(json!({
+        "anomaly_count": anomaly_count,
+        "checks": {
+            "broken_bindings": {
+                "count": broken_bindings.len(),
+                "samples": broken_bindings.iter().take(VERIFY_SAMPLE_LIMIT as usize).collect::<Vec<_>>(),
+                "fix": "POST /api/admin/bindings/validate"
+            },
+            "orphan_vods": {
+                "count": orphan_vod_count,
+                "by_type": orphan_vod_groups,
+                "fix": "POST /api/admin/vods/rebind"
+            },
+            "duplicate_collection_names": {
+                "count": duplicate_collection_names.len(),
+                "samples": duplicate_collection_names,
+                "fix": "PUT /api/admin/collections/{id}"
+            },
+            "duplicate_play_sources": {
+                "count": duplicate_source_count,
+                "samples": duplicate_source_samples,
+                "fix": "PUT /api/admin/vods/{id}"
+            }
+        }
+    }))
+}
+
+// POST /api/admin/maintenance/verify
+// 只读的数据一致性检查：失效绑定、指向不存在分类的视频、重名采集源、重复播放源。
+// 每项返回数量和最多 VERIFY_SAMPLE_LIMIT 条样例，以及可用于修复的接口。
+pub async fn verify_data_integrity(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    match run_integrity_checks(&db).await {
+        Ok(report) => {
+            let anomaly_count = report["anomaly_count"].as_i64().unwrap_or(0);
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "message": if anomaly_count == 0 {
+                    "未发现数据异常".to_string()
+                } else {
+                    format!("发现 {} 处数据异常", anomaly_count)
+                },
+                "checked_at": chrono::Utc::now().to_rfc3339(),
+                "report": report
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to verify data integrity: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": format!("数据检查失败: {}", e)
+            }))
+        }
+    }
+}
+
+// POST /api/admin/collect/parse-play
+// 用采集时的 parse_play_urls 解析一组 vod_play_from / vod_play_url，排查某个采集源的集数解析问题，不访问数据库
+pub async fn parse_play(session: Session, payload: web::Json<ParsePlayRequest>) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let play_sources = crate::collect_handlers::parse_play_urls(
+        &payload.vod_play_from,
+        &payload.vod_play_url,
+        payload.normalize_episode_names,
+    );
+    let episode_count: usize = play_sources.iter().map(|s| s.urls.len()).sum();
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "source_count": play_sources.len(),
+        "episode_count": episode_count,
+        "play_sources": play_sources
+    }))
+}
+
+// POST /api/admin/maintenance/test-image
+// 用采集时的下载/webp转换流程处理一张图片，用于在开启 webp 转换前验证图片处理是否正常
+pub async fn test_image(session: Session, payload: web::Json<TestImageRequest>) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let image_url = payload.image_url.trim();
+    if !(image_url.starts_with("http://") || image_url.starts_with("https://")) {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "图片地址必须以 http:// 或 https:// 开头"
+        }));
+    }
+    let quality = payload.quality.unwrap_or(crate::collect_handlers::DEFAULT_WEBP_QUALITY);
+    if !(0.0..=100.0).contains(&quality) {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "quality 必须在 0-100 之间"
+        }));
+    }
+
+    match crate::collect_handlers::test_image_pipeline(image_url, payload.convert_webp, quality).await {
+        Ok(result) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "图片处理成功，测试文件已删除",
+            "data": result
+        })),
+        Err(e) => HttpResponse::Ok().json(json!({
+            "success": false,
+            "message": format!("图片处理失败: {}", e)
+        })),
+    }
+}
+
+const DEFAULT_LOG_BUMP_SECS: u64 = 600;
+const MAX_LOG_BUMP_SECS: u64 = 3600;
+
+fn log_level_status() -> serde_json::Value {
+    let override_info = logging::current_override().map(|(target, level, expires_at)| {
+        json!({
+            "target": target,
+            "level": level,
+            "expires_at": expires_at.to_rfc3339()
+        })
+    });
+    json!({
+        "base_filter": logging::base_filter(),
+        "override": override_info
+    })
+}
+
+// GET /api/admin/logging/level
+pub async fn get_log_level(session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": log_level_status()
+    }))
+}
+
+// PUT /api/admin/logging/level
+// 临时调整采集模块的日志级别（例如排查采集问题时改为 debug），到期后自动恢复
+pub async fn set_collect_log_level(
+    session: Session,
+    payload: web::Json<LogLevelRequest>,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let level = payload.level.trim().to_lowercase();
+    if !["error", "warn", "info", "debug", "trace"].contains(&level.as_str()) {
+        return HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": "日志级别必须是 error/warn/info/debug/trace 之一"
+        }));
+    }
+    let duration_secs = payload
+        .duration_secs
+        .unwrap_or(DEFAULT_LOG_BUMP_SECS)
+        .clamp(1, MAX_LOG_BUMP_SECS);
+
+    match logging::set_temporary_level(logging::COLLECT_TARGET, &level, duration_secs) {
+        Ok(expires_at) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": format!("采集日志级别已调整为 {}，将于 {} 恢复", level, expires_at.to_rfc3339()),
+            "data": log_level_status()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": e
+        })),
+    }
+}
+
+// DELETE /api/admin/logging/level
+pub async fn reset_log_level(session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    match logging::reset() {
+        Ok(()) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "message": "日志级别已恢复默认",
+            "data": log_level_status()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "success": false,
+            "message": e
+        })),
+    }
+}
+
+// GET /api/admin/broken-reports
+pub async fn get_broken_reports(
+    db: web::Data<Database>,
+    query: web::Query<BrokenReportsQuery>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).min(100);
+    let skip = (page - 1) * limit;
+
+    let collection = db.collection::<BrokenReport>("broken_reports");
+
+    let total = match collection.count_documents(None, None).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count broken reports: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取报告总数失败"
+            }));
+        }
+    };
+
+    // 按报告次数倒序
+    let find_options = FindOptions::builder()
+        .sort(doc! {"report_count": -1, "last_reported_at": -1})
+        .skip(skip as u64)
+        .limit(limit as i64)
+        .build();
+
+    match collection.find(None, find_options).await {
+        Ok(cursor) => {
+            let reports: Vec<BrokenReport> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "page": page,
+                "limit": limit,
+                "total": total,
+                "reports": reports
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch broken reports: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取失效报告失败"
+            }))
+        }
+    }
+}
+
+// 聚合结果中的计数字段，$sum 结果可能是 Int32 或 Int64
+fn doc_count(doc: &Document, key: &str) -> i64 {
+    doc.get(key)
+        .and_then(|v| v.as_i64().or_else(|| v.as_i32().map(i64::from)))
+        .unwrap_or(0)
+}
+
+// 单个播放源的健康统计
+struct PlaySourceHealth {
+    source_name: String,
+    vod_count: i64,
+    total_episodes: i64,
+    dead_episodes: i64,
+    report_count: i64,
+}
 
-        // 更新last_id为当前批次的最后一个视频ID
-        if let Some(last_vod) = vods_in_batch.last() {
-            last_id = last_vod.id;
+impl PlaySourceHealth {
+    fn health_percent(&self) -> f64 {
+        if self.total_episodes == 0 {
+            return 100.0;
         }
+        let alive = (self.total_episodes - self.dead_episodes).max(0) as f64;
+        (alive / self.total_episodes as f64 * 1000.0).round() / 10.0
+    }
 
-        // 处理这一批视频
-        for vod in &mut vods_in_batch {
-            if let Some(vod_id) = vod.id {
-                let mut has_changed = false;
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "source_name": self.source_name,
+            "vod_count": self.vod_count,
+            "total_episodes": self.total_episodes,
+            "dead_episodes": self.dead_episodes,
+            "report_count": self.report_count,
+            "health_percent": self.health_percent(),
+        })
+    }
+}
 
-                // 检查vod_play_urls数组中是否有匹配的source_name
-                let mut new_play_urls = Vec::new();
+// 按播放源名称汇总剧集总数和被标记失效的剧集数（失效标记来自 broken_reports，每条记录对应一集）
+async fn summarize_play_source_health(
+    db: &Database,
+) -> Result<Vec<PlaySourceHealth>, mongodb::error::Error> {
+    let episodes = aggregate_docs(
+        db,
+        "vods",
+        vec![
+            doc! { "$match": { "vod_deleted_at": { "$exists": false } } },
+            doc! { "$unwind": "$vod_play_urls" },
+            doc! { "$group": {
+                "_id": "$vod_play_urls.source_name",
+                "vod_count": { "$sum": 1 },
+                "total_episodes": { "$sum": { "$size": { "$ifNull": ["$vod_play_urls.urls", []] } } },
+            } },
+        ],
+    )
+    .await?;
+
+    let reports = aggregate_docs(
+        db,
+        "broken_reports",
+        vec![doc! { "$group": {
+            "_id": "$source_name",
+            "dead_episodes": { "$sum": 1 },
+            "report_count": { "$sum": "$report_count" },
+        } }],
+    )
+    .await?;
+    let reports: std::collections::HashMap<String, (i64, i64)> = reports
+        .into_iter()
+        .filter_map(|d| {
+            let name = d.get_str("_id").ok()?.to_string();
+            Some((name, (doc_count(&d, "dead_episodes"), doc_count(&d, "report_count"))))
+        })
+        .collect();
+
+    // 只统计当前仍存在的播放源，已被删除的播放源上残留的报告不再计入
+    let mut summary: Vec<PlaySourceHealth> = episodes
+        .into_iter()
+        .filter_map(|d| {
+            let source_name = d.get_str("_id").ok()?.to_string();
+            let total_episodes = doc_count(&d, "total_episodes");
+            let (dead, report_count) = reports.get(&source_name).copied().unwrap_or((0, 0));
+            Some(PlaySourceHealth {
+                vod_count: doc_count(&d, "vod_count"),
+                total_episodes,
+                // 剧集被修复或重新采集后旧报告仍在，不让失效数超过剧集总数
+                dead_episodes: dead.min(total_episodes),
+                report_count,
+                source_name,
+            })
+        })
+        .collect();
+
+    // 健康度最低的排在前面，相同时失效剧集多的优先
+    summary.sort_by(|a, b| {
+        a.health_percent()
+            .total_cmp(&b.health_percent())
+            .then(b.dead_episodes.cmp(&a.dead_episodes))
+            .then(a.source_name.cmp(&b.source_name))
+    });
+    Ok(summary)
+}
 
-                for play_source in &vod.vod_play_urls {
-                    if play_source.source_name != source_name {
-                        new_play_urls.push(play_source.to_owned());
-                    } else {
-                        deleted_count += 1;
-                        has_changed = true;
-                    }
-                }
+// GET /api/admin/play-sources/health
+pub async fn get_play_source_health(db: web::Data<Database>, session: Session) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
 
-                if has_changed {
-                    vod.vod_play_urls = new_play_urls;
-                }
+    match summarize_play_source_health(&db).await {
+        Ok(summary) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "total": summary.len(),
+            "sources": summary.iter().map(PlaySourceHealth::to_json).collect::<Vec<_>>()
+        })),
+        Err(e) => {
+            eprintln!("Failed to summarize play source health: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "统计播放源健康度失败"
+            }))
+        }
+    }
+}
 
-                // 如果有更改，更新数据库
-                if has_changed {
-                    // 使用mongodb::bson::to_document来序列化vod结构
-                    let mut update_doc = mongodb::bson::to_document(&vod)?;
-                    // 移除_id字段，因为我们不能更新主键
-                    update_doc.remove("_id");
+// GET /api/admin/audit
+pub async fn get_audit_log(
+    db: web::Data<Database>,
+    query: web::Query<AuditLogQuery>,
+    session: Session,
+) -> impl Responder {
+    if let Err(response) = check_auth(&session) {
+        return response;
+    }
 
-                    let update_doc = doc! {
-                        "$set": update_doc
-                    };
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).min(100);
+    let skip = (page - 1) * limit;
 
-                    // 这里我们可以选择不等待update_one，增加并发性
-                    if let Err(e) = collection.update_one(doc! {"_id": vod_id}, update_doc, None).await {
-                        eprintln!("Failed to update vod {}: {}", vod_id, e);
-                        // 继续处理，不因为单个错误而停止
-                    }
-                }
-            }
+    let mut filter = doc! {};
+    if let Some(user_id) = query.user_id.as_deref().filter(|v| !v.is_empty()) {
+        filter.insert("user_id", user_id);
+    }
+    if let Some(action) = query.action.as_deref().filter(|v| !v.is_empty()) {
+        filter.insert("action", action.to_uppercase());
+    }
+    if let Some(resource) = query.resource.as_deref().filter(|v| !v.is_empty()) {
+        filter.insert("resource", resource);
+    }
 
-            processed_count += 1;
+    let collection = db.collection::<AuditLogEntry>("audit_log");
 
-            // 每处理100个视频更新一次进度
-            if processed_count % 100 == 0 {
-                let progress = BatchDeleteProgress {
-                    status: "running".to_string(),
-                    processed_count,
-                    deleted_count,
-                    total_count: total_count_u64,
-                    log: format!("正在处理中... 已处理 {}/{} 个视频", processed_count, total_count_u64),
-                };
-                update_batch_delete_progress(task_id, progress, format!("批量删除播放源: {}", source_name)).await;
-            }
+    let total = match collection.count_documents(filter.clone(), None).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count audit log: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取审计日志总数失败"
+            }));
         }
+    };
 
-        // 如果这一批没有达到BATCH_SIZE，说明已经处理完了所有数据
-        if vods_in_batch.len() < batch_size as usize {
-            // 更新最终状态
-            let completed_progress = BatchDeleteProgress {
-                status: "completed".to_string(),
-                processed_count: total_count_u64,
-                deleted_count,
-                total_count: total_count_u64,
-                log: format!("批量删除完成：处理了 {} 个视频，删除了 {} 个播放源", processed_count, deleted_count),
-            };
-            update_batch_delete_progress(task_id, completed_progress, format!("批量删除播放源: {}", source_name)).await;
-            break;
+    let find_options = FindOptions::builder()
+        .sort(doc! {"timestamp": -1})
+        .skip(skip as u64)
+        .limit(limit as i64)
+        .build();
+
+    match collection.find(filter, find_options).await {
+        Ok(cursor) => {
+            let entries: Vec<AuditLogEntry> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "page": page,
+                "limit": limit,
+                "total": total,
+                "entries": entries
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch audit log: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取审计日志失败"
+            }))
         }
     }
+}
 
-    Ok(())
+// 单条失败记录，来源: collect（collection_runs）、scheduled（task_execution_logs）、batch_delete（内存中的批量删除任务）
+struct TaskFailure {
+    source: &'static str,
+    task_id: String,
+    name: String,
+    failed_at: mongodb::bson::DateTime,
+    reason: Option<String>,
+    trigger: Option<String>,
 }
 
-// POST /api/admin/batch-delete-source
-pub async fn batch_delete_source(
+impl TaskFailure {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "source": self.source,
+            "task_id": self.task_id,
+            "name": self.name,
+            "failed_at": self.failed_at.try_to_rfc3339_string().unwrap_or_default(),
+            "reason": self.reason,
+            "trigger": self.trigger,
+        })
+    }
+}
+
+// 最多翻到第 MAX_FAILURE_DEPTH 条，合并多个来源时每个来源只需取前 skip+limit 条
+const MAX_FAILURE_DEPTH: u64 = 1000;
+
+// GET /api/admin/tasks/recent-failures
+// 合并采集记录、定时任务执行日志和批量删除任务中的失败项，按失败时间倒序分页。
+// 定时采集失败时两处都有记录（task_id 相同），执行日志中已有对应采集记录的条目不重复返回。
+pub async fn get_recent_task_failures(
     db: web::Data<Database>,
-    request: web::Json<BatchDeleteSourceRequest>,
+    query: web::Query<RecentFailuresQuery>,
     session: Session,
 ) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
 
-    let source_name = request.source_name.trim();
-    if source_name.is_empty() {
+    let page = query.page.unwrap_or(1).max(1) as u64;
+    let limit = query.limit.unwrap_or(20).clamp(1, 100) as u64;
+    let skip = (page - 1) * limit;
+    if skip >= MAX_FAILURE_DEPTH {
         return HttpResponse::BadRequest().json(json!({
             "success": false,
-            "message": "播放源名称不能为空"
+            "message": format!("最多只能查看最近 {} 条失败记录", MAX_FAILURE_DEPTH)
         }));
     }
+    let depth = (skip + limit).min(MAX_FAILURE_DEPTH) as i64;
 
-    // 检查是否存在正在运行的任务
-    let running_tasks = get_all_batch_delete_tasks().await;
-    let has_running = running_tasks
-        .iter()
-        .any(|task| task["status"] == "running");
+    let mut failures: Vec<TaskFailure> = Vec::new();
+    let mut total: u64 = 0;
 
-    if has_running {
-        return HttpResponse::BadRequest().json(json!({
-            "success": false,
-            "message": "已有正在运行的批量删除任务，请等待完成后重试"
-        }));
+    // 采集记录（手动 + 定时）
+    let runs = db.collection::<CollectionRun>("collection_runs");
+    let run_filter = doc! { "status": "failed" };
+    let run_options = FindOptions::builder()
+        .sort(doc! { "finished_at": -1 })
+        .limit(depth)
+        .build();
+    let run_result = async {
+        let count = runs.count_documents(run_filter.clone(), None).await?;
+        let found: Vec<CollectionRun> = runs.find(run_filter, run_options).await?.try_collect().await?;
+        Ok::<_, mongodb::error::Error>((count, found))
+    }
+    .await;
+    match run_result {
+        Ok((count, found)) => {
+            total += count;
+            failures.extend(found.into_iter().map(|run| TaskFailure {
+                source: "collect",
+                task_id: run.task_id,
+                name: run.collection_name,
+                failed_at: run.finished_at,
+                reason: run.message,
+                trigger: Some(run.trigger),
+            }));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch failed collection runs: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取采集失败记录失败"
+            }));
+        }
     }
 
-    // 启动后台任务
-    let task_id = start_batch_delete_source(db, source_name.to_string()).await;
+    // 定时任务执行日志，排除已有同 task_id 采集记录的条目
+    let logs = db.collection::<Document>("task_execution_logs");
+    let log_match = vec![
+        doc! { "$match": { "status": "failed" } },
+        doc! { "$lookup": {
+            "from": "collection_runs",
+            "localField": "task_id",
+            "foreignField": "task_id",
+            "as": "runs"
+        } },
+        doc! { "$match": { "runs": { "$size": 0 } } },
+    ];
+    let mut count_pipeline = log_match.clone();
+    count_pipeline.push(doc! { "$count": "total" });
+    let mut page_pipeline = log_match;
+    page_pipeline.push(doc! { "$sort": { "completed_at": -1 } });
+    page_pipeline.push(doc! { "$limit": depth });
+    page_pipeline.push(doc! { "$project": { "runs": 0 } });
+
+    let log_result = async {
+        let counted: Vec<Document> = logs.aggregate(count_pipeline, None).await?.try_collect().await?;
+        let found: Vec<Document> = logs.aggregate(page_pipeline, None).await?.try_collect().await?;
+        Ok::<_, mongodb::error::Error>((counted, found))
+    }
+    .await;
+    match log_result {
+        Ok((counted, found)) => {
+            total += counted
+                .first()
+                .and_then(|d| d.get("total"))
+                .and_then(|v| v.as_i64().or_else(|| v.as_i32().map(i64::from)))
+                .unwrap_or(0) as u64;
+            failures.extend(found.into_iter().map(|log| TaskFailure {
+                source: "scheduled",
+                task_id: log.get_str("task_id").unwrap_or_default().to_string(),
+                name: log.get_str("collection_name").unwrap_or_default().to_string(),
+                failed_at: log
+                    .get_datetime("completed_at")
+                    .or_else(|_| log.get_datetime("started_at"))
+                    .copied()
+                    .unwrap_or(mongodb::bson::DateTime::MIN),
+                reason: log
+                    .get_str("errors")
+                    .or_else(|_| log.get_str("message"))
+                    .ok()
+                    .map(|r| r.to_string()),
+                trigger: Some(crate::collect_handlers::TRIGGER_SCHEDULED.to_string()),
+            }));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch failed task execution logs: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取定时任务失败记录失败"
+            }));
+        }
+    }
+
+    // 批量删除任务（仅本次启动以来、仍在内存中的任务）
+    {
+        let failed_at = get_batch_delete_failed_at_store().read().await;
+        let progress_map = get_batch_delete_progress_store().read().await;
+        for (task_id, (progress, task_name, _)) in progress_map.iter() {
+            if progress.status != "failed" {
+                continue;
+            }
+            total += 1;
+            failures.push(TaskFailure {
+                source: "batch_delete",
+                task_id: task_id.clone(),
+                name: task_name.clone(),
+                failed_at: failed_at
+                    .get(task_id)
+                    .copied()
+                    .unwrap_or(mongodb::bson::DateTime::MIN),
+                reason: Some(progress.log.clone()),
+                trigger: None,
+            });
+        }
+    }
+
+    failures.sort_by_key(|f| std::cmp::Reverse(f.failed_at));
+    let items: Vec<serde_json::Value> = failures
+        .iter()
+        .skip(skip as usize)
+        .take(limit as usize)
+        .map(TaskFailure::to_json)
+        .collect();
 
     HttpResponse::Ok().json(json!({
         "success": true,
-        "message": "批量删除任务已启动",
-        "task_id": task_id,
-        "source_name": source_name
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "failures": items
     }))
 }
 
-// GET /api/admin/batch-delete/progress/{task_id}
-pub async fn get_batch_delete_progress_handler(path: web::Path<String>, session: Session) -> impl Responder {
+// GET /api/admin/collect/runs
+pub async fn get_collection_runs(
+    db: web::Data<Database>,
+    query: web::Query<CollectionRunsQuery>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
 
-    let task_id = path.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).min(100);
+    let skip = (page - 1) * limit;
 
-    let progress = get_batch_delete_progress(&task_id).await
-        .unwrap_or_else(|| BatchDeleteProgress {
-            status: "not_found".to_string(),
-            processed_count: 0,
-            deleted_count: 0,
-            total_count: 0,
-            log: "任务不存在".to_string(),
-        });
+    let mut filter = doc! {};
+    if let Some(ref collection_id) = query.collection_id {
+        if !collection_id.is_empty() {
+            filter.insert("collection_id", collection_id);
+        }
+    }
 
-    HttpResponse::Ok().json(json!({
-        "success": true,
-        "progress": progress
-    }))
-}
+    let collection = db.collection::<CollectionRun>("collection_runs");
 
-// GET /api/admin/batch-delete/running-tasks
-pub async fn get_running_batch_delete_tasks_handler(session: Session) -> impl Responder {
-    if let Err(response) = check_auth(&session) {
-        return response;
-    }
+    let total = match collection.count_documents(filter.clone(), None).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count collection runs: {}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取采集记录总数失败"
+            }));
+        }
+    };
 
-    let tasks = get_all_batch_delete_tasks().await;
+    let find_options = FindOptions::builder()
+        .sort(doc! {"started_at": -1})
+        .skip(skip as u64)
+        .limit(limit as i64)
+        .build();
 
-    HttpResponse::Ok().json(json!({
-        "success": true,
-        "tasks": tasks
-    }))
+    match collection.find(filter, find_options).await {
+        Ok(cursor) => {
+            let runs: Vec<CollectionRun> = cursor.try_collect().await.unwrap_or_else(|_| vec![]);
+
+            HttpResponse::Ok().json(json!({
+                "success": true,
+                "page": page,
+                "limit": limit,
+                "total": total,
+                "runs": runs
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch collection runs: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "success": false,
+                "message": "获取采集记录失败"
+            }))
+        }
+    }
 }
 
-// POST /api/admin/batch-delete/stop/{task_id}
-pub async fn stop_batch_delete_task_handler(path: web::Path<String>, session: Session) -> impl Responder {
+// POST /api/admin/cache/purge
+// scope:
+//   home      - 首页渲染缓存
+//   counts    - 后台统计使用的集合计数缓存，可用 key 指定集合名
+//   sitemap   - 站点地图，可用 key 指定 index 或子地图序号
+//   site_data - 分类/配置等站点数据，从数据库重新加载
+//   all       - 以上全部（等同于 /admin/refresh-cache 并清除页面缓存）
+pub async fn purge_cache(
+    site_data_manager: web::Data<SiteDataManager>,
+    req: web::Json<CachePurgeRequest>,
+    session: Session,
+) -> impl Responder {
     if let Err(response) = check_auth(&session) {
         return response;
     }
 
-    let task_id = path.into_inner();
+    let scope = req.scope.trim();
+    let key = req.key.as_deref().map(|k| k.trim()).filter(|k| !k.is_empty());
 
-    let stopped = stop_batch_delete_task(&task_id).await;
+    let scopes: Vec<&str> = match scope {
+        cache::SCOPE_HOME | cache::SCOPE_COUNTS | cache::SCOPE_SITEMAP | "site_data" => vec![scope],
+        "all" => vec![cache::SCOPE_HOME, cache::SCOPE_COUNTS, cache::SCOPE_SITEMAP, "site_data"],
+        _ => {
+            return HttpResponse::BadRequest().json(json!({
+                "success": false,
+                "message": "无效的缓存区域，可选: home, counts, sitemap, site_data, all"
+            }));
+        }
+    };
 
-    if stopped {
-        HttpResponse::Ok().json(json!({
-            "success": true,
-            "message": "批量删除任务已成功停止"
-        }))
-    } else {
-        HttpResponse::NotFound().json(json!({
-            "success": false,
-            "message": "任务不存在或已经停止"
-        }))
+    let mut purged = Vec::new();
+    for scope in scopes {
+        if scope == "site_data" {
+            if let Err(e) = site_data_manager.refresh().await {
+                eprintln!("Cache refresh failed: {}", e);
+                return HttpResponse::InternalServerError().json(json!({
+                    "success": false,
+                    "message": format!("站点数据刷新失败: {}", e),
+                    "purged": purged
+                }));
+            }
+            purged.push("site_data".to_string());
+        } else {
+            purged.extend(cache::purge(scope, key).await);
+        }
     }
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": "缓存已清除",
+        "purged": purged
+    }))
 }
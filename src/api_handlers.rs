@@ -1,12 +1,33 @@
-use actix_web::{web, HttpResponse, Responder};
-use mongodb::{Database, bson::doc, options::FindOptions};
-use crate::dto::{ApiParams, JsonResponse, VodApiListEntry, VodId, Category, VideoFilterParams, CategoryHierarchy};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use mongodb::{Database, bson::{doc, oid::ObjectId, DateTime, Document}, options::{FindOptions, UpdateOptions}};
+use crate::dto::{ApiParams, JsonResponse, VodApiListEntry, VodId, Category, VideoFilterParams, CategoryHierarchy, CategoryTreeNode, BrokenReportRequest, VodLookupQuery, VideoDetailParams, VodEpisodesQuery, WatchProgressRequest, WatchHistoryQuery};
 use crate::models;
+use crate::client_ip::client_ip;
+use crate::resolve::resolve_object_id;
+use crate::site_data::SiteDataManager;
 use futures::{StreamExt, TryStreamExt};
 
+// Exclude videos from adult categories (and their sub-categories) unless the
+// caller explicitly passed include_adult=true
+async fn exclude_adult_content(
+    filter: &mut Document,
+    include_adult: Option<bool>,
+    site_data_manager: &SiteDataManager,
+) {
+    if include_adult.unwrap_or(false) {
+        return;
+    }
+    let adult_ids = site_data_manager.get_adult_type_ids().await;
+    models::exclude_vod_types(filter, &adult_ids);
+}
+
 // The main handler for the vod collection API
-pub async fn provide_vod(params: web::Query<ApiParams>, db: web::Data<Database>) -> impl Responder {
-    // Check for the format parameter, default to JSON
+pub async fn provide_vod(
+    params: web::Query<ApiParams>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    // Check for the format parameter, default to JSON; at=xml returns the classic MacCMS XML
     let format = params.at.as_deref().unwrap_or("json");
 
     // Build the MongoDB filter based on query parameters
@@ -17,6 +38,8 @@ pub async fn provide_vod(params: web::Query<ApiParams>, db: web::Data<Database>)
     if let Some(t) = params.t {
         filter.insert("type_id", t);
     }
+    exclude_adult_content(&mut filter, params.include_adult, &site_data_manager).await;
+    models::exclude_deleted_vods(&mut filter);
     // The 'h' parameter logic is temporarily removed due to a dependency issue.
     /*
     if let Some(h) = params.h {
@@ -35,7 +58,7 @@ pub async fn provide_vod(params: web::Query<ApiParams>, db: web::Data<Database>)
     let find_options = FindOptions::builder()
         .skip(Some(skip))
         .limit(Some(limit as i64))
-        .sort(doc! { "vod_pubdate": -1 })
+        .sort(crate::pagination::stable_sort(doc! { "vod_pubdate": -1 }))
         .build();
 
     // --- Database Query --- 
@@ -58,109 +81,258 @@ pub async fn provide_vod(params: web::Query<ApiParams>, db: web::Data<Database>)
     };
 
     // --- Data Transformation --- 
+    let poster = poster_fallback(params.fill_poster, &None, &site_data_manager).await;
+
+    // at=xml renders the same vods (including play sources) in the classic MacCMS XML layout
+    if format == "xml" {
+        let types = site_data_manager.get_all_categories().await;
+        let xml = render_vod_xml(&vod_docs, &types, &poster, page, pagecount, limit, total);
+        return HttpResponse::Ok().content_type("application/xml; charset=utf-8").body(xml);
+    }
+
     // In a real app, you'd query the Type collection. For now, we'll use a placeholder.
-    let list: Vec<VodApiListEntry> = vod_docs.into_iter().map(|vod| {
-        VodApiListEntry {
-            vod_id: VodId::Number(vod.id.unwrap().timestamp().to_string().parse().unwrap_or(0)),
-            vod_name: vod.vod_name,
-            type_id: vod.type_id,
-            type_name: Some("N/A".to_string()),
-            vod_time: vod.vod_pubdate.to_string(),
-            vod_remarks: vod.vod_remarks.unwrap_or_default(),
-            vod_play_from: vod.vod_play_urls.into_iter().map(|s| s.source_name).collect::<Vec<_>>().join(","),
-            vod_status: Some(vod.vod_status),
-            vod_letter: None,
-            vod_color: None,
-            vod_tag: None,
-            vod_class: vod.vod_class,
-            vod_pic: vod.vod_pic,
-            vod_pic_thumb: None,
-            vod_pic_slide: None,
-            vod_pic_screenshot: None,
-            vod_actor: vod.vod_actor,
-            vod_director: vod.vod_director,
-            vod_writer: None,
-            vod_behind: None,
-            vod_blurb: None,
-            vod_pubdate: None,
-            vod_total: None,
-            vod_serial: None,
-            vod_tv: None,
-            vod_weekday: None,
-            vod_area: vod.vod_area,
-            vod_lang: vod.vod_lang,
-            vod_year: vod.vod_year,
-            vod_version: None,
-            vod_state: None,
-            vod_author: None,
-            vod_jumpurl: None,
-            vod_tpl: None,
-            vod_tpl_play: None,
-            vod_tpl_down: None,
-            vod_isend: None,
-            vod_lock: None,
-            vod_level: None,
-            vod_copyright: None,
-            vod_points: None,
-            vod_points_play: None,
-            vod_points_down: None,
-            vod_hits: None,
-            vod_hits_day: None,
-            vod_hits_week: None,
-            vod_hits_month: None,
-            vod_duration: None,
-            vod_up: None,
-            vod_down: None,
-            vod_score: None,
-            vod_score_all: None,
-            vod_score_num: None,
-            vod_time_add: None,
-            vod_time_hits: None,
-            vod_time_make: None,
-            vod_trysee: None,
-            vod_douban_id: None,
-            vod_douban_score: None,
-            vod_reurl: None,
-            vod_rel_vod: None,
-            vod_rel_art: None,
-            vod_pwd: None,
-            vod_pwd_url: None,
-            vod_pwd_play: None,
-            vod_pwd_play_url: None,
-            vod_pwd_down: None,
-            vod_pwd_down_url: None,
-            vod_content: vod.vod_content,
-            vod_play_server: None,
-            vod_play_note: None,
-            vod_play_url: None,
-            vod_down_from: None,
-            vod_down_server: None,
-            vod_down_note: None,
-            vod_down_url: None,
-        }
-    }).collect();
+    let list: Vec<VodApiListEntry> = vod_docs.into_iter().map(|vod| vod_api_entry(vod, &poster)).collect();
 
     // --- Category List --- 
     // Placeholder for category list. A real implementation would query the 'types' collection.
     let categories: Vec<Category> = vec![];
 
     // --- Response Formatting --- 
-    if format == "xml" {
-        // TODO: Implement XML serialization using quick-xml
-        HttpResponse::Ok().content_type("application/xml").body("<rss><list><video><name>XML support coming soon</name></video></list></rss>")
-    } else {
-        let response = JsonResponse {
-            code: 1,
-            msg: "success".to_string(),
-            page,
-            pagecount,
-            limit,
-            total,
-            list,
-            categories,
+    let response = JsonResponse {
+        code: 1,
+        msg: "success".to_string(),
+        page,
+        pagecount,
+        limit,
+        total,
+        list,
+        categories,
+    };
+    HttpResponse::Ok().json(response)
+}
+
+// Convert a stored vod into a provide-vod list entry; an empty vod_pic falls back to poster
+pub(crate) fn vod_api_entry(vod: models::Vod, poster: &Option<String>) -> VodApiListEntry {
+    VodApiListEntry {
+        vod_id: VodId::Number(vod.id.unwrap().timestamp().to_string().parse().unwrap_or(0)),
+        vod_name: vod.vod_name,
+        type_id: vod.type_id,
+        type_name: Some("N/A".to_string()),
+        vod_time: vod.vod_pubdate.to_string(),
+        vod_remarks: vod.vod_remarks.unwrap_or_default(),
+        vod_play_from: vod.vod_play_urls.into_iter().map(|s| s.source_name).collect::<Vec<_>>().join(","),
+        vod_status: Some(vod.vod_status),
+        vod_letter: None,
+        vod_color: None,
+        vod_tag: None,
+        vod_class: vod.vod_class,
+        vod_pic: match poster {
+            Some(poster) if vod.vod_pic.as_deref().is_none_or(|p| p.trim().is_empty()) => Some(poster.clone()),
+            _ => vod.vod_pic,
+        },
+        vod_pic_thumb: None,
+        vod_pic_slide: None,
+        vod_pic_screenshot: None,
+        vod_actor: vod.vod_actor,
+        vod_director: vod.vod_director,
+        vod_writer: None,
+        vod_behind: None,
+        vod_blurb: None,
+        vod_pubdate: None,
+        vod_total: None,
+        vod_serial: None,
+        vod_tv: None,
+        vod_weekday: None,
+        vod_area: vod.vod_area,
+        vod_lang: vod.vod_lang,
+        vod_year: vod.vod_year,
+        vod_version: None,
+        vod_state: None,
+        vod_author: None,
+        vod_jumpurl: None,
+        vod_tpl: None,
+        vod_tpl_play: None,
+        vod_tpl_down: None,
+        vod_isend: None,
+        vod_lock: None,
+        vod_level: None,
+        vod_copyright: None,
+        vod_points: None,
+        vod_points_play: None,
+        vod_points_down: None,
+        vod_hits: None,
+        vod_hits_day: None,
+        vod_hits_week: None,
+        vod_hits_month: None,
+        vod_duration: None,
+        vod_up: None,
+        vod_down: None,
+        vod_score: None,
+        vod_score_all: None,
+        vod_score_num: None,
+        vod_time_add: None,
+        vod_time_hits: None,
+        vod_time_make: None,
+        vod_trysee: None,
+        vod_douban_id: None,
+        vod_douban_score: None,
+        vod_reurl: None,
+        vod_rel_vod: None,
+        vod_rel_art: None,
+        vod_pwd: None,
+        vod_pwd_url: None,
+        vod_pwd_play: None,
+        vod_pwd_play_url: None,
+        vod_pwd_down: None,
+        vod_pwd_down_url: None,
+        vod_content: vod.vod_content,
+        vod_play_server: None,
+        vod_play_note: None,
+        vod_play_url: None,
+        vod_down_from: None,
+        vod_down_server: None,
+        vod_down_note: None,
+        vod_down_url: None,
+    }
+}
+
+// Escape text and attribute values for the XML output (&, <, > and quotes)
+fn xml_escape(value: &str) -> std::borrow::Cow<'_, str> {
+    quick_xml::escape::escape(value)
+}
+
+// Wrap free-form HTML in CDATA; a literal "]]>" inside is split across two sections
+fn xml_cdata(value: &str) -> String {
+    format!("<![CDATA[{}]]>", value.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+// Classic MacCMS play URL block: episodes as "name$url" joined by '#', one <dd> per source
+fn xml_play_sources(sources: &[models::PlaySource]) -> String {
+    let mut dl = String::from("<dl>");
+    for source in sources {
+        let urls = source
+            .urls
+            .iter()
+            .map(|u| format!("{}${}", u.name, u.url))
+            .collect::<Vec<_>>()
+            .join("#");
+        dl.push_str(&format!(
+            "<dd flag=\"{}\">{}</dd>",
+            xml_escape(&source.source_name),
+            xml_escape(&urls)
+        ));
+    }
+    dl.push_str("</dl>");
+    dl
+}
+
+// Render the at=xml response: <rss><list pg pagecount pagesize total><video>...</video></list><class>...</class></rss>
+fn render_vod_xml(
+    vods: &[models::Vod],
+    types: &[models::Type],
+    poster: &Option<String>,
+    page: u64,
+    pagecount: u64,
+    pagesize: u64,
+    total: u64,
+) -> String {
+    let type_name = |type_id: i32| {
+        types
+            .iter()
+            .find(|t| t.type_id == type_id)
+            .map(|t| t.type_name.as_str())
+            .unwrap_or("")
+    };
+    let text = |value: &Option<String>| xml_escape(value.as_deref().unwrap_or("")).into_owned();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"5.1\">");
+    xml.push_str(&format!(
+        "<list pg=\"{}\" pagecount=\"{}\" pagesize=\"{}\" total=\"{}\">",
+        page, pagecount, pagesize, total
+    ));
+    for vod in vods {
+        let pic = match poster {
+            Some(poster) if vod.vod_pic.as_deref().is_none_or(|p| p.trim().is_empty()) => Some(poster.clone()),
+            _ => vod.vod_pic.clone(),
         };
-        HttpResponse::Ok().json(response)
+        xml.push_str("<video>");
+        let last = chrono::DateTime::from_timestamp_millis(vod.vod_pubdate.timestamp_millis())
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        xml.push_str(&format!("<last>{}</last>", last));
+        xml.push_str(&format!("<id>{}</id>", vod.id.map(|id| id.to_hex()).unwrap_or_default()));
+        xml.push_str(&format!("<tid>{}</tid>", vod.type_id));
+        xml.push_str(&format!("<name>{}</name>", xml_escape(&vod.vod_name)));
+        xml.push_str(&format!("<type>{}</type>", xml_escape(type_name(vod.type_id))));
+        xml.push_str(&format!("<pic>{}</pic>", text(&pic)));
+        xml.push_str(&format!("<lang>{}</lang>", text(&vod.vod_lang)));
+        xml.push_str(&format!("<area>{}</area>", text(&vod.vod_area)));
+        xml.push_str(&format!("<year>{}</year>", text(&vod.vod_year)));
+        xml.push_str(&format!("<note>{}</note>", text(&vod.vod_remarks)));
+        xml.push_str(&format!("<actor>{}</actor>", text(&vod.vod_actor)));
+        xml.push_str(&format!("<director>{}</director>", text(&vod.vod_director)));
+        xml.push_str(&xml_play_sources(&vod.vod_play_urls));
+        xml.push_str(&format!("<des>{}</des>", xml_cdata(vod.vod_content.as_deref().unwrap_or(""))));
+        xml.push_str("</video>");
+    }
+    xml.push_str("</list><class>");
+    for t in types.iter().filter(|t| t.type_status == 1) {
+        xml.push_str(&format!("<ty id=\"{}\">{}</ty>", t.type_id, xml_escape(&t.type_name)));
+    }
+    xml.push_str("</class></rss>");
+    xml
+}
+
+// Substitute the default poster for a missing or empty vod_pic; only applied when the
+// client passes fill_poster=true so the raw data stays retrievable
+fn fill_default_poster(video: &mut Document, poster: &str) {
+    let missing = match video.get("vod_pic") {
+        Some(mongodb::bson::Bson::String(pic)) => pic.trim().is_empty(),
+        Some(_) => false,
+        None => true,
+    };
+    if missing {
+        video.insert("vod_pic", poster);
+    }
+}
+
+// fill_poster only applies when vod_pic is part of the response (no projection, or vod_pic requested)
+async fn poster_fallback(
+    fill_poster: Option<bool>,
+    projection: &Option<Document>,
+    site_data_manager: &SiteDataManager,
+) -> Option<String> {
+    let requested = projection.as_ref().is_none_or(|p| p.contains_key("vod_pic"));
+    if fill_poster.unwrap_or(false) && requested {
+        Some(site_data_manager.get_default_poster().await)
+    } else {
+        None
+    }
+}
+
+// Vod fields clients may request through the `fields` query parameter
+const PROJECTABLE_VOD_FIELDS: &[&str] = &[
+    "vod_name", "type_id", "vod_status", "vod_class", "vod_pic", "vod_actor",
+    "vod_director", "vod_remarks", "vod_pubdate", "vod_area", "vod_lang", "vod_year",
+    "vod_content", "vod_hits", "vod_score", "vod_play_urls",
+];
+
+// Build a MongoDB projection from a comma-separated field list.
+// None means no projection (full document); unknown fields are rejected.
+fn build_fields_projection(fields: Option<&str>) -> Result<Option<Document>, String> {
+    let fields = match fields.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(fields) => fields,
+        None => return Ok(None),
+    };
+
+    let mut projection = doc! { "_id": 1 };
+    for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        if !PROJECTABLE_VOD_FIELDS.contains(&field) {
+            return Err(format!("Unknown field: {}", field));
+        }
+        projection.insert(field, 1);
     }
+    Ok(Some(projection))
 }
 
 // API endpoint to get videos by type_id
@@ -168,48 +340,90 @@ pub async fn get_videos_by_type(
     path: web::Path<i32>,
     query: web::Query<VideoFilterParams>,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
     let type_id = path.into_inner();
-    let mut filter = doc! { "type_id": type_id };
+    let projection = match build_fields_projection(query.fields.as_deref()) {
+        Ok(projection) => projection,
+        Err(msg) => return HttpResponse::BadRequest().json(serde_json::json!({ "code": 0, "msg": msg })),
+    };
+    // Exact type_id by default; include_children=true matches the category and all of its descendants,
+    // sub_type narrows to a single category. Vods listing a matched category in vod_extra_types are included
+    let type_ids = if let Some(sub_type) = query.sub_type {
+        vec![sub_type]
+    } else if query.include_children.unwrap_or(false) {
+        site_data_manager.get_descendant_type_ids(type_id).await
+    } else {
+        vec![type_id]
+    };
+    let mut filter = models::vod_type_filter(&type_ids);
     
     // Apply additional filters
     if let Some(area) = &query.area {
         filter.insert("vod_area", area);
     }
     if let Some(year) = &query.year {
-        filter.insert("vod_year", year);
-    }
-    if let Some(sub_type) = query.sub_type {
-        filter.insert("type_id", sub_type);
+        match models::parse_year_num(year) {
+            Some(year_num) => filter.insert("vod_year_num", year_num),
+            None => filter.insert("vod_year", year),
+        };
     }
+    exclude_adult_content(&mut filter, query.include_adult, &site_data_manager).await;
+    models::exclude_deleted_vods(&mut filter);
     
     // Pagination
     let page = query.pg.unwrap_or(1);
     let limit = query.limit.unwrap_or(20);
     let skip = if page > 0 { (page - 1) * limit } else { 0 };
     
-    let find_options = FindOptions::builder()
-        .skip(Some(skip))
-        .limit(Some(limit as i64))
-        .sort(doc! { "vod_pubdate": -1 })
-        .build();
-    
-    let vod_collection = db.collection::<models::Vod>("vods");
+    let poster = poster_fallback(query.fill_poster, &projection, &site_data_manager).await;
+
+    // Projected documents are partial, so read them as raw documents
+    let vod_collection = db.collection::<Document>("vods");
     
     let total = match vod_collection.count_documents(filter.clone(), None).await {
         Ok(count) => count,
         Err(_) => return HttpResponse::InternalServerError().body("Failed to count documents"),
     };
     
-    let cursor = match vod_collection.find(filter, find_options).await {
+    let cursor = match query.sort.as_deref() {
+        Some("trending") => {
+            // The trending score depends on the current time, so compute it per query
+            let gravity = crate::ranking::trending_gravity(&site_data_manager).await;
+            let pipeline = vec![
+                doc! { "$match": filter },
+                crate::ranking::trending_score_stage(gravity),
+                doc! { "$sort": crate::pagination::stable_sort(crate::ranking::trending_sort()) },
+                doc! { "$skip": skip as i64 },
+                doc! { "$limit": limit as i64 },
+                doc! { "$project": projection.unwrap_or_else(|| doc! { "trending_score": 0 }) },
+            ];
+            vod_collection.aggregate(pipeline, None).await
+        }
+        _ => {
+            let find_options = FindOptions::builder()
+                .skip(Some(skip))
+                .limit(Some(limit as i64))
+                .sort(crate::pagination::stable_sort(doc! { "vod_pubdate": -1 }))
+                .projection(projection)
+                .build();
+            vod_collection.find(filter, find_options).await
+        }
+    };
+    let cursor = match cursor {
         Ok(cursor) => cursor,
         Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch videos"),
     };
     
-    let videos: Vec<models::Vod> = match cursor.try_collect().await {
+    let mut videos: Vec<Document> = match cursor.try_collect().await {
         Ok(docs) => docs,
         Err(_) => return HttpResponse::InternalServerError().body("Failed to collect documents"),
     };
+    if let Some(poster) = &poster {
+        for video in videos.iter_mut() {
+            fill_default_poster(video, poster);
+        }
+    }
     
     HttpResponse::Ok().json(serde_json::json!({
         "code": 1,
@@ -256,10 +470,59 @@ pub async fn get_category_hierarchy(db: web::Data<Database>) -> impl Responder {
     }))
 }
 
+// API endpoint to get the enabled categories as a nested tree, siblings ordered by type_sort
+pub async fn get_category_tree(site_data_manager: web::Data<SiteDataManager>) -> impl Responder {
+    let categories: Vec<models::Type> = site_data_manager
+        .get_all_categories()
+        .await
+        .into_iter()
+        .filter(|c| c.type_status == 1)
+        .collect();
+
+    let mut visited = std::collections::HashSet::new();
+    let tree = build_category_tree(&categories, 0, &mut visited);
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "code": 1,
+        "msg": "success",
+        "tree": tree
+    }))
+}
+
+// Recursively nest categories under `pid`; `visited` guards against type_pid cycles
+fn build_category_tree(
+    categories: &[models::Type],
+    pid: i32,
+    visited: &mut std::collections::HashSet<i32>,
+) -> Vec<CategoryTreeNode> {
+    let mut children: Vec<&models::Type> = categories
+        .iter()
+        .filter(|c| c.type_pid == pid && c.type_id != pid)
+        .collect();
+    children.sort_by_key(|c| (c.type_sort, c.type_id));
+
+    let mut nodes = Vec::new();
+    for category in children {
+        if !visited.insert(category.type_id) {
+            continue;
+        }
+        nodes.push(CategoryTreeNode {
+            type_id: category.type_id,
+            type_name: category.type_name.clone(),
+            type_en: category.type_en.clone(),
+            type_sort: category.type_sort,
+            children: build_category_tree(categories, category.type_id, visited),
+        });
+    }
+    nodes
+}
+
 // API endpoint to get video details with play URLs grouped by source
 pub async fn get_video_details(
     path: web::Path<String>,
+    query: web::Query<VideoDetailParams>,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
     let vod_id = path.into_inner();
     
@@ -267,17 +530,34 @@ pub async fn get_video_details(
         Ok(id) => id,
         Err(_) => return HttpResponse::BadRequest().body("Invalid video ID"),
     };
+
+    let projection = match build_fields_projection(query.fields.as_deref()) {
+        Ok(projection) => projection,
+        Err(msg) => return HttpResponse::BadRequest().json(serde_json::json!({ "code": 0, "msg": msg })),
+    };
+    let poster = poster_fallback(query.fill_poster, &projection, &site_data_manager).await;
+    let find_options = mongodb::options::FindOneOptions::builder()
+        .projection(projection)
+        .build();
     
-    let vod_collection = db.collection::<models::Vod>("vods");
+    // Projected documents are partial, so read them as raw documents
+    let vod_collection = db.collection::<Document>("vods");
     
-    let video = match vod_collection.find_one(doc!{"_id": object_id}, None).await {
+    let mut filter = doc! {"_id": object_id};
+    exclude_adult_content(&mut filter, query.include_adult, &site_data_manager).await;
+    models::exclude_deleted_vods(&mut filter);
+
+    let mut video = match vod_collection.find_one(filter, find_options).await {
         Ok(Some(v)) => v,
         Ok(None) => return HttpResponse::NotFound().body("Video not found"),
         Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch video"),
     };
+    if let Some(poster) = &poster {
+        fill_default_poster(&mut video, poster);
+    }
     
     // Group play URLs by source (already grouped in the model)
-    let play_sources = video.vod_play_urls.clone();
+    let play_sources = video.get("vod_play_urls").cloned().unwrap_or_else(|| mongodb::bson::Bson::Array(vec![]));
     
     HttpResponse::Ok().json(serde_json::json!({
         "code": 1,
@@ -287,13 +567,417 @@ pub async fn get_video_details(
     }))
 }
 
+// Rebuild MacCMS-style play strings from the grouped play sources:
+// vod_play_from = "src1$$$src2", vod_play_url = "ep1$url1#ep2$url2$$$..."
+pub(crate) fn build_play_strings(play_sources: &[models::PlaySource]) -> (String, String) {
+    let play_from = play_sources
+        .iter()
+        .map(|s| s.source_name.as_str())
+        .collect::<Vec<_>>()
+        .join("$$$");
+    let play_url = play_sources
+        .iter()
+        .map(|s| {
+            s.urls
+                .iter()
+                .map(|u| format!("{}${}", u.name, u.url))
+                .collect::<Vec<_>>()
+                .join("#")
+        })
+        .collect::<Vec<_>>()
+        .join("$$$");
+    (play_from, play_url)
+}
+
+// API endpoint to look up a video by its natural key (vod_name + vod_year),
+// the same key collect_single_video uses to dedup collected videos
+pub async fn lookup_video(
+    query: web::Query<VodLookupQuery>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let name = query.name.trim();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "code": 0,
+            "msg": "name is required"
+        }));
+    }
+
+    let mut filter = doc! { "vod_name": name };
+    if let Some(year) = query.year.as_deref().map(str::trim).filter(|y| !y.is_empty()) {
+        filter.insert("vod_year", year);
+    }
+    exclude_adult_content(&mut filter, query.include_adult, &site_data_manager).await;
+    models::exclude_deleted_vods(&mut filter);
+
+    // Fetch at most a handful of candidates; more than one match is a conflict anyway
+    let find_options = FindOptions::builder()
+        .sort(doc! { "vod_pubdate": -1 })
+        .limit(Some(20))
+        .build();
+
+    let vod_collection = db.collection::<models::Vod>("vods");
+    let videos: Vec<models::Vod> = match vod_collection.find(filter, find_options).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(_) => return HttpResponse::InternalServerError().body("Failed to collect documents"),
+        },
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch videos"),
+    };
+
+    match videos.len() {
+        0 => HttpResponse::NotFound().json(serde_json::json!({
+            "code": 0,
+            "msg": "Video not found"
+        })),
+        1 => {
+            let video = &videos[0];
+            let (vod_play_from, vod_play_url) = build_play_strings(&video.vod_play_urls);
+            HttpResponse::Ok().json(serde_json::json!({
+                "code": 1,
+                "msg": "success",
+                "video": video,
+                "vod_play_from": vod_play_from,
+                "vod_play_url": vod_play_url
+            }))
+        }
+        _ => {
+            let candidates: Vec<serde_json::Value> = videos
+                .iter()
+                .map(|v| {
+                    serde_json::json!({
+                        "vod_id": v.id.map(|id| id.to_hex()).unwrap_or_default(),
+                        "vod_name": v.vod_name,
+                        "vod_year": v.vod_year,
+                        "type_id": v.type_id,
+                        "vod_remarks": v.vod_remarks
+                    })
+                })
+                .collect();
+            HttpResponse::Conflict().json(serde_json::json!({
+                "code": 0,
+                "msg": "Multiple videos match, specify year to disambiguate",
+                "candidates": candidates
+            }))
+        }
+    }
+}
+
+// Compare strings treating runs of digits as numbers, so "第2集" < "第10集"
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_num = String::new();
+                while let Some(c) = a_chars.peek().copied().filter(char::is_ascii_digit) {
+                    x_num.push(c);
+                    a_chars.next();
+                }
+                let mut y_num = String::new();
+                while let Some(c) = b_chars.peek().copied().filter(char::is_ascii_digit) {
+                    y_num.push(c);
+                    b_chars.next();
+                }
+                // Compare by magnitude without parsing, so long digit runs cannot overflow
+                let x_trim = x_num.trim_start_matches('0');
+                let y_trim = y_num.trim_start_matches('0');
+                let ord = x_trim.len().cmp(&y_trim.len()).then_with(|| x_trim.cmp(y_trim));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+// Play URLs are only returned to referers listed in the comma-separated
+// `episode_api_referers` config; an empty config returns them to everyone
+async fn episode_urls_allowed(req: &HttpRequest, site_data_manager: &SiteDataManager) -> bool {
+    let allowed = site_data_manager
+        .get_config("episode_api_referers")
+        .await
+        .unwrap_or_default();
+    let allowed: Vec<&str> = allowed.split(',').map(str::trim).filter(|h| !h.is_empty()).collect();
+    if allowed.is_empty() {
+        return true;
+    }
+
+    let referer_host = req
+        .headers()
+        .get(actix_web::http::header::REFERER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| reqwest::Url::parse(v).ok())
+        .and_then(|u| u.host_str().map(str::to_lowercase));
+    match referer_host {
+        Some(host) => allowed.iter().any(|h| h.eq_ignore_ascii_case(&host)),
+        None => false,
+    }
+}
+
+// Only published videos are playable; adult categories are hidden unless include_adult=true
+async fn fetch_playable_vod(
+    db: &Database,
+    site_data_manager: &SiteDataManager,
+    object_id: ObjectId,
+    include_adult: Option<bool>,
+) -> Result<models::Vod, HttpResponse> {
+    let mut filter = doc! { "_id": object_id, "vod_status": 1 };
+    exclude_adult_content(&mut filter, include_adult, site_data_manager).await;
+    models::exclude_deleted_vods(&mut filter);
+
+    match db.collection::<models::Vod>("vods").find_one(filter, None).await {
+        Ok(Some(v)) => Ok(v),
+        Ok(None) => Err(HttpResponse::NotFound().json(serde_json::json!({ "code": 0, "msg": "Video not found" }))),
+        Err(_) => Err(HttpResponse::InternalServerError().body("Failed to fetch video")),
+    }
+}
+
+fn play_source_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "code": 0,
+        "msg": "Play source not found"
+    }))
+}
+
+// Episodes in natural order ("第2集" before "第10集"), paired with their stored index
+fn sorted_episodes(source: &models::PlaySource) -> Vec<(usize, &models::PlayUrl)> {
+    let mut episodes: Vec<(usize, &models::PlayUrl)> = source.urls.iter().enumerate().collect();
+    episodes.sort_by(|(_, a), (_, b)| natural_cmp(&a.name, &b.name));
+    episodes
+}
+
+// API endpoint to get the episode list of one play source, for custom players
+pub async fn get_video_episodes(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<VodEpisodesQuery>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let object_id = match resolve_object_id(&path.into_inner(), "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let video = match fetch_playable_vod(&db, &site_data_manager, object_id, query.include_adult).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    let source_idx = query.source.unwrap_or(0);
+    let source = match video.vod_play_urls.get(source_idx) {
+        Some(source) => source,
+        None => return play_source_not_found(),
+    };
+
+    let sources: Vec<serde_json::Value> = video
+        .vod_play_urls
+        .iter()
+        .enumerate()
+        .map(|(index, s)| serde_json::json!({
+            "index": index,
+            "source_name": s.source_name,
+            "episode_count": s.urls.len()
+        }))
+        .collect();
+
+    // `index` is the position in the stored list, used by /play/{vod_id}/{source}-{index}
+    let with_urls = episode_urls_allowed(&req, &site_data_manager).await;
+    let token_ttl = crate::play_token::play_token_ttl(&site_data_manager).await;
+    let ip = client_ip(&req);
+    let episodes: Vec<serde_json::Value> = sorted_episodes(source)
+        .into_iter()
+        .map(|(index, ep)| {
+            let mut entry = serde_json::json!({ "index": index, "name": ep.name });
+            if let Some(resolution) = &ep.resolution {
+                entry["resolution"] = serde_json::Value::String(resolution.clone());
+            }
+            if let Some(format) = &ep.format {
+                entry["format"] = serde_json::Value::String(format.clone());
+            }
+            if with_urls {
+                // With protect_play_urls on, hand out signed /stream URLs instead of raw ones
+                let url = match token_ttl {
+                    Some(ttl) => crate::play_token::stream_url(&object_id, source_idx, index, &ep.url, &ip, ttl),
+                    None => ep.url.clone(),
+                };
+                entry["url"] = serde_json::Value::String(url);
+            }
+            entry
+        })
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "code": 1,
+        "msg": "success",
+        "vod_id": object_id.to_hex(),
+        "vod_name": video.vod_name,
+        "source": source_idx,
+        "sources": sources,
+        "urls_hidden": !with_urls,
+        "episodes": episodes
+    }))
+}
+
+// M3U playlist of one play source, so a whole series can be opened in an external player
+pub async fn get_video_playlist(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<VodEpisodesQuery>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let object_id = match resolve_object_id(&path.into_inner(), "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    // A playlist without URLs is useless, so referers outside the whitelist are rejected outright
+    if !episode_urls_allowed(&req, &site_data_manager).await {
+        return HttpResponse::Forbidden().json(serde_json::json!({ "code": 0, "msg": "Play URLs are not available" }));
+    }
+
+    let video = match fetch_playable_vod(&db, &site_data_manager, object_id, query.include_adult).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    let source_idx = query.source.unwrap_or(0);
+    let source = match video.vod_play_urls.get(source_idx) {
+        Some(source) => source,
+        None => return play_source_not_found(),
+    };
+
+    // External players resolve relative URLs against the file location, so /stream links are made absolute
+    let token_ttl = crate::play_token::play_token_ttl(&site_data_manager).await;
+    let ip = client_ip(&req);
+    let base_url = match site_data_manager.get_config("site_url").await {
+        Some(url) if !url.trim().is_empty() => url.trim().trim_end_matches('/').to_string(),
+        _ => {
+            let info = req.connection_info();
+            format!("{}://{}", info.scheme(), info.host())
+        }
+    };
+
+    let mut playlist = String::from("#EXTM3U\n");
+    for (index, ep) in sorted_episodes(source) {
+        if ep.url.trim().is_empty() {
+            continue;
+        }
+        let url = match token_ttl {
+            Some(ttl) => format!(
+                "{}{}",
+                base_url,
+                crate::play_token::stream_url(&object_id, source_idx, index, &ep.url, &ip, ttl)
+            ),
+            None => ep.url.trim().to_string(),
+        };
+        // A line break in the title would split the #EXTINF entry
+        let title = format!("{} {}", video.vod_name, ep.name).replace(['\r', '\n'], " ");
+        playlist.push_str(&format!("#EXTINF:-1,{}\n{}\n", title.trim(), url));
+    }
+
+    HttpResponse::Ok()
+        .content_type("audio/x-mpegurl; charset=utf-8")
+        .insert_header((
+            actix_web::http::header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}-{}.m3u\"", object_id.to_hex(), source_idx),
+        ))
+        .body(playlist)
+}
+
+// API endpoint to get the category chain (top-level first) for a video
+pub async fn get_video_breadcrumb(
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let object_id = match resolve_object_id(&path.into_inner(), "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let find_options = mongodb::options::FindOneOptions::builder()
+        .projection(doc! { "type_id": 1 })
+        .build();
+    let mut filter = doc! { "_id": object_id };
+    models::exclude_deleted_vods(&mut filter);
+    let type_id = match db.collection::<Document>("vods").find_one(filter, find_options).await {
+        Ok(Some(v)) => match v.get_i32("type_id") {
+            Ok(type_id) => type_id,
+            Err(_) => return HttpResponse::InternalServerError().body("Invalid video type"),
+        },
+        Ok(None) => return HttpResponse::NotFound().body("Video not found"),
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch video"),
+    };
+
+    let breadcrumb: Vec<serde_json::Value> = site_data_manager
+        .get_category_breadcrumb(type_id)
+        .await
+        .into_iter()
+        .map(|c| serde_json::json!({ "type_id": c.type_id, "type_name": c.type_name, "type_en": c.type_en }))
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "code": 1,
+        "msg": "success",
+        "breadcrumb": breadcrumb
+    }))
+}
+
+// Config keys the public frontend may read. Anything not listed here stays
+// admin-only, so new (possibly sensitive) configs are private by default.
+const PUBLIC_CONFIG_KEYS: &[&str] = &[
+    "site_name",
+    "site_url",
+    "site_keywords",
+    "site_description",
+    "site_logo",
+];
+const PUBLIC_CONFIG_PREFIXES: &[&str] = &["notice_"];
+
+fn is_public_config(key: &str) -> bool {
+    PUBLIC_CONFIG_KEYS.contains(&key) || PUBLIC_CONFIG_PREFIXES.iter().any(|p| key.starts_with(p))
+}
+
+// API endpoint to get the allowlisted public configs (branding, notices)
+pub async fn get_public_configs(site_data_manager: web::Data<SiteDataManager>) -> impl Responder {
+    let configs: std::collections::BTreeMap<String, String> = site_data_manager
+        .get_all_configs()
+        .await
+        .into_iter()
+        .filter(|(key, _)| is_public_config(key))
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "code": 1,
+        "msg": "success",
+        "configs": configs
+    }))
+}
+
 // API endpoint to get unique areas and years for filtering
 pub async fn get_filter_options(db: web::Data<Database>) -> impl Responder {
     let vod_collection = db.collection::<models::Vod>("vods");
     
     // Get unique areas
     let areas_pipeline = vec![
-        doc! { "$match": { "vod_area": { "$ne": null, "$ne": "" } } },
+        doc! { "$match": { "vod_area": { "$ne": null, "$ne": "" }, "vod_deleted_at": { "$exists": false } } },
         doc! { "$group": { "_id": "$vod_area" } },
         doc! { "$sort": { "_id": 1 } }
     ];
@@ -315,8 +999,8 @@ pub async fn get_filter_options(db: web::Data<Database>) -> impl Responder {
     
     // Get unique years
     let years_pipeline = vec![
-        doc! { "$match": { "vod_year": { "$ne": null, "$ne": "" } } },
-        doc! { "$group": { "_id": "$vod_year" } },
+        doc! { "$match": { "vod_year_num": { "$ne": null }, "vod_deleted_at": { "$exists": false } } },
+        doc! { "$group": { "_id": "$vod_year_num" } },
         doc! { "$sort": { "_id": -1 } }
     ];
     
@@ -325,7 +1009,7 @@ pub async fn get_filter_options(db: web::Data<Database>) -> impl Responder {
             let mut result = Vec::new();
             while let Some(doc) = cursor.next().await {
                 if let Ok(year_doc) = doc {
-                    if let Ok(year) = year_doc.get_str("_id") {
+                    if let Ok(year) = year_doc.get_i32("_id") {
                         result.push(year.to_string());
                     }
                 }
@@ -342,3 +1026,369 @@ pub async fn get_filter_options(db: web::Data<Database>) -> impl Responder {
         "years": years
     }))
 }
+
+// --- Broken link reports ---
+
+// 每个IP在时间窗口内允许提交的报告次数
+const REPORT_RATE_LIMIT: u32 = 10;
+const REPORT_RATE_WINDOW_SECS: u64 = 3600;
+const REPORT_REASON_MAX_LEN: usize = 200;
+// 同一IP对同一剧集在此时间内只计一次；去重记录只保存哈希，由 TTL 索引到期删除，不长期保留访客IP
+pub const REPORT_DEDUP_TTL_SECS: u64 = 24 * 3600;
+
+fn report_dedup_key(vod_id: &ObjectId, play_source: usize, play_idx: usize, ip: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(format!("{}.{}.{}.{}", vod_id.to_hex(), play_source, play_idx, ip));
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// In-memory per-IP rate limiter: ip -> (count, window start)
+static REPORT_RATE_LIMITER: std::sync::OnceLock<
+    tokio::sync::RwLock<std::collections::HashMap<String, (u32, std::time::Instant)>>,
+> = std::sync::OnceLock::new();
+
+fn get_report_rate_limiter(
+) -> &'static tokio::sync::RwLock<std::collections::HashMap<String, (u32, std::time::Instant)>> {
+    REPORT_RATE_LIMITER.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
+}
+
+// Returns false when the IP has used up its quota for the current window
+async fn check_report_rate_limit(ip: &str) -> bool {
+    let window = std::time::Duration::from_secs(REPORT_RATE_WINDOW_SECS);
+    let mut limiter = get_report_rate_limiter().write().await;
+    let now = std::time::Instant::now();
+
+    // 顺带清理过期的记录，避免内存无限增长
+    limiter.retain(|_, (_, started)| now.duration_since(*started) < window);
+
+    let entry = limiter.entry(ip.to_string()).or_insert((0, now));
+    if entry.0 >= REPORT_RATE_LIMIT {
+        return false;
+    }
+    entry.0 += 1;
+    true
+}
+
+// Public endpoint for users to report a broken play link
+pub async fn report_broken(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    payload: web::Json<BrokenReportRequest>,
+) -> impl Responder {
+    let client_ip = client_ip(&req);
+
+    if !check_report_rate_limit(&client_ip).await {
+        return HttpResponse::TooManyRequests().json(serde_json::json!({
+            "code": 0,
+            "msg": "Too many reports, please try again later"
+        }));
+    }
+
+    let vod_id = match resolve_object_id(&payload.vod_id, "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let reason = payload
+        .reason
+        .as_deref()
+        .map(|r| r.trim().chars().take(REPORT_REASON_MAX_LEN).collect::<String>())
+        .filter(|r| !r.is_empty());
+
+    let mut filter = doc! { "_id": vod_id };
+    models::exclude_deleted_vods(&mut filter);
+    let video = match db.collection::<models::Vod>("vods").find_one(filter, None).await {
+        Ok(Some(video)) => video,
+        Ok(None) => return HttpResponse::NotFound().body("Video not found"),
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch video"),
+    };
+
+    // 校验播放源和剧集索引
+    let source = match video.vod_play_urls.get(payload.play_source) {
+        Some(source) if payload.play_idx < source.urls.len() => source,
+        _ => return HttpResponse::BadRequest().body("Invalid play source or episode"),
+    };
+
+    let collection = db.collection::<models::BrokenReport>("broken_reports");
+    let key = doc! {
+        "vod_id": vod_id,
+        "play_source": payload.play_source as i32,
+        "play_idx": payload.play_idx as i32,
+    };
+
+    // 同一IP对同一剧集在去重窗口内只计一次：_id 唯一，重复写入即已报告过
+    let reporters = db.collection::<Document>("broken_report_reporters");
+    let dedup_id = report_dedup_key(&vod_id, payload.play_source, payload.play_idx, &client_ip);
+    match reporters
+        .insert_one(doc! { "_id": &dedup_id, "created_at": DateTime::now() }, None)
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("E11000 duplicate key error") => {
+            return HttpResponse::Ok().json(serde_json::json!({
+                "code": 1,
+                "msg": "Already reported, thanks"
+            }))
+        }
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to save report"),
+    }
+
+    let now = DateTime::now();
+    let mut set_doc = doc! {
+        "vod_name": &video.vod_name,
+        "source_name": &source.source_name,
+        "last_reported_at": now,
+    };
+    if let Some(reason) = &reason {
+        set_doc.insert("last_reason", reason);
+    }
+
+    let update = doc! {
+        "$inc": { "report_count": 1 },
+        "$set": set_doc,
+        "$setOnInsert": { "first_reported_at": now },
+    };
+    let options = UpdateOptions::builder().upsert(true).build();
+
+    match collection.update_one(key, update, options).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "code": 1,
+            "msg": "Report received, thanks"
+        })),
+        Err(_) => {
+            // 报告未写入，撤销去重记录以便重试
+            let _ = reporters.delete_one(doc! { "_id": &dedup_id }, None).await;
+            HttpResponse::InternalServerError().body("Failed to save report")
+        }
+    }
+}
+
+fn login_required() -> HttpResponse {
+    HttpResponse::Unauthorized().json(serde_json::json!({
+        "code": 0,
+        "msg": "请先登录"
+    }))
+}
+
+fn watch_history_json(entry: &models::WatchHistory) -> serde_json::Value {
+    let vod_id = entry.vod_id.to_hex();
+    serde_json::json!({
+        "vod_id": &vod_id,
+        "vod_name": &entry.vod_name,
+        "vod_pic": &entry.vod_pic,
+        "source": entry.play_source,
+        "episode": entry.play_idx,
+        "episode_name": &entry.episode_name,
+        "position_seconds": entry.position_seconds,
+        "play_url": format!("/play/{}/{}-{}", vod_id, entry.play_source, entry.play_idx),
+        "updated_at": entry.updated_at.timestamp_millis() / 1000,
+    })
+}
+
+// POST /api/user/progress - save the playback position of the current episode.
+// The player posts every watch_progress_interval seconds; anonymous users are rejected.
+pub async fn save_watch_progress(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    payload: web::Json<WatchProgressRequest>,
+) -> impl Responder {
+    let Some(user_id) = crate::auth_handlers::bearer_user_id(&req) else {
+        return login_required();
+    };
+
+    let interval = crate::watch_history::watch_progress_interval(&site_data_manager).await;
+    if interval == 0 {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "code": 0,
+            "msg": "播放进度记录已关闭"
+        }));
+    }
+
+    let position_seconds = match crate::watch_history::validate_position(payload.position_seconds) {
+        Ok(position) => position,
+        Err(msg) => return HttpResponse::BadRequest().json(serde_json::json!({"code": 0, "msg": msg})),
+    };
+
+    let vod_id = match resolve_object_id(&payload.vod_id, "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    if !crate::watch_history::allow_progress_write(user_id, vod_id, interval).await {
+        return HttpResponse::TooManyRequests().json(serde_json::json!({
+            "code": 0,
+            "msg": "播放进度上报过于频繁"
+        }));
+    }
+
+    let mut filter = doc! { "_id": vod_id };
+    models::exclude_deleted_vods(&mut filter);
+    let video = match db.collection::<models::Vod>("vods").find_one(filter, None).await {
+        Ok(Some(video)) => video,
+        Ok(None) => return HttpResponse::NotFound().body("Video not found"),
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch video"),
+    };
+
+    // 校验播放源和剧集索引
+    let episode = match video
+        .vod_play_urls
+        .get(payload.source)
+        .and_then(|source| source.urls.get(payload.episode))
+    {
+        Some(episode) => episode,
+        None => return HttpResponse::BadRequest().body("Invalid play source or episode"),
+    };
+
+    // 每个用户每部视频只保留一条记录，切换剧集时覆盖
+    let update = doc! {
+        "$set": {
+            "vod_name": &video.vod_name,
+            "vod_pic": video.vod_pic.as_deref().unwrap_or(""),
+            "play_source": payload.source as i32,
+            "play_idx": payload.episode as i32,
+            "episode_name": &episode.name,
+            "position_seconds": position_seconds,
+            "updated_at": DateTime::now(),
+        }
+    };
+    let options = UpdateOptions::builder().upsert(true).build();
+
+    match db
+        .collection::<models::WatchHistory>("watch_history")
+        .update_one(doc! { "user_id": user_id, "vod_id": vod_id }, update, options)
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "code": 1,
+            "msg": "ok"
+        })),
+        Err(_) => HttpResponse::InternalServerError().body("Failed to save progress"),
+    }
+}
+
+// GET /api/user/progress/{vod_id} - resume point for one video, data is null when never watched
+pub async fn get_watch_progress(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let Some(user_id) = crate::auth_handlers::bearer_user_id(&req) else {
+        return login_required();
+    };
+
+    let vod_id = match resolve_object_id(&path.into_inner(), "video") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    match db
+        .collection::<models::WatchHistory>("watch_history")
+        .find_one(doc! { "user_id": user_id, "vod_id": vod_id }, None)
+        .await
+    {
+        Ok(entry) => HttpResponse::Ok().json(serde_json::json!({
+            "code": 1,
+            "msg": "ok",
+            "data": entry.as_ref().map(watch_history_json),
+        })),
+        Err(_) => HttpResponse::InternalServerError().body("Failed to fetch progress"),
+    }
+}
+
+// GET /api/user/history - recently watched videos with their resume points, newest first
+pub async fn get_watch_history(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    query: web::Query<WatchHistoryQuery>,
+) -> impl Responder {
+    let Some(user_id) = crate::auth_handlers::bearer_user_id(&req) else {
+        return login_required();
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(crate::watch_history::DEFAULT_HISTORY_LIMIT)
+        .clamp(1, crate::watch_history::MAX_HISTORY_LIMIT);
+    // Skip entries whose video is in the trash or gone; the limit applies after filtering
+    let pipeline = vec![
+        doc! { "$match": { "user_id": user_id } },
+        doc! { "$sort": { "updated_at": -1 } },
+        doc! { "$lookup": {
+            "from": "vods",
+            "let": { "vod_id": "$vod_id" },
+            "pipeline": [
+                { "$match": { "$expr": { "$eq": ["$_id", "$$vod_id"] }, "vod_deleted_at": { "$exists": false } } },
+                { "$project": { "_id": 1 } }
+            ],
+            "as": "live_vod"
+        } },
+        doc! { "$match": { "live_vod": { "$ne": [] } } },
+        doc! { "$limit": limit },
+        doc! { "$project": { "live_vod": 0 } },
+    ];
+
+    let entries: Vec<models::WatchHistory> = match db
+        .collection::<Document>("watch_history")
+        .aggregate(pipeline, None)
+        .await
+    {
+        Ok(cursor) => cursor
+            .try_collect::<Vec<Document>>()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|d| mongodb::bson::from_document(d).ok())
+            .collect(),
+        Err(_) => return HttpResponse::InternalServerError().body("Failed to fetch history"),
+    };
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "code": 1,
+        "msg": "ok",
+        "list": entries.iter().map(watch_history_json).collect::<Vec<_>>(),
+    }))
+}
+
+// GET /version - build info for checking which build is deployed
+pub async fn get_version() -> impl Responder {
+    let build_time = env!("BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    HttpResponse::Ok()
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-store"))
+        .json(serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "git_commit": env!("GIT_COMMIT_HASH"),
+            "build_time": build_time
+        }))
+}
+
+// Process start time, stored once in app state for the uptime reported by /healthz
+#[derive(Clone, Copy)]
+pub struct StartedAt(pub std::time::Instant);
+
+// GET /healthz - liveness/readiness probe; only pings MongoDB, no site data or collection scans
+pub async fn healthz(db: web::Data<Database>, started_at: web::Data<StartedAt>) -> impl Responder {
+    let uptime_seconds = started_at.0.elapsed().as_secs();
+    let (mut response, body) = match db.run_command(doc! { "ping": 1 }, None).await {
+        Ok(_) => (
+            HttpResponse::Ok(),
+            serde_json::json!({ "status": "ok", "db": "up", "uptime_seconds": uptime_seconds }),
+        ),
+        Err(e) => {
+            eprintln!("Health check ping failed: {}", e);
+            (
+                HttpResponse::ServiceUnavailable(),
+                serde_json::json!({ "status": "error", "db": "down", "uptime_seconds": uptime_seconds }),
+            )
+        }
+    };
+    response
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-store"))
+        .json(body)
+}
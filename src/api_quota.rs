@@ -0,0 +1,183 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::{web, Error, HttpResponse};
+use crate::client_ip::client_ip;
+use crate::site_data::SiteDataManager;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+// 单个客户端IP每天可调用 /api/* 的次数上限（api_daily_quota 配置），超出时返回 429。
+// 与 concurrency_limit 互补：并发限制挡住瞬时洪峰，配额限制挡住全天持续抓取的爬虫。
+// 计数按 UTC 自然日划分，每天 UTC 0 点清零；计数保存在进程内存中，多实例部署时各实例分别计数。
+pub const DEFAULT_API_DAILY_QUOTA: u64 = 0;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+struct QuotaState {
+    // 当前计数所属的日期（自 1970-01-01 起的 UTC 天数）
+    day: i64,
+    counts: HashMap<String, u64>,
+}
+
+// 在 HttpServer::new 之外创建一次，所有 worker 共享同一张计数表
+#[derive(Clone)]
+pub struct ApiQuota {
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl ApiQuota {
+    pub fn new() -> Self {
+        ApiQuota {
+            state: Arc::new(Mutex::new(QuotaState {
+                day: current_day(),
+                counts: HashMap::new(),
+            })),
+        }
+    }
+}
+
+fn current_day() -> i64 {
+    Utc::now().timestamp().div_euclid(SECONDS_PER_DAY)
+}
+
+impl QuotaState {
+    // 记一次请求，返回记录后的已用次数；已达上限时不再累加并返回 None
+    fn try_consume(&mut self, ip: &str, limit: u64) -> Option<u64> {
+        let today = current_day();
+        if today != self.day {
+            // 跨天后整表清空，昨天的IP不再占用内存
+            self.counts.clear();
+            self.day = today;
+        }
+
+        let used = self.counts.entry(ip.to_string()).or_insert(0);
+        if *used >= limit {
+            return None;
+        }
+        *used += 1;
+        Some(*used)
+    }
+
+    // 下一次清零的时间（Unix 秒）
+    fn reset_at(&self) -> i64 {
+        (self.day + 1) * SECONDS_PER_DAY
+    }
+}
+
+// 校验 api_daily_quota 配置：非负整数，0 表示不限制
+pub fn parse_api_daily_quota(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "api_daily_quota 必须是非负整数，0 表示不限制".to_string())
+}
+
+async fn api_daily_quota(site_data: &SiteDataManager) -> u64 {
+    site_data
+        .get_config("api_daily_quota")
+        .await
+        .and_then(|v| parse_api_daily_quota(&v).ok())
+        .unwrap_or(DEFAULT_API_DAILY_QUOTA)
+}
+
+// 只统计公开 API；后台管理、登录和采集控制接口由登录态保护，不计入配额
+fn is_counted(path: &str) -> bool {
+    if !path.starts_with("/api/") {
+        return false;
+    }
+    !["/api/admin", "/api/auth", "/api/collect"]
+        .iter()
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+}
+
+fn insert_quota_headers(headers: &mut HeaderMap, limit: u64, remaining: u64, reset_at: i64) {
+    headers.insert(HeaderName::from_static("x-ratelimit-limit"), HeaderValue::from(limit));
+    headers.insert(HeaderName::from_static("x-ratelimit-remaining"), HeaderValue::from(remaining));
+    headers.insert(HeaderName::from_static("x-ratelimit-reset"), HeaderValue::from(reset_at));
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiQuota
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiQuotaService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiQuotaService {
+            service: Rc::new(service),
+            state: self.state.clone(),
+        }))
+    }
+}
+
+pub struct ApiQuotaService<S> {
+    service: Rc<S>,
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiQuotaService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        if !is_counted(req.path()) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+
+        let state = self.state.clone();
+        let site_data = req.app_data::<web::Data<SiteDataManager>>().cloned();
+
+        Box::pin(async move {
+            let limit = match &site_data {
+                Some(site_data) => api_daily_quota(site_data).await,
+                None => 0,
+            };
+            if limit == 0 {
+                return Ok(service.call(req).await?.map_into_left_body());
+            }
+
+            let ip = client_ip(req.request());
+            let (used, reset_at) = {
+                let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                let used = state.try_consume(&ip, limit);
+                (used, state.reset_at())
+            };
+
+            let Some(used) = used else {
+                let retry_after = (reset_at - Utc::now().timestamp()).max(1);
+                let mut response = HttpResponse::TooManyRequests()
+                    .content_type("text/plain; charset=utf-8")
+                    .body("Daily API quota exceeded");
+                let headers = response.headers_mut();
+                insert_quota_headers(headers, limit, 0, reset_at);
+                headers.insert(RETRY_AFTER, HeaderValue::from(retry_after));
+                return Ok(req.into_response(response).map_into_right_body());
+            };
+
+            let mut res = service.call(req).await?;
+            insert_quota_headers(res.headers_mut(), limit, limit - used, reset_at);
+            Ok(res.map_into_left_body())
+        })
+    }
+}
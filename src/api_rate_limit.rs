@@ -0,0 +1,160 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use actix_web::{Error, HttpResponse};
+use crate::client_ip::client_ip;
+use crate::site_data::SiteDataManager;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+// 单个客户端IP每分钟可请求公开数据接口（/api/provide/vod、/api/videos/{type_id}、/search）的次数
+// （api_rate_limit 配置），超出时返回 429。这几个接口不需要登录且每次都查询数据库，单个爬虫连续请求就能占满 MongoDB。
+// 与 api_daily_quota 互补：每日配额挡住全天持续抓取，本限制挡住短时间内的密集请求。
+// 按固定的 60 秒窗口计数；上限只在启动时读取一次，修改后需重启生效。
+pub const DEFAULT_API_RATE_LIMIT: u32 = 120;
+
+const WINDOW: Duration = Duration::from_secs(60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct RateState {
+    // 每个IP：当前窗口内的请求数、窗口开始时间
+    counters: HashMap<IpAddr, (u32, Instant)>,
+    last_sweep: Instant,
+}
+
+impl RateState {
+    // 记一次请求；已达上限时返回距窗口结束的时间
+    fn hit(&mut self, ip: IpAddr, limit: u32) -> Result<(), Duration> {
+        let now = Instant::now();
+        if now.duration_since(self.last_sweep) >= SWEEP_INTERVAL {
+            // 窗口已结束的IP不再占用内存
+            self.counters
+                .retain(|_, (_, window_start)| now.duration_since(*window_start) < WINDOW);
+            self.last_sweep = now;
+        }
+
+        let (count, window_start) = self.counters.entry(ip).or_insert((0, now));
+        if now.duration_since(*window_start) >= WINDOW {
+            *count = 0;
+            *window_start = now;
+        }
+        if *count >= limit {
+            return Err(WINDOW.saturating_sub(now.duration_since(*window_start)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+// 在 HttpServer::new 之外创建一次，所有 worker 和被包装的路由共享同一张计数表
+#[derive(Clone)]
+pub struct ApiRateLimit {
+    limit: u32,
+    state: Arc<RwLock<RateState>>,
+}
+
+impl ApiRateLimit {
+    // 启动时从 configs 集合读取 api_rate_limit，无法解析时使用默认值
+    pub async fn from_config(site_data: &SiteDataManager) -> Self {
+        let limit = site_data
+            .get_config("api_rate_limit")
+            .await
+            .and_then(|v| parse_api_rate_limit(&v).ok())
+            .unwrap_or(DEFAULT_API_RATE_LIMIT);
+        ApiRateLimit {
+            limit,
+            state: Arc::new(RwLock::new(RateState {
+                counters: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+// 校验 api_rate_limit 配置：非负整数（每分钟次数），0 表示不限制
+pub fn parse_api_rate_limit(value: &str) -> Result<u32, String> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| "api_rate_limit 必须是非负整数（每分钟次数），0 表示不限制".to_string())
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiRateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiRateLimitService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiRateLimitService {
+            service: Rc::new(service),
+            limit: self.limit,
+            state: self.state.clone(),
+        }))
+    }
+}
+
+pub struct ApiRateLimitService<S> {
+    service: Rc<S>,
+    limit: u32,
+    state: Arc<RwLock<RateState>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiRateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        // 取不到客户端IP（没有对端地址）时不限制
+        let ip = client_ip(req.request()).parse::<IpAddr>().ok();
+        let (limit, ip) = match ip {
+            Some(ip) if self.limit > 0 => (self.limit, ip),
+            _ => return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) }),
+        };
+
+        let state = self.state.clone();
+
+        Box::pin(async move {
+            let result = state.write().await.hit(ip, limit);
+            if let Err(wait) = result {
+                // 向上取整，至少 1 秒
+                let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                let mut response = HttpResponse::TooManyRequests()
+                    .content_type("text/plain; charset=utf-8")
+                    .body("Rate limit exceeded");
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
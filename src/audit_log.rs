@@ -0,0 +1,220 @@
+use actix_session::SessionExt;
+use actix_web::body::{self, BodySize, BoxBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, Error};
+use crate::client_ip::client_ip;
+use crate::models::AuditLogEntry;
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::DateTime;
+use mongodb::Database;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+// 管理操作审计：记录 /api/admin 下所有非只读请求（方法、路径、操作人、目标资源与ID、响应状态和结果消息）。
+// 请求体不会被记录，避免把密码等敏感字段写入日志。
+pub struct AuditLog;
+
+impl<S, B> Transform<S, ServiceRequest> for AuditLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditLogService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditLogService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct AuditLogService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        // 只读请求不记录
+        let method = req.method().clone();
+        if matches!(method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        }
+
+        let target = req.path().to_string();
+        let user_id = req.get_session().get::<String>("user_id").ok().flatten();
+        let ip = client_ip(req.request());
+        let db = req.app_data::<web::Data<Database>>().cloned();
+
+        Box::pin(async move {
+            let res = service.call(req).await?.map_into_boxed_body();
+            let Some(db) = db else {
+                return Ok(res);
+            };
+
+            let status = res.status();
+            let (res, outcome) = read_outcome(res).await;
+            let resource = resource_of(&target);
+            let target_id = target_id_of(&target);
+            let entry = AuditLogEntry {
+                id: None,
+                user_id,
+                action: method.to_string(),
+                summary: summarize(&resource, target_id.as_deref(), status, &outcome),
+                resource,
+                target_id,
+                target,
+                status: status.as_u16() as i32,
+                ip,
+                timestamp: DateTime::now(),
+            };
+
+            actix_web::rt::spawn(async move {
+                if let Err(e) = db
+                    .collection::<AuditLogEntry>("audit_log")
+                    .insert_one(&entry, None)
+                    .await
+                {
+                    eprintln!("Failed to write audit log: {}", e);
+                }
+            });
+
+            Ok(res)
+        })
+    }
+}
+
+// "/api/admin/types/3" -> "types"
+fn resource_of(path: &str) -> String {
+    path.trim_start_matches("/api/admin")
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+// "/api/admin/collections/64f0.../enable" -> Some("64f0...")，"/api/admin/types/3" -> Some("3")；
+// 只认 ObjectId 和数字，"/api/admin/vods/batch-delete" 之类的动作名不是目标ID
+fn target_id_of(path: &str) -> Option<String> {
+    path.trim_start_matches("/api/admin")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .skip(1)
+        .find(|segment| ObjectId::parse_str(segment).is_ok() || segment.parse::<i64>().is_ok())
+        .map(str::to_string)
+}
+
+// 处理结果：JSON 响应中的 success 字段（没有时按状态码判断）和 message/msg 消息
+struct Outcome {
+    success: Option<bool>,
+    message: Option<String>,
+}
+
+// 只读取已知长度且较小的响应体（导出文件、流式响应等不读取），读取后原样放回
+const MAX_OUTCOME_BODY_BYTES: u64 = 16 * 1024;
+const MAX_OUTCOME_MESSAGE_CHARS: usize = 200;
+
+async fn read_outcome(res: ServiceResponse<BoxBody>) -> (ServiceResponse<BoxBody>, Outcome) {
+    let readable = matches!(res.response().body().size(), BodySize::Sized(n) if n > 0 && n <= MAX_OUTCOME_BODY_BYTES);
+    if !readable {
+        return (res, Outcome { success: None, message: None });
+    }
+
+    let (req, response) = res.into_parts();
+    let (response, body) = response.into_parts();
+    let bytes = body::to_bytes(body).await.unwrap_or_default();
+    let outcome = parse_outcome(&bytes);
+    let response = response.set_body(BoxBody::new(bytes));
+    (ServiceResponse::new(req, response), outcome)
+}
+
+fn parse_outcome(bytes: &[u8]) -> Outcome {
+    let truncate = |s: &str| s.trim().chars().take(MAX_OUTCOME_MESSAGE_CHARS).collect::<String>();
+
+    match serde_json::from_slice::<serde_json::Value>(bytes) {
+        Ok(serde_json::Value::Object(map)) => Outcome {
+            success: map.get("success").and_then(|v| v.as_bool()),
+            message: map
+                .get("message")
+                .or_else(|| map.get("msg"))
+                .and_then(|v| v.as_str())
+                .map(truncate)
+                .filter(|m| !m.is_empty()),
+        },
+        Ok(_) => Outcome { success: None, message: None },
+        // 纯文本响应（如 "Collection not found"）直接作为消息
+        Err(_) => Outcome {
+            success: None,
+            message: std::str::from_utf8(bytes).ok().map(truncate).filter(|m| !m.is_empty()),
+        },
+    }
+}
+
+// "collections 64f0...: 失败 404 Collection not found"
+fn summarize(resource: &str, target_id: Option<&str>, status: StatusCode, outcome: &Outcome) -> String {
+    let target = match target_id {
+        Some(id) => format!("{} {}", resource, id),
+        None => resource.to_string(),
+    };
+    let succeeded = outcome.success.unwrap_or_else(|| status.is_success());
+    let mut summary = format!("{}: {} {}", target, if succeeded { "成功" } else { "失败" }, status.as_u16());
+    if let Some(message) = &outcome.message {
+        summary.push(' ');
+        summary.push_str(message);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_id_from_path() {
+        assert_eq!(
+            target_id_of("/api/admin/collections/64f0c2a1b2c3d4e5f6a7b8c9/enable").as_deref(),
+            Some("64f0c2a1b2c3d4e5f6a7b8c9")
+        );
+        assert_eq!(target_id_of("/api/admin/types/3").as_deref(), Some("3"));
+        assert_eq!(target_id_of("/api/admin/vods/batch-delete"), None);
+        assert_eq!(target_id_of("/api/admin/config"), None);
+    }
+
+    #[test]
+    fn summary_uses_json_outcome() {
+        let outcome = parse_outcome(br#"{"success":false,"message":"Collection not found"}"#);
+        assert_eq!(
+            summarize("collections", Some("64f0"), StatusCode::OK, &outcome),
+            "collections 64f0: 失败 200 Collection not found"
+        );
+
+        let outcome = parse_outcome(b"Type deleted");
+        assert_eq!(
+            summarize("types", Some("3"), StatusCode::OK, &outcome),
+            "types 3: 成功 200 Type deleted"
+        );
+
+        let outcome = Outcome { success: None, message: None };
+        assert_eq!(summarize("config", None, StatusCode::FORBIDDEN, &outcome), "config: 失败 403");
+    }
+}
@@ -1,13 +1,38 @@
 use crate::models::User;
+use actix_session::{Session, SessionInsertError};
 use mongodb::{bson::doc, Database};
 use std::env;
+use std::sync::OnceLock;
+
+// Reads the first non-empty env var among `keys`.
+fn env_any(keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| env::var(key).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+// Random password for a freshly created admin when none is configured.
+fn generate_password() -> String {
+    use rand::distributions::Alphanumeric;
+    use rand::Rng;
+
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(20)
+        .map(char::from)
+        .collect()
+}
 
 // This function is called on startup to ensure the admin user exists.
+// Username/password come from ADMIN_USERNAME/ADMIN_PASSWORD (or the older ADMIN_USER/ADMIN_PASS).
+// Without a configured password a random one is generated and printed once; an existing
+// admin's password is never reset.
 pub async fn ensure_admin_user_exists(db: &Database) {
     let user_collection = db.collection::<User>("users");
 
-    let admin_user = env::var("ADMIN_USER").expect("ADMIN_USER not set in .env");
-    let admin_pass = env::var("ADMIN_PASS").expect("ADMIN_PASS not set in .env");
+    let admin_user =
+        env_any(&["ADMIN_USERNAME", "ADMIN_USER"]).unwrap_or_else(|| "admin".to_string());
 
     match user_collection
         .find_one(doc! { "user_name": &admin_user }, None)
@@ -21,33 +46,22 @@ pub async fn ensure_admin_user_exists(db: &Database) {
             // Admin user does not exist, create it
             println!("Admin user '{}' not found, creating now...", admin_user);
 
-            let hashed_password = match bcrypt::hash(&admin_pass, bcrypt::DEFAULT_COST) {
-                Ok(h) => h,
-                Err(e) => {
-                    eprintln!("Failed to hash password: {}", e);
-                    return;
-                }
-            };
-
-            let new_admin = User {
-                id: None,
-                user_name: admin_user.clone(),
-                user_pwd: hashed_password,
-                group_id: 1, // Assuming 1 is the admin group
-                user_status: 1,
-                user_nick_name: Some(admin_user.clone()),
-                user_email: None,
-                user_phone: None,
-                user_portrait: None,
-                user_points: 0,
-                user_end_time: mongodb::bson::DateTime::from_millis(253402300799999), // Using a large timestamp for "never expires"
-                vip_level: None,
-                vip_end_time: None,
-                created_at: Some(mongodb::bson::DateTime::now()),
+            let (admin_pass, generated) = match env_any(&["ADMIN_PASSWORD", "ADMIN_PASS"]) {
+                Some(pass) => (pass, false),
+                None => (generate_password(), true),
             };
 
-            match user_collection.insert_one(new_admin, None).await {
-                Ok(_) => println!("Successfully created admin user '{}'.", admin_user),
+            match create_admin_user(db, &admin_user, &admin_pass).await {
+                Ok(_) => {
+                    println!("Successfully created admin user '{}'.", admin_user);
+                    if generated {
+                        println!("==============================================================");
+                        println!("ADMIN_PASSWORD not set, generated initial admin password:");
+                        println!("    {}", admin_pass);
+                        println!("It will not be shown again. Log in and change it now.");
+                        println!("==============================================================");
+                    }
+                }
                 Err(e) => eprintln!("Failed to create admin user: {}", e),
             }
         }
@@ -56,3 +70,125 @@ pub async fn ensure_admin_user_exists(db: &Database) {
         }
     }
 }
+
+// Creates an admin-group user with a bcrypt-hashed password.
+pub async fn create_admin_user(
+    db: &Database,
+    user_name: &str,
+    password: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let hashed_password = bcrypt::hash(password, bcrypt::DEFAULT_COST)?;
+
+    let new_admin = User {
+        id: None,
+        user_name: user_name.to_string(),
+        user_pwd: hashed_password,
+        group_id: 1, // Assuming 1 is the admin group
+        user_status: 1,
+        user_nick_name: Some(user_name.to_string()),
+        user_email: None,
+        user_phone: None,
+        user_portrait: None,
+        user_points: 0,
+        user_end_time: mongodb::bson::DateTime::from_millis(253402300799999), // Using a large timestamp for "never expires"
+        vip_level: None,
+        vip_end_time: None,
+        created_at: Some(mongodb::bson::DateTime::now()),
+    };
+
+    db.collection::<User>("users")
+        .insert_one(new_admin, None)
+        .await?;
+    Ok(())
+}
+
+// 后台会话超时（秒），从环境变量读取：
+//   SESSION_IDLE_TIMEOUT      空闲超时，超过该时间无请求即失效，默认 7200（2小时）
+//   SESSION_ABSOLUTE_TIMEOUT  绝对超时，自登录起无论是否活跃到期即失效，默认 86400（24小时）
+//   SESSION_REMEMBER_TTL      勾选「记住我」时的会话有效期，替代以上两者，默认 2592000（30天）
+#[derive(Debug, Clone, Copy)]
+pub struct SessionTimeouts {
+    pub idle: i64,
+    pub absolute: i64,
+    pub remember: i64,
+}
+
+impl SessionTimeouts {
+    fn from_env() -> Self {
+        let read = |key: &str, default: i64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+
+        SessionTimeouts {
+            idle: read("SESSION_IDLE_TIMEOUT", 2 * 3600),
+            absolute: read("SESSION_ABSOLUTE_TIMEOUT", 24 * 3600),
+            remember: read("SESSION_REMEMBER_TTL", 30 * 24 * 3600),
+        }
+    }
+
+    // Session cookie lifetime: long enough for the longest session kind; the
+    // idle/absolute limits themselves are enforced by `session_user_id`.
+    pub fn cookie_ttl(&self) -> i64 {
+        self.absolute.max(self.remember)
+    }
+}
+
+static SESSION_TIMEOUTS: OnceLock<SessionTimeouts> = OnceLock::new();
+
+pub fn session_timeouts() -> SessionTimeouts {
+    *SESSION_TIMEOUTS.get_or_init(SessionTimeouts::from_env)
+}
+
+// 登录成功后写入会话，renew 防止会话固定攻击
+pub fn start_session(
+    session: &Session,
+    user_id: String,
+    remember: bool,
+) -> Result<(), SessionInsertError> {
+    let now = chrono::Utc::now().timestamp();
+    session.renew();
+    session.insert("user_id", user_id)?;
+    session.insert("login_at", now)?;
+    session.insert("last_seen", now)?;
+    session.insert("remember", remember)?;
+    Ok(())
+}
+
+// 返回当前登录用户ID；会话超时则清空会话并返回 None
+pub fn session_user_id(session: &Session) -> Option<String> {
+    let user_id = session.get::<String>("user_id").ok().flatten()?;
+    let login_at = session.get::<i64>("login_at").ok().flatten();
+    let last_seen = session.get::<i64>("last_seen").ok().flatten();
+    let remember = session.get::<bool>("remember").ok().flatten().unwrap_or(false);
+
+    let timeouts = session_timeouts();
+    let now = chrono::Utc::now().timestamp();
+
+    let valid = match (login_at, last_seen) {
+        (Some(login_at), Some(last_seen)) => {
+            if remember {
+                now - login_at <= timeouts.remember
+            } else {
+                now - last_seen <= timeouts.idle && now - login_at <= timeouts.absolute
+            }
+        }
+        // Sessions created before timeouts existed carry no timestamps
+        _ => false,
+    };
+
+    if !valid {
+        session.purge();
+        return None;
+    }
+
+    // 限制写入频率，避免每个请求都重写 cookie
+    if now - last_seen.unwrap_or(now) >= 60 {
+        let _ = session.insert("last_seen", now);
+    }
+
+    Some(user_id)
+}
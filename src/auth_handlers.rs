@@ -1,11 +1,40 @@
 use crate::dto::{AuthResponse, LoginRequest, RegisterRequest, UserResponse};
 use crate::models::User;
-use actix_web::{web, HttpResponse, Responder};
+use crate::client_ip::client_ip;
+use crate::login_lockout::{self, LockoutSettings};
+use crate::site_data::SiteDataManager;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use bcrypt::{hash, verify, DEFAULT_COST};
-use mongodb::{bson::doc, Database};
-use uuid::Uuid;
+use hmac::{Hmac, Mac};
+use mongodb::{bson::doc, bson::oid::ObjectId, Database};
+use rand::Rng;
+use sha2::Sha256;
+use std::sync::OnceLock;
+
+pub async fn login(
+    req: HttpRequest,
+    login_req: web::Json<LoginRequest>,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    // 尝试次数过多时直接拒绝，不查询用户也不校验密码；未超过时先计入本次尝试
+    let ip = client_ip(&req);
+    let lockout = LockoutSettings::load(&site_data_manager).await;
+    if let Err(wait) = login_lockout::reserve_attempt(lockout, &login_req.username, &ip).await {
+        return HttpResponse::TooManyRequests()
+            .insert_header((
+                actix_web::http::header::RETRY_AFTER,
+                login_lockout::retry_after_secs(wait),
+            ))
+            .json(AuthResponse {
+                code: 0,
+                msg: "登录尝试次数过多，请稍后再试".to_string(),
+                token: None,
+                user: None,
+            });
+    }
 
-pub async fn login(login_req: web::Json<LoginRequest>, db: web::Data<Database>) -> impl Responder {
     let user_collection = db.collection::<User>("users");
 
     // 查找用户
@@ -17,6 +46,8 @@ pub async fn login(login_req: web::Json<LoginRequest>, db: web::Data<Database>)
             // 验证密码
             match verify(&login_req.password, &user.user_pwd) {
                 Ok(true) => {
+                    login_lockout::clear(&login_req.username, &ip).await;
+
                     // 检查用户状态
                     if user.user_status != 1 {
                         return HttpResponse::Forbidden().json(AuthResponse {
@@ -27,7 +58,7 @@ pub async fn login(login_req: web::Json<LoginRequest>, db: web::Data<Database>)
                         });
                     }
 
-                    // 生成JWT令牌（简化版本，实际项目中应该使用真实的JWT库）
+                    // 生成签名令牌
                     let token = generate_token(&user.id.unwrap().to_string());
 
                     HttpResponse::Ok().json(AuthResponse {
@@ -253,7 +284,7 @@ pub async fn get_current_user(
         }
     };
 
-    // 验证token并获取用户信息（简化版本）
+    // 校验令牌签名并获取用户信息
     let user_id = match validate_token(&token) {
         Ok(id) => id,
         Err(e) => {
@@ -303,18 +334,93 @@ pub async fn logout() -> impl Responder {
     }))
 }
 
-// 简化的令牌生成函数（实际项目中应该使用真实的JWT库）
+// 前台登录令牌：{user_id}.{expires}.{sig}
+//   sig = base64url(HMAC-SHA256(secret, "{user_id}.{expires}"))
+// 服务端不保存令牌，只校验签名和过期时间，伪造或篡改 user_id 的令牌都会被拒绝。
+type HmacSha256 = Hmac<Sha256>;
+
+const AUTH_TOKEN_TTL_SECS: i64 = 7 * 24 * 3600;
+
+static AUTH_TOKEN_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+// 签名密钥：优先使用 AUTH_TOKEN_SECRET 环境变量，未设置时每次启动随机生成（重启后需重新登录）
+fn token_secret() -> &'static [u8] {
+    AUTH_TOKEN_SECRET.get_or_init(|| match std::env::var("AUTH_TOKEN_SECRET") {
+        Ok(secret) if !secret.trim().is_empty() => secret.into_bytes(),
+        _ => rand::thread_rng().gen::<[u8; 32]>().to_vec(),
+    })
+}
+
+fn sign_token(payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(token_secret()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
 fn generate_token(user_id: &str) -> String {
-    let uuid = Uuid::new_v4();
-    format!("{}_{}", user_id, uuid)
+    let expires = chrono::Utc::now().timestamp() + AUTH_TOKEN_TTL_SECS;
+    let payload = format!("{}.{}", user_id, expires);
+    let sig = URL_SAFE_NO_PAD.encode(sign_token(&payload).finalize().into_bytes());
+    format!("{}.{}", payload, sig)
 }
 
-// 简化的令牌验证函数（实际项目中应该使用真实的JWT库）
+// 校验令牌签名和有效期，成功返回用户ID
 pub fn validate_token(token: &str) -> Result<String, String> {
-    let parts: Vec<&str> = token.split('_').collect();
-    if parts.len() >= 2 {
-        Ok(parts[0].to_string())
-    } else {
-        Err("无效的令牌格式".to_string())
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 || ObjectId::parse_str(parts[0]).is_err() {
+        return Err("无效的令牌格式".to_string());
+    }
+    let expires = parts[1].parse::<i64>().map_err(|_| "无效的令牌格式".to_string())?;
+    let sig = URL_SAFE_NO_PAD
+        .decode(parts[2])
+        .map_err(|_| "无效的令牌格式".to_string())?;
+    sign_token(&parts[..2].join("."))
+        .verify_slice(&sig)
+        .map_err(|_| "令牌签名无效".to_string())?;
+    if expires < chrono::Utc::now().timestamp() {
+        return Err("令牌已过期，请重新登录".to_string());
+    }
+    Ok(parts[0].to_string())
+}
+
+// 从 Authorization: Bearer 头解析当前前台用户ID，未登录或令牌无效时返回 None
+pub fn bearer_user_id(req: &actix_web::HttpRequest) -> Option<ObjectId> {
+    let header = req.headers().get("Authorization")?.to_str().ok()?;
+    let token = header.strip_prefix("Bearer ")?;
+    let user_id = validate_token(token).ok()?;
+    ObjectId::parse_str(user_id).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_issued_token() {
+        let user_id = ObjectId::new().to_hex();
+        assert_eq!(validate_token(&generate_token(&user_id)), Ok(user_id));
+    }
+
+    #[test]
+    fn rejects_forged_and_tampered_tokens() {
+        let user_id = ObjectId::new().to_hex();
+        let other = ObjectId::new().to_hex();
+        // 旧格式 {user_id}_{uuid} 不再被接受
+        assert!(validate_token(&format!("{}_x", other)).is_err());
+
+        let token = generate_token(&user_id);
+        let (_, rest) = token.split_once('.').unwrap();
+        assert!(validate_token(&format!("{}.{}", other, rest)).is_err());
+
+        let (payload, _) = token.rsplit_once('.').unwrap();
+        assert!(validate_token(&format!("{}.AAAA", payload)).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let user_id = ObjectId::new().to_hex();
+        let payload = format!("{}.{}", user_id, chrono::Utc::now().timestamp() - 1);
+        let sig = URL_SAFE_NO_PAD.encode(sign_token(&payload).finalize().into_bytes());
+        assert!(validate_token(&format!("{}.{}", payload, sig)).is_err());
     }
 }
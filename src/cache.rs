@@ -0,0 +1,208 @@
+use crate::site_data::SiteDataManager;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Database;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// 缓存区域
+pub const SCOPE_HOME: &str = "home"; // 首页渲染结果
+pub const SCOPE_COUNTS: &str = "counts"; // 各集合文档总数（后台统计）
+pub const SCOPE_UPSTREAM_CATEGORIES: &str = "upstream_categories"; // 采集源分类列表，按接口地址缓存
+pub const SCOPE_SITEMAP: &str = "sitemap"; // 站点地图，key 为 index 或子地图序号
+
+// 集合计数默认缓存时间，可通过 stats_cache_ttl 配置调整
+const DEFAULT_COUNT_CACHE_TTL: u64 = 60;
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    value: String,
+    expires_at: Instant,
+}
+
+// In-memory cache store: scope -> key -> entry
+static CACHE_STORE: std::sync::OnceLock<
+    tokio::sync::RwLock<HashMap<String, HashMap<String, CacheEntry>>>,
+> = std::sync::OnceLock::new();
+
+fn get_cache_store() -> &'static tokio::sync::RwLock<HashMap<String, HashMap<String, CacheEntry>>> {
+    CACHE_STORE.get_or_init(|| tokio::sync::RwLock::new(HashMap::new()))
+}
+
+// 读取缓存，过期返回 None
+pub async fn get_cached(scope: &str, key: &str) -> Option<String> {
+    let store = get_cache_store().read().await;
+    store
+        .get(scope)
+        .and_then(|entries| entries.get(key))
+        .filter(|entry| entry.expires_at > Instant::now())
+        .map(|entry| entry.value.clone())
+}
+
+pub async fn set_cached(scope: &str, key: &str, value: String, ttl: Duration) {
+    let mut store = get_cache_store().write().await;
+    store.entry(scope.to_string()).or_default().insert(
+        key.to_string(),
+        CacheEntry {
+            value,
+            expires_at: Instant::now() + ttl,
+        },
+    );
+}
+
+// 清除指定区域的缓存；指定 key 时只清除该条目。返回被清除的 "scope:key" 列表
+pub async fn purge(scope: &str, key: Option<&str>) -> Vec<String> {
+    let mut store = get_cache_store().write().await;
+    let mut purged = Vec::new();
+
+    if let Some(entries) = store.get_mut(scope) {
+        match key {
+            Some(key) => {
+                if entries.remove(key).is_some() {
+                    purged.push(format!("{}:{}", scope, key));
+                }
+            }
+            None => {
+                purged.extend(entries.drain().map(|(k, _)| format!("{}:{}", scope, k)));
+            }
+        }
+    }
+
+    purged.sort();
+    purged
+}
+
+// 后台统计计数缓存时间，stats_cache_ttl 为 0 时不缓存
+pub async fn count_cache_ttl(site_data: &SiteDataManager) -> Duration {
+    let secs = site_data
+        .get_config("stats_cache_ttl")
+        .await
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_COUNT_CACHE_TTL);
+    Duration::from_secs(secs)
+}
+
+// 获取集合文档总数，结果缓存 ttl
+pub async fn cached_count(
+    db: &Database,
+    collection: &str,
+    ttl: Duration,
+) -> Result<u64, mongodb::error::Error> {
+    if !ttl.is_zero() {
+        if let Some(count) = get_cached(SCOPE_COUNTS, collection)
+            .await
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            return Ok(count);
+        }
+    }
+
+    let count = db
+        .collection::<mongodb::bson::Document>(collection)
+        .count_documents(None, None)
+        .await?;
+    if !ttl.is_zero() {
+        set_cached(SCOPE_COUNTS, collection, count.to_string(), ttl).await;
+    }
+    Ok(count)
+}
+
+// 视频按状态的数量缓存键，值为 "上架数,隐藏数,回收站数"
+pub const VODS_BY_STATUS_KEY: &str = "vods_by_status";
+
+// 后台首页和 /api/admin/statistics 使用的各集合数量；查询失败的项为 0
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SiteCounts {
+    pub vods: u64, // 不含回收站，等于 vods_published + vods_hidden
+    pub types: u64,
+    pub collections: u64,
+    pub bindings: u64,
+    pub configs: u64,
+    pub users: u64,
+    pub vods_published: u64, // vod_status = 1，前台可见
+    pub vods_hidden: u64,    // 其他状态
+    pub vods_trashed: u64,   // 回收站中的视频
+}
+
+impl SiteCounts {
+    // 各项计数同时查询（每项仍按 ttl 缓存），不再逐个等待数据库往返
+    pub async fn load(db: &Database, ttl: Duration) -> Self {
+        let (types, collections, bindings, configs, users, by_status) = tokio::join!(
+            cached_count(db, "types", ttl),
+            cached_count(db, "collections", ttl),
+            cached_count(db, "bindings", ttl),
+            cached_count(db, "configs", ttl),
+            cached_count(db, "users", ttl),
+            cached_vods_by_status(db, ttl),
+        );
+        let (vods_published, vods_hidden, vods_trashed) = by_status.unwrap_or_else(|e| {
+            tracing::warn!("统计视频状态失败: {}", e);
+            (0, 0, 0)
+        });
+        SiteCounts {
+            vods: vods_published + vods_hidden,
+            types: types.unwrap_or(0),
+            collections: collections.unwrap_or(0),
+            bindings: bindings.unwrap_or(0),
+            configs: configs.unwrap_or(0),
+            users: users.unwrap_or(0),
+            vods_published,
+            vods_hidden,
+            vods_trashed,
+        }
+    }
+}
+
+// 按 vod_status 分组统计上架/隐藏的视频数，回收站中的视频单独计数，一次聚合完成，结果缓存 ttl
+async fn cached_vods_by_status(
+    db: &Database,
+    ttl: Duration,
+) -> Result<(u64, u64, u64), mongodb::error::Error> {
+    if !ttl.is_zero() {
+        if let Some(counts) = get_cached(SCOPE_COUNTS, VODS_BY_STATUS_KEY)
+            .await
+            .and_then(|v| {
+                let mut parts = v.split(',').map(|n| n.parse::<u64>().ok());
+                Some((parts.next()??, parts.next()??, parts.next()??))
+            })
+        {
+            return Ok(counts);
+        }
+    }
+
+    // 与 exclude_deleted_vods 一致：有 vod_deleted_at 字段即在回收站中，归为 "trashed" 组
+    let pipeline = vec![doc! {"$group": {
+        "_id": {"$cond": [
+            {"$eq": [{"$type": "$vod_deleted_at"}, "missing"]},
+            "$vod_status",
+            "trashed"
+        ]},
+        "count": {"$sum": 1}
+    }}];
+    let groups: Vec<Document> = db
+        .collection::<Document>("vods")
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let (mut published, mut hidden, mut trashed) = (0, 0, 0);
+    for group in groups {
+        let count = match group.get("count") {
+            Some(Bson::Int32(n)) => *n as u64,
+            Some(Bson::Int64(n)) => *n as u64,
+            _ => 0,
+        };
+        if matches!(group.get("_id"), Some(Bson::Int32(1)) | Some(Bson::Int64(1))) {
+            published += count;
+        } else if group.get_str("_id") == Ok("trashed") {
+            trashed += count;
+        } else {
+            hidden += count;
+        }
+    }
+    if !ttl.is_zero() {
+        set_cached(SCOPE_COUNTS, VODS_BY_STATUS_KEY, format!("{},{},{}", published, hidden, trashed), ttl).await;
+    }
+    Ok((published, hidden, trashed))
+}
@@ -0,0 +1,155 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header::LOCATION, Method};
+use actix_web::{web, Error, HttpResponse};
+use crate::client_ip::request_scheme_and_host;
+use crate::site_data::SiteDataManager;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+// 规范域名跳转：配置了 canonical_host 时，把通过其他域名（www/裸域名、IP）访问的请求 301 到规范域名，
+// 开启 force_https 时同时把 http 请求跳转到 https，避免同一内容在多个地址被搜索引擎收录。
+// 域名和协议取自 Host 头；只有来自 TRUSTED_PROXIES 的请求才采用 X-Forwarded-Host / X-Forwarded-Proto
+// （见 client_ip::request_scheme_and_host），反向代理需要传递这些头并配置为受信任代理，否则会循环跳转。
+#[derive(Clone)]
+pub struct CanonicalHost;
+
+struct RedirectSettings {
+    host: Option<String>,
+    force_https: bool,
+}
+
+// 校验 canonical_host 配置：只填域名（可带端口），如 "www.example.com"，空值表示不跳转
+pub fn parse_canonical_host(value: &str) -> Result<Option<String>, String> {
+    let host = value.trim().to_lowercase();
+    if host.is_empty() {
+        return Ok(None);
+    }
+    let valid = host
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'));
+    if !valid {
+        return Err("canonical_host 只填写域名（可带端口），不要包含 http:// 或路径".to_string());
+    }
+    Ok(Some(host))
+}
+
+// 校验 force_https 配置：1/0（也接受 true/false、on/off）
+pub fn parse_force_https(value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "0" | "false" | "off" => Ok(false),
+        "1" | "true" | "on" => Ok(true),
+        _ => Err("force_https 必须是 1 或 0".to_string()),
+    }
+}
+
+async fn redirect_settings(site_data: &SiteDataManager) -> RedirectSettings {
+    let host = site_data
+        .get_config("canonical_host")
+        .await
+        .and_then(|v| parse_canonical_host(&v).ok().flatten());
+    let force_https = site_data
+        .get_config("force_https")
+        .await
+        .and_then(|v| parse_force_https(&v).ok())
+        .unwrap_or(false);
+    RedirectSettings { host, force_https }
+}
+
+// 去掉协议默认端口后比较，"example.com:443" 与 "example.com" 视为同一域名
+fn normalize_host<'a>(host: &'a str, scheme: &str) -> &'a str {
+    let default_port = if scheme == "https" { ":443" } else { ":80" };
+    host.strip_suffix(default_port).unwrap_or(host)
+}
+
+// 健康检查不跳转；只跳转 GET/HEAD，POST 等请求被 301 后会丢失请求体
+fn is_exempt(req: &ServiceRequest) -> bool {
+    matches!(req.path(), "/version" | "/healthz") || !matches!(*req.method(), Method::GET | Method::HEAD)
+}
+
+// 返回需要跳转的目标地址；已在规范域名和协议上时返回 None，因此跳转后的请求不会再次跳转
+fn redirect_target(req: &ServiceRequest, settings: &RedirectSettings) -> Option<String> {
+    let (scheme, host) = request_scheme_and_host(req.request());
+
+    let target_scheme = if settings.force_https { "https" } else { scheme.as_str() };
+    let target_host = settings.host.as_deref().unwrap_or(host.as_str());
+
+    let same_host = normalize_host(&host, &scheme) == normalize_host(target_host, target_scheme);
+    if same_host && scheme == target_scheme {
+        return None;
+    }
+
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    Some(format!("{}://{}{}", target_scheme, target_host, path_and_query))
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CanonicalHost
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CanonicalHostService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CanonicalHostService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct CanonicalHostService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for CanonicalHostService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        if is_exempt(&req) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+
+        let site_data = req.app_data::<web::Data<SiteDataManager>>().cloned();
+
+        Box::pin(async move {
+            let settings = match &site_data {
+                Some(site_data) => redirect_settings(site_data).await,
+                None => RedirectSettings { host: None, force_https: false },
+            };
+            if settings.host.is_none() && !settings.force_https {
+                return Ok(service.call(req).await?.map_into_left_body());
+            }
+
+            match redirect_target(&req, &settings) {
+                Some(location) => {
+                    let response = HttpResponse::MovedPermanently()
+                        .insert_header((LOCATION, location))
+                        .finish();
+                    Ok(req.into_response(response).map_into_right_body())
+                }
+                None => Ok(service.call(req).await?.map_into_left_body()),
+            }
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use actix_web::http::header;
+use actix_web::HttpRequest;
+use ipnet::IpNet;
+use std::env;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+// Trusted reverse proxies, loaded once from TRUSTED_PROXIES (comma-separated CIDRs or IPs)
+static TRUSTED_PROXIES: OnceLock<Vec<IpNet>> = OnceLock::new();
+
+fn trusted_proxies() -> &'static Vec<IpNet> {
+    TRUSTED_PROXIES.get_or_init(|| {
+        env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| match parse_net(s) {
+                Some(net) => Some(net),
+                None => {
+                    eprintln!("⚠️  忽略无效的 TRUSTED_PROXIES 条目: {}", s);
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+// Accepts both "10.0.0.0/8" and a bare "127.0.0.1" (treated as a single host)
+fn parse_net(s: &str) -> Option<IpNet> {
+    s.parse::<IpNet>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+fn is_trusted(ip: &IpAddr) -> bool {
+    trusted_proxies().iter().any(|net| net.contains(ip))
+}
+
+/// 获取请求的真实客户端IP。所有基于IP的功能（限流、黑名单、访问日志）都应使用此函数。
+///
+/// 只有当直连的对端地址属于受信任代理时才会读取 `X-Forwarded-For`，否则直接使用对端地址。
+/// 读取时从右往左遍历，跳过受信任代理，返回第一个不受信任的地址。
+///
+/// Why rightmost-untrusted: each proxy *appends* the address it received the
+/// connection from, so only the entries added by our own proxies are trustworthy.
+/// Anything to the left of them was supplied by the client and can be forged —
+/// taking the leftmost entry would let anyone bypass IP controls by sending a fake
+/// `X-Forwarded-For` header. The first untrusted hop from the right is the address
+/// our outermost proxy actually saw.
+pub fn client_ip(req: &HttpRequest) -> String {
+    let peer_ip = match req.peer_addr() {
+        Some(addr) => addr.ip(),
+        None => return "unknown".to_string(),
+    };
+
+    if !is_trusted(&peer_ip) {
+        return peer_ip.to_string();
+    }
+
+    // 多个 X-Forwarded-For 头按顺序拼接，等价于一个逗号分隔的列表
+    let forwarded: Vec<&str> = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|s| s.trim())
+        .collect();
+
+    let mut candidate = peer_ip;
+    for entry in forwarded.iter().rev() {
+        // 无法解析的条目之后的内容都不可信，停在最后一个有效地址
+        let Ok(ip) = entry.parse::<IpAddr>() else {
+            break;
+        };
+        candidate = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+
+    candidate.to_string()
+}
+
+/// 获取请求的协议和域名（均为小写），用于生成跳转地址和绝对链接。
+///
+/// 与 `client_ip` 相同，只有直连的对端地址属于受信任代理时才采用 `Forwarded` / `X-Forwarded-Host` /
+/// `X-Forwarded-Proto`，否则使用 `Host` 头和连接本身的协议：这些头同样可以由客户端伪造，
+/// 直接信任会让任何人把跳转或站点地图中的地址指向自己的域名。
+pub fn request_scheme_and_host(req: &HttpRequest) -> (String, String) {
+    let trusted = req.peer_addr().is_some_and(|addr| is_trusted(&addr.ip()));
+    if trusted {
+        let info = req.connection_info();
+        return (info.scheme().to_lowercase(), info.host().to_lowercase());
+    }
+
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| req.uri().authority().map(|a| a.to_string()))
+        .unwrap_or_else(|| req.app_config().host().to_string());
+    let scheme = if req.app_config().secure() { "https" } else { "http" };
+    (scheme.to_string(), host.to_lowercase())
+}
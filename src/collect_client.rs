@@ -0,0 +1,100 @@
+use crate::models::Collection;
+use reqwest::header::{HeaderMap, HeaderValue, REFERER};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+// 请求采集源（列表、分类、图片）使用的 HTTP 客户端。部分上游屏蔽机房IP或校验 User-Agent / Referer，
+// 直接用 reqwest::get 会被返回 403，因此按采集源的 collect_proxy、collect_user_agent、collect_referer 构建客户端。
+// 相同配置的采集源共用一个客户端（及其连接池），任务之间不会重复建立连接。
+
+// 未设置 collect_user_agent 时使用的浏览器 UA
+pub const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+// 代理、User-Agent、Referer（均已 trim）
+type ClientKey = (String, String, String);
+
+fn clients() -> &'static Mutex<HashMap<ClientKey, Client>> {
+    static CLIENTS: OnceLock<Mutex<HashMap<ClientKey, Client>>> = OnceLock::new();
+    CLIENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 校验 collect_proxy：空值表示直连，否则为 http://、https://、socks5:// 或 socks5h:// 开头的代理地址（可带 user:pass@）
+pub fn parse_collect_proxy(value: &str) -> Result<Option<reqwest::Proxy>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let scheme = value.split_once("://").map(|(scheme, _)| scheme.to_lowercase());
+    if !matches!(scheme.as_deref(), Some("http" | "https" | "socks5" | "socks5h")) {
+        return Err(format!(
+            "collect_proxy 必须以 http://、https://、socks5:// 或 socks5h:// 开头: {}",
+            value
+        ));
+    }
+    reqwest::Proxy::all(value)
+        .map(Some)
+        .map_err(|e| format!("collect_proxy 无效: {}", e))
+}
+
+// 去掉 collect_proxy 中的 user:pass@，用于导出配置包等会离开服务器的场景
+pub fn redact_proxy_credentials(value: &str) -> String {
+    let value = value.trim();
+    let Some((scheme, rest)) = value.split_once("://") else {
+        return value.to_string();
+    };
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    match rest[..authority_end].rsplit_once('@') {
+        Some((_, host)) => format!("{}://{}{}", scheme, host, &rest[authority_end..]),
+        None => value.to_string(),
+    }
+}
+
+// 校验 collect_user_agent / collect_referer：不能包含换行等无法作为请求头的字符
+pub fn validate_header_value(field: &str, value: &str) -> Result<(), String> {
+    HeaderValue::from_str(value.trim())
+        .map(|_| ())
+        .map_err(|_| format!("{} 包含无法作为请求头的字符", field))
+}
+
+fn build_client(key: &ClientKey) -> Result<Client, String> {
+    let (proxy, user_agent, referer) = key;
+    let mut builder = Client::builder()
+        .user_agent(if user_agent.is_empty() { DEFAULT_USER_AGENT } else { user_agent.as_str() });
+    if !referer.is_empty() {
+        let mut headers = HeaderMap::new();
+        let value = HeaderValue::from_str(referer)
+            .map_err(|_| "collect_referer 包含无法作为请求头的字符".to_string())?;
+        headers.insert(REFERER, value);
+        builder = builder.default_headers(headers);
+    }
+    if let Some(proxy) = parse_collect_proxy(proxy)? {
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(|e| format!("创建HTTP客户端失败: {}", e))
+}
+
+fn cached_client(key: ClientKey) -> Result<Client, String> {
+    let mut clients = clients().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+    let client = build_client(&key)?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+// 采集源对应的客户端。代理配置无效（校验出现之前保存的记录）时返回错误而不是改为直连，避免暴露服务器IP
+pub fn client_for(collection: &Collection) -> Result<Client, String> {
+    cached_client((
+        collection.collect_proxy.trim().to_string(),
+        collection.collect_user_agent.trim().to_string(),
+        collection.collect_referer.trim().to_string(),
+    ))
+}
+
+// 不属于某个采集源的请求（按URL预览分类/视频、测试图片）使用的客户端：直连，浏览器 UA
+pub fn default_client() -> Client {
+    cached_client((String::new(), String::new(), String::new())).unwrap_or_default()
+}
@@ -1,15 +1,25 @@
+use crate::cache;
+use crate::collect_client;
 use crate::dto::{Category, JsonResponse, VideoListResponse, VodApiListEntry};
-use crate::models::{Binding, Collection, PlaySource, PlayUrl, Vod};
+use crate::models::{Binding, Collection, CollectTaskState, CollectionRun, PlaySource, PlayUrl, Vod};
 use actix_web::{web, HttpResponse, Responder};
 use chrono::Timelike;
-use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
 use mongodb::Database;
-use reqwest;
+use lazy_static::lazy_static;
+use futures::StreamExt;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // 解析播放地址函数
-fn parse_play_urls(vod_play_from: &str, vod_play_url: &Option<String>) -> Vec<PlaySource> {
+// normalize_names 为 true 时将集数名称统一为「第N集」，原名称保留在 original_name
+pub fn parse_play_urls(
+    vod_play_from: &str,
+    vod_play_url: &Option<String>,
+    normalize_names: bool,
+) -> Vec<PlaySource> {
     let mut play_sources = Vec::new();
 
     if let Some(play_url) = vod_play_url {
@@ -33,12 +43,18 @@ fn parse_play_urls(vod_play_from: &str, vod_play_url: &Option<String>) -> Vec<Pl
                         urls.push(PlayUrl {
                             name: name.to_string(),
                             url: url.to_string(),
+                            original_name: None,
+                            resolution: None,
+                            format: None,
                         });
                     } else {
                         // 如果没有$分割符，可能是特殊情况
                         urls.push(PlayUrl {
                             name: episode.to_string(),
                             url: String::new(),
+                            original_name: None,
+                            resolution: None,
+                            format: None,
                         });
                     }
                 }
@@ -59,12 +75,18 @@ fn parse_play_urls(vod_play_from: &str, vod_play_url: &Option<String>) -> Vec<Pl
                     urls.push(PlayUrl {
                         name: name.to_string(),
                         url: url.to_string(),
+                        original_name: None,
+                        resolution: None,
+                        format: None,
                     });
                 } else {
                     // 如果没有$分割符，可能是纯URL
                     urls.push(PlayUrl {
                         name: String::new(),
                         url: play_url.to_string(),
+                        original_name: None,
+                        resolution: None,
+                        format: None,
                     });
                 }
 
@@ -78,9 +100,75 @@ fn parse_play_urls(vod_play_from: &str, vod_play_url: &Option<String>) -> Vec<Pl
         }
     }
 
+    // 在规范化名称之前识别清晰度，「HD1080P第1集」规范化后会丢失 1080P
+    for source in play_sources.iter_mut() {
+        for play_url in source.urls.iter_mut() {
+            play_url.resolution = detect_resolution(&play_url.name).or_else(|| detect_resolution(&play_url.url));
+            play_url.format = detect_format(&play_url.url);
+        }
+    }
+
+    if normalize_names {
+        for source in play_sources.iter_mut() {
+            for play_url in source.urls.iter_mut() {
+                if let Some(name) = normalize_episode_name(&play_url.name) {
+                    if name != play_url.name {
+                        play_url.original_name = Some(std::mem::replace(&mut play_url.name, name));
+                    }
+                }
+            }
+        }
+    }
+
     play_sources
 }
 
+lazy_static! {
+    // 「第01集」「HD1080P第1集」「第1.5话」
+    static ref EPISODE_ORDINAL_RE: Regex =
+        Regex::new(r"第\s*0*(\d+(?:\.\d+)?)\s*[集话話期]").unwrap();
+    // 「01」「EP01」「E1」「1.5」；综艺常用的日期期数（如「20240105」）不在此列
+    static ref EPISODE_NUMBER_RE: Regex =
+        Regex::new(r"^(?i:ep?)?\s*0*(\d{1,4}(?:\.\d+)?)$").unwrap();
+}
+
+lazy_static! {
+    // 「HD1080P」「1080p」「4K」，前后不能紧跟数字/字母，避免把「21080P」「4KB」之类误认为清晰度
+    static ref RESOLUTION_RE: Regex =
+        Regex::new(r"(?i)(?:^|[^0-9])(2160|1440|1080|720|576|480|360)p(?:$|[^a-z0-9])|(?:^|[^a-z0-9])([48])k(?:$|[^a-z0-9])").unwrap();
+}
+
+// 从集数名称或地址中识别清晰度，统一为「1080P」「4K」形式，无法识别时返回 None
+fn detect_resolution(text: &str) -> Option<String> {
+    let caps = RESOLUTION_RE.captures(text)?;
+    match (caps.get(1), caps.get(2)) {
+        (Some(height), _) if height.as_str() == "2160" => Some("4K".to_string()),
+        (Some(height), _) => Some(format!("{}P", height.as_str())),
+        (None, Some(k)) => Some(format!("{}K", k.as_str())),
+        _ => None,
+    }
+}
+
+// 播放地址的格式（扩展名），只识别常见的视频/流媒体格式
+fn detect_format(url: &str) -> Option<String> {
+    const KNOWN_FORMATS: [&str; 7] = ["m3u8", "mp4", "flv", "mkv", "webm", "mpd", "ts"];
+
+    let path = url.split(['?', '#']).next().unwrap_or("");
+    let ext = path.rsplit('/').next()?.rsplit_once('.')?.1.to_ascii_lowercase();
+    KNOWN_FORMATS.contains(&ext.as_str()).then_some(ext)
+}
+
+// 将集数名称规范为「第N集」，无法识别集数（如「正片」「HD」「预告」）时返回 None
+fn normalize_episode_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    let number = EPISODE_ORDINAL_RE
+        .captures(name)
+        .or_else(|| EPISODE_NUMBER_RE.captures(name))
+        .map(|caps| caps[1].to_string())?;
+
+    Some(format!("第{}集", number))
+}
+
 #[derive(Deserialize)]
 pub struct CollectCategoriesQuery {
     url: String,
@@ -114,6 +202,12 @@ pub struct CollectProgress {
     pub total_pages: u32,
     pub success: u32,
     pub failed: u32,
+    #[serde(default)]
+    pub skipped: u32, // 未达到 collect_min_episodes 而跳过的视频数
+    #[serde(default)]
+    pub filtered: u32, // 片名命中 collect_filter 而不入库的视频数
+    #[serde(default)]
+    pub filtered_sources: u32, // 按 collect_filter_from 丢弃的播放源数
     pub log: String,
 }
 
@@ -125,6 +219,9 @@ impl Default for CollectProgress {
             total_pages: 0,
             success: 0,
             failed: 0,
+            skipped: 0,
+            filtered: 0,
+            filtered_sources: 0,
             log: "未知状态".to_string(),
         }
     }
@@ -151,6 +248,42 @@ fn get_task_progress_store() -> &'static TaskProgressStore {
     TASK_PROGRESS.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
 }
 
+// 手动启动的采集任务的启动参数，重启任务时按相同参数重新启动
+#[derive(Clone)]
+struct TaskLaunch {
+    collection_id: ObjectId,
+    hours: Option<String>,
+    launched_at: std::time::Instant,
+}
+
+// 已结束（完成/失败/停止）的任务启动参数保留的时间，超过后在下次启动任务时清理；运行中的任务一直保留
+const TASK_LAUNCH_TTL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+static TASK_LAUNCHES: std::sync::OnceLock<tokio::sync::RwLock<std::collections::HashMap<String, TaskLaunch>>> =
+    std::sync::OnceLock::new();
+
+fn get_task_launches() -> &'static tokio::sync::RwLock<std::collections::HashMap<String, TaskLaunch>> {
+    TASK_LAUNCHES.get_or_init(|| tokio::sync::RwLock::new(std::collections::HashMap::new()))
+}
+
+// 清理过期的启动参数：不再运行且启动超过 TASK_LAUNCH_TTL 的任务
+async fn prune_task_launches() {
+    let running: std::collections::HashSet<String> = get_task_progress_store()
+        .read()
+        .await
+        .iter()
+        .filter(|(_, (progress, _, _))| progress.status == "running")
+        .map(|(task_id, _)| task_id.clone())
+        .collect();
+    get_task_launches()
+        .write()
+        .await
+        .retain(|task_id, launch| running.contains(task_id) || launch.launched_at.elapsed() < TASK_LAUNCH_TTL);
+}
+
+// 重启任务后等待旧任务退出的最长时间
+const RESTART_ABORT_WAIT: std::time::Duration = std::time::Duration::from_secs(5);
+
 // 获取任务进度
 pub async fn get_task_progress(task_id: &str) -> Option<CollectProgress> {
     let store = get_task_progress_store();
@@ -165,7 +298,13 @@ async fn update_task_progress(task_id: &str, progress: CollectProgress, collecti
     let store = get_task_progress_store();
     let mut progress_map = store.write().await;
     if let Some((current_progress, current_name, handle)) = progress_map.get_mut(task_id) {
+        // 任务已被手动停止时，并发采集中仍在收尾的页面只更新计数，不覆盖停止状态和日志
+        let stopped_log = (current_progress.status == "stopped").then(|| current_progress.log.clone());
         *current_progress = progress;
+        if let Some(log) = stopped_log {
+            current_progress.status = "stopped".to_string();
+            current_progress.log = log;
+        }
         *current_name = collection_name;
         // 保持原有的handle不变，不需要克隆
     } else {
@@ -197,6 +336,180 @@ pub async fn stop_task(task_id: &str) -> bool {
     }
 }
 
+// 为采集源登记新任务：同一采集源已有运行中的任务时返回 Err(已有任务ID)，防止重复点击启动多个任务。
+// 检查与登记在同一把写锁内完成，避免并发请求同时通过检查。
+pub async fn reserve_collect_task(collection_name: &str, task_id: &str) -> Result<(), String> {
+    let store = get_task_progress_store();
+    let mut progress_map = store.write().await;
+
+    if let Some((existing_id, _)) = progress_map
+        .iter()
+        .find(|(_, (progress, name, _))| name == collection_name && progress.status == "running")
+    {
+        return Err(existing_id.clone());
+    }
+
+    let initial_progress = CollectProgress {
+        status: "running".to_string(),
+        current_page: 0,
+        total_pages: 1,
+        success: 0,
+        failed: 0,
+        skipped: 0,
+        filtered: 0,
+        filtered_sources: 0,
+        log: "正在启动采集任务...".to_string(),
+    };
+    progress_map.insert(
+        task_id.to_string(),
+        (initial_progress, collection_name.to_string(), None),
+    );
+    Ok(())
+}
+
+// 在后台运行已通过 reserve_collect_task 登记的手动采集任务。
+// 保存任务句柄（stop_task / restart_task 据此 abort）和启动参数（restart_task 据此重新启动）。
+pub async fn spawn_collect_task(db: Database, collection: Collection, hours: Option<String>, task_id: String) {
+    spawn_batch_task(db, collection, hours, task_id, None).await;
+}
+
+// resume 为 Some 时按保存的页面状态继续采集（resume_task）
+async fn spawn_batch_task(
+    db: Database,
+    collection: Collection,
+    hours: Option<String>,
+    task_id: String,
+    resume: Option<CollectTaskState>,
+) {
+    if let Some(collection_id) = collection.id {
+        prune_task_launches().await;
+        get_task_launches().write().await.insert(
+            task_id.clone(),
+            TaskLaunch {
+                collection_id,
+                hours: hours.clone(),
+                launched_at: std::time::Instant::now(),
+            },
+        );
+    }
+
+    let task_id_for_closure = task_id.clone();
+    let handle = tokio::spawn(async move {
+        let collection_name = collection.collect_name.clone();
+        // 正常结束时 run_batch_collect 已写入最终状态（完成/停止）
+        let trigger = if resume.is_some() { TRIGGER_RESUME } else { TRIGGER_MANUAL };
+        if let Err(e) = record_batch_collect(&db, collection, hours, task_id_for_closure.clone(), trigger, resume).await {
+            let mut progress = get_task_progress(&task_id_for_closure).await.unwrap_or_default();
+            progress.status = "failed".to_string();
+            progress.log = format!("采集失败: {}", e);
+            update_task_progress(&task_id_for_closure, progress, collection_name).await;
+        }
+    });
+
+    let store = get_task_progress_store();
+    let mut progress_map = store.write().await;
+    if let Some((_, _, task_handle)) = progress_map.get_mut(&task_id) {
+        *task_handle = Some(handle);
+    }
+}
+
+#[derive(Debug)]
+pub enum RestartError {
+    TaskNotFound,           // 任务不存在，或不是手动启动的任务（定时采集没有保存启动参数）
+    CollectionNotFound,     // 采集源已被删除
+    AlreadyRunning(String), // 同一采集源已有其他运行中的任务
+    Database(String),
+}
+
+// 强制重启采集任务：abort 旧任务并等待其退出（最多 RESTART_ABORT_WAIT），清除旧任务的进度记录，
+// 再按相同采集源和参数启动新任务，返回新任务ID。
+// 与 stop_task 的区别：stop_task 只标记状态并 abort，卡在上游请求里的任务依赖 abort 生效；
+// 这里会等待旧任务真正结束后再启动，且旧任务的进度记录直接删除，不会与新任务同时显示为运行中。
+pub async fn restart_task(db: &Database, task_id: &str) -> Result<String, RestartError> {
+    let launch = get_task_launches()
+        .read()
+        .await
+        .get(task_id)
+        .cloned()
+        .ok_or(RestartError::TaskNotFound)?;
+
+    let old_handle = {
+        let mut progress_map = get_task_progress_store().write().await;
+        progress_map.remove(task_id).and_then(|(_, _, handle)| handle)
+    };
+    if let Some(handle) = old_handle {
+        handle.abort();
+        // abort 在任务下一次 await 时生效；spawn_blocking 中的图片转换等阻塞操作不受影响，超时后不再等待
+        if tokio::time::timeout(RESTART_ABORT_WAIT, handle).await.is_err() {
+            tracing::warn!("采集任务 {} 在 {} 秒内未退出，继续重启", task_id, RESTART_ABORT_WAIT.as_secs());
+        }
+    }
+    // 旧任务被 abort 时可能正好在写进度，退出后再清理一次
+    get_task_progress_store().write().await.remove(task_id);
+    get_task_launches().write().await.remove(task_id);
+
+    let collection = db
+        .collection::<Collection>("collections")
+        .find_one(doc! {"_id": launch.collection_id}, None)
+        .await
+        .map_err(|e| RestartError::Database(e.to_string()))?
+        .ok_or(RestartError::CollectionNotFound)?;
+
+    let new_task_id = ObjectId::new().to_hex();
+    reserve_collect_task(&collection.collect_name, &new_task_id)
+        .await
+        .map_err(RestartError::AlreadyRunning)?;
+    tracing::info!("采集任务 {} 已重启为 {} ({})", task_id, new_task_id, collection.collect_name);
+    spawn_collect_task(db.clone(), collection, launch.hours, new_task_id.clone()).await;
+
+    Ok(new_task_id)
+}
+
+#[derive(Debug)]
+pub enum ResumeError {
+    StateNotFound,          // 没有该任务的页面状态（任务在获取总页数前就失败，或记录已被清理）
+    CollectionNotFound,     // 采集源已被删除
+    NothingToResume,        // 所有页面都已成功采集
+    AlreadyRunning(String), // 该任务或同一采集源的其他任务正在运行
+    Database(String),
+}
+
+// 继续采集失败或中断的任务：读取 collect_task_state 中保存的页面状态，用同一个任务ID重新运行，
+// 先重试记录的失败页，再从第一个未完成的页继续；进度计数从保存的状态恢复
+pub async fn resume_task(db: &Database, task_id: &str) -> Result<(), ResumeError> {
+    let state = db
+        .collection::<CollectTaskState>(COLLECT_TASK_STATE)
+        .find_one(doc! {"task_id": task_id}, None)
+        .await
+        .map_err(|e| ResumeError::Database(e.to_string()))?
+        .ok_or(ResumeError::StateNotFound)?;
+    if state.remaining_pages().is_empty() {
+        return Err(ResumeError::NothingToResume);
+    }
+
+    let collection = db
+        .collection::<Collection>("collections")
+        .find_one(doc! {"_id": state.collection_id}, None)
+        .await
+        .map_err(|e| ResumeError::Database(e.to_string()))?
+        .ok_or(ResumeError::CollectionNotFound)?;
+
+    reserve_collect_task(&collection.collect_name, task_id)
+        .await
+        .map_err(ResumeError::AlreadyRunning)?;
+    tracing::info!(
+        "继续采集任务 {} ({})：重试 {} 个失败页，从第 {}/{} 页继续",
+        task_id,
+        collection.collect_name,
+        state.failed_pages.len(),
+        state.next_page,
+        state.total_pages
+    );
+    let hours = state.hours.clone();
+    spawn_batch_task(db.clone(), collection, hours, task_id.to_string(), Some(state)).await;
+    Ok(())
+}
+
 // 获取所有运行中的任务
 pub async fn get_all_running_tasks() -> Vec<serde_json::Value> {
     let store = get_task_progress_store();
@@ -227,6 +540,49 @@ pub async fn get_all_running_tasks() -> Vec<serde_json::Value> {
     tasks
 }
 
+// 采集源分类列表缓存时间，避免频繁请求上游接口
+const UPSTREAM_CATEGORIES_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+// 获取采集源的分类列表（带短时缓存）
+pub async fn fetch_source_categories(
+    collection: &Collection,
+) -> Result<Vec<Category>, Box<dyn std::error::Error + Send + Sync>> {
+    let collect_url = collection.collect_url.as_str();
+    if let Some(cached) = cache::get_cached(cache::SCOPE_UPSTREAM_CATEGORIES, collect_url).await {
+        if let Ok(categories) = serde_json::from_str::<Vec<Category>>(&cached) {
+            return Ok(categories);
+        }
+    }
+
+    let mut api_url = collect_url.to_string();
+    if api_url.contains('?') {
+        if api_url.ends_with('?') {
+            api_url.push_str("ac=list");
+        } else {
+            api_url.push_str("&ac=list");
+        }
+    } else {
+        api_url.push_str("?ac=list");
+    }
+
+    let client = collect_client::client_for(collection)?;
+    let response_text = fetch_with_timeout(&client, &api_url, 30).await?;
+    let api_response: JsonResponse<Category> = serde_json::from_str(&response_text)?;
+    if api_response.code != 1 {
+        return Err("API返回错误".into());
+    }
+
+    cache::set_cached(
+        cache::SCOPE_UPSTREAM_CATEGORIES,
+        collect_url,
+        serde_json::to_string(&api_response.categories)?,
+        UPSTREAM_CATEGORIES_TTL,
+    )
+    .await;
+
+    Ok(api_response.categories)
+}
+
 // 获取采集源分类列表
 pub async fn get_collect_categories(query: web::Query<CollectCategoriesQuery>) -> impl Responder {
     let mut api_url = query.url.clone();
@@ -241,7 +597,7 @@ pub async fn get_collect_categories(query: web::Query<CollectCategoriesQuery>) -
         api_url.push_str("?ac=list");
     }
 
-    match reqwest::get(&api_url).await {
+    match collect_client::default_client().get(&api_url).send().await {
         Ok(response) => match response.text().await {
             Ok(response_text) => {
                 // eprintln!("API Response: {}", response_text);
@@ -260,7 +616,7 @@ pub async fn get_collect_categories(query: web::Query<CollectCategoriesQuery>) -
                         }
                     }
                     Err(e) => {
-                        eprintln!("Failed to parse API response: {}", e);
+                        tracing::error!("Failed to parse API response: {}", e);
                         HttpResponse::Ok().json(serde_json::json!({
                             "success": false,
                             "message": "解析API响应失败"
@@ -269,7 +625,7 @@ pub async fn get_collect_categories(query: web::Query<CollectCategoriesQuery>) -
                 }
             }
             Err(e) => {
-                eprintln!("Failed to get response text: {}", e);
+                tracing::error!("Failed to get response text: {}", e);
                 HttpResponse::Ok().json(serde_json::json!({
                     "success": false,
                     "message": "获取响应失败"
@@ -277,7 +633,7 @@ pub async fn get_collect_categories(query: web::Query<CollectCategoriesQuery>) -
             }
         },
         Err(e) => {
-            eprintln!("Failed to fetch categories: {}", e);
+            tracing::error!("Failed to fetch categories: {}", e);
             HttpResponse::Ok().json(serde_json::json!({
                 "success": false,
                 "message": "获取分类列表失败"
@@ -310,7 +666,7 @@ pub async fn get_collect_videos(query: web::Query<CollectVideosQuery>) -> impl R
         api_url.push_str(&params.join("&"));
     }
 
-    match reqwest::get(&api_url).await {
+    match collect_client::default_client().get(&api_url).send().await {
         Ok(response) => match response.text().await {
             Ok(response_text) => match serde_json::from_str::<VideoListResponse>(&response_text) {
                 Ok(api_response) => {
@@ -332,7 +688,7 @@ pub async fn get_collect_videos(query: web::Query<CollectVideosQuery>) -> impl R
                     }
                 }
                 Err(e) => {
-                    eprintln!("Failed to parse API response: {}", e);
+                    tracing::error!("Failed to parse API response: {}", e);
                     HttpResponse::Ok().json(serde_json::json!({
                         "success": false,
                         "message": "解析API响应失败"
@@ -340,7 +696,7 @@ pub async fn get_collect_videos(query: web::Query<CollectVideosQuery>) -> impl R
                 }
             },
             Err(e) => {
-                eprintln!("Failed to get response text: {}", e);
+                tracing::error!("Failed to get response text: {}", e);
                 HttpResponse::Ok().json(serde_json::json!({
                     "success": false,
                     "message": "获取响应失败"
@@ -348,7 +704,7 @@ pub async fn get_collect_videos(query: web::Query<CollectVideosQuery>) -> impl R
             }
         },
         Err(e) => {
-            eprintln!("Failed to fetch videos: {}", e);
+            tracing::error!("Failed to fetch videos: {}", e);
             HttpResponse::Ok().json(serde_json::json!({
                 "success": false,
                 "message": "获取视频列表失败"
@@ -382,7 +738,7 @@ pub async fn start_collect_task(
             }));
         }
         Err(e) => {
-            eprintln!("Failed to get collection: {}", e);
+            tracing::error!("Failed to get collection: {}", e);
             return HttpResponse::InternalServerError().json(serde_json::json!({
                 "success": false,
                 "message": "获取采集源失败"
@@ -390,61 +746,19 @@ pub async fn start_collect_task(
         }
     };
 
-    // 初始化任务进度
-    let initial_progress = CollectProgress {
-        status: "running".to_string(),
-        current_page: 0,
-        total_pages: 1,
-        success: 0,
-        failed: 0,
-        log: "正在启动采集任务...".to_string(),
-    };
-    update_task_progress(
-        &task_id,
-        initial_progress.clone(),
-        collection.collect_name.clone(),
-    )
-    .await;
+    // 初始化任务进度，同一采集源已在采集时直接返回已有任务
+    if let Err(existing_task_id) = reserve_collect_task(&collection.collect_name, &task_id).await {
+        return HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "task_id": existing_task_id,
+            "already_running": true,
+            "message": "该采集源已有正在运行的采集任务"
+        }));
+    }
 
     // 启动后台采集任务
-    let db_clone = db.clone();
-    let task_id_clone = task_id.clone();
-    let collection_name_clone = collection.collect_name.clone();
-    let handle = tokio::spawn(async move {
-        let hours = request.hours.map(|h| h.to_string());
-        let task_id_for_closure = task_id_clone.clone();
-        match start_batch_collect(&db_clone, collection.clone(), hours, task_id_clone).await {
-            Ok(_) => {
-                // 任务正常完成
-                let mut progress = get_task_progress(&task_id_for_closure)
-                    .await
-                    .unwrap_or_default();
-                progress.status = "completed".to_string();
-                progress.log = format!(
-                    "采集完成，成功: {}，失败: {}",
-                    progress.success, progress.failed
-                );
-                update_task_progress(&task_id_for_closure, progress, collection_name_clone).await;
-            }
-            Err(e) => {
-                // 任务失败
-                let mut progress = get_task_progress(&task_id_for_closure)
-                    .await
-                    .unwrap_or_default();
-                progress.status = "failed".to_string();
-                progress.log = format!("采集失败: {}", e);
-                update_task_progress(&task_id_for_closure, progress, collection_name_clone).await;
-            }
-        }
-    });
-
-    // 存储任务句柄
-    let store = get_task_progress_store();
-    let mut progress_map = store.write().await;
-    if let Some((progress, collection_name, _)) = progress_map.get_mut(&task_id) {
-        *progress_map.get_mut(&task_id).unwrap() =
-            (progress.clone(), collection_name.clone(), Some(handle));
-    }
+    let hours = request.hours.map(|h| h.to_string());
+    spawn_collect_task(db.get_ref().clone(), collection, hours, task_id.clone()).await;
 
     HttpResponse::Ok().json(serde_json::json!({
         "success": true,
@@ -471,20 +785,64 @@ pub async fn get_collect_progress(path: web::Path<String>) -> impl Responder {
                 total_pages: 0,
                 success: 0,
                 failed: 0,
+                skipped: 0,
+                filtered: 0,
+                filtered_sources: 0,
                 log: "任务不存在".to_string(),
             },
         })
     }
 }
 
+// 校验采集源的字段映射：键是我们的列表字段名（vod_* / type_id / type_name），值是上游的字段名
+pub fn validate_field_mapping(mapping: &std::collections::HashMap<String, String>) -> Result<(), String> {
+    for (ours, theirs) in mapping {
+        if !(ours.starts_with("vod_") || ours == "type_id" || ours == "type_name") {
+            return Err(format!("字段映射的键必须是 vod_*、type_id 或 type_name: {}", ours));
+        }
+        if theirs.trim().is_empty() {
+            return Err(format!("字段 {} 映射的上游字段名不能为空", ours));
+        }
+    }
+    Ok(())
+}
+
+// 解析上游视频列表页。采集源配置了字段映射时先解析为 serde_json::Value，
+// 把 list 中每条记录的上游字段改名为标准字段名后再转换为 VodApiListEntry；未配置时直接按标准字段名解析
+fn parse_video_list(
+    response_text: &str,
+    field_mapping: &std::collections::HashMap<String, String>,
+) -> Result<VideoListResponse, serde_json::Error> {
+    if field_mapping.is_empty() {
+        return serde_json::from_str(response_text);
+    }
+
+    let mut value: serde_json::Value = serde_json::from_str(response_text)?;
+    if let Some(list) = value.get_mut("list").and_then(|l| l.as_array_mut()) {
+        for entry in list.iter_mut().filter_map(|e| e.as_object_mut()) {
+            // 先取出全部映射字段再写回，映射之间互换字段名（a->b, b->a）或共用同一个上游字段时不会互相覆盖
+            let mapped: Vec<(String, serde_json::Value)> = field_mapping
+                .iter()
+                .filter_map(|(ours, theirs)| entry.get(theirs.as_str()).cloned().map(|v| (ours.clone(), v)))
+                .collect();
+            for theirs in field_mapping.values() {
+                entry.remove(theirs.as_str());
+            }
+            entry.extend(mapped);
+        }
+    }
+    serde_json::from_value(value)
+}
+
 // 带超时的HTTP请求
 async fn fetch_with_timeout(
+    client: &reqwest::Client,
     url: &str,
     timeout_secs: u64,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     use tokio::time::{timeout, Duration};
 
-    match timeout(Duration::from_secs(timeout_secs), reqwest::get(url)).await {
+    match timeout(Duration::from_secs(timeout_secs), client.get(url).send()).await {
         Ok(Ok(response)) => match response.text().await {
             Ok(text) => Ok(text),
             Err(e) => Err(format!("读取响应失败: {}", e).into()),
@@ -496,6 +854,7 @@ async fn fetch_with_timeout(
 
 // 带重试的获取总页数函数
 async fn get_total_pages_with_retry(
+    client: &reqwest::Client,
     api_url: &str,
     max_retries: usize,
     timeout_secs: u64,
@@ -505,31 +864,31 @@ async fn get_total_pages_with_retry(
     for attempt in 1..=max_retries {
         let first_page_url = format!("{}&pg=1", api_url);
 
-        println!("🔄 获取总页数 (尝试 {}/{})", attempt, max_retries);
+        tracing::info!("🔄 获取总页数 (尝试 {}/{})", attempt, max_retries);
 
-        match fetch_with_timeout(&first_page_url, timeout_secs).await {
+        match fetch_with_timeout(client, &first_page_url, timeout_secs).await {
             Ok(response_text) => match serde_json::from_str::<VideoListResponse>(&response_text) {
                 Ok(api_response) => {
                     if api_response.code == 1 {
                         let total_pages =
                             (api_response.total as f64 / api_response.limit as f64).ceil() as u32;
-                        println!("✅ 获取总页数成功: {} 页", total_pages);
+                        tracing::info!("✅ 获取总页数成功: {} 页", total_pages);
                         return Ok(total_pages);
                     } else {
                         let error = format!("API返回错误: {:?}", api_response);
-                        println!("❌ {}", error);
+                        tracing::warn!("❌ {}", error);
                         last_error = Some(error.into());
                     }
                 }
                 Err(e) => {
                     let error = format!("解析API响应失败: {}", e);
-                    println!("❌ {}", error);
+                    tracing::warn!("❌ {}", error);
                     last_error = Some(error.into());
                 }
             },
             Err(e) => {
                 let error = format!("获取总页数失败: {}", e);
-                println!("❌ {}", error);
+                tracing::warn!("❌ {}", error);
                 last_error = Some(error.into());
             }
         }
@@ -537,7 +896,7 @@ async fn get_total_pages_with_retry(
         // 如果不是最后一次尝试，等待一段时间再重试
         if attempt < max_retries {
             let delay = std::time::Duration::from_secs(2u64.pow(attempt as u32 - 1));
-            println!("⏳ 等待 {} 秒后重试...", delay.as_secs());
+            tracing::info!("⏳ 等待 {} 秒后重试...", delay.as_secs());
             tokio::time::sleep(delay).await;
         }
     }
@@ -545,29 +904,80 @@ async fn get_total_pages_with_retry(
     Err(last_error.unwrap_or_else(|| "未知错误".into()))
 }
 
+// 采集触发方式
+pub const TRIGGER_MANUAL: &str = "manual";
+pub const TRIGGER_SCHEDULED: &str = "scheduled";
+pub const TRIGGER_RESUME: &str = "resume";
+
+// 保存各任务页面状态的集合，用于继续采集
+const COLLECT_TASK_STATE: &str = "collect_task_state";
+
 // 批量采集主函数
+// 结束时（完成/失败/停止）写入一条 collection_runs 汇总记录；被 abort 的任务不会记录
 pub async fn start_batch_collect(
     db: &Database,
     collection: Collection,
     hours: Option<String>,
     task_id: String,
+    trigger: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // 初始化任务进度
-    let initial_progress = CollectProgress {
-        status: "running".to_string(),
-        current_page: 0,
-        total_pages: 1,
-        success: 0,
-        failed: 0,
-        log: "正在获取总页数...".to_string(),
+    record_batch_collect(db, collection, hours, task_id, trigger, None).await
+}
+
+async fn record_batch_collect(
+    db: &Database,
+    collection: Collection,
+    hours: Option<String>,
+    task_id: String,
+    trigger: &str,
+    resume: Option<CollectTaskState>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let started_at = DateTime::now();
+    let collection_id = collection.id.map(|id| id.to_hex()).unwrap_or_default();
+    let collection_name = collection.collect_name.clone();
+
+    let result = run_batch_collect(db, collection, hours, task_id.clone(), resume).await;
+
+    let progress = get_task_progress(&task_id).await.unwrap_or_default();
+    let (status, message) = match &result {
+        Ok(_) if progress.status == "stopped" => ("stopped".to_string(), None),
+        Ok(_) => ("completed".to_string(), None),
+        Err(e) => ("failed".to_string(), Some(e.to_string())),
     };
-    update_task_progress(
-        &task_id,
-        initial_progress.clone(),
-        collection.collect_name.clone(),
-    )
-    .await;
 
+    let run = CollectionRun {
+        id: None,
+        task_id,
+        collection_id,
+        collection_name,
+        trigger: trigger.to_string(),
+        status,
+        started_at,
+        finished_at: DateTime::now(),
+        total_pages: progress.total_pages,
+        success: progress.success,
+        failed: progress.failed,
+        skipped: progress.skipped,
+        message,
+    };
+    if let Err(e) = db
+        .collection::<CollectionRun>("collection_runs")
+        .insert_one(&run, None)
+        .await
+    {
+        tracing::error!("保存采集记录失败: {}", e);
+    }
+
+    result
+}
+
+async fn run_batch_collect(
+    db: &Database,
+    collection: Collection,
+    hours: Option<String>,
+    task_id: String,
+    resume: Option<CollectTaskState>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // 构建API URL
     let mut api_url = collection.collect_url.clone();
     if api_url.contains('?') {
@@ -582,113 +992,865 @@ pub async fn start_batch_collect(
     }
 
     // 添加hours参数
-    if let Some(h) = hours {
+    if let Some(h) = &hours {
         api_url.push_str(&format!("&h={}", h));
     }
 
-    // 获取总页数（带重试机制）
-    let total_pages = match get_total_pages_with_retry(&api_url, 3, 30).await {
-        Ok(pages) => pages,
-        Err(e) => {
-            eprintln!("❌ 获取总页数失败，已重试3次: {}", e);
-            return Err(format!("获取总页数失败: {}", e).into());
+    // 按采集源的代理和请求头配置构建（或复用）客户端，各页共用连接池
+    let client = collect_client::client_for(&collection)?;
+
+    let (progress, pages, mut tracker) = match resume {
+        // 继续采集：页数沿用上次运行，计数从保存的状态恢复；重试的失败页不再计入失败数
+        Some(state) => {
+            let pages = state.remaining_pages();
+            let progress = CollectProgress {
+                status: "running".to_string(),
+                current_page: 0,
+                total_pages: state.total_pages,
+                success: state.success,
+                failed: state.failed.saturating_sub(state.failed_pages.len() as u32),
+                skipped: state.skipped,
+                filtered: state.filtered,
+                filtered_sources: state.filtered_sources,
+                log: format!(
+                    "继续采集，重试 {} 个失败页，从第 {}/{} 页继续",
+                    state.failed_pages.len(),
+                    state.next_page,
+                    state.total_pages
+                ),
+            };
+            (progress, pages, TaskStateTracker::new(state))
         }
-    };
+        None => {
+            // 初始化任务进度
+            let initial_progress = CollectProgress {
+                status: "running".to_string(),
+                current_page: 0,
+                total_pages: 1,
+                success: 0,
+                failed: 0,
+                skipped: 0,
+                filtered: 0,
+                filtered_sources: 0,
+                log: "正在获取总页数...".to_string(),
+            };
+            update_task_progress(
+                &task_id,
+                initial_progress.clone(),
+                collection.collect_name.clone(),
+            )
+            .await;
 
-    // 更新进度信息
-    let mut progress = initial_progress;
-    progress.total_pages = total_pages;
-    progress.log = format!("开始采集，总页数: {}", total_pages);
+            // 获取总页数（带重试机制）
+            let total_pages = match get_total_pages_with_retry(&client, &api_url, 3, collection.timeout_secs()).await {
+                Ok(pages) => pages,
+                Err(e) => {
+                    tracing::error!("❌ 获取总页数失败，已重试3次: {}", e);
+                    return Err(format!("获取总页数失败: {}", e).into());
+                }
+            };
+
+            // 更新进度信息
+            let mut progress = initial_progress;
+            progress.total_pages = total_pages;
+            progress.log = format!("开始采集，总页数: {}", total_pages);
+            let tracker = TaskStateTracker::new(CollectTaskState {
+                id: None,
+                task_id: task_id.clone(),
+                collection_id: collection.id.unwrap_or_default(),
+                collection_name: collection.collect_name.clone(),
+                hours: hours.clone(),
+                total_pages,
+                next_page: 1,
+                failed_pages: Vec::new(),
+                status: "running".to_string(),
+                success: 0,
+                failed: 0,
+                skipped: 0,
+                filtered: 0,
+                filtered_sources: 0,
+                updated_at: DateTime::now(),
+            });
+            (progress, (1..=total_pages).collect::<Vec<_>>(), tracker)
+        }
+    };
+    let total_pages = progress.total_pages;
     update_task_progress(&task_id, progress.clone(), collection.collect_name.clone()).await;
+    tracker.save(db, &progress, "running").await;
 
-    // 逐页采集
-    for page in 1..=total_pages {
-        // 检查任务是否被停止
-        if let Some(current_progress) = get_task_progress(&task_id).await {
-            if current_progress.status == "stopped" {
-                return Ok(()); // 任务已被停止，直接返回
+    let options = CollectOptions::load(db).await;
+    // 保存时已校验；校验出现之前保存的无效表达式不阻止采集，只记录警告
+    let name_filters = parse_collect_filter(&collection.collect_filter).unwrap_or_else(|e| {
+        tracing::warn!("{}: {}，本次采集不按片名过滤", collection.collect_name, e);
+        Vec::new()
+    });
+    let progress = std::sync::Mutex::new(progress);
+    let ctx = PageCollectContext {
+        db,
+        client: &client,
+        collection: &collection,
+        task_id: &task_id,
+        options: &options,
+        name_filters: &name_filters,
+        progress: &progress,
+    };
+    // 连续失败的页数，达到 collect_max_consecutive_failures 时中止整个任务
+    let mut consecutive_failures = 0;
+    // 中止后尚未开始的页面不再请求，已在进行中的页面处理完再返回
+    let aborted = AtomicBool::new(false);
+    let mut abort_message = None;
+
+    // 同时采集 collect_concurrency 页（默认 4），各页完成的先后不固定，连续失败按完成的先后计算
+    let mut pages = futures::stream::iter(pages)
+        .map(|page| {
+            let ctx = &ctx;
+            let aborted = &aborted;
+            let page_url = format!("{}&pg={}", api_url, page);
+            // 返回 None 表示该页没有完整处理（任务已中止或被停止），不记入页面状态
+            async move {
+                // 检查任务是否被停止
+                if aborted.load(Ordering::Relaxed) || is_task_stopped(ctx.task_id).await {
+                    return (page, None);
+                }
+                // 并发时 current_page 为已开始的最大页码
+                ctx.update_progress(|p| {
+                    p.current_page = p.current_page.max(page);
+                    p.log = format!("正在采集第 {}/{} 页", page, total_pages);
+                })
+                .await;
+
+                let result = match collect_page(ctx, &page_url, page).await {
+                    Ok(false) => return (page, None),
+                    Ok(true) => Ok(()),
+                    Err(e) => Err(e),
+                };
+                if result.is_ok() {
+                    // 添加延时避免请求过快（每个并发页面各自间隔）
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                }
+                (page, Some(result))
             }
-        }
-
-        progress.current_page = page;
-        progress.log = format!("正在采集第 {}/{} 页", page, total_pages);
-        update_task_progress(&task_id, progress.clone(), collection.collect_name.clone()).await;
+        })
+        .buffer_unordered(collection.concurrency());
 
-        let page_url = format!("{}&pg={}", api_url, page);
-        if let Err(e) = collect_page(db, &collection, &page_url, &mut progress, &task_id).await {
-            progress.failed += 1;
-            progress.log = format!("第 {} 页采集失败: {}", page, e);
-            update_task_progress(&task_id, progress.clone(), collection.collect_name.clone()).await;
+    while let Some((page, outcome)) = pages.next().await {
+        let Some(result) = outcome else {
+            continue;
+        };
+        tracker.page_finished(page, result.is_ok());
+        let Err(e) = result else {
+            consecutive_failures = 0;
+            tracker.save(db, &ctx.snapshot(), "running").await;
             continue;
+        };
+        consecutive_failures += 1;
+        ctx.update_progress(|p| {
+            p.failed += 1;
+            p.log = format!("第 {} 页采集失败: {}", page, e);
+        })
+        .await;
+        tracker.save(db, &ctx.snapshot(), "running").await;
+
+        if abort_message.is_none()
+            && options.max_consecutive_failures > 0
+            && consecutive_failures >= options.max_consecutive_failures
+        {
+            let message = format!(
+                "连续 {} 页采集失败，采集源可能已失效，任务已中止（最后错误: {}）",
+                consecutive_failures, e
+            );
+            tracing::error!("❌ {}: {}", collection.collect_name, message);
+            aborted.store(true, Ordering::Relaxed);
+            abort_message = Some(message);
         }
+    }
+    drop(pages);
 
-        // 添加延时避免请求过快
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    if let Some(message) = abort_message {
+        ctx.update_progress(|p| {
+            p.status = "failed".to_string();
+            p.log = message.clone();
+        })
+        .await;
+        tracker.save(db, &ctx.snapshot(), "failed").await;
+        return Err(message.into());
+    }
+    if is_task_stopped(&task_id).await {
+        tracker.save(db, &ctx.snapshot(), "stopped").await;
+        return Ok(()); // 任务已被停止，直接返回
     }
 
-    // 完成采集
-    progress.status = "completed".to_string();
-    progress.log = format!(
-        "采集完成，成功: {}，失败: {}",
-        progress.success, progress.failed
-    );
-    update_task_progress(&task_id, progress, collection.collect_name).await;
+    // 完成采集（所有页面都已结束，计数是最终值）
+    ctx.update_progress(|p| {
+        p.status = "completed".to_string();
+        p.log = format!(
+            "采集完成，成功: {}，失败: {}，跳过: {}，过滤视频: {}，过滤播放源: {}",
+            p.success, p.failed, p.skipped, p.filtered, p.filtered_sources
+        );
+    })
+    .await;
+    tracker.save(db, &ctx.snapshot(), "completed").await;
 
     Ok(())
 }
 
-// 采集单页数据（带超时）
-async fn collect_page(
+// 记录各页的完成情况并保存到 collect_task_state，任务失败或中断后 resume_task 据此继续采集
+struct TaskStateTracker {
+    state: CollectTaskState,
+    // next_page 之后已经结束的页；并发采集时页面乱序完成，连续的一段结束后 next_page 才前移
+    finished_ahead: std::collections::BTreeSet<u32>,
+}
+
+impl TaskStateTracker {
+    fn new(state: CollectTaskState) -> Self {
+        Self {
+            state,
+            finished_ahead: std::collections::BTreeSet::new(),
+        }
+    }
+
+    // 页面结束（成功或失败）；失败页记入 failed_pages，之后成功采集时移除
+    fn page_finished(&mut self, page: u32, ok: bool) {
+        if ok {
+            self.state.failed_pages.retain(|&p| p != page);
+        } else if !self.state.failed_pages.contains(&page) {
+            self.state.failed_pages.push(page);
+        }
+        if page >= self.state.next_page {
+            self.finished_ahead.insert(page);
+            while self.finished_ahead.remove(&self.state.next_page) {
+                self.state.next_page += 1;
+            }
+        }
+    }
+
+    // 保存失败只记录警告，不影响采集本身
+    async fn save(&mut self, db: &Database, progress: &CollectProgress, status: &str) {
+        self.state.status = status.to_string();
+        self.state.success = progress.success;
+        self.state.failed = progress.failed;
+        self.state.skipped = progress.skipped;
+        self.state.filtered = progress.filtered;
+        self.state.filtered_sources = progress.filtered_sources;
+        self.state.updated_at = DateTime::now();
+
+        let options = mongodb::options::ReplaceOptions::builder().upsert(true).build();
+        if let Err(e) = db
+            .collection::<CollectTaskState>(COLLECT_TASK_STATE)
+            .replace_one(doc! {"task_id": &self.state.task_id}, &self.state, options)
+            .await
+        {
+            tracing::warn!("保存采集任务状态失败 {}: {}", self.state.task_id, e);
+        }
+    }
+}
+
+// 任务是否已被手动停止
+async fn is_task_stopped(task_id: &str) -> bool {
+    get_task_progress(task_id)
+        .await
+        .is_some_and(|progress| progress.status == "stopped")
+}
+
+// 一次批量采集中各页面共享的参数。并发采集时进度放在锁里，计数在锁内累加后再写入进度表，
+// 多个页面同时完成时计数不会互相覆盖；锁不跨 await 持有
+struct PageCollectContext<'a> {
+    db: &'a Database,
+    client: &'a reqwest::Client,
+    collection: &'a Collection,
+    task_id: &'a str,
+    options: &'a CollectOptions,
+    name_filters: &'a [Regex],
+    progress: &'a std::sync::Mutex<CollectProgress>,
+}
+
+impl PageCollectContext<'_> {
+    fn snapshot(&self) -> CollectProgress {
+        self.progress.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    async fn update_progress(&self, f: impl FnOnce(&mut CollectProgress)) {
+        let snapshot = {
+            let mut progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+            f(&mut progress);
+            progress.clone()
+        };
+        update_task_progress(self.task_id, snapshot, self.collection.collect_name.clone()).await;
+    }
+}
+
+// 单页的采集计数，页面结束（或任务被停止）时一次性累加到总进度
+#[derive(Default)]
+struct PageCounts {
+    success: u32,
+    failed: u32,
+    skipped: u32,
+    filtered: u32,
+    filtered_sources: u32,
+}
+
+impl PageCounts {
+    fn add_to(&self, progress: &mut CollectProgress) {
+        progress.success += self.success;
+        progress.failed += self.failed;
+        progress.skipped += self.skipped;
+        progress.filtered += self.filtered;
+        progress.filtered_sources += self.filtered_sources;
+    }
+}
+
+// MongoDB 唯一索引冲突的错误码
+const DUPLICATE_KEY_CODE: i32 = 11000;
+
+// 等待批量写入的新视频
+struct PendingInsert {
+    vod_data: VodApiListEntry,
+    vod: Box<Vod>,
+}
+
+impl PendingInsert {
+    // 与 find_one(dedup_filter) 相同的判断：条件里的每个字段都与待写入视频相等
+    fn matches(&self, filter: &Document) -> bool {
+        let fields = doc! {
+            "vod_name": &self.vod.vod_name,
+            "vod_year": &self.vod.vod_year,
+            "vod_area": &self.vod.vod_area,
+        };
+        filter.iter().all(|(key, value)| fields.get(key) == Some(value))
+    }
+}
+
+// 无序批量写入：单条失败不会中断同批其余记录
+fn unordered_insert_options() -> mongodb::options::InsertManyOptions {
+    mongodb::options::InsertManyOptions::builder().ordered(false).build()
+}
+
+// 用 insert_many(ordered=false) 写入待写入的新视频，返回 (成功数, 失败数)。
+// 无序写入时单条失败不会中断其余记录；因唯一索引冲突（E11000，例如其他任务刚写入了同一视频）失败的记录
+// 改为逐条走 collect_single_video，此时能查到已有视频并合并播放源。
+async fn flush_pending_inserts(
     db: &Database,
     collection: &Collection,
+    options: &CollectOptions,
+    pending: &mut Vec<PendingInsert>,
+) -> (u32, u32) {
+    if pending.is_empty() {
+        return (0, 0);
+    }
+    let batch = std::mem::take(pending);
+    let total = batch.len() as u32;
+
+    let result = db
+        .collection::<Vod>("vods")
+        .insert_many(batch.iter().map(|p| p.vod.as_ref()), unordered_insert_options())
+        .await;
+
+    let write_errors = match result {
+        Ok(_) => {
+            tracing::debug!("批量写入新视频 {} 条", total);
+            return (total, 0);
+        }
+        Err(e) => match *e.kind {
+            mongodb::error::ErrorKind::BulkWrite(failure) => failure.write_errors.unwrap_or_default(),
+            _ => {
+                tracing::error!("批量写入新视频失败（{} 条）: {}", total, e);
+                return (0, total);
+            }
+        },
+    };
+
+    let mut success = total - write_errors.len() as u32;
+    let mut failed = 0;
+    for write_error in write_errors {
+        let Some(item) = batch.get(write_error.index) else {
+            failed += 1;
+            continue;
+        };
+        if write_error.code != DUPLICATE_KEY_CODE {
+            tracing::warn!("采集视频失败 {}: {}", item.vod_data.vod_name, write_error.message);
+            failed += 1;
+            continue;
+        }
+        match collect_single_video(db, collection, &item.vod_data, options).await {
+            Ok(_) => success += 1,
+            Err(e) => {
+                tracing::warn!("采集视频失败 {}: {}", item.vod_data.vod_name, e);
+                failed += 1;
+            }
+        }
+    }
+    (success, failed)
+}
+
+// 请求并解析一页列表
+async fn fetch_video_page(
+    client: &reqwest::Client,
+    collection: &Collection,
     page_url: &str,
-    progress: &mut CollectProgress,
-    task_id: &str,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let response_text = fetch_with_timeout(page_url, 30).await?;
-    let api_response: VideoListResponse = serde_json::from_str(&response_text)?;
+) -> Result<VideoListResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let response_text = fetch_with_timeout(client, page_url, collection.timeout_secs()).await?;
+    let api_response = parse_video_list(&response_text, &collection.field_mapping)?;
 
     if api_response.code != 1 {
         return Err(format!("API返回错误: {:?}", api_response).into());
     }
+    Ok(api_response)
+}
+
+// 请求一页列表，失败时按 collect_page_retries 退避重试（1、2、4...秒，最长 30 秒），重试情况写入进度日志。
+// 只重试请求和解析：列表拿到后逐条处理，单个视频的失败单独计数，不会导致整页重来。
+// 等待重试期间任务被停止时返回 None
+async fn fetch_page_with_retry(
+    ctx: &PageCollectContext<'_>,
+    page_url: &str,
+    page: u32,
+) -> Result<Option<VideoListResponse>, Box<dyn std::error::Error + Send + Sync>> {
+    let collection = ctx.collection;
+    let mut attempt = 0;
+    loop {
+        let error = match fetch_video_page(ctx.client, collection, page_url).await {
+            Ok(api_response) => return Ok(Some(api_response)),
+            Err(e) => e,
+        };
+        if attempt >= ctx.options.page_retries {
+            return Err(error);
+        }
+        attempt += 1;
+
+        let delay = std::time::Duration::from_secs(2u64.pow(attempt as u32 - 1).min(30));
+        let log = format!(
+            "第 {} 页请求失败，{} 秒后重试 ({}/{}): {}",
+            page,
+            delay.as_secs(),
+            attempt,
+            ctx.options.page_retries,
+            error
+        );
+        tracing::warn!("{}: {}", collection.collect_name, log);
+        ctx.update_progress(|p| p.log = log).await;
+        tokio::time::sleep(delay).await;
+
+        if is_task_stopped(ctx.task_id).await {
+            return Ok(None);
+        }
+    }
+}
+
+// 采集单页数据（带超时）
+// 返回 Ok(false) 表示任务在处理本页期间被停止，本页没有处理完
+async fn collect_page(
+    ctx: &PageCollectContext<'_>,
+    page_url: &str,
+    page: u32,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let PageCollectContext { db, collection, task_id, options, name_filters, .. } = *ctx;
+    let Some(api_response) = fetch_page_with_retry(ctx, page_url, page).await? else {
+        return Ok(false); // 重试等待期间任务被停止
+    };
 
-    let mut page_success = 0;
-    let mut page_failed = 0;
+    let mut counts = PageCounts::default();
+    // 本页新视频先攒起来，最后一次 insert_many 写入；已存在视频的更新仍逐条写入
+    let mut pending: Vec<PendingInsert> = Vec::new();
 
     for vod_data in api_response.list {
         // 检查任务是否被停止
-        if let Some(current_progress) = get_task_progress(task_id).await {
-            if current_progress.status == "stopped" {
-                return Ok(()); // 任务已被停止，直接返回
-            }
+        if is_task_stopped(task_id).await {
+            // 已处理（海报已下载）的新视频仍然写入
+            let (success, failed) = flush_pending_inserts(db, collection, options, &mut pending).await;
+            counts.success += success;
+            counts.failed += failed;
+            ctx.update_progress(|p| counts.add_to(p)).await;
+            return Ok(false); // 任务已被停止，直接返回
+        }
+
+        if name_filters.iter().any(|re| re.is_match(&vod_data.vod_name)) {
+            tracing::debug!("片名命中 collect_filter，跳过视频 {}", vod_data.vod_name);
+            counts.filtered += 1;
+            continue;
+        }
+        counts.filtered_sources += count_filtered_sources(&vod_data, &collection.collect_filter_from);
+
+        // 同一页里出现与待写入视频相同的视频时，先写入之前的，让这一条走合并播放源的逻辑
+        let filter = dedup_filter(options.dedup_key, collection, &vod_data);
+        if pending.iter().any(|p| p.matches(&filter)) {
+            let (success, failed) = flush_pending_inserts(db, collection, options, &mut pending).await;
+            counts.success += success;
+            counts.failed += failed;
         }
 
-        match collect_single_video(db, collection, &vod_data).await {
-            Ok(_) => page_success += 1,
+        match prepare_single_video(db, collection, &vod_data, options).await {
+            Ok(PreparedVideo::Updated) => counts.success += 1,
+            Ok(PreparedVideo::Skipped) => {
+                tracing::debug!("集数不足，跳过视频 {} (vod_id={})", vod_data.vod_name, vod_data.vod_id.to_string());
+                counts.skipped += 1;
+            }
+            Ok(PreparedVideo::Insert(vod)) => pending.push(PendingInsert { vod_data, vod }),
             Err(e) => {
-                eprintln!("采集视频失败 {}: {}", vod_data.vod_name, e);
-                page_failed += 1;
+                tracing::warn!("采集视频失败 {}: {}", vod_data.vod_name, e);
+                counts.failed += 1;
             }
         }
     }
 
-    progress.success += page_success;
-    progress.failed += page_failed;
-    progress.log = format!(
-        "本页采集完成，成功: {}，失败: {}",
-        page_success, page_failed
+    let (success, failed) = flush_pending_inserts(db, collection, options, &mut pending).await;
+    counts.success += success;
+    counts.failed += failed;
+
+    tracing::debug!(
+        "页面采集完成 {}: 成功 {}, 失败 {}, 跳过 {}, 过滤视频 {}, 过滤播放源 {}",
+        page_url, counts.success, counts.failed, counts.skipped, counts.filtered, counts.filtered_sources
     );
-    update_task_progress(task_id, progress.clone(), collection.collect_name.clone()).await;
+    ctx.update_progress(|p| {
+        counts.add_to(p);
+        p.log = format!(
+            "第 {} 页采集完成，成功: {}，失败: {}，跳过: {}，过滤视频: {}，过滤播放源: {}",
+            page, counts.success, counts.failed, counts.skipped, counts.filtered, counts.filtered_sources
+        );
+    })
+    .await;
 
-    Ok(())
+    Ok(true)
+}
+
+// 解析 collect_max_episodes_per_source：空值或 0 表示不限制，否则必须是正整数
+pub fn parse_max_episodes_per_source(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(0);
+    }
+    value
+        .parse::<usize>()
+        .map_err(|_| format!("每个播放源的最大集数必须是非负整数: {}", value))
+}
+
+// 每个播放源只保留前 max 集（0 为不限制），防止异常上游数据让视频文档过大（BSON 单文档上限 16MB）
+fn cap_episodes_per_source(mut play_sources: Vec<PlaySource>, max: usize, vod_name: &str) -> Vec<PlaySource> {
+    if max == 0 {
+        return play_sources;
+    }
+    for source in play_sources.iter_mut() {
+        if source.urls.len() > max {
+            tracing::warn!(
+                "播放源集数超过上限，已截断: {} [{}] {} -> {}",
+                vod_name,
+                source.source_name,
+                source.urls.len(),
+                max
+            );
+            source.urls.truncate(max);
+        }
+    }
+    play_sources
+}
+
+// MongoDB 单文档上限为 16MB，默认在 15MB 时就拦截，给其他字段和更新留出余量
+pub const DEFAULT_MAX_VOD_DOC_KB: usize = 15 * 1024;
+const BSON_MAX_DOC_KB: usize = 16 * 1024;
+
+// 解析 collect_max_vod_doc_kb：1 - 16384 之间的整数（KB）
+pub fn parse_max_vod_doc_kb(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(kb) if (1..=BSON_MAX_DOC_KB).contains(&kb) => Ok(kb),
+        _ => Err(format!("视频文档大小上限必须是 1-{} 之间的整数（KB）: {}", BSON_MAX_DOC_KB, value.trim())),
+    }
+}
+
+pub const DEFAULT_COLLECT_PAGE_RETRIES: usize = 2;
+const MAX_COLLECT_PAGE_RETRIES: usize = 10;
+pub const DEFAULT_MAX_CONSECUTIVE_FAILURES: u32 = 10;
+
+// 解析 collect_page_retries：0 - 10 之间的整数，0 表示失败后不重试
+pub fn parse_collect_page_retries(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(n) if n <= MAX_COLLECT_PAGE_RETRIES => Ok(n),
+        _ => Err(format!("每页重试次数必须是 0-{} 之间的整数: {}", MAX_COLLECT_PAGE_RETRIES, value.trim())),
+    }
+}
+
+// 解析 collect_max_consecutive_failures：非负整数，0 表示不因连续失败中止
+pub fn parse_max_consecutive_failures(value: &str) -> Result<u32, String> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("连续失败页数上限必须是非负整数: {}", value.trim()))
+}
+
+fn vod_doc_size(vod: &Vod) -> Result<usize, String> {
+    let document = mongodb::bson::to_document(vod).map_err(|e| format!("序列化视频失败: {}", e))?;
+    let mut buf = Vec::new();
+    document
+        .to_writer(&mut buf)
+        .map_err(|e| format!("序列化视频失败: {}", e))?;
+    Ok(buf.len())
+}
+
+// 写入前检查视频文档大小。超过 max_bytes 时从最早加入的播放源开始删除（本次上游提供的播放源 keep_sources 保留），
+// 删除后仍然超过上限则返回错误，由调用方跳过本次写入，避免 MongoDB 返回难以理解的 BSON 大小错误
+fn enforce_doc_size_limit(vod: &mut Vod, max_bytes: usize, keep_sources: &[String]) -> Result<(), String> {
+    loop {
+        let size = vod_doc_size(vod)?;
+        if size <= max_bytes {
+            return Ok(());
+        }
+
+        let oldest = vod
+            .vod_play_urls
+            .iter()
+            .position(|source| !keep_sources.contains(&source.source_name));
+        match oldest {
+            Some(index) => {
+                let removed = vod.vod_play_urls.remove(index);
+                tracing::warn!(
+                    "视频文档 {} 大小 {} 字节超过上限 {} 字节，已删除最早的播放源 [{}]（{} 集）",
+                    vod.vod_name,
+                    size,
+                    max_bytes,
+                    removed.source_name,
+                    removed.urls.len()
+                );
+            }
+            None => {
+                return Err(format!(
+                    "视频文档 {} 大小 {} 字节超过上限 {} 字节，已跳过写入",
+                    vod.vod_name, size, max_bytes
+                ));
+            }
+        }
+    }
+}
+
+// 采集相关的全局配置，每次批量采集开始时读取一次
+pub struct CollectOptions {
+    pub min_episodes: usize, // collect_min_episodes：新视频的可播放集数低于该值时不入库，默认 0 即不过滤
+    pub normalize_episode_names: bool, // normalize_episode_names：集数名称统一为「第N集」，默认关闭
+    pub dedup_key: DedupKey,  // collect_dedup_key：判断视频是否已存在的匹配字段，默认 name_year
+    pub max_episodes_per_source: usize, // collect_max_episodes_per_source：每个播放源最多保存的集数，默认 0 即不限制
+    pub max_doc_bytes: usize, // collect_max_vod_doc_kb：视频文档写入前的大小上限，默认 15MB
+    pub page_retries: usize, // collect_page_retries：每页请求失败后的重试次数，默认 2
+    pub max_consecutive_failures: u32, // collect_max_consecutive_failures：连续失败多少页后中止任务，默认 10，0 为不中止
+}
+
+// 采集去重方式：上游视频与库中哪个视频视为同一部
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupKey {
+    NameYear,     // 名称 + 年份（上游缺少年份时只按名称）
+    Name,         // 只按名称
+    NameYearArea, // 名称 + 年份 + 地区（缺少的字段不参与匹配）
+}
+
+impl DedupKey {
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "name" => DedupKey::Name,
+            "name_year_area" => DedupKey::NameYearArea,
+            _ => DedupKey::NameYear,
+        }
+    }
+}
+
+impl CollectOptions {
+    pub async fn load(db: &Database) -> Self {
+        let min_episodes = get_collect_config(db, "collect_min_episodes")
+            .await
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        let normalize_episode_names = get_collect_config(db, "normalize_episode_names")
+            .await
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "on"))
+            .unwrap_or(false);
+
+        let dedup_key = get_collect_config(db, "collect_dedup_key")
+            .await
+            .map(|v| DedupKey::parse(&v))
+            .unwrap_or(DedupKey::NameYear);
+
+        let max_episodes_per_source = match get_collect_config(db, "collect_max_episodes_per_source").await {
+            Some(v) => parse_max_episodes_per_source(&v).unwrap_or_else(|e| {
+                tracing::warn!("collect_max_episodes_per_source 配置无效，不限制集数: {}", e);
+                0
+            }),
+            None => 0,
+        };
+
+        let max_doc_bytes = match get_collect_config(db, "collect_max_vod_doc_kb").await {
+            Some(v) => parse_max_vod_doc_kb(&v).unwrap_or_else(|e| {
+                tracing::warn!("collect_max_vod_doc_kb 配置无效，使用默认值 {}KB: {}", DEFAULT_MAX_VOD_DOC_KB, e);
+                DEFAULT_MAX_VOD_DOC_KB
+            }),
+            None => DEFAULT_MAX_VOD_DOC_KB,
+        } * 1024;
+
+        let page_retries = match get_collect_config(db, "collect_page_retries").await {
+            Some(v) => parse_collect_page_retries(&v).unwrap_or_else(|e| {
+                tracing::warn!("collect_page_retries 配置无效，使用默认值 {}: {}", DEFAULT_COLLECT_PAGE_RETRIES, e);
+                DEFAULT_COLLECT_PAGE_RETRIES
+            }),
+            None => DEFAULT_COLLECT_PAGE_RETRIES,
+        };
+
+        let max_consecutive_failures = match get_collect_config(db, "collect_max_consecutive_failures").await {
+            Some(v) => parse_max_consecutive_failures(&v).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "collect_max_consecutive_failures 配置无效，使用默认值 {}: {}",
+                    DEFAULT_MAX_CONSECUTIVE_FAILURES,
+                    e
+                );
+                DEFAULT_MAX_CONSECUTIVE_FAILURES
+            }),
+            None => DEFAULT_MAX_CONSECUTIVE_FAILURES,
+        };
+
+        CollectOptions {
+            min_episodes,
+            normalize_episode_names,
+            dedup_key,
+            max_episodes_per_source,
+            max_doc_bytes,
+            page_retries,
+            max_consecutive_failures,
+        }
+    }
+}
+
+async fn get_collect_config(db: &Database, key: &str) -> Option<String> {
+    match db
+        .collection::<crate::models::Config>("configs")
+        .find_one(doc! { "config_key": key }, None)
+        .await
+    {
+        Ok(Some(config)) => Some(config.config_value),
+        _ => None,
+    }
+}
+
+// 统计所有播放源中有实际播放地址的总集数，没有地址的占位条目不计入
+fn count_episodes(play_sources: &[PlaySource]) -> usize {
+    play_sources
+        .iter()
+        .flat_map(|source| source.urls.iter())
+        .filter(|url| !url.url.trim().is_empty())
+        .count()
+}
+
+// 可播放集数低于 collect_min_episodes 的新视频不入库，计为跳过
+fn below_min_episodes(play_sources: &[PlaySource], min_episodes: usize) -> bool {
+    count_episodes(play_sources) < min_episodes
+}
+
+// 解析 collect_filter：逗号或换行分隔的正则表达式，片名（vod_name）匹配任一表达式的视频不入库；为空时不过滤。
+// 表达式区分大小写（可用 (?i) 忽略），由于按逗号分隔，表达式中不能包含逗号
+pub fn parse_collect_filter(value: &str) -> Result<Vec<Regex>, String> {
+    value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| format!("collect_filter 中的正则表达式无效 \"{}\": {}", pattern, e))
+        })
+        .collect()
+}
+
+// collect_filter_from 中的播放源名称，统一为小写
+fn filter_from_terms(filter_from: &str) -> Vec<String> {
+    filter_from
+        .split(',')
+        .map(|term| term.trim().to_lowercase())
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+// 统计上游视频中会被 collect_filter_from 丢弃的播放源数，与 parse_play_urls 一样按逗号拆分 vod_play_from
+fn count_filtered_sources(vod_data: &VodApiListEntry, filter_from: &str) -> u32 {
+    let terms = filter_from_terms(filter_from);
+    if terms.is_empty() || vod_data.vod_play_url.is_none() {
+        return 0;
+    }
+    vod_data
+        .vod_play_from
+        .split(',')
+        .filter(|name| terms.contains(&name.trim().to_lowercase()))
+        .count() as u32
+}
+
+// 按 collect_filter_from 过滤播放源：逗号分隔的播放源名称（不区分大小写），命中的播放源不入库；为空时不过滤
+fn filter_play_sources(play_sources: Vec<PlaySource>, filter_from: &str) -> Vec<PlaySource> {
+    let terms = filter_from_terms(filter_from);
+
+    if terms.is_empty() {
+        return play_sources;
+    }
+
+    play_sources
+        .into_iter()
+        .filter(|source| !terms.contains(&source.source_name.trim().to_lowercase()))
+        .collect()
+}
+
+// 上游字段为空时使用采集源配置的默认值（default_area / default_lang），默认值为空时保持原样
+fn fill_default(value: &Option<String>, default: &str) -> Option<String> {
+    match value {
+        Some(v) if !v.trim().is_empty() => value.clone(),
+        _ if !default.trim().is_empty() => Some(default.trim().to_string()),
+        _ => value.clone(),
+    }
+}
+
+// 按去重方式构造查找已有视频的条件；上游缺少的字段不参与匹配
+fn dedup_filter(key: DedupKey, collection: &Collection, vod_data: &VodApiListEntry) -> Document {
+    // 入库时地区会用采集源默认值补全，匹配时同样补全
+    let area = fill_default(&vod_data.vod_area, &collection.default_area);
+    vod_dedup_filter(key, &vod_data.vod_name, vod_data.vod_year.as_deref(), area.as_deref())
+}
+
+// 按名称、年份、地区查找已有视频的条件（采集和批量导入共用）；为空的字段不参与匹配
+pub fn vod_dedup_filter(key: DedupKey, name: &str, year: Option<&str>, area: Option<&str>) -> Document {
+    let mut filter = doc! { "vod_name": name };
+    if key == DedupKey::Name {
+        return filter;
+    }
+
+    if let Some(year) = year {
+        filter.insert("vod_year", year);
+    }
+    if key == DedupKey::NameYearArea {
+        if let Some(area) = area.filter(|a| !a.trim().is_empty()) {
+            filter.insert("vod_area", area);
+        }
+    }
+    filter
+}
+
+// 单个视频的处理结果：已存在的视频直接合并写入，新视频交给调用方写入（collect_page 攒批后 insert_many）
+enum PreparedVideo {
+    Updated,
+    Skipped, // 集数不足 min_episodes
+    Insert(Box<Vod>),
 }
 
 // 采集单个视频
+// 返回 Ok(false) 表示视频因集数不足 min_episodes 被跳过
 pub async fn collect_single_video(
     db: &Database,
     collection: &Collection,
     vod_data: &VodApiListEntry,
+    options: &CollectOptions,
 ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    match prepare_single_video(db, collection, vod_data, options).await? {
+        PreparedVideo::Updated => Ok(true),
+        PreparedVideo::Skipped => Ok(false),
+        PreparedVideo::Insert(vod) => {
+            db.collection::<Vod>("vods").insert_one(vod.as_ref(), None).await?;
+            Ok(true)
+        }
+    }
+}
+
+async fn prepare_single_video(
+    db: &Database,
+    collection: &Collection,
+    vod_data: &VodApiListEntry,
+    options: &CollectOptions,
+) -> Result<PreparedVideo, Box<dyn std::error::Error + Send + Sync>> {
     // 查找分类绑定
     let bindings_collection = db.collection::<Binding>("bindings");
     let binding = bindings_collection
@@ -704,7 +1866,7 @@ pub async fn collect_single_video(
     let local_type_id = match binding {
         Some(b) => b.local_type_id,
         None => {
-            eprintln!(
+            tracing::warn!(
                 "未找到分类绑定: source_flag={}, external_id={}",
                 collection.collect_name, vod_data.type_id
             );
@@ -712,28 +1874,11 @@ pub async fn collect_single_video(
         }
     };
 
-    // 检查视频是否已存在（基于vod_name和vod_year）
+    // 检查视频是否已存在（匹配字段由 collect_dedup_key 决定）
     let vods_collection = db.collection::<Vod>("vods");
-    let existing_vod = if let Some(ref year) = vod_data.vod_year {
-        vods_collection
-            .find_one(
-                doc! {
-                    "vod_name": &vod_data.vod_name,
-                    "vod_year": year
-                },
-                None,
-            )
-            .await?
-    } else {
-        vods_collection
-            .find_one(
-                doc! {
-                    "vod_name": &vod_data.vod_name
-                },
-                None,
-            )
-            .await?
-    };
+    let existing_vod = vods_collection
+        .find_one(dedup_filter(options.dedup_key, collection, vod_data), None)
+        .await?;
 
     let current_time = DateTime::from_millis(
         SystemTime::now()
@@ -744,9 +1889,21 @@ pub async fn collect_single_video(
 
     if let Some(mut existing) = existing_vod {
         // 更新现有视频 - 处理播放源替换
-        let new_play_sources = parse_play_urls(&vod_data.vod_play_from, &vod_data.vod_play_url);
+        let new_play_sources = cap_episodes_per_source(
+            filter_play_sources(
+                parse_play_urls(
+                    &vod_data.vod_play_from,
+                    &vod_data.vod_play_url,
+                    options.normalize_episode_names,
+                ),
+                &collection.collect_filter_from,
+            ),
+            options.max_episodes_per_source,
+            &vod_data.vod_name,
+        );
 
         // 根据source_name匹配更新播放源
+        let incoming_sources: Vec<String> = new_play_sources.iter().map(|s| s.source_name.clone()).collect();
         let mut updated = false;
         for new_source in new_play_sources {
             if let Some(pos) = existing
@@ -767,15 +1924,39 @@ pub async fn collect_single_video(
         if updated {
             existing.vod_pubdate = current_time;
             existing.vod_remarks = Some(vod_data.vod_remarks.clone());
+            existing.vod_area = fill_default(&existing.vod_area, &collection.default_area);
+            existing.vod_lang = fill_default(&existing.vod_lang, &collection.default_lang);
+            existing.normalize();
+            if let Err(e) = enforce_doc_size_limit(&mut existing, options.max_doc_bytes, &incoming_sources) {
+                tracing::warn!("{}", e);
+                return Err(e.into());
+            }
             vods_collection
                 .replace_one(doc! { "_id": existing.id }, &existing, None)
                 .await?;
         }
 
-        Ok(true)
+        Ok(PreparedVideo::Updated)
     } else {
+        // 过滤没有实际播放地址的占位条目
+        let play_sources = cap_episodes_per_source(
+            filter_play_sources(
+                parse_play_urls(
+                    &vod_data.vod_play_from,
+                    &vod_data.vod_play_url,
+                    options.normalize_episode_names,
+                ),
+                &collection.collect_filter_from,
+            ),
+            options.max_episodes_per_source,
+            &vod_data.vod_name,
+        );
+        if below_min_episodes(&play_sources, options.min_episodes) {
+            return Ok(PreparedVideo::Skipped);
+        }
+
         // 创建新视频
-        let new_vod = Vod {
+        let mut new_vod = Vod {
             id: None,
             vod_name: vod_data.vod_name.clone(),
             type_id: local_type_id,
@@ -786,25 +1967,38 @@ pub async fn collect_single_video(
             vod_director: vod_data.vod_director.clone(),
             vod_remarks: Some(vod_data.vod_remarks.clone()),
             vod_pubdate: current_time.clone(),
-            vod_area: vod_data.vod_area.clone(),
-            vod_lang: vod_data.vod_lang.clone(),
+            vod_created_at: Some(current_time),
+            vod_area: fill_default(&vod_data.vod_area, &collection.default_area),
+            vod_lang: fill_default(&vod_data.vod_lang, &collection.default_lang),
             vod_year: vod_data.vod_year.clone(),
+            vod_year_num: None,
             vod_content: vod_data.vod_content.clone(),
             vod_hits: Some(0),
             vod_hits_day: Some(0),
             vod_hits_week: Some(0),
             vod_hits_month: Some(0),
             vod_score: Some("0.0".to_string()),
-            vod_play_urls: parse_play_urls(&vod_data.vod_play_from, &vod_data.vod_play_url),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
+            vod_play_urls: play_sources,
         };
 
+        // 新视频的播放源都来自本次上游数据，超过上限时直接跳过，不下载海报
+        let incoming_sources: Vec<String> = new_vod.vod_play_urls.iter().map(|s| s.source_name.clone()).collect();
+        if let Err(e) = enforce_doc_size_limit(&mut new_vod, options.max_doc_bytes, &incoming_sources) {
+            tracing::warn!("{}", e);
+            return Err(e.into());
+        }
+
         // 如果启用了图片本地化，下载海报
         let final_vod_pic = if collection.collect_sync_pic_opt == 1 {
             if let Some(ref pic_url) = vod_data.vod_pic {
                 match download_image_to_local_with_config(pic_url, collection).await {
                     Ok(local_path) => Some(local_path),
                     Err(e) => {
-                        eprintln!("下载图片失败 {}: {}", pic_url, e);
+                        tracing::warn!("下载图片失败 {}: {}", pic_url, e);
                         vod_data.vod_pic.clone()
                     }
                 }
@@ -817,12 +2011,17 @@ pub async fn collect_single_video(
 
         let mut final_vod = new_vod;
         final_vod.vod_pic = final_vod_pic;
+        final_vod.normalize();
 
-        vods_collection.insert_one(&final_vod, None).await?;
-        Ok(true)
+        Ok(PreparedVideo::Insert(Box::new(final_vod)))
     }
 }
 
+// webp 转换质量（0-100）
+pub const DEFAULT_WEBP_QUALITY: f32 = 75.0;
+// 测试图片管道时下载超时
+const TEST_IMAGE_TIMEOUT_SECS: u64 = 30;
+
 // 下载图片到本地（带重试机制和webp转换）
 async fn download_image_to_local_with_config(
     image_url: &str,
@@ -839,27 +2038,23 @@ async fn download_image_to_local_with_config(
     };
 
     let convert_to_webp = collection.collect_convert_webp == 1;
+    let client = collect_client::client_for(collection)?;
 
     // 生成文件名
-    let file_extension = if convert_to_webp {
-        "webp"
-    } else {
-        image_url.split('.').last().unwrap_or("jpg")
-    };
-    let file_name = format!("{}.{}", uuid::Uuid::new_v4(), file_extension);
+    let file_name = format!("{}.{}", uuid::Uuid::new_v4(), image_file_extension(image_url, convert_to_webp));
     let local_path = format!("static/images/{}", file_name);
 
     // 重试下载
     let mut last_error = None;
     for attempt in 1..=max_retries {
-        match download_and_process_image(image_url, &local_path, convert_to_webp, attempt).await {
+        match download_and_process_image(&client, image_url, &local_path, convert_to_webp, DEFAULT_WEBP_QUALITY).await {
             Ok(_) => {
-                println!("图片下载成功: {} (尝试次数: {})", image_url, attempt);
+                tracing::debug!("图片下载成功: {} (尝试次数: {})", image_url, attempt);
                 return Ok(format!("/static/images/{}", file_name));
             }
             Err(e) => {
                 let error_msg = format!("下载失败 (尝试 {}/{}): {}", attempt, max_retries, e);
-                println!("{}", error_msg);
+                tracing::warn!("{}", error_msg);
                 last_error = Some(e);
 
                 // 如果不是最后一次尝试，等待一段时间再重试
@@ -875,15 +2070,26 @@ async fn download_image_to_local_with_config(
     Err(last_error.unwrap_or_else(|| "未知下载错误".into()))
 }
 
-// 下载并处理图片
+fn image_file_extension(image_url: &str, convert_to_webp: bool) -> &str {
+    if convert_to_webp {
+        "webp"
+    } else {
+        image_url.split('.').last().unwrap_or("jpg")
+    }
+}
+
+// 下载并处理图片，返回下载的原始字节数
 async fn download_and_process_image(
+    client: &reqwest::Client,
     image_url: &str,
     local_path: &str,
     convert_to_webp: bool,
-    attempt: usize,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    quality: f32,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
     // 下载图片
-    let response = reqwest::get(image_url)
+    let response = client
+        .get(image_url)
+        .send()
         .await
         .map_err(|e| format!("网络请求失败: {}", e))?;
 
@@ -898,7 +2104,7 @@ async fn download_and_process_image(
 
     if convert_to_webp {
         // 转换为webp格式
-        convert_to_webp_format(&image_data, local_path).await?;
+        convert_to_webp_format(&image_data, local_path, quality).await?;
     } else {
         // 直接保存原格式
         tokio::fs::write(local_path, &image_data)
@@ -906,13 +2112,14 @@ async fn download_and_process_image(
             .map_err(|e| format!("保存文件失败: {}", e))?;
     }
 
-    Ok(())
+    Ok(image_data.len())
 }
 
 // 转换图片为webp格式
 async fn convert_to_webp_format(
     image_data: &[u8],
     output_path: &str,
+    quality: f32,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     use image::io::Reader as ImageReader;
     use std::io::Cursor;
@@ -937,7 +2144,7 @@ async fn convert_to_webp_format(
         // 使用webp编码器编码
         let webp_data =
             webp::Encoder::from_rgb(rgb_image.as_raw(), rgb_image.width(), rgb_image.height())
-                .encode(75.0); // 质量75
+                .encode(quality);
 
         // 保存webp文件 (需要解引用WebPMemory)
         std::fs::write(output_path_owned, &*webp_data)
@@ -949,48 +2156,121 @@ async fn convert_to_webp_format(
     .map_err(|e| format!("图片转换任务失败: {}", e))?
 }
 
-// 保持原有函数用于向后兼容
-async fn download_image_to_local(
+#[derive(Debug, Serialize)]
+pub struct TestImageResult {
+    pub local_path: String,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub original_size: usize,
+    pub file_size: u64,
+    pub elapsed_ms: u128,
+}
+
+// 用采集时相同的下载/转换流程处理一张图片，返回结果信息后删除测试文件。
+// 文件写入 static/images/tmp/ 并以 test_ 开头，即使清理失败也不会和正式图片混在一起。
+pub async fn test_image_pipeline(
     image_url: &str,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    // 创建一个默认的collection配置用于兼容性
-    let default_collection = Collection {
-        id: None,
-        collect_name: "default".to_string(),
-        collect_url: "".to_string(),
-        collect_type: 1,
-        collect_mid: 1,
-        collect_appid: "".to_string(),
-        collect_appkey: "".to_string(),
-        collect_param: "".to_string(),
-        collect_filter: "".to_string(),
-        collect_filter_from: "".to_string(),
-        collect_opt: 0,
-        collect_sync_pic_opt: 1,
-        collect_remove_ad: 1,
-        collect_convert_webp: 0,   // 默认不转换webp
-        collect_download_retry: 3, // 默认重试3次
-        collect_status: 1,
-        created_at: mongodb::bson::DateTime::now(),
-        updated_at: mongodb::bson::DateTime::now(),
+    convert_to_webp: bool,
+    quality: f32,
+) -> Result<TestImageResult, String> {
+    tokio::fs::create_dir_all("static/images/tmp")
+        .await
+        .map_err(|e| format!("创建临时目录失败: {}", e))?;
+
+    let file_name = format!("test_{}.{}", uuid::Uuid::new_v4(), image_file_extension(image_url, convert_to_webp));
+    let local_path = format!("static/images/tmp/{}", file_name);
+    let started = std::time::Instant::now();
+
+    let processed = tokio::time::timeout(
+        std::time::Duration::from_secs(TEST_IMAGE_TIMEOUT_SECS),
+        download_and_process_image(
+            &collect_client::default_client(),
+            image_url,
+            &local_path,
+            convert_to_webp,
+            quality,
+        ),
+    )
+    .await;
+    let elapsed_ms = started.elapsed().as_millis();
+
+    let result = match processed {
+        Err(_) => Err(format!("下载超时 ({}秒)", TEST_IMAGE_TIMEOUT_SECS)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Ok(Ok(original_size)) => inspect_image_file(&local_path).await.map(|(format, width, height, file_size)| {
+            TestImageResult {
+                local_path: format!("/{}", local_path),
+                format,
+                width,
+                height,
+                original_size,
+                file_size,
+                elapsed_ms,
+            }
+        }),
     };
 
-    download_image_to_local_with_config(image_url, &default_collection).await
+    // 文件可能在转换中途失败时未生成，忽略 NotFound
+    if let Err(e) = tokio::fs::remove_file(&local_path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("删除测试图片失败 {}: {}", local_path, e);
+        }
+    }
+
+    result
+}
+
+// 读取已保存图片的格式、尺寸和文件大小
+async fn inspect_image_file(path: &str) -> Result<(String, u32, u32, u64), String> {
+    let file_size = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("读取文件信息失败: {}", e))?
+        .len();
+
+    let path_owned = path.to_string();
+    let (format, width, height) = tokio::task::spawn_blocking(move || {
+        let reader = image::io::Reader::open(&path_owned)
+            .and_then(|r| r.with_guessed_format())
+            .map_err(|e| format!("读取图片失败: {}", e))?;
+        let format = reader
+            .format()
+            .map(|f| format!("{:?}", f).to_lowercase())
+            .ok_or("无法识别图片格式")?;
+        let (width, height) = reader
+            .into_dimensions()
+            .map_err(|e| format!("无法读取图片尺寸: {}", e))?;
+        Ok::<_, String>((format, width, height))
+    })
+    .await
+    .map_err(|e| format!("图片检查任务失败: {}", e))??;
+
+    Ok((format, width, height, file_size))
 }
 
-// 采集单个视频详情（保留原有函数用于兼容性）
+// 采集单个视频详情（保留原有函数用于兼容性），片名命中 collect_filter 时返回 false
 pub async fn collect_video_detail(
     db: web::Data<Database>,
     api_url: &str,
     vod_id: &str,
     source_flag: &str,
 ) -> Result<bool, Box<dyn std::error::Error>> {
+    // source_flag 即采集源名称；请求使用该采集源的代理和请求头，与批量采集相同
+    let collection = db
+        .collection::<Collection>("collections")
+        .find_one(doc! { "collect_name": source_flag }, None)
+        .await?
+        .ok_or_else(|| format!("采集源不存在: {}", source_flag))?;
+    let client = collect_client::client_for(&collection)?;
+
     // 构建详情API URL
     let detail_url = format!("{}?ac=detail&h=24&ids={}", api_url, vod_id);
 
     // 获取视频详情
-    let response = reqwest::get(&detail_url).await?;
-    let api_response: JsonResponse<VodApiListEntry> = response.json().await?;
+    let body = fetch_with_timeout(&client, &detail_url, collection.timeout_secs())
+        .await
+        .map_err(|e| e.to_string())?;
+    let api_response: JsonResponse<VodApiListEntry> = serde_json::from_str(&body)?;
 
     if api_response.code != 1 || api_response.list.is_empty() {
         return Err("获取视频详情失败".into());
@@ -998,6 +2278,18 @@ pub async fn collect_video_detail(
 
     let vod_data = &api_response.list[0];
 
+    // 与批量采集一样按 collect_filter 过滤片名，命中时不入库，返回 false
+    let name_filters = parse_collect_filter(&collection.collect_filter).unwrap_or_else(|e| {
+        tracing::warn!("{}: {}，本次采集不按片名过滤", collection.collect_name, e);
+        Vec::new()
+    });
+    if name_filters.iter().any(|re| re.is_match(&vod_data.vod_name)) {
+        tracing::debug!("片名命中 collect_filter，跳过视频 {}", vod_data.vod_name);
+        return Ok(false);
+    }
+
+    let options = CollectOptions::load(&db).await;
+
     // 查找分类绑定
     let bindings_collection = db.collection::<Binding>("bindings");
     let binding = bindings_collection
@@ -1013,7 +2305,7 @@ pub async fn collect_video_detail(
     let local_type_id = match binding {
         Some(b) => b.local_type_id,
         None => {
-            eprintln!(
+            tracing::warn!(
                 "未找到分类绑定: source_flag={}, external_id={}",
                 source_flag, vod_data.type_id
             );
@@ -1021,13 +2313,11 @@ pub async fn collect_video_detail(
         }
     };
 
-    // 检查视频是否已存在
+    // 检查视频是否已存在（匹配字段由 collect_dedup_key 决定，与批量采集一致）
     let vods_collection = db.collection::<Vod>("vods");
     let existing_vod = vods_collection
         .find_one(
-            doc! {
-                "vod_name": &vod_data.vod_name
-            },
+            dedup_filter(options.dedup_key, &collection, vod_data),
             None,
         )
         .await?;
@@ -1075,16 +2365,27 @@ pub async fn collect_video_detail(
         }
         // 解析播放地址
         if !vod_data.vod_play_from.is_empty() {
-            existing.vod_play_urls =
-                parse_play_urls(&vod_data.vod_play_from, &vod_data.vod_play_url);
+            existing.vod_play_urls = cap_episodes_per_source(
+                parse_play_urls(
+                    &vod_data.vod_play_from,
+                    &vod_data.vod_play_url,
+                    options.normalize_episode_names,
+                ),
+                options.max_episodes_per_source,
+                &vod_data.vod_name,
+            );
         }
+        existing.normalize();
+        // 详情采集会整体替换播放源，这里的播放源都来自上游，超过上限时跳过写入
+        let incoming_sources: Vec<String> = existing.vod_play_urls.iter().map(|s| s.source_name.clone()).collect();
+        enforce_doc_size_limit(&mut existing, options.max_doc_bytes, &incoming_sources)?;
 
         vods_collection
             .replace_one(doc! { "_id": existing.id }, &existing, None)
             .await?;
     } else {
         // 创建新视频 - 只使用VodApiListEntry中实际存在的字段
-        let new_vod = Vod {
+        let mut new_vod = Vod {
             id: None,
             vod_name: vod_data.vod_name.clone(),
             type_id: local_type_id,
@@ -1095,20 +2396,298 @@ pub async fn collect_video_detail(
             vod_director: vod_data.vod_director.clone(),
             vod_remarks: Some(vod_data.vod_remarks.clone()),
             vod_pubdate: current_time.clone(),
+            vod_created_at: Some(current_time),
             vod_area: vod_data.vod_area.clone(),
             vod_lang: vod_data.vod_lang.clone(),
             vod_year: vod_data.vod_year.clone(),
+            vod_year_num: None,
             vod_content: vod_data.vod_content.clone(),
             vod_hits: Some(0),
             vod_hits_day: Some(0),
             vod_hits_week: Some(0),
             vod_hits_month: Some(0),
             vod_score: Some("0.0".to_string()),
-            vod_play_urls: parse_play_urls(&vod_data.vod_play_from, &vod_data.vod_play_url),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
+            vod_play_urls: cap_episodes_per_source(
+                parse_play_urls(
+                    &vod_data.vod_play_from,
+                    &vod_data.vod_play_url,
+                    options.normalize_episode_names,
+                ),
+                options.max_episodes_per_source,
+                &vod_data.vod_name,
+            ),
         };
+        new_vod.normalize();
+        let incoming_sources: Vec<String> = new_vod.vod_play_urls.iter().map(|s| s.source_name.clone()).collect();
+        enforce_doc_size_limit(&mut new_vod, options.max_doc_bytes, &incoming_sources)?;
 
         vods_collection.insert_one(&new_vod, None).await?;
     }
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_without_playable_episodes_is_skipped() {
+        // 只有名称没有地址的占位条目
+        let placeholder = parse_play_urls("m3u8", &Some("第1集$#第2集$".to_string()), false);
+        assert_eq!(count_episodes(&placeholder), 0);
+        assert!(below_min_episodes(&placeholder, 1));
+
+        // 上游没有 vod_play_url
+        let empty = parse_play_urls("m3u8", &None, false);
+        assert!(empty.is_empty());
+        assert!(below_min_episodes(&empty, 1));
+
+        // 默认 0 时保持原有行为，照常入库
+        assert!(!below_min_episodes(&placeholder, 0));
+        assert!(!below_min_episodes(&empty, 0));
+    }
+
+    #[test]
+    fn record_with_enough_episodes_is_kept() {
+        let sources = parse_play_urls(
+            "m3u8",
+            &Some("第1集$https://a.com/1.m3u8#第2集$https://a.com/2.m3u8".to_string()),
+            false,
+        );
+        assert_eq!(count_episodes(&sources), 2);
+        assert!(!below_min_episodes(&sources, 2));
+        assert!(below_min_episodes(&sources, 3));
+    }
+
+    #[test]
+    fn filter_from_drops_matching_sources() {
+        let sources = parse_play_urls(
+            "lzm3u8,ffm3u8,bjm3u8",
+            &Some("第1集$https://a.com/1.m3u8#第2集$https://a.com/2.m3u8".to_string()),
+            false,
+        );
+        assert_eq!(sources.len(), 3);
+
+        // 名称不区分大小写，忽略两侧空白
+        let kept = filter_play_sources(sources.clone(), " FFm3u8 ");
+        let names: Vec<&str> = kept.iter().map(|s| s.source_name.as_str()).collect();
+        assert_eq!(names, vec!["lzm3u8", "bjm3u8"]);
+        assert!(kept.iter().all(|s| s.urls.len() == 2));
+
+        let kept = filter_play_sources(sources.clone(), "ffm3u8,bjm3u8");
+        let names: Vec<&str> = kept.iter().map(|s| s.source_name.as_str()).collect();
+        assert_eq!(names, vec!["lzm3u8"]);
+    }
+
+    #[test]
+    fn empty_filter_from_keeps_all_sources() {
+        let sources = parse_play_urls("lzm3u8,ffm3u8", &Some("HD$https://a.com/1.m3u8".to_string()), false);
+        assert_eq!(filter_play_sources(sources.clone(), "").len(), 2);
+        assert_eq!(filter_play_sources(sources, " , ").len(), 2);
+    }
+
+    #[test]
+    fn normalize_episode_name_formats() {
+        assert_eq!(normalize_episode_name("第01集").as_deref(), Some("第1集"));
+        assert_eq!(normalize_episode_name("HD1080P第1集").as_deref(), Some("第1集"));
+        assert_eq!(normalize_episode_name("第1.5集").as_deref(), Some("第1.5集"));
+        assert_eq!(normalize_episode_name("第3话").as_deref(), Some("第3集"));
+        assert_eq!(normalize_episode_name("EP01").as_deref(), Some("第1集"));
+        assert_eq!(normalize_episode_name("e12").as_deref(), Some("第12集"));
+        assert_eq!(normalize_episode_name("01").as_deref(), Some("第1集"));
+        assert_eq!(normalize_episode_name(" 1.5 ").as_deref(), Some("第1.5集"));
+
+        // 无法识别集数的标签保持原样
+        assert_eq!(normalize_episode_name("HD中字"), None);
+        assert_eq!(normalize_episode_name("正片"), None);
+        assert_eq!(normalize_episode_name("20240105"), None);
+    }
+
+    #[test]
+    fn episode_names_are_untouched_when_normalization_is_off() {
+        let play_url = Some("01$https://a.com/1.m3u8#HD中字$https://a.com/2.m3u8".to_string());
+
+        let sources = parse_play_urls("m3u8", &play_url, false);
+        let names: Vec<&str> = sources[0].urls.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["01", "HD中字"]);
+        assert!(sources[0].urls.iter().all(|u| u.original_name.is_none()));
+
+        // 开启后保留原始名称，无法识别的标签不变
+        let sources = parse_play_urls("m3u8", &play_url, true);
+        assert_eq!(sources[0].urls[0].name, "第1集");
+        assert_eq!(sources[0].urls[0].original_name.as_deref(), Some("01"));
+        assert_eq!(sources[0].urls[1].name, "HD中字");
+        assert!(sources[0].urls[1].original_name.is_none());
+    }
+
+    #[test]
+    fn detect_resolution_from_names_and_urls() {
+        assert_eq!(detect_resolution("HD1080P第1集").as_deref(), Some("1080P"));
+        assert_eq!(detect_resolution("720p").as_deref(), Some("720P"));
+        assert_eq!(detect_resolution("2160P").as_deref(), Some("4K"));
+        assert_eq!(detect_resolution("4K高清").as_deref(), Some("4K"));
+        assert_eq!(
+            detect_resolution("https://cdn.example.com/1080p/index.m3u8").as_deref(),
+            Some("1080P")
+        );
+
+        // 数字/字母相连时不当作清晰度
+        assert_eq!(detect_resolution("21080P"), None);
+        assert_eq!(detect_resolution("4KB"), None);
+        assert_eq!(detect_resolution("第1集"), None);
+        assert_eq!(detect_resolution("HD中字"), None);
+    }
+
+    #[test]
+    fn detect_format_from_urls() {
+        assert_eq!(detect_format("https://a.com/v/index.m3u8").as_deref(), Some("m3u8"));
+        assert_eq!(detect_format("https://a.com/v/movie.MP4?token=abc").as_deref(), Some("mp4"));
+        assert_eq!(detect_format("https://a.com/v/seg.ts").as_deref(), Some("ts"));
+
+        // 无扩展名、未知扩展名、扩展名只出现在域名中
+        assert_eq!(detect_format("https://a.com/share/abc123"), None);
+        assert_eq!(detect_format("https://a.com/v/page.html"), None);
+        assert_eq!(detect_format("https://mp4.example.com/"), None);
+        assert_eq!(detect_format(""), None);
+    }
+
+    #[test]
+    fn parse_play_urls_fills_episode_metadata() {
+        let sources = parse_play_urls(
+            "m3u8",
+            &Some("HD1080P第1集$https://a.com/1.m3u8#第2集$https://a.com/720p/2.mp4#预告$https://a.com/play/3".to_string()),
+            false,
+        );
+        let urls = &sources[0].urls;
+        assert_eq!(urls[0].resolution.as_deref(), Some("1080P"));
+        assert_eq!(urls[0].format.as_deref(), Some("m3u8"));
+        assert_eq!(urls[1].resolution.as_deref(), Some("720P"));
+        assert_eq!(urls[1].format.as_deref(), Some("mp4"));
+        assert!(urls[2].resolution.is_none());
+        assert!(urls[2].format.is_none());
+    }
+
+    #[test]
+    fn fill_default_keeps_present_values() {
+        assert_eq!(fill_default(&Some("美国".to_string()), "大陆").as_deref(), Some("美国"));
+        assert_eq!(fill_default(&Some("英语".to_string()), "").as_deref(), Some("英语"));
+    }
+
+    #[test]
+    fn fill_default_fills_missing_or_empty_values() {
+        assert_eq!(fill_default(&None, "大陆").as_deref(), Some("大陆"));
+        assert_eq!(fill_default(&Some("".to_string()), "大陆").as_deref(), Some("大陆"));
+        assert_eq!(fill_default(&Some("  ".to_string()), " 国语 ").as_deref(), Some("国语"));
+
+        // 默认值为空时保持原样
+        assert_eq!(fill_default(&None, ""), None);
+        assert_eq!(fill_default(&Some("".to_string()), " ").as_deref(), Some(""));
+    }
+
+    fn oversized_sources(episodes: usize) -> Vec<PlaySource> {
+        let play_url = (1..=episodes)
+            .map(|i| format!("第{}集$https://a.com/{}.m3u8", i, i))
+            .collect::<Vec<_>>()
+            .join("#");
+        parse_play_urls("lzm3u8,ffm3u8", &Some(play_url), false)
+    }
+
+    #[test]
+    fn cap_truncates_oversized_sources() {
+        let capped = cap_episodes_per_source(oversized_sources(500), 100, "测试");
+        assert_eq!(capped.len(), 2);
+        for source in &capped {
+            assert_eq!(source.urls.len(), 100);
+            // 只保留前 N 集
+            assert_eq!(source.urls[0].name, "第1集");
+            assert_eq!(source.urls[99].name, "第100集");
+        }
+
+        // 未超出上限的播放源不变
+        let capped = cap_episodes_per_source(oversized_sources(5), 100, "测试");
+        assert!(capped.iter().all(|s| s.urls.len() == 5));
+    }
+
+    #[test]
+    fn cap_zero_means_unlimited() {
+        let capped = cap_episodes_per_source(oversized_sources(500), 0, "测试");
+        assert!(capped.iter().all(|s| s.urls.len() == 500));
+    }
+
+    fn bench_vod(i: usize) -> Vod {
+        let now = mongodb::bson::DateTime::now();
+        Vod {
+            id: None,
+            vod_name: format!("批量写入测试 {}", i),
+            type_id: 1,
+            vod_status: 1,
+            vod_class: Some("剧情".to_string()),
+            vod_pic: Some(format!("https://img.example.com/{}.jpg", i)),
+            vod_actor: Some("张三,李四".to_string()),
+            vod_director: Some("王五".to_string()),
+            vod_remarks: Some("更新至20集".to_string()),
+            vod_pubdate: now,
+            vod_created_at: Some(now),
+            vod_area: Some("大陆".to_string()),
+            vod_lang: Some("国语".to_string()),
+            vod_year: Some("2024".to_string()),
+            vod_year_num: Some(2024),
+            vod_content: Some("简介".repeat(100)),
+            vod_hits: Some(0),
+            vod_hits_day: Some(0),
+            vod_hits_week: Some(0),
+            vod_hits_month: Some(0),
+            vod_score: Some("0.0".to_string()),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
+            vod_play_urls: oversized_sources(20),
+        }
+    }
+
+    // 对比逐条 insert_one 与 flush_pending_inserts 使用的 insert_many(ordered=false) 的耗时。
+    // 需要可连接的 MongoDB，默认忽略，手动运行：
+    //   DATABASE_URL=mongodb://localhost:27017 BENCH_VODS=100 \
+    //     cargo test --release bench_batch_insert -- --ignored --nocapture
+    // 写入临时数据库 maccms_bench_<uuid>，结束后删除
+    #[tokio::test]
+    #[ignore]
+    async fn bench_batch_insert() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let count: usize = std::env::var("BENCH_VODS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100);
+        let client = mongodb::Client::with_uri_str(&url).await.unwrap();
+        let db = client.database(&format!("maccms_bench_{}", uuid::Uuid::new_v4().simple()));
+        let vods: Vec<Vod> = (0..count).map(bench_vod).collect();
+
+        let single = db.collection::<Vod>("vods_single");
+        let start = std::time::Instant::now();
+        for vod in &vods {
+            single.insert_one(vod, None).await.unwrap();
+        }
+        let single_elapsed = start.elapsed();
+
+        let batch = db.collection::<Vod>("vods_batch");
+        let start = std::time::Instant::now();
+        batch.insert_many(&vods, unordered_insert_options()).await.unwrap();
+        let batch_elapsed = start.elapsed();
+
+        db.drop(None).await.unwrap();
+
+        println!(
+            "{} 条新视频: 逐条 insert_one {:?}，insert_many(ordered=false) {:?}，约 {:.1} 倍",
+            count,
+            single_elapsed,
+            batch_elapsed,
+            single_elapsed.as_secs_f64() / batch_elapsed.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+}
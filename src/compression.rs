@@ -0,0 +1,328 @@
+use actix_web::body::{self, BodySize, BoxBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, VARY};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, Error};
+use std::env;
+use std::future::{ready, Ready};
+use std::io::Write;
+use std::rc::Rc;
+
+// 响应压缩配置
+// actix-web 的 Compress 中间件根据 Accept-Encoding 在 br / gzip 之间协商，
+// 它的压缩级别由 actix-http 固定（brotli quality 3，gzip fast）。
+// COMPRESSION_BROTLI_QUALITY（0-11）/ COMPRESSION_GZIP_LEVEL（1-9）设置后，固定长度的响应（页面、JSON）
+// 由 LeveledCompress 按该级别压缩，流式响应（导出等）仍由 Compress 按默认级别压缩。
+// COMPRESSION_ENABLED=false 可整体关闭压缩（例如前面的 nginx 已经负责压缩时）。
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    pub enabled: bool,
+    pub brotli_quality: Option<u32>,
+    pub gzip_level: Option<u32>,
+}
+
+impl CompressionConfig {
+    pub fn from_env() -> Self {
+        let enabled = env::var("COMPRESSION_ENABLED")
+            .map(|v| !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "off"))
+            .unwrap_or(true);
+
+        let level = |name: &str, min: u32, max: u32| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .map(|v| v.clamp(min, max))
+        };
+
+        CompressionConfig {
+            enabled,
+            brotli_quality: level("COMPRESSION_BROTLI_QUALITY", 0, 11),
+            gzip_level: level("COMPRESSION_GZIP_LEVEL", 1, 9),
+        }
+    }
+
+    // 是否需要 LeveledCompress：开启压缩且至少设置了一个级别
+    fn custom_levels(&self) -> bool {
+        self.enabled && (self.brotli_quality.is_some() || self.gzip_level.is_some())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn header_value(self) -> HeaderValue {
+        match self {
+            Encoding::Brotli => HeaderValue::from_static("br"),
+            Encoding::Gzip => HeaderValue::from_static("gzip"),
+        }
+    }
+}
+
+// 与 Compress 相同的偏好：客户端接受 br 时用 br，否则 gzip；q=0 表示不接受
+fn preferred_encoding(accept_encoding: &str) -> Option<Encoding> {
+    let accepts = |name: &str| {
+        accept_encoding.split(',').any(|item| {
+            let mut parts = item.split(';');
+            let token = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let q = parts
+                .find_map(|p| p.trim().strip_prefix("q=").and_then(|q| q.trim().parse::<f32>().ok()))
+                .unwrap_or(1.0);
+            (token == name || token == "*") && q > 0.0
+        })
+    };
+    if accepts("br") {
+        Some(Encoding::Brotli)
+    } else if accepts("gzip") {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+fn compress(data: &[u8], encoding: Encoding, level: u32) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Brotli => {
+            let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, level, 22);
+            writer.write_all(data)?;
+            Ok(writer.into_inner())
+        }
+        Encoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+    }
+}
+
+// Content types that are already compressed; re-compressing them only burns CPU.
+// image/* (except svg) and video/* are skipped by actix-web itself.
+const PRECOMPRESSED_TYPES: &[&str] = &[
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/octet-stream",
+    "application/pdf",
+    "font/woff",
+    "font/woff2",
+    "audio/",
+];
+
+// Marks already-compressed responses with `Content-Encoding: identity` so that the
+// Compress middleware passes them through. Must be registered *before* Compress
+// (i.e. wrapped inside it) so the header is present when Compress inspects the response.
+pub struct SkipPrecompressed;
+
+impl<S, B> Transform<S, ServiceRequest> for SkipPrecompressed
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SkipPrecompressedService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SkipPrecompressedService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct SkipPrecompressedService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SkipPrecompressedService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let mut res = service.call(req).await?;
+
+            let precompressed = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|ct| {
+                    let ct = ct.to_ascii_lowercase();
+                    PRECOMPRESSED_TYPES.iter().any(|t| ct.starts_with(t))
+                })
+                .unwrap_or(false);
+
+            if precompressed && !res.headers().contains_key(CONTENT_ENCODING) {
+                res.headers_mut()
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static("identity"));
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+// 按 CompressionConfig 中的级别压缩固定长度的响应，并设置 Content-Encoding，外层的 Compress 看到后直接放行。
+// 必须注册在 Compress 之内、SkipPrecompressed 之外：已压缩内容被标记为 identity 后这里同样跳过。
+// 协商出的编码没有配置级别、或响应是流式的，都原样交给 Compress 处理。
+pub struct LeveledCompress {
+    config: CompressionConfig,
+}
+
+impl LeveledCompress {
+    pub fn new(config: &CompressionConfig) -> Self {
+        LeveledCompress { config: config.clone() }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for LeveledCompress
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = LeveledCompressService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LeveledCompressService {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct LeveledCompressService<S> {
+    service: Rc<S>,
+    config: CompressionConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for LeveledCompressService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let encoding = if self.config.custom_levels() && req.method() != Method::HEAD {
+            req.headers()
+                .get(ACCEPT_ENCODING)
+                .and_then(|v| v.to_str().ok())
+                .and_then(preferred_encoding)
+        } else {
+            None
+        };
+        let level = encoding.and_then(|encoding| match encoding {
+            Encoding::Brotli => self.config.brotli_quality,
+            Encoding::Gzip => self.config.gzip_level,
+        });
+
+        Box::pin(async move {
+            let res = service.call(req).await?.map_into_boxed_body();
+            let (Some(encoding), Some(level)) = (encoding, level) else {
+                return Ok(res);
+            };
+            if !should_compress(&res) {
+                return Ok(res);
+            }
+
+            let (req, response) = res.into_parts();
+            let (mut response, body) = response.into_parts();
+            let data = match body::to_bytes(body).await {
+                Ok(data) => data,
+                Err(e) => return Err(actix_web::error::ErrorInternalServerError(e.to_string())),
+            };
+            let compressed = web::block(move || compress(&data, encoding, level).map_err(|e| (e, data)))
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            let body = match compressed {
+                Ok(compressed) => {
+                    let headers = response.headers_mut();
+                    headers.insert(CONTENT_ENCODING, encoding.header_value());
+                    headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+                    compressed.into()
+                }
+                Err((e, data)) => {
+                    tracing::warn!("响应压缩失败，按原样返回: {}", e);
+                    data
+                }
+            };
+            Ok(ServiceResponse::new(req, response.set_body(BoxBody::new(body))))
+        })
+    }
+}
+
+// 超过此大小的响应不整体读入内存，交给 Compress 流式压缩
+const MAX_BUFFERED_BODY: u64 = 8 * 1024 * 1024;
+
+// 与 Compress 的判断一致：跳过已设置编码、无内容的状态码、流式响应和图片（SVG 除外）/视频
+fn should_compress(res: &ServiceResponse<BoxBody>) -> bool {
+    if res.headers().contains_key(CONTENT_ENCODING)
+        || matches!(res.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED | StatusCode::SWITCHING_PROTOCOLS)
+        || !matches!(res.response().body().size(), BodySize::Sized(n) if n > 0 && n <= MAX_BUFFERED_BODY)
+    {
+        return false;
+    }
+    let content_type = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    !(content_type.starts_with("video/") || content_type.starts_with("image/") && !content_type.starts_with("image/svg+xml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_like_compress() {
+        assert_eq!(preferred_encoding("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(preferred_encoding("gzip, br;q=0"), Some(Encoding::Gzip));
+        assert_eq!(preferred_encoding("identity"), None);
+        assert_eq!(preferred_encoding("*"), Some(Encoding::Brotli));
+    }
+
+    #[test]
+    fn higher_levels_compress_smaller() {
+        let data = "<li class=\"vod-item\">示例视频</li>\n".repeat(500);
+        for encoding in [Encoding::Brotli, Encoding::Gzip] {
+            let fast = compress(data.as_bytes(), encoding, 1).unwrap();
+            let best = compress(data.as_bytes(), encoding, 9).unwrap();
+            assert!(fast.len() < data.len());
+            assert!(best.len() <= fast.len());
+        }
+    }
+}
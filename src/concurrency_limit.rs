@@ -0,0 +1,170 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use actix_web::{web, Error, HttpResponse};
+use crate::client_ip::client_ip;
+use crate::site_data::SiteDataManager;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+// 单个客户端IP的并发请求上限（max_concurrent_per_ip 配置），超出时返回 429。
+// 用于防止单个爬虫同时发起大量请求拖垮首页/列表/搜索等数据库查询较重的页面。
+// 只限制“同时处理中”的请求数，不限制请求频率；静态文件和 /version 健康检查不受限制。
+pub const DEFAULT_MAX_CONCURRENT_PER_IP: usize = 10;
+
+// 超过该时间没有请求且没有处理中请求的IP会被清理
+const IDLE_TTL: Duration = Duration::from_secs(300);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct IpSlot {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+    last_seen: Instant,
+}
+
+struct LimiterState {
+    slots: HashMap<String, IpSlot>,
+    last_sweep: Instant,
+}
+
+// 在 HttpServer::new 之外创建一次，所有 worker 共享同一张IP表
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new() -> Self {
+        ConcurrencyLimit {
+            state: Arc::new(Mutex::new(LimiterState {
+                slots: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl LimiterState {
+    // 获取该IP的信号量；配置的上限变化后换成新的信号量，旧信号量上的请求结束后自然释放
+    fn semaphore_for(&mut self, ip: &str, limit: usize) -> Arc<Semaphore> {
+        let now = Instant::now();
+        if now.duration_since(self.last_sweep) >= SWEEP_INTERVAL {
+            // 信号量只被表本身引用时，说明该IP没有处理中的请求
+            self.slots.retain(|_, slot| {
+                Arc::strong_count(&slot.semaphore) > 1 || now.duration_since(slot.last_seen) < IDLE_TTL
+            });
+            self.last_sweep = now;
+        }
+
+        let slot = self.slots.entry(ip.to_string()).or_insert_with(|| IpSlot {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit,
+            last_seen: now,
+        });
+        if slot.limit != limit {
+            slot.semaphore = Arc::new(Semaphore::new(limit));
+            slot.limit = limit;
+        }
+        slot.last_seen = now;
+        slot.semaphore.clone()
+    }
+}
+
+// 读取 max_concurrent_per_ip 配置，0 表示不限制
+async fn max_concurrent_per_ip(site_data: &SiteDataManager) -> usize {
+    site_data
+        .get_config("max_concurrent_per_ip")
+        .await
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_PER_IP)
+}
+
+fn is_exempt(path: &str) -> bool {
+    path.starts_with("/static/") || path == "/version" || path == "/healthz"
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ConcurrencyLimitService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConcurrencyLimitService {
+            service: Rc::new(service),
+            state: self.state.clone(),
+        }))
+    }
+}
+
+pub struct ConcurrencyLimitService<S> {
+    service: Rc<S>,
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        if is_exempt(req.path()) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+
+        let state = self.state.clone();
+        let site_data = req.app_data::<web::Data<SiteDataManager>>().cloned();
+
+        Box::pin(async move {
+            let limit = match &site_data {
+                Some(site_data) => max_concurrent_per_ip(site_data).await,
+                None => 0,
+            };
+            if limit == 0 {
+                return Ok(service.call(req).await?.map_into_left_body());
+            }
+
+            let ip = client_ip(req.request());
+            let semaphore = state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .semaphore_for(&ip, limit);
+
+            // 许可在响应返回后释放
+            let _permit = match semaphore.try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    let mut response = HttpResponse::TooManyRequests()
+                        .content_type("text/plain; charset=utf-8")
+                        .body("Too many concurrent requests");
+                    response
+                        .headers_mut()
+                        .insert(RETRY_AFTER, HeaderValue::from_static("1"));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
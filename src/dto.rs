@@ -37,6 +37,8 @@ pub struct ApiParams {
     pub pagesize: Option<u64>,
     pub h: Option<u64>,
     pub wd: Option<String>,
+    pub include_adult: Option<bool>, // Include videos from adult categories
+    pub fill_poster: Option<bool>, // Substitute default_poster when vod_pic is empty
 }
 
 // Struct for the JSON response, mirroring the PHP API's output
@@ -201,9 +203,6 @@ pub struct VodApiListEntry {
     pub vod_down_url: Option<String>,
 }
 
-// TODO: Define structs for XML serialization using quick-xml attributes
-// This will be more involved and will be handled in the handler implementation.
-
 #[derive(Debug, Deserialize)]
 pub struct ListPageParams {
     #[serde(default, deserialize_with = "deserialize_empty_string_to_none")]
@@ -221,6 +220,24 @@ pub struct VideoFilterParams {
     pub year: Option<String>,
     pub pg: Option<u64>,
     pub limit: Option<u64>,
+    pub fields: Option<String>, // Comma-separated field allowlist, e.g. "vod_name,vod_pic"
+    pub include_adult: Option<bool>,
+    pub sort: Option<String>, // "trending" ranks by hits decayed by age; default is latest published
+    pub include_children: Option<bool>, // Also match all descendant categories of type_id
+    pub fill_poster: Option<bool>, // Substitute default_poster when vod_pic is empty
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VideoDetailParams {
+    pub fields: Option<String>,
+    pub include_adult: Option<bool>,
+    pub fill_poster: Option<bool>, // Substitute default_poster when vod_pic is empty
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VodEpisodesQuery {
+    pub source: Option<usize>, // Index into vod_play_urls, defaults to 0
+    pub include_adult: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -229,6 +246,45 @@ pub struct CategoryHierarchy {
     pub sub_categories: Vec<crate::models::Type>,
 }
 
+// Nested category node for tree UI components (/api/categories/tree)
+#[derive(Debug, Serialize)]
+pub struct CategoryTreeNode {
+    pub type_id: i32,
+    pub type_name: String,
+    pub type_en: Option<String>,
+    pub type_sort: i32,
+    pub children: Vec<CategoryTreeNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BrokenReportRequest {
+    pub vod_id: String,
+    pub play_source: usize,
+    pub play_idx: usize,
+    pub reason: Option<String>,
+}
+
+// POST /api/user/progress
+#[derive(Debug, Deserialize)]
+pub struct WatchProgressRequest {
+    pub vod_id: String,
+    pub source: usize,
+    pub episode: usize,
+    pub position_seconds: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatchHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VodLookupQuery {
+    pub name: String,
+    pub year: Option<String>,
+    pub include_adult: Option<bool>,
+}
+
 // User authentication DTOs
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginRequest {
@@ -3,6 +3,8 @@ use mongodb::bson::{doc, Document};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use futures::TryStreamExt;
+use crate::collect_handlers::DedupKey;
+use crate::models::Config;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CollectionIndexInfo {
@@ -20,7 +22,7 @@ pub struct SingleIndexInfo {
     pub version: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct IndexInfo {
     pub collection: String,
     pub keys: HashMap<String, i32>,
@@ -28,8 +30,13 @@ pub struct IndexInfo {
     pub unique: Option<bool>,
     pub sparse: Option<bool>,
     pub background: Option<bool>,
+    pub text: Option<bool>, // 全文索引：keys 中的字段都以 "text" 建索引，数值被忽略
+    pub expire_after_secs: Option<u64>, // TTL 索引：文档在索引字段的时间之后这么多秒被自动删除
 }
 
+// vods 的 vod_name + vod_year 索引。是否唯一随采集去重方式 collect_dedup_key 变化，见 sync_vod_dedup_index
+const VOD_DEDUP_INDEX: &str = "vod_name_1_vod_year_1";
+
 pub struct IndexManager {
     db: Database,
 }
@@ -51,10 +58,11 @@ impl IndexManager {
                     keys.insert("vod_year".to_string(), 1);
                     keys
                 },
-                name: "vod_name_1_vod_year_1".to_string(),
+                name: VOD_DEDUP_INDEX.to_string(),
                 unique: Some(true),
                 sparse: Some(true),
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -64,9 +72,8 @@ impl IndexManager {
                     keys
                 },
                 name: "type_id_1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -76,9 +83,8 @@ impl IndexManager {
                     keys
                 },
                 name: "vod_pubdate_-1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -88,9 +94,32 @@ impl IndexManager {
                     keys
                 },
                 name: "vod_year_1".to_string(),
-                unique: None,
                 sparse: Some(true),
                 background: Some(true),
+                ..Default::default()
+            },
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_year_num".to_string(), -1);
+                    keys
+                },
+                name: "vod_year_num_-1".to_string(),
+                sparse: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_score_num".to_string(), -1);
+                    keys
+                },
+                name: "vod_score_num_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -100,9 +129,9 @@ impl IndexManager {
                     keys
                 },
                 name: "vod_area_1".to_string(),
-                unique: None,
                 sparse: Some(true),
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -113,9 +142,8 @@ impl IndexManager {
                     keys
                 },
                 name: "vod_status_1_vod_pubdate_-1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "vods".to_string(),
@@ -126,9 +154,20 @@ impl IndexManager {
                     keys
                 },
                 name: "type_id_1_vod_pubdate_-1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
+            },
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_extra_types".to_string(), 1);
+                    keys
+                },
+                name: "vod_extra_types_1".to_string(),
+                sparse: Some(true), // 只有设置了附加分类的视频才有该字段
+                background: Some(true),
+                ..Default::default()
             },
             
             // types 集合索引
@@ -141,8 +180,8 @@ impl IndexManager {
                 },
                 name: "type_id_1".to_string(),
                 unique: Some(true),
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "types".to_string(),
@@ -153,9 +192,8 @@ impl IndexManager {
                     keys
                 },
                 name: "type_pid_1_type_sort_1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             
             // bindings 集合索引
@@ -171,6 +209,7 @@ impl IndexManager {
                 unique: Some(true),
                 sparse: Some(true), // 使用稀疏索引避免空值问题
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "bindings".to_string(),
@@ -180,9 +219,8 @@ impl IndexManager {
                     keys
                 },
                 name: "local_type_id_1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             
             // collections 集合索引
@@ -195,9 +233,8 @@ impl IndexManager {
                     keys
                 },
                 name: "collect_status_1_collect_type_1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "collections".to_string(),
@@ -207,9 +244,8 @@ impl IndexManager {
                     keys
                 },
                 name: "created_at_-1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             
             // configs 集合索引
@@ -222,8 +258,8 @@ impl IndexManager {
                 },
                 name: "config_key_1".to_string(),
                 unique: Some(true),
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
             },
             IndexInfo {
                 collection: "configs".to_string(),
@@ -234,9 +270,187 @@ impl IndexManager {
                     keys
                 },
                 name: "config_group_1_config_sort_1".to_string(),
-                unique: None,
-                sparse: None,
                 background: Some(true),
+                ..Default::default()
+            },
+            // watch_history 集合索引
+            IndexInfo {
+                collection: "watch_history".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("user_id".to_string(), 1);
+                    keys.insert("vod_id".to_string(), 1);
+                    keys
+                },
+                name: "user_id_1_vod_id_1".to_string(),
+                unique: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            // scheduled_source_configs 集合索引（每个采集源最多一条间隔覆盖）
+            IndexInfo {
+                collection: "scheduled_source_configs".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("collection_id".to_string(), 1);
+                    keys
+                },
+                name: "collection_id_1".to_string(),
+                unique: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            // collect_task_state 集合索引（每个采集任务一条页面状态）
+            IndexInfo {
+                collection: "collect_task_state".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("task_id".to_string(), 1);
+                    keys
+                },
+                name: "task_id_1".to_string(),
+                unique: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            // broken_reports 集合索引
+            IndexInfo {
+                collection: "broken_reports".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_id".to_string(), 1);
+                    keys.insert("play_source".to_string(), 1);
+                    keys.insert("play_idx".to_string(), 1);
+                    keys
+                },
+                name: "vod_id_1_play_source_1_play_idx_1".to_string(),
+                unique: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            IndexInfo {
+                collection: "broken_reports".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("report_count".to_string(), -1);
+                    keys
+                },
+                name: "report_count_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // broken_report_reporters 集合索引（报告去重记录，到期自动删除）
+            IndexInfo {
+                collection: "broken_report_reporters".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("created_at".to_string(), 1);
+                    keys
+                },
+                name: "created_at_1".to_string(),
+                background: Some(true),
+                expire_after_secs: Some(crate::api_handlers::REPORT_DEDUP_TTL_SECS),
+                ..Default::default()
+            },
+            // collection_runs 集合索引
+            IndexInfo {
+                collection: "collection_runs".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("started_at".to_string(), -1);
+                    keys
+                },
+                name: "started_at_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // 最近失败任务列表按结束时间倒序（/api/admin/tasks/recent-failures）
+            IndexInfo {
+                collection: "collection_runs".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("finished_at".to_string(), -1);
+                    keys
+                },
+                name: "finished_at_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // task_execution_logs 集合索引
+            IndexInfo {
+                collection: "task_execution_logs".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("completed_at".to_string(), -1);
+                    keys
+                },
+                name: "completed_at_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // audit_log 集合索引
+            IndexInfo {
+                collection: "audit_log".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("timestamp".to_string(), -1);
+                    keys
+                },
+                name: "timestamp_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            IndexInfo {
+                collection: "audit_log".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("user_id".to_string(), 1);
+                    keys.insert("timestamp".to_string(), -1);
+                    keys
+                },
+                name: "user_id_1_timestamp_-1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // 按相同主演推荐相关视频（fetch_related_videos 的 $in 查询）
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_actor_list".to_string(), 1);
+                    keys
+                },
+                name: "vod_actor_list_1".to_string(),
+                background: Some(true),
+                ..Default::default()
+            },
+            // 回收站列表（GET /api/admin/vods/trash）按删除时间倒序；只有回收站中的视频有该字段
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_deleted_at".to_string(), -1);
+                    keys
+                },
+                name: "vod_deleted_at_-1".to_string(),
+                sparse: Some(true),
+                background: Some(true),
+                ..Default::default()
+            },
+            // 前台搜索使用的全文索引（search_page_handler 的 $text 查询）
+            IndexInfo {
+                collection: "vods".to_string(),
+                keys: {
+                    let mut keys = HashMap::new();
+                    keys.insert("vod_name".to_string(), 1);
+                    keys.insert("vod_actor".to_string(), 1);
+                    keys.insert("vod_director".to_string(), 1);
+                    keys
+                },
+                name: "vod_name_text_vod_actor_text_vod_director_text".to_string(),
+                background: Some(true),
+                text: Some(true),
+                ..Default::default()
             },
         ]
     }
@@ -268,6 +482,53 @@ impl IndexManager {
         }
     }
 
+    /// 已存在的索引是否唯一；索引不存在时返回 None
+    async fn index_unique(&self, collection_name: &str, index_name: &str) -> Result<Option<bool>, mongodb::error::Error> {
+        let collection = self.db.collection::<Document>(collection_name);
+        let mut cursor = collection.list_indexes(None).await?;
+        while let Some(index_model) = cursor.try_next().await? {
+            if let Some(options) = &index_model.options {
+                if options.name.as_deref() == Some(index_name) {
+                    return Ok(Some(options.unique.unwrap_or(false)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// 按 collect_dedup_key 同步 vod_name + vod_year 索引的唯一性。
+    /// name_year_area 允许同名同年、不同地区的视频分别入库，唯一索引会让这些写入失败（E11000），此时改为普通索引；
+    /// 其他去重方式保持唯一索引，防止并发采集重复写入。唯一性与配置不一致时删除后重建。
+    pub async fn sync_vod_dedup_index(&self) -> Result<(), mongodb::error::Error> {
+        let dedup_key = self
+            .db
+            .collection::<Config>("configs")
+            .find_one(doc! { "config_key": "collect_dedup_key" }, None)
+            .await?
+            .map(|config| DedupKey::parse(&config.config_value))
+            .unwrap_or(DedupKey::NameYear);
+        let unique = dedup_key != DedupKey::NameYearArea;
+
+        match self.index_unique("vods", VOD_DEDUP_INDEX).await? {
+            Some(existing) if existing == unique => return Ok(()),
+            Some(_) => {
+                println!("🔄 采集去重方式已变更，重建索引 {}（unique={}）", VOD_DEDUP_INDEX, unique);
+                self.db
+                    .collection::<Document>("vods")
+                    .drop_index(VOD_DEDUP_INDEX, None)
+                    .await?;
+            }
+            None => {}
+        }
+
+        let mut index_info = Self::get_index_configs()
+            .into_iter()
+            .find(|info| info.name == VOD_DEDUP_INDEX)
+            .expect("vod dedup index config");
+        index_info.unique = Some(unique);
+        self.create_index("vods", &index_info).await
+    }
+
     /// 创建单个索引（先检查是否存在）
     async fn create_index(&self, collection_name: &str, index_info: &IndexInfo) -> Result<(), mongodb::error::Error> {
         // 先检查索引是否已存在
@@ -298,11 +559,23 @@ impl IndexManager {
         if let Some(background) = index_info.background {
             options.background = Some(background);
         }
+        if let Some(secs) = index_info.expire_after_secs {
+            options.expire_after = Some(std::time::Duration::from_secs(secs));
+        }
+        let is_text = index_info.text.unwrap_or(false);
+        if is_text {
+            // 片名/演员多为中文，按英文词干处理没有意义，关闭语言相关的词干和停用词
+            options.default_language = Some("none".to_string());
+        }
         
         // 构建键文档
         let mut keys_doc = Document::new();
         for (key, value) in &index_info.keys {
-            keys_doc.insert(key, *value);
+            if is_text {
+                keys_doc.insert(key, "text");
+            } else {
+                keys_doc.insert(key, *value);
+            }
         }
         
         // 构建索引模型
@@ -338,9 +611,19 @@ impl IndexManager {
         let mut error_count = 0;
 
         for index_info in index_configs {
-            match self.create_index(&index_info.collection, &index_info).await {
+            let result = if index_info.name == VOD_DEDUP_INDEX {
+                self.sync_vod_dedup_index().await
+            } else {
+                self.create_index(&index_info.collection, &index_info).await
+            };
+            match result {
                 Ok(_) => success_count += 1,
-                Err(_) => error_count += 1,
+                Err(e) => {
+                    if index_info.name == VOD_DEDUP_INDEX {
+                        eprintln!("❌ 同步索引失败: {}: {}", VOD_DEDUP_INDEX, e);
+                    }
+                    error_count += 1
+                }
             }
         }
 
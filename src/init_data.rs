@@ -1,4 +1,7 @@
-use crate::models::{Binding, Collection, Config, PlaySource, PlayUrl, Type, Vod};
+use crate::models::{
+    Binding, Collection, Config, PlaySource, PlayUrl, Type, Vod, DEFAULT_COLLECT_CONCURRENCY,
+    DEFAULT_COLLECT_TIMEOUT_SECS,
+};
 use mongodb::bson::DateTime;
 use mongodb::{bson::doc, Database};
 
@@ -66,6 +69,426 @@ pub async fn init_website_config(db: &Database) -> Result<(), Box<dyn std::error
             config_sort: 5,
             updated_at: DateTime::now(),
         },
+        Config {
+            id: None,
+            config_key: "asset_base_url".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("静态资源CDN地址（留空使用相对路径）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 6,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "home_cache_ttl".to_string(),
+            config_value: "60".to_string(),
+            config_desc: Some("首页缓存时间（秒，0为不缓存）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 7,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "robots_txt".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("robots.txt 内容（留空使用默认规则）".to_string()),
+            config_type: "textarea".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 8,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "hot_hits_threshold".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("周点击数达到该值时显示“热”标记（0为关闭）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 9,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_min_episodes".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("采集新视频的最少可播放集数（无播放地址的占位条目不计），低于该值跳过（0为不限制）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 10,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "normalize_episode_names".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("采集时将集数名称统一为「第N集」（1开启，0关闭）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 11,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "detail_related_count".to_string(),
+            config_value: "10".to_string(),
+            config_desc: Some("详情页相关视频数量（0-50）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 12,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "player_recommend_count".to_string(),
+            config_value: "6".to_string(),
+            config_desc: Some("播放页推荐视频数量（0-50）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 13,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "related_strategy".to_string(),
+            config_value: "category".to_string(),
+            config_desc: Some("相关推荐策略（两种都优先推荐相同主演的视频）：category 再用同分类补齐，smart 先匹配类型标签再用同分类补齐".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 14,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "age_gate_text".to_string(),
+            config_value: "本分类包含仅限成年人观看的内容。继续访问即表示您确认已年满18周岁，且所在地区法律允许观看此类内容。".to_string(),
+            config_desc: Some("成人分类年龄确认页提示文字".to_string()),
+            config_type: "textarea".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 15,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "trending_gravity".to_string(),
+            config_value: "1.8".to_string(),
+            config_desc: Some("近期热门排序衰减系数：分数 = 播放量 / (入库小时数 + 2) ^ 系数，越大越偏向新视频".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 16,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "pagination_window".to_string(),
+            config_value: "3".to_string(),
+            config_desc: Some("分页页码窗口：当前页前后各显示的页码数量（列表页与后台视频列表）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 17,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "episode_api_referers".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("剧集接口返回播放地址的来源域名白名单（逗号分隔），留空则不限制".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("播放设置".to_string()),
+            config_sort: 18,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "protect_play_urls".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("播放地址防盗链：1 开启后播放页与剧集接口输出带签名的 /stream 地址，0 关闭".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("播放设置".to_string()),
+            config_sort: 19,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "play_token_ttl".to_string(),
+            config_value: "600".to_string(),
+            config_desc: Some("防盗链播放地址有效期（秒）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("播放设置".to_string()),
+            config_sort: 20,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "stats_cache_ttl".to_string(),
+            config_value: "60".to_string(),
+            config_desc: Some("后台统计数量缓存时间（秒），0 表示每次实时统计".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 21,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "new_badge_days".to_string(),
+            config_value: "3".to_string(),
+            config_desc: Some("入库多少天内的视频显示“新”角标，0 表示关闭".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 22,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "max_concurrent_per_ip".to_string(),
+            config_value: "10".to_string(),
+            config_desc: Some("单个IP同时处理中的请求数上限，超出返回429，0 表示不限制（不含静态文件）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 23,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_dedup_key".to_string(),
+            config_value: "name_year".to_string(),
+            config_desc: Some("采集去重方式：name_year（名称+年份）、name（仅名称）、name_year_area（名称+年份+地区）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 24,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_max_episodes_per_source".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("采集时每个播放源最多保存的集数，超出部分截断（0为不限制）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 25,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_max_vod_doc_kb".to_string(),
+            config_value: "15360".to_string(),
+            config_desc: Some("采集写入视频前的文档大小上限（KB，1-16384），超过时删除最早的播放源，仍超过则跳过".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 26,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "task_poll_interval_ms".to_string(),
+            config_value: "3000".to_string(),
+            config_desc: Some("后台任务页面轮询进度的间隔（毫秒，500-60000），部署规模大时可调大以降低接口压力".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 27,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "task_auto_refresh".to_string(),
+            config_value: "1".to_string(),
+            config_desc: Some("采集页面运行中任务列表是否默认自动刷新：1 开启，0 关闭（可在页面上手动切换）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 28,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "feed_excluded_type_ids".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("sitemap、RSS 中排除的分类ID（逗号分隔，包含其子分类和分类下的视频）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 29,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "feed_include_adult".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("sitemap、RSS 是否包含成人分类：0 排除（默认），1 包含".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 30,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_page_retries".to_string(),
+            config_value: "2".to_string(),
+            config_desc: Some("采集列表页请求失败后的重试次数（0-10），按 1、2、4... 秒退避，重试用完才计为该页失败".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 31,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "collect_max_consecutive_failures".to_string(),
+            config_value: "10".to_string(),
+            config_desc: Some("连续多少页采集失败后中止整个采集任务（采集源可能已失效），0 表示不中止".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("采集设置".to_string()),
+            config_sort: 32,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "home_section_size".to_string(),
+            config_value: "12".to_string(),
+            config_desc: Some("首页每个分类区块显示的视频数（1-60）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 33,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "home_sections".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("首页显示的分类及顺序（逗号分隔的分类ID，如 1,2,4），留空按导航顺序显示全部顶级分类".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 34,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "default_poster".to_string(),
+            config_value: "/static/images/no-poster.png".to_string(),
+            config_desc: Some("视频没有海报时显示的默认图片（路径或完整URL）".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 35,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "api_daily_quota".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("单个IP每天可调用公开API（/api/*，不含后台、登录和采集接口）的次数，超出返回429，0 表示不限制".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 36,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "watch_progress_interval".to_string(),
+            config_value: "15".to_string(),
+            config_desc: Some("登录用户播放时每隔多少秒保存一次播放进度（用于续播），可设 5-300，0 表示不记录".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 37,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "custom_head_html".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("原样插入每个前台页面 <head> 末尾的HTML（统计代码、自定义CSS等），仅管理员组可修改，最大64KB".to_string()),
+            config_type: "textarea".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 38,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "custom_footer_html".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("原样插入每个前台页面 </body> 之前的HTML（统计脚本、客服插件等），仅管理员组可修改，最大64KB".to_string()),
+            config_type: "textarea".to_string(),
+            config_group: Some("外观设置".to_string()),
+            config_sort: 39,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "canonical_host".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("规范域名（如 www.example.com），通过其他域名或IP访问时301跳转到该域名，留空不跳转".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 40,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "force_https".to_string(),
+            config_value: "0".to_string(),
+            config_desc: Some("1 表示把 http 请求301跳转到 https（反向代理需传递 X-Forwarded-Proto），0 表示不跳转".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 41,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "api_rate_limit".to_string(),
+            config_value: "120".to_string(),
+            config_desc: Some("单个IP每分钟可请求 /api/provide/vod、/api/videos/{type_id}、/search 的次数，超出返回429，0 表示不限制；修改后需重启生效".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 42,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "sitemap_cache_ttl".to_string(),
+            config_value: "3600".to_string(),
+            config_desc: Some("站点地图（/sitemap.xml 及子地图）缓存时间（秒），0 表示每次实时生成".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("SEO设置".to_string()),
+            config_sort: 43,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "cache_ttl_seconds".to_string(),
+            config_value: "300".to_string(),
+            config_desc: Some("分类、配置等站点数据缓存的自动刷新间隔（秒），后台修改最迟在该时间后生效；0 表示只在启动和手动刷新缓存时加载".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("性能设置".to_string()),
+            config_sort: 44,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "login_max_attempts".to_string(),
+            config_value: "5".to_string(),
+            config_desc: Some("同一用户名或IP在锁定时间内允许的登录失败次数，达到后拒绝登录（429）直到时间窗口结束，0 表示不锁定".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("安全设置".to_string()),
+            config_sort: 45,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "login_lockout_minutes".to_string(),
+            config_value: "15".to_string(),
+            config_desc: Some("登录失败计数的时间窗口（分钟，1-1440），从第一次失败开始计算".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("安全设置".to_string()),
+            config_sort: 46,
+            updated_at: DateTime::now(),
+        },
+        Config {
+            id: None,
+            config_key: "play_source_priority".to_string(),
+            config_value: "".to_string(),
+            config_desc: Some("默认播放源优先级：播放源名称按优先级逗号分隔（如 lzm3u8,ffm3u8），未指定播放源时选第一个包含该集的源，留空则使用第一个播放源".to_string()),
+            config_type: "text".to_string(),
+            config_group: Some("播放设置".to_string()),
+            config_sort: 47,
+            updated_at: DateTime::now(),
+        },
     ];
 
     let mut created_count = 0;
@@ -171,6 +594,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
                 "2025,2024,2023,2022,2021,2020,2019,2018,2017,2016,2015,2014,2013,2012,2011,2010"
                     .to_string(),
             ),
+            is_adult: false,
         },
         Type {
             id: None,
@@ -196,6 +620,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
                 "2025,2024,2023,2022,2021,2020,2019,2018,2017,2016,2015,2014,2013,2012,2011,2010"
                     .to_string(),
             ),
+            is_adult: false,
         },
         Type {
             id: None,
@@ -221,6 +646,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
                 "2025,2024,2023,2022,2021,2020,2019,2018,2017,2016,2015,2014,2013,2012,2011,2010"
                     .to_string(),
             ),
+            is_adult: false,
         },
         Type {
             id: None,
@@ -246,6 +672,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
                 "2025,2024,2023,2022,2021,2020,2019,2018,2017,2016,2015,2014,2013,2012,2011,2010"
                     .to_string(),
             ),
+            is_adult: false,
         },
         // 二级分类 - 电影子分类
         Type {
@@ -267,6 +694,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -287,6 +715,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -307,6 +736,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -327,6 +757,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -347,6 +778,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -367,6 +799,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -387,6 +820,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -407,6 +841,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -427,6 +862,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -447,6 +883,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -467,6 +904,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -487,6 +925,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         // 二级分类 - 电视剧子分类
         Type {
@@ -508,6 +947,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -528,6 +968,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -548,6 +989,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -568,6 +1010,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -588,6 +1031,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -608,6 +1052,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -628,6 +1073,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -648,6 +1094,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -668,6 +1115,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         // 二级分类 - 动漫子分类
         Type {
@@ -689,6 +1137,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -709,6 +1158,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -729,6 +1179,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         // 二级分类 - 综艺子分类
         Type {
@@ -750,6 +1201,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -770,6 +1222,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -790,6 +1243,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
         Type {
             id: None,
@@ -810,6 +1264,7 @@ pub async fn init_test_categories(db: &Database) -> Result<(), Box<dyn std::erro
             type_tpl_down: None,
             subarea: None,
             subyear: None,
+            is_adult: false,
         },
     ];
 
@@ -924,9 +1379,11 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
             vod_director: Some("安东尼·罗素,乔·罗素".to_string()),
             vod_remarks: Some("超清".to_string()),
             vod_pubdate: DateTime::now(),
+            vod_created_at: Some(DateTime::now()),
             vod_area: Some("美国".to_string()),
             vod_lang: Some("英语".to_string()),
             vod_year: Some("2019".to_string()),
+            vod_year_num: None,
             vod_content: Some(
                 "漫威电影宇宙的史诗级终章，超级英雄们为了拯救宇宙而展开最后的战斗。".to_string(),
             ),
@@ -935,11 +1392,18 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
             vod_hits_week: Some(0),
             vod_hits_month: Some(0),
             vod_score: Some("9.2".to_string()),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
             vod_play_urls: vec![PlaySource {
                 source_name: "高清播放".to_string(),
                 urls: vec![PlayUrl {
                     name: "第01集".to_string(),
                     url: "https://example.com/video/avengers4.m3u8".to_string(),
+                    original_name: None,
+                    resolution: None,
+                    format: Some("m3u8".to_string()),
                 }],
             }],
         },
@@ -954,9 +1418,11 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
             vod_director: Some("郭帆".to_string()),
             vod_remarks: Some("超清".to_string()),
             vod_pubdate: DateTime::now(),
+            vod_created_at: Some(DateTime::now()),
             vod_area: Some("中国".to_string()),
             vod_lang: Some("中文".to_string()),
             vod_year: Some("2019".to_string()),
+            vod_year_num: None,
             vod_content: Some(
                 "太阳即将毁灭，人类在地球表面建造出巨大的推进器，寻找新的家园。".to_string(),
             ),
@@ -965,11 +1431,18 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
             vod_hits_week: Some(0),
             vod_hits_month: Some(0),
             vod_score: Some("8.8".to_string()),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
             vod_play_urls: vec![PlaySource {
                 source_name: "高清播放".to_string(),
                 urls: vec![PlayUrl {
                     name: "第01集".to_string(),
                     url: "https://example.com/video/wandering_earth.m3u8".to_string(),
+                    original_name: None,
+                    resolution: None,
+                    format: Some("m3u8".to_string()),
                 }],
             }],
         },
@@ -984,20 +1457,29 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
             vod_director: Some("贾玲".to_string()),
             vod_remarks: Some("超清".to_string()),
             vod_pubdate: DateTime::now(),
+            vod_created_at: Some(DateTime::now()),
             vod_area: Some("中国".to_string()),
             vod_lang: Some("中文".to_string()),
             vod_year: Some("2021".to_string()),
+            vod_year_num: None,
             vod_content: Some("女儿穿越回到过去，想要让母亲过上更好的生活。".to_string()),
             vod_hits: Some(0),
             vod_hits_day: Some(0),
             vod_hits_week: Some(0),
             vod_hits_month: Some(0),
             vod_score: Some("8.5".to_string()),
+            vod_score_num: 0.0,
+            vod_deleted_at: None,
+            vod_actor_list: vec![],
+            vod_extra_types: vec![],
             vod_play_urls: vec![PlaySource {
                 source_name: "高清播放".to_string(),
                 urls: vec![PlayUrl {
                     name: "第01集".to_string(),
                     url: "https://example.com/video/hello_mom.m3u8".to_string(),
+                    original_name: None,
+                    resolution: None,
+                    format: Some("m3u8".to_string()),
                 }],
             }],
         },
@@ -1005,7 +1487,8 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
 
     let mut created_count = 0;
 
-    for video in videos {
+    for mut video in videos {
+        video.normalize();
         // 先检查视频是否已存在
         match vod_exists(db, &video.vod_name).await {
             Ok(true) => {
@@ -1025,9 +1508,14 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
                         "vod_director": &video.vod_director,
                         "vod_remarks": &video.vod_remarks,
                         "vod_pubdate": video.vod_pubdate,
+                        "vod_created_at": video.vod_created_at,
                         "vod_area": &video.vod_area,
                         "vod_lang": &video.vod_lang,
                         "vod_year": &video.vod_year,
+                        "vod_year_num": video.vod_year_num,
+                        "vod_score": &video.vod_score,
+                        "vod_score_num": video.vod_score_num,
+                        "vod_actor_list": &video.vod_actor_list,
                         "vod_content": &video.vod_content,
                         "vod_play_urls": mongodb::bson::to_bson(&video.vod_play_urls).unwrap(),
                     }
@@ -1055,9 +1543,14 @@ pub async fn init_test_videos(db: &Database) -> Result<(), Box<dyn std::error::E
                         "vod_director": &video.vod_director,
                         "vod_remarks": &video.vod_remarks,
                         "vod_pubdate": video.vod_pubdate,
+                        "vod_created_at": video.vod_created_at,
                         "vod_area": &video.vod_area,
                         "vod_lang": &video.vod_lang,
                         "vod_year": &video.vod_year,
+                        "vod_year_num": video.vod_year_num,
+                        "vod_score": &video.vod_score,
+                        "vod_score_num": video.vod_score_num,
+                        "vod_actor_list": &video.vod_actor_list,
                         "vod_content": &video.vod_content,
                         "vod_play_urls": mongodb::bson::to_bson(&video.vod_play_urls).unwrap(),
                     }
@@ -1109,11 +1602,20 @@ pub async fn init_collection_sources(db: &Database) -> Result<(), Box<dyn std::e
             collect_param: "ac=videolist".to_string(),
             collect_filter: "".to_string(),
             collect_filter_from: "".to_string(),
+            default_area: "".to_string(),
+            default_lang: "".to_string(),
+            field_mapping: std::collections::HashMap::new(),
             collect_opt: 0,
             collect_sync_pic_opt: 1,
             collect_remove_ad: 1,
             collect_convert_webp: 1,   // 启用webp转换
             collect_download_retry: 3, // 重试3次
+            collect_timeout_secs: DEFAULT_COLLECT_TIMEOUT_SECS,
+            collect_concurrency: DEFAULT_COLLECT_CONCURRENCY,
+            collect_window: "".to_string(),
+            collect_proxy: "".to_string(),
+            collect_user_agent: "".to_string(),
+            collect_referer: "".to_string(),
             collect_status: 1,
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
@@ -1129,11 +1631,20 @@ pub async fn init_collection_sources(db: &Database) -> Result<(), Box<dyn std::e
             collect_param: "ac=videolist".to_string(),
             collect_filter: "".to_string(),
             collect_filter_from: "".to_string(),
+            default_area: "".to_string(),
+            default_lang: "".to_string(),
+            field_mapping: std::collections::HashMap::new(),
             collect_opt: 0,
             collect_sync_pic_opt: 1,
             collect_remove_ad: 1,
             collect_convert_webp: 1,   // 启用webp转换
             collect_download_retry: 3, // 重试3次
+            collect_timeout_secs: DEFAULT_COLLECT_TIMEOUT_SECS,
+            collect_concurrency: DEFAULT_COLLECT_CONCURRENCY,
+            collect_window: "".to_string(),
+            collect_proxy: "".to_string(),
+            collect_user_agent: "".to_string(),
+            collect_referer: "".to_string(),
             collect_status: 1,
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
@@ -1162,11 +1673,16 @@ pub async fn init_collection_sources(db: &Database) -> Result<(), Box<dyn std::e
                         "collect_param": &collect.collect_param,
                         "collect_filter": &collect.collect_filter,
                         "collect_filter_from": &collect.collect_filter_from,
+                        "default_area": &collect.default_area,
+                        "default_lang": &collect.default_lang,
+                        "field_mapping": {},
                         "collect_opt": collect.collect_opt,
                         "collect_sync_pic_opt": collect.collect_sync_pic_opt,
                         "collect_remove_ad": collect.collect_remove_ad,
                         "collect_convert_webp": collect.collect_convert_webp,
                         "collect_download_retry": collect.collect_download_retry,
+                        "collect_timeout_secs": collect.collect_timeout_secs,
+                        "collect_concurrency": collect.collect_concurrency,
                         "collect_status": collect.collect_status,
                         "created_at": collect.created_at,
                         "updated_at": collect.updated_at,
@@ -1195,11 +1711,16 @@ pub async fn init_collection_sources(db: &Database) -> Result<(), Box<dyn std::e
                         "collect_param": &collect.collect_param,
                         "collect_filter": &collect.collect_filter,
                         "collect_filter_from": &collect.collect_filter_from,
+                        "default_area": &collect.default_area,
+                        "default_lang": &collect.default_lang,
+                        "field_mapping": {},
                         "collect_opt": collect.collect_opt,
                         "collect_sync_pic_opt": collect.collect_sync_pic_opt,
                         "collect_remove_ad": collect.collect_remove_ad,
                         "collect_convert_webp": collect.collect_convert_webp,
                         "collect_download_retry": collect.collect_download_retry,
+                        "collect_timeout_secs": collect.collect_timeout_secs,
+                        "collect_concurrency": collect.collect_concurrency,
                         "collect_status": collect.collect_status,
                         "created_at": collect.created_at,
                         "updated_at": collect.updated_at,
@@ -1345,6 +1866,33 @@ pub async fn init_bindings(db: &Database) -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
+// 首次安装（POST /api/admin/setup）：只初始化网站配置和基础分类，不写入演示视频、采集源和绑定
+pub async fn init_essential_data(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    init_website_config(db).await?;
+    init_test_categories(db).await?;
+    Ok(())
+}
+
+// 站点是否已初始化（已有配置或分类）。未初始化时由 POST /api/admin/setup 创建配置、分类和管理员账户；
+// 查询失败时按已初始化处理
+pub async fn is_site_initialized(db: &Database) -> bool {
+    for name in ["configs", "types"] {
+        match db
+            .collection::<mongodb::bson::Document>(name)
+            .count_documents(None, None)
+            .await
+        {
+            Ok(0) => {}
+            Ok(_) => return true,
+            Err(e) => {
+                eprintln!("Failed to count {}: {}", name, e);
+                return true;
+            }
+        }
+    }
+    false
+}
+
 // 检查数据库是否为空（没有任何数据）
 async fn is_database_empty(db: &Database) -> Result<bool, Box<dyn std::error::Error>> {
     // 检查主要集合是否都为空
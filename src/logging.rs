@@ -0,0 +1,97 @@
+use crate::access_log::{AccessLogConfig, AccessLogFormat};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+// 默认日志过滤规则，未设置 RUST_LOG 时使用：
+// 应用自身（含访问日志和采集流程）为 info，框架和依赖库只输出警告，避免每个请求都打印连接/查询细节
+pub const DEFAULT_LOG_FILTER: &str =
+    "info,actix_server=warn,actix_web=warn,mongodb=warn,reqwest=warn,hyper=warn";
+
+// 采集流程的日志 target，运行时调整日志级别的目标
+pub const COLLECT_TARGET: &str = "maccms_rust::collect_handlers";
+
+type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+static FILTER_HANDLE: OnceLock<FilterHandle> = OnceLock::new();
+// 启动时的过滤规则，临时调整到期后恢复为该规则
+static BASE_FILTER: OnceLock<String> = OnceLock::new();
+// 当前生效的临时调整（target, level, 到期时间），用于查询
+static OVERRIDE: Mutex<Option<(String, String, chrono::DateTime<chrono::Utc>)>> = Mutex::new(None);
+// 每次调整递增，旧的到期任务发现编号变化后不再恢复，避免覆盖后来的调整
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Install the global tracing subscriber matching the access log format.
+// The filter sits behind a reload layer so its level can be changed at runtime.
+pub fn init_tracing(config: &AccessLogConfig) {
+    let directives = std::env::var("RUST_LOG")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_LOG_FILTER.to_string());
+    let filter = EnvFilter::try_new(&directives).unwrap_or_else(|e| {
+        eprintln!("Invalid RUST_LOG ({}), using default filter: {}", e, DEFAULT_LOG_FILTER);
+        EnvFilter::new(DEFAULT_LOG_FILTER)
+    });
+
+    let (filter_layer, handle) = reload::Layer::new(filter);
+    let registry = tracing_subscriber::registry().with(filter_layer);
+    let result = match config.format {
+        AccessLogFormat::Json => registry.with(fmt::layer().json()).try_init(),
+        AccessLogFormat::Combined => registry.with(fmt::layer()).try_init(),
+    };
+
+    match result {
+        Ok(_) => {
+            let _ = FILTER_HANDLE.set(handle);
+            let _ = BASE_FILTER.set(directives);
+        }
+        Err(e) => eprintln!("Failed to initialize tracing subscriber: {}", e),
+    }
+}
+
+pub fn base_filter() -> String {
+    BASE_FILTER.get().cloned().unwrap_or_else(|| DEFAULT_LOG_FILTER.to_string())
+}
+
+// 当前的临时调整：(target, level, 到期时间)
+pub fn current_override() -> Option<(String, String, chrono::DateTime<chrono::Utc>)> {
+    OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn apply(directives: &str) -> Result<(), String> {
+    let handle = FILTER_HANDLE.get().ok_or("日志系统未初始化")?;
+    let filter = EnvFilter::try_new(directives).map_err(|e| format!("无效的过滤规则: {}", e))?;
+    handle.reload(filter).map_err(|e| format!("更新日志级别失败: {}", e))
+}
+
+// 临时把 target 的日志级别调整为 level，duration_secs 秒后自动恢复启动时的规则
+pub fn set_temporary_level(target: &str, level: &str, duration_secs: u64) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let directives = format!("{},{}={}", base_filter(), target, level);
+    apply(&directives)?;
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(duration_secs as i64);
+    *OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some((target.to_string(), level.to_string(), expires_at));
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(duration_secs)).await;
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            if let Err(e) = reset() {
+                eprintln!("Failed to restore log level: {}", e);
+            }
+        }
+    });
+
+    Ok(expires_at)
+}
+
+// 立即恢复启动时的过滤规则
+pub fn reset() -> Result<(), String> {
+    apply(&base_filter())?;
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    *OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    Ok(())
+}
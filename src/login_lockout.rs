@@ -0,0 +1,129 @@
+use crate::site_data::SiteDataManager;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+// 登录失败锁定：后台表单登录（/admin/login）和 /api/auth/login 共用。按用户名和客户端IP分别计数，
+// 任一计数在 login_lockout_minutes 分钟内达到 login_max_attempts 次尝试后，该用户名或IP在窗口结束前的登录请求
+// 直接返回 429，不再查询用户、也不执行 bcrypt 校验。每次尝试在校验密码前计数（见 reserve_attempt），
+// 不存在的用户名同样计数，避免通过锁定与否判断用户是否存在。
+// 登录成功清除该用户名和IP的计数。计数保存在内存中，重启后清零。
+pub const DEFAULT_LOGIN_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_LOGIN_LOCKOUT_MINUTES: u64 = 15;
+
+pub const LOCKOUT_MESSAGE: &str = "Too many attempts, try again later";
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct AttemptState {
+    // "user:{用户名}" / "ip:{IP}" -> (窗口内未成功的尝试次数, 第一次尝试时间)
+    failures: HashMap<String, (u32, Instant)>,
+    last_sweep: Instant,
+}
+
+fn attempt_state() -> &'static RwLock<AttemptState> {
+    static STATE: OnceLock<RwLock<AttemptState>> = OnceLock::new();
+    STATE.get_or_init(|| {
+        RwLock::new(AttemptState {
+            failures: HashMap::new(),
+            last_sweep: Instant::now(),
+        })
+    })
+}
+
+fn attempt_keys(username: &str, ip: &str) -> [String; 2] {
+    [format!("user:{}", username.trim()), format!("ip:{}", ip)]
+}
+
+// 校验 login_max_attempts 配置：非负整数，0 表示不锁定
+pub fn parse_login_max_attempts(value: &str) -> Result<u32, String> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| "login_max_attempts 必须是非负整数，0 表示不锁定".to_string())
+}
+
+// 校验 login_lockout_minutes 配置：1-1440 分钟
+pub fn parse_login_lockout_minutes(value: &str) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(minutes) if (1..=1440).contains(&minutes) => Ok(minutes),
+        _ => Err("login_lockout_minutes 必须是 1-1440 之间的整数（分钟）".to_string()),
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LockoutSettings {
+    max_attempts: u32,
+    window: Duration,
+}
+
+impl LockoutSettings {
+    pub async fn load(site_data: &SiteDataManager) -> Self {
+        let max_attempts = site_data
+            .get_config("login_max_attempts")
+            .await
+            .and_then(|v| parse_login_max_attempts(&v).ok())
+            .unwrap_or(DEFAULT_LOGIN_MAX_ATTEMPTS);
+        let minutes = site_data
+            .get_config("login_lockout_minutes")
+            .await
+            .and_then(|v| parse_login_lockout_minutes(&v).ok())
+            .unwrap_or(DEFAULT_LOGIN_LOCKOUT_MINUTES);
+        LockoutSettings {
+            max_attempts,
+            window: Duration::from_secs(minutes * 60),
+        }
+    }
+}
+
+// 在查询用户和校验密码之前占用一次尝试：写锁内检查并计数，并发请求不会都在计数之前通过检查。
+// 用户名或IP已被锁定时返回距解锁的时间，不计数；否则两个计数各加一，登录成功后由 clear 清除
+pub async fn reserve_attempt(settings: LockoutSettings, username: &str, ip: &str) -> Result<(), Duration> {
+    if settings.max_attempts == 0 {
+        return Ok(());
+    }
+    let now = Instant::now();
+    let mut state = attempt_state().write().await;
+    if now.duration_since(state.last_sweep) >= SWEEP_INTERVAL {
+        // 窗口已结束的计数不再占用内存
+        state
+            .failures
+            .retain(|_, (_, first)| now.duration_since(*first) < settings.window);
+        state.last_sweep = now;
+    }
+
+    let keys = attempt_keys(username, ip);
+    let wait = keys
+        .iter()
+        .filter_map(|key| state.failures.get(key))
+        .filter(|(count, first)| *count >= settings.max_attempts && now.duration_since(*first) < settings.window)
+        .map(|(_, first)| settings.window.saturating_sub(now.duration_since(*first)))
+        .max();
+    if let Some(wait) = wait {
+        return Err(wait);
+    }
+
+    for key in keys {
+        let (count, first) = state.failures.entry(key).or_insert((0, now));
+        if now.duration_since(*first) >= settings.window {
+            *count = 0;
+            *first = now;
+        }
+        *count += 1;
+    }
+    Ok(())
+}
+
+// 登录成功后清除该用户名和IP的失败计数
+pub async fn clear(username: &str, ip: &str) {
+    let mut state = attempt_state().write().await;
+    for key in attempt_keys(username, ip) {
+        state.failures.remove(&key);
+    }
+}
+
+// Retry-After 秒数，向上取整，至少 1 秒
+pub fn retry_after_secs(wait: Duration) -> u64 {
+    (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1)
+}
@@ -1,36 +1,59 @@
+mod access_log;
 mod admin_handlers;
 mod api_handlers;
+mod api_quota;
+mod api_rate_limit;
+mod audit_log;
 mod auth;
 mod auth_handlers;
+mod cache;
+mod canonical_host;
+mod client_ip;
+mod collect_client;
 mod collect_handlers;
+mod compression;
+mod concurrency_limit;
 mod db;
 mod dto;
 mod index_manager;
 mod init_data;
+mod logging;
+mod login_lockout;
+mod migrations;
 mod models;
+mod pagination;
+mod play_token;
+mod ranking;
+mod resolve;
+mod rss;
 mod scheduled_task;
 mod site_data;
+mod sitemap;
+mod task_poll;
 mod template;
+mod vod_export;
+mod vod_import;
+mod watch_history;
 mod web_handlers;
 
 use admin_handlers::{
-    batch_delete_source, batch_delete_vods, create_collection, create_config, create_indexes,
+    batch_create_bindings, batch_delete_source, batch_delete_vods, clone_collection, create_collection, create_config, purge_cache, create_indexes,
     create_or_update_binding, create_type, create_vod, delete_binding, delete_collection,
-    delete_config, delete_type, delete_vod, get_batch_delete_progress_handler, get_bindings,
-    get_collect_progress, get_collection_binding_status, get_collections, get_config_by_key,
-    get_configs, get_index_status, get_indexes_data, get_running_batch_delete_tasks_handler,
+    delete_config, delete_empty_vods, disable_collection, enable_collection, delete_type, delete_vod, fix_broken_bindings, get_batch_delete_progress_handler, get_bindings, get_broken_reports, get_incomplete_vods,
+    get_audit_log, get_collect_progress, get_collection, get_collection_binding_coverage, get_collection_binding_status, get_collection_categories, get_collection_runs, get_collections, get_play_source_health, get_recent_task_failures, verify_data_integrity, test_image, get_log_level, set_collect_log_level, reset_log_level, get_config_by_key,
+    get_configs, export_site_bundle, export_vods, import_site_bundle, get_index_status, get_indexes_data, get_running_batch_delete_tasks_handler,
     get_running_tasks, get_scheduled_task_logs, get_scheduled_task_status, get_statistics,
-    get_types, get_vods_admin, list_indexes, start_collection_collect, start_scheduled_task,
-    stop_batch_delete_task_handler, stop_collect_task, stop_scheduled_task, update_collection,
-    update_config, update_scheduled_task_config, update_type, update_vod,
+    get_types, get_vod_import_progress, get_vod_trash, get_vods_admin, import_vods, list_indexes, rebind_vods, restore_vod, setup_site, start_collection_collect, start_scheduled_task,
+    stop_batch_delete_task_handler, stop_collect_task, restart_collect_task, resume_collect_task, parse_play, stop_scheduled_task, update_collection,
+    update_config, update_scheduled_task_config, update_type, update_vod, validate_bindings,
 };
 use auth_handlers::{get_current_user, login, logout, register};
 use collect_handlers::{get_collect_categories, get_collect_videos, start_collect_task};
 use site_data::SiteDataManager;
 
 use actix_files::Files;
-use actix_session::{storage::CookieSessionStore, SessionMiddleware};
-use actix_web::cookie::Key;
+use actix_session::{config::PersistentSession, storage::CookieSessionStore, SessionMiddleware};
+use actix_web::cookie::{time::Duration as CookieDuration, Key};
 use actix_web::dev::{forward_ready, Service, Transform};
 use actix_web::http::header::{HeaderValue, CACHE_CONTROL};
 use actix_web::{
@@ -40,12 +63,43 @@ use actix_web::{
 use actix_web_flash_messages::{storage::CookieMessageStore, FlashMessagesFramework};
 use futures::stream::TryStreamExt;
 use mongodb::Database;
+use regex::Regex;
 use std::env;
 use std::future::{ready, Ready};
 use std::rc::Rc;
 
 // Static file cache middleware
-pub struct StaticCacheMiddleware;
+// Fingerprinted files (matching `hashed_pattern`) are cached immutably for a year,
+// everything else under /static/ keeps the 24 hour TTL.
+#[derive(Clone)]
+pub struct StaticCacheMiddleware {
+    hashed_pattern: Option<Regex>,
+}
+
+impl StaticCacheMiddleware {
+    // STATIC_HASHED_PATTERN 覆盖默认的带哈希文件名匹配规则，设为空则禁用
+    pub fn from_env() -> Self {
+        let pattern = env::var("STATIC_HASHED_PATTERN")
+            .unwrap_or_else(|_| DEFAULT_HASHED_PATTERN.to_string());
+
+        let hashed_pattern = if pattern.trim().is_empty() {
+            None
+        } else {
+            match Regex::new(pattern.trim()) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    eprintln!("⚠️  STATIC_HASHED_PATTERN 无效，已忽略: {}", e);
+                    None
+                }
+            }
+        };
+
+        StaticCacheMiddleware { hashed_pattern }
+    }
+}
+
+// e.g. app.3f9a2c1b.js, output.5d41402abc4b2a76.css
+const DEFAULT_HASHED_PATTERN: &str = r"[.-][0-9a-fA-F]{8,}\.[A-Za-z0-9]+$";
 
 impl<S, B> Transform<S, ServiceRequest> for StaticCacheMiddleware
 where
@@ -62,12 +116,14 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(StaticCacheMiddlewareService {
             service: Rc::new(service),
+            hashed_pattern: self.hashed_pattern.clone(),
         }))
     }
 }
 
 pub struct StaticCacheMiddlewareService<S> {
     service: Rc<S>,
+    hashed_pattern: Option<Regex>,
 }
 
 impl<S, B> Service<ServiceRequest> for StaticCacheMiddlewareService<S>
@@ -85,17 +141,27 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
+        let is_static = req.path().starts_with("/static/");
+        let is_hashed = is_static
+            && self
+                .hashed_pattern
+                .as_ref()
+                .map(|re| re.is_match(req.path()))
+                .unwrap_or(false);
 
         Box::pin(async move {
-            let is_static = req.path().starts_with("/static/");
             let mut res = service.call(req).await?;
 
             if is_static {
-                // Set cache headers for static files (24 hours)
-                res.headers_mut().insert(
-                    CACHE_CONTROL,
-                    HeaderValue::from_static("public, max-age=86400"),
-                );
+                // Errors never get the immutable header, or a 404 would stick for a year
+                let cache_control = if is_hashed && res.status().is_success() {
+                    // Content-hashed filenames never change, cache for a year
+                    HeaderValue::from_static("public, max-age=31536000, immutable")
+                } else {
+                    // Set cache headers for static files (24 hours)
+                    HeaderValue::from_static("public, max-age=86400")
+                };
+                res.headers_mut().insert(CACHE_CONTROL, cache_control);
             }
 
             Ok(res)
@@ -108,7 +174,7 @@ where
 async fn get_vods(db: web::Data<Database>) -> impl Responder {
     let collection = db.collection::<models::Vod>("vods");
 
-    match collection.find(None, None).await {
+    match collection.find(mongodb::bson::doc! {"vod_deleted_at": {"$exists": false}}, None).await {
         Ok(cursor) => {
             let vods: Vec<models::Vod> = match cursor.try_collect().await {
                 Ok(docs) => docs,
@@ -128,6 +194,14 @@ async fn get_vods(db: web::Data<Database>) -> impl Responder {
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    dotenv::dotenv().ok();
+
+    // Initialize tracing and the access log configuration
+    let access_log_config = access_log::AccessLogConfig::from_env();
+    logging::init_tracing(&access_log_config);
+    let compression_config = compression::CompressionConfig::from_env();
+    let static_cache = StaticCacheMiddleware::from_env();
+
     // Initialize the database
     let db = match db::init().await {
         Ok(db) => db,
@@ -156,7 +230,18 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
-    auth::ensure_admin_user_exists(&db).await;
+    // 初始化测试数据（演示视频、采集源等），仅在 SEED_TEST_DATA=true 时执行
+    let seed_test_data = env::var("SEED_TEST_DATA")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "on"))
+        .unwrap_or(false);
+
+    // 全新安装时不创建默认管理员，由 POST /api/admin/setup 使用提交的账户和密码创建；
+    // 否则安装接口需要先用默认账户登录，提交的密码也不会生效
+    if seed_test_data || init_data::is_site_initialized(&db).await {
+        auth::ensure_admin_user_exists(&db).await;
+    } else {
+        println!("ℹ️  站点尚未初始化，跳过默认管理员创建；请调用 POST /api/admin/setup 创建管理员账户");
+    }
 
     // 初始化站点数据管理器
     let site_data_manager = SiteDataManager::new(db.clone());
@@ -170,17 +255,27 @@ async fn main() -> std::io::Result<()> {
             // 不退出应用，因为基本功能仍可使用
         }
     }
+    // 每隔 cache_ttl_seconds 自动重新加载分类和配置
+    site_data_manager.spawn_auto_refresh();
 
-    // 初始化测试数据
-    println!("🔧 正在初始化测试数据...");
-    match init_data::init_all_data(&db).await {
-        Ok(_) => {
-            println!("✅ 测试数据初始化完成");
-        }
-        Err(e) => {
-            eprintln!("⚠️  测试数据初始化失败: {}", e);
-            // 不退出应用，因为基本功能仍可使用
+    if seed_test_data {
+        println!("🔧 正在初始化测试数据...");
+        match init_data::init_all_data(&db).await {
+            Ok(_) => {
+                println!("✅ 测试数据初始化完成");
+            }
+            Err(e) => {
+                eprintln!("⚠️  测试数据初始化失败: {}", e);
+                // 不退出应用，因为基本功能仍可使用
+            }
         }
+    } else {
+        println!("ℹ️  未开启 SEED_TEST_DATA，跳过测试数据初始化；全新安装请调用 POST /api/admin/setup");
+    }
+
+    // 执行数据迁移
+    if let Err(e) = migrations::run_migrations(&db).await {
+        eprintln!("⚠️  数据迁移失败: {}", e);
     }
 
     // 初始化定时任务配置
@@ -197,7 +292,17 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
+    // Shared across workers so the per-IP limit is global, not per worker
+    let concurrency_limit = concurrency_limit::ConcurrencyLimit::new();
+    let api_quota = api_quota::ApiQuota::new();
+    // 公开数据接口的每分钟限流，上限在启动时读取
+    let api_rate_limit = api_rate_limit::ApiRateLimit::from_config(&site_data_manager).await;
+    println!("ℹ️  公开API限流: 每IP每分钟 {} 次（0 表示不限制）", api_rate_limit.limit());
+
+    let started_at = api_handlers::StartedAt(std::time::Instant::now());
+
     let session_secret_key = Key::generate();
+    let session_timeouts = auth::session_timeouts();
 
     println!("Starting server at http://127.0.0.1:8080");
 
@@ -209,10 +314,19 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(site_data_manager.clone()))
             // Store the scheduled task manager in the application state
             .app_data(web::Data::new(scheduled_task_manager.clone()))
-            // Gzip compression middleware
-            .wrap(middleware::Compress::default())
+            // Process start time for the /healthz uptime
+            .app_data(web::Data::new(started_at))
+            // Keep already-compressed content (archives, fonts, audio) out of Compress
+            .wrap(compression::SkipPrecompressed)
+            // Fixed-size responses at COMPRESSION_BROTLI_QUALITY / COMPRESSION_GZIP_LEVEL, when set
+            .wrap(compression::LeveledCompress::new(&compression_config))
+            // Brotli/Gzip compression middleware (HTML, JSON API, CSS/JS)
+            .wrap(middleware::Condition::new(
+                compression_config.enabled,
+                middleware::Compress::default(),
+            ))
             // Static file cache middleware
-            .wrap(StaticCacheMiddleware)
+            .wrap(static_cache.clone())
             // Session and Flash Messages Middleware
             .wrap(
                 FlashMessagesFramework::builder(
@@ -225,8 +339,20 @@ async fn main() -> std::io::Result<()> {
                     CookieSessionStore::default(),
                     session_secret_key.clone(),
                 )
+                .session_lifecycle(
+                    PersistentSession::default()
+                        .session_ttl(CookieDuration::seconds(session_timeouts.cookie_ttl())),
+                )
                 .build(),
             )
+            // Per-IP daily quota for public /api/* endpoints (api_daily_quota)
+            .wrap(api_quota.clone())
+            // Per-IP concurrent request cap (max_concurrent_per_ip), inside the access log so 429s are logged
+            .wrap(concurrency_limit.clone())
+            // 301 to canonical_host / https before any other work, inside the access log so redirects are logged
+            .wrap(canonical_host::CanonicalHost)
+            // Access log middleware (outermost, so timing covers all other middleware)
+            .wrap(access_log::AccessLog::new(access_log_config.clone()))
             // Web routes
             .service(web::resource("/").route(web::get().to(web_handlers::home_page_wrapper)))
             .service(
@@ -243,13 +369,29 @@ async fn main() -> std::io::Result<()> {
             )
             .service(
                 web::resource("/search")
+                    .wrap(api_rate_limit.clone())
                     .route(web::get().to(web_handlers::search_page_handler_wrapper)),
             )
+            .service(
+                web::resource("/age-gate").route(web::post().to(web_handlers::age_gate_confirm)),
+            )
+            .service(
+                web::resource("/stream/{token}").route(web::get().to(web_handlers::stream_redirect)),
+            )
             // Static pages
             .service(web::resource("/about").route(web::get().to(web_handlers::about_page)))
             .service(web::resource("/contact").route(web::get().to(web_handlers::contact_page)))
             .service(web::resource("/privacy").route(web::get().to(web_handlers::privacy_page)))
             .service(web::resource("/terms").route(web::get().to(web_handlers::terms_page)))
+            .service(
+                web::resource("/robots.txt").route(web::get().to(web_handlers::robots_txt_handler)),
+            )
+            .service(web::resource("/sitemap.xml").route(web::get().to(sitemap::sitemap_index)))
+            .service(
+                web::resource("/sitemap-{page:\\d+}.xml").route(web::get().to(sitemap::sitemap_page)),
+            )
+            .service(web::resource("/rss").route(web::get().to(rss::rss_feed)))
+            .service(web::resource("/rss/{type_id}").route(web::get().to(rss::category_rss_feed)))
             // User pages
             .service(
                 web::resource("/user/profile")
@@ -308,24 +450,71 @@ async fn main() -> std::io::Result<()> {
             // API routes
             .service(get_vods)
             .service(
-                web::resource("/api/provide/vod").route(web::get().to(api_handlers::provide_vod)),
+                web::resource("/api/provide/vod")
+                    .wrap(api_rate_limit.clone())
+                    .route(web::get().to(api_handlers::provide_vod)),
+            )
+            // 需在 /api/videos/{type_id} 之前注册
+            .service(
+                web::resource("/api/videos/lookup")
+                    .route(web::get().to(api_handlers::lookup_video)),
             )
             .service(
                 web::resource("/api/videos/{type_id}")
+                    .wrap(api_rate_limit.clone())
                     .route(web::get().to(api_handlers::get_videos_by_type)),
             )
             .service(
                 web::resource("/api/categories/hierarchy")
                     .route(web::get().to(api_handlers::get_category_hierarchy)),
             )
+            .service(
+                web::resource("/api/categories/tree")
+                    .route(web::get().to(api_handlers::get_category_tree)),
+            )
             .service(
                 web::resource("/api/videos/detail/{vod_id}")
                     .route(web::get().to(api_handlers::get_video_details)),
             )
+            .service(
+                web::resource("/api/videos/{vod_id}/breadcrumb")
+                    .route(web::get().to(api_handlers::get_video_breadcrumb)),
+            )
+            .service(
+                web::resource("/api/videos/{vod_id}/episodes")
+                    .route(web::get().to(api_handlers::get_video_episodes)),
+            )
+            .service(
+                web::resource("/api/videos/{vod_id}/playlist.m3u")
+                    .route(web::get().to(api_handlers::get_video_playlist)),
+            )
             .service(
                 web::resource("/api/filter-options")
                     .route(web::get().to(api_handlers::get_filter_options)),
             )
+            .service(
+                web::resource("/api/public/configs")
+                    .route(web::get().to(api_handlers::get_public_configs)),
+            )
+            .service(web::resource("/version").route(web::get().to(api_handlers::get_version)))
+            .service(web::resource("/healthz").route(web::get().to(api_handlers::healthz)))
+            .service(
+                web::resource("/api/report-broken")
+                    .route(web::post().to(api_handlers::report_broken)),
+            )
+            // Watch history / resume position for logged-in site users (Bearer token)
+            .service(
+                web::resource("/api/user/progress")
+                    .route(web::post().to(api_handlers::save_watch_progress)),
+            )
+            .service(
+                web::resource("/api/user/progress/{vod_id}")
+                    .route(web::get().to(api_handlers::get_watch_progress)),
+            )
+            .service(
+                web::resource("/api/user/history")
+                    .route(web::get().to(api_handlers::get_watch_history)),
+            )
             // Authentication API routes
             .service(web::resource("/api/auth/login").route(web::post().to(login)))
             .service(web::resource("/api/auth/register").route(web::post().to(register)))
@@ -334,6 +523,19 @@ async fn main() -> std::io::Result<()> {
             // Admin API routes
             .service(
                 web::scope("/api/admin")
+                    .wrap(audit_log::AuditLog)
+                    // Site bundle: configs, types, collections and bindings in one file
+                    .service(
+                        web::resource("/export/bundle").route(web::get().to(export_site_bundle)),
+                    )
+                    .service(
+                        web::resource("/import/bundle")
+                            .app_data(
+                                web::JsonConfig::default()
+                                    .limit(admin_handlers::MAX_SITE_BUNDLE_BYTES),
+                            )
+                            .route(web::post().to(import_site_bundle)),
+                    )
                     // Category Management
                     .service(
                         web::resource("/types")
@@ -351,6 +553,14 @@ async fn main() -> std::io::Result<()> {
                             .route(web::get().to(get_bindings))
                             .route(web::post().to(create_or_update_binding)),
                     )
+                    .service(
+                        web::resource("/bindings/batch").route(web::post().to(batch_create_bindings)),
+                    )
+                    .service(
+                        web::resource("/bindings/validate")
+                            .route(web::get().to(validate_bindings))
+                            .route(web::post().to(fix_broken_bindings)),
+                    )
                     .service(
                         web::resource("/bindings/{id}").route(web::delete().to(delete_binding)),
                     )
@@ -374,6 +584,7 @@ async fn main() -> std::io::Result<()> {
                     )
                     .service(
                         web::resource("/collections/{id}")
+                            .route(web::get().to(get_collection))
                             .route(web::put().to(update_collection))
                             .route(web::delete().to(delete_collection)),
                     )
@@ -381,14 +592,52 @@ async fn main() -> std::io::Result<()> {
                         web::resource("/collections/{id}/binding-status")
                             .route(web::get().to(get_collection_binding_status)),
                     )
+                    .service(
+                        web::resource("/collections/{id}/binding-coverage")
+                            .route(web::get().to(get_collection_binding_coverage)),
+                    )
+                    .service(
+                        web::resource("/collections/{id}/categories")
+                            .route(web::get().to(get_collection_categories)),
+                    )
+                    .service(
+                        web::resource("/collections/{id}/enable")
+                            .route(web::post().to(enable_collection)),
+                    )
+                    .service(
+                        web::resource("/collections/{id}/disable")
+                            .route(web::post().to(disable_collection)),
+                    )
+                    .service(
+                        web::resource("/collections/{id}/clone")
+                            .route(web::post().to(clone_collection)),
+                    )
                     .service(
                         web::resource("/collections/{id}/collect")
                             .route(web::post().to(start_collection_collect)),
                     )
+                    .service(
+                        web::resource("/collect/runs").route(web::get().to(get_collection_runs)),
+                    )
+                    .service(
+                        web::resource("/tasks/recent-failures")
+                            .route(web::get().to(get_recent_task_failures)),
+                    )
                     .service(
                         web::resource("/collect/progress/{task_id}")
                             .route(web::get().to(get_collect_progress)),
                     )
+                    .service(
+                        web::resource("/collect/restart/{task_id}")
+                            .route(web::post().to(restart_collect_task)),
+                    )
+                    .service(
+                        web::resource("/collect/resume/{task_id}")
+                            .route(web::post().to(resume_collect_task)),
+                    )
+                    .service(
+                        web::resource("/collect/parse-play").route(web::post().to(parse_play)),
+                    )
                     .service(
                         web::resource("/running-tasks").route(web::get().to(get_running_tasks)),
                     )
@@ -415,11 +664,64 @@ async fn main() -> std::io::Result<()> {
                         web::resource("/batch-delete/stop/{task_id}")
                             .route(web::post().to(stop_batch_delete_task_handler)),
                     )
+                    .service(web::resource("/vods/rebind").route(web::post().to(rebind_vods)))
+                    .service(
+                        web::resource("/vods/incomplete").route(web::get().to(get_incomplete_vods)),
+                    )
+                    .service(web::resource("/vods/trash").route(web::get().to(get_vod_trash)))
+                    .service(web::resource("/vods/export").route(web::get().to(export_vods)))
+                    .service(web::resource("/vods/import").route(web::post().to(import_vods)))
+                    .service(
+                        web::resource("/vods/import/progress/{task_id}")
+                            .route(web::get().to(get_vod_import_progress)),
+                    )
+                    .service(
+                        web::resource("/vods/{id}/restore").route(web::post().to(restore_vod)),
+                    )
                     .service(
                         web::resource("/vods/{id}")
                             .route(web::put().to(update_vod))
                             .route(web::delete().to(delete_vod)),
                     )
+                    // Audit log
+                    .service(web::resource("/audit").route(web::get().to(get_audit_log)))
+                    // First-run setup
+                    .service(web::resource("/setup").route(web::post().to(setup_site)))
+                    // Maintenance
+                    .service(
+                        web::resource("/maintenance/delete-empty-vods")
+                            .route(web::post().to(delete_empty_vods)),
+                    )
+                    .service(
+                        web::resource("/maintenance/verify")
+                            .route(web::post().to(verify_data_integrity)),
+                    )
+                    .service(
+                        web::resource("/maintenance/test-image")
+                            .route(web::post().to(test_image)),
+                    )
+                    // Logging
+                    .service(
+                        web::resource("/logging/level")
+                            .route(web::get().to(get_log_level))
+                            .route(web::put().to(set_collect_log_level))
+                            .route(web::delete().to(reset_log_level)),
+                    )
+                    // Cache management
+                    .service(web::resource("/cache/purge").route(web::post().to(purge_cache)))
+                    // Template preview
+                    .service(
+                        web::resource("/templates/preview")
+                            .route(web::post().to(web_handlers::preview_template)),
+                    )
+                    // Broken link reports
+                    .service(
+                        web::resource("/broken-reports").route(web::get().to(get_broken_reports)),
+                    )
+                    .service(
+                        web::resource("/play-sources/health")
+                            .route(web::get().to(get_play_source_health)),
+                    )
                     // Index Management
                     .service(web::resource("/indexes/create").route(web::post().to(create_indexes)))
                     .service(
@@ -0,0 +1,168 @@
+use crate::models::{parse_actor_list, parse_score_num, parse_year_num};
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, DateTime, Document};
+use mongodb::options::FindOptions;
+use mongodb::Database;
+
+// 数据迁移：按顺序执行，已执行的迁移记录在 migrations 集合中，每个只运行一次
+const MIGRATIONS: &[&str] = &[
+    "vod_year_num_backfill",
+    "vod_score_num_backfill",
+    "vod_created_at_backfill",
+    "vod_actor_list_backfill",
+    "broken_report_reporter_ips_cleanup",
+];
+
+pub async fn run_migrations(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let migrations = db.collection::<Document>("migrations");
+
+    for name in MIGRATIONS {
+        if migrations
+            .count_documents(doc! { "_id": *name }, None)
+            .await?
+            > 0
+        {
+            continue;
+        }
+
+        println!("🔧 正在执行数据迁移: {}", name);
+        let affected = match *name {
+            "vod_year_num_backfill" => backfill_vod_year_num(db).await?,
+            "vod_score_num_backfill" => backfill_vod_score_num(db).await?,
+            "vod_created_at_backfill" => backfill_vod_created_at(db).await?,
+            "vod_actor_list_backfill" => backfill_vod_actor_list(db).await?,
+            "broken_report_reporter_ips_cleanup" => cleanup_broken_report_reporter_ips(db).await?,
+            _ => 0,
+        };
+
+        migrations
+            .insert_one(
+                doc! {
+                    "_id": *name,
+                    "affected": affected as i64,
+                    "applied_at": DateTime::now(),
+                },
+                None,
+            )
+            .await?;
+        println!("✅ 数据迁移完成: {}，更新 {} 条记录", name, affected);
+    }
+
+    Ok(())
+}
+
+// 为已有视频补充 vod_year_num 字段
+async fn backfill_vod_year_num(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let collection = db.collection::<Document>("vods");
+    let find_options = FindOptions::builder()
+        .projection(doc! { "_id": 1, "vod_year": 1 })
+        .build();
+
+    let mut cursor = collection
+        .find(
+            doc! { "vod_year_num": { "$exists": false }, "vod_year": { "$type": "string" } },
+            find_options,
+        )
+        .await?;
+
+    let mut updated = 0;
+    while let Some(vod) = cursor.try_next().await? {
+        let year_num = match vod.get_str("vod_year").ok().and_then(parse_year_num) {
+            Some(year_num) => year_num,
+            None => continue,
+        };
+        if let Ok(id) = vod.get_object_id("_id") {
+            collection
+                .update_one(doc! { "_id": id }, doc! { "$set": { "vod_year_num": year_num } }, None)
+                .await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+// 为已有视频补充 vod_score_num 字段，无法解析的评分记为 0.0
+async fn backfill_vod_score_num(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let collection = db.collection::<Document>("vods");
+    let find_options = FindOptions::builder()
+        .projection(doc! { "_id": 1, "vod_score": 1 })
+        .build();
+
+    let mut cursor = collection
+        .find(doc! { "vod_score_num": { "$exists": false } }, find_options)
+        .await?;
+
+    let mut updated = 0;
+    while let Some(vod) = cursor.try_next().await? {
+        let score_num = parse_score_num(vod.get_str("vod_score").ok());
+        if let Ok(id) = vod.get_object_id("_id") {
+            collection
+                .update_one(doc! { "_id": id }, doc! { "$set": { "vod_score_num": score_num } }, None)
+                .await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+// 为已有视频补充 vod_created_at 字段，取 ObjectId 中的创建时间
+// （vod_pubdate 会在重新采集时被刷新，不能代表入库时间）
+async fn backfill_vod_created_at(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let result = db
+        .collection::<Document>("vods")
+        .update_many(
+            doc! { "vod_created_at": { "$exists": false } },
+            vec![doc! { "$set": { "vod_created_at": { "$toDate": "$_id" } } }],
+            None,
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+// 为已有视频补充 vod_actor_list 字段（由 vod_actor 拆分出的主演名，用于按相同主演推荐）
+async fn backfill_vod_actor_list(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let collection = db.collection::<Document>("vods");
+    let find_options = FindOptions::builder()
+        .projection(doc! { "_id": 1, "vod_actor": 1 })
+        .build();
+
+    let mut cursor = collection
+        .find(
+            doc! { "vod_actor_list": { "$exists": false }, "vod_actor": { "$type": "string", "$ne": "" } },
+            find_options,
+        )
+        .await?;
+
+    let mut updated = 0;
+    while let Some(vod) = cursor.try_next().await? {
+        let actor_list = parse_actor_list(vod.get_str("vod_actor").unwrap_or(""));
+        if actor_list.is_empty() {
+            continue;
+        }
+        if let Ok(id) = vod.get_object_id("_id") {
+            collection
+                .update_one(doc! { "_id": id }, doc! { "$set": { "vod_actor_list": actor_list } }, None)
+                .await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+// 删除失效报告中旧版本保存的 reporter_ips（访客原始IP），去重改由 broken_report_reporters 的哈希记录完成
+async fn cleanup_broken_report_reporter_ips(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let result = db
+        .collection::<Document>("broken_reports")
+        .update_many(
+            doc! { "reporter_ips": { "$exists": true } },
+            doc! { "$unset": { "reporter_ips": "" } },
+            None,
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
@@ -1,5 +1,6 @@
-use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // Note: In a real application, you would want to use a library like `chrono` for more robust date/time handling.
 // Here we use mongodb::bson::DateTime for simplicity.
@@ -10,6 +11,8 @@ pub struct Vod {
     pub id: Option<ObjectId>,
     pub vod_name: String,
     pub type_id: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vod_extra_types: Vec<i32>, // Additional categories the vod is also listed under
     pub vod_status: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_class: Option<String>,
@@ -17,18 +20,24 @@ pub struct Vod {
     pub vod_pic: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vod_actor_list: Vec<String>, // Normalized names split from vod_actor, indexed for same-cast related videos
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_director: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_remarks: Option<String>,
     pub vod_pubdate: DateTime,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub vod_created_at: Option<DateTime>, // Set once when the vod is first inserted, never updated
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_area: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_lang: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_year: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub vod_year_num: Option<i32>, // Normalized 4-digit year for sorting/filtering
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_hits: Option<i32>,
@@ -40,8 +49,125 @@ pub struct Vod {
     pub vod_hits_month: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vod_score: Option<String>,
+    #[serde(default)]
+    pub vod_score_num: f64, // Numeric copy of vod_score for sorting/filtering
     // In MongoDB, this is better represented as a nested structure
     pub vod_play_urls: Vec<PlaySource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vod_deleted_at: Option<DateTime>, // Set when moved to the trash; trashed vods are excluded from every listing and lookup
+}
+
+impl Vod {
+    // Recompute fields derived from the display strings; call before every write
+    pub fn normalize(&mut self) {
+        self.vod_year_num = self.vod_year.as_deref().and_then(parse_year_num);
+        self.vod_score_num = parse_score_num(self.vod_score.as_deref());
+        self.vod_actor_list = parse_actor_list(self.vod_actor.as_deref().unwrap_or(""));
+    }
+}
+
+// Exclude trashed vods (vod_deleted_at set); add to every listing, search and lookup filter
+pub fn exclude_deleted_vods(filter: &mut Document) {
+    filter.insert("vod_deleted_at", doc! { "$exists": false });
+}
+
+// Append a condition to the filter's $and list, keeping any conditions already there
+pub fn push_and_condition(filter: &mut Document, condition: Document) {
+    match filter.get_array_mut("$and") {
+        Ok(conditions) => conditions.push(condition.into()),
+        Err(_) => {
+            filter.insert("$and", vec![condition]);
+        }
+    }
+}
+
+// Exclude vods listed under any of type_ids, either as the primary type_id
+// or through vod_extra_types
+pub fn exclude_vod_types(filter: &mut Document, type_ids: &[i32]) {
+    if type_ids.is_empty() {
+        return;
+    }
+    push_and_condition(
+        filter,
+        doc! {
+            "type_id": { "$nin": type_ids },
+            "vod_extra_types": { "$nin": type_ids },
+        },
+    );
+}
+
+// Filter for vods listed under any of type_ids, either as the primary type_id
+// or through vod_extra_types
+pub fn vod_type_filter(type_ids: &[i32]) -> Document {
+    doc! {
+        "$or": [
+            { "type_id": { "$in": type_ids } },
+            { "vod_extra_types": { "$in": type_ids } },
+        ]
+    }
+}
+
+// Values upstream sources put in vod_actor when the cast is unknown
+const ACTOR_PLACEHOLDERS: &[&str] = &["未知", "内详", "暂无", "不详", "佚名", "unknown", "n/a"];
+const MAX_ACTOR_LIST_LEN: usize = 20;
+
+// Split a free-form vod_actor string into lowercased, deduplicated names,
+// e.g. "张三,李四/王五" -> ["张三", "李四", "王五"]. Spaces also separate names
+// unless the part contains Latin letters, so "Tom Hanks" stays one name
+pub fn parse_actor_list(actor: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let parts = actor.split([',', '，', '/', '、', '|', ';', '；']).flat_map(|part| {
+        let part = part.trim();
+        if part.chars().any(|c| c.is_ascii_alphabetic()) {
+            vec![part]
+        } else {
+            part.split_whitespace().collect::<Vec<_>>()
+        }
+    });
+    for part in parts {
+        let name = part.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        if name.is_empty() || ACTOR_PLACEHOLDERS.contains(&name.as_str()) || names.contains(&name) {
+            continue;
+        }
+        names.push(name);
+        if names.len() >= MAX_ACTOR_LIST_LEN {
+            break;
+        }
+    }
+    names
+}
+
+// Extract the first standalone 4-digit year (1900-2100) from a free-form string,
+// e.g. "2019" -> 2019, "2019-2021" -> 2019, "2020年" -> 2020, "未知" -> None
+pub fn parse_year_num(year: &str) -> Option<i32> {
+    let bytes = year.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i - start == 4 {
+            if let Ok(num) = year[start..i].parse::<i32>() {
+                if (1900..=2100).contains(&num) {
+                    return Some(num);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Parse the display score ("9.2") into a number; unparseable or missing values become 0.0
+pub fn parse_score_num(score: Option<&str>) -> f64 {
+    score
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .unwrap_or(0.0)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +180,12 @@ pub struct PlaySource {
 pub struct PlayUrl {
     pub name: String,
     pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_name: Option<String>, // Upstream episode name before normalize_episode_names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>, // e.g. "1080P", "4K"; parsed from the episode name/URL at collect time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>, // Lowercase container/stream extension, e.g. "m3u8", "mp4"
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -134,6 +266,8 @@ pub struct Type {
     pub subarea: Option<String>, // Available areas for filtering (comma-separated)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subyear: Option<String>, // Available years for filtering (comma-separated)
+    #[serde(default)]
+    pub is_adult: bool, // Adult-only category, pages require age confirmation
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -173,6 +307,45 @@ fn default_download_retry() -> i32 {
     3 // Default to 3 retry attempts
 }
 
+// Upstream API request timeout for a collection source, in seconds
+pub const DEFAULT_COLLECT_TIMEOUT_SECS: i32 = 30;
+pub const MIN_COLLECT_TIMEOUT_SECS: i32 = 5;
+pub const MAX_COLLECT_TIMEOUT_SECS: i32 = 300;
+
+pub fn default_collect_timeout() -> i32 {
+    DEFAULT_COLLECT_TIMEOUT_SECS
+}
+
+// Number of list pages a batch collect fetches and processes at the same time
+pub const DEFAULT_COLLECT_CONCURRENCY: i32 = 4;
+pub const MIN_COLLECT_CONCURRENCY: i32 = 1;
+pub const MAX_COLLECT_CONCURRENCY: i32 = 16;
+
+pub fn default_collect_concurrency() -> i32 {
+    DEFAULT_COLLECT_CONCURRENCY
+}
+
+// Parse an allowed collect window "HH:MM-HH:MM" into (start, end) minutes of the day.
+// Empty means no restriction; start > end is an overnight window such as "22:00-06:00".
+pub fn parse_collect_window(value: &str) -> Result<Option<(u32, u32)>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || format!("采集时间窗口格式应为 HH:MM-HH:MM，如 01:00-06:00: {}", value);
+    let parse_time = |s: &str| -> Option<u32> {
+        let (h, m) = s.trim().split_once(':')?;
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        (h < 24 && m < 60).then_some(h * 60 + m)
+    };
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (parse_time(start).ok_or_else(invalid)?, parse_time(end).ok_or_else(invalid)?);
+    if start == end {
+        return Err("采集时间窗口的开始和结束时间不能相同".to_string());
+    }
+    Ok(Some((start, end)))
+}
+
 // Collection source model
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Collection {
@@ -187,7 +360,13 @@ pub struct Collection {
     pub collect_param: String,  // Additional parameters
     pub collect_filter: String, // Filter rules
     #[serde(default)]
-    pub collect_filter_from: String, // Filter play sources
+    pub collect_filter_from: String, // Play sources to skip, comma-separated source names
+    #[serde(default)]
+    pub default_area: String, // Fallback vod_area when upstream leaves it empty; "" keeps it empty
+    #[serde(default)]
+    pub default_lang: String, // Fallback vod_lang when upstream leaves it empty; "" keeps it empty
+    #[serde(default)]
+    pub field_mapping: HashMap<String, String>, // Our list field name -> upstream field name; empty uses standard MacCMS names
     pub collect_opt: i32,       // Collection option: 0=all, 1=today, 2=yesterday, 3=week
     pub collect_sync_pic_opt: i32, // Sync picture option
     pub collect_remove_ad: i32, // Remove ads: 0=no, 1=yes
@@ -195,11 +374,47 @@ pub struct Collection {
     pub collect_convert_webp: i32, // Convert to WebP: 0=no, 1=yes
     #[serde(default = "default_download_retry")]
     pub collect_download_retry: i32, // Download retry times
+    #[serde(default = "default_collect_timeout")]
+    pub collect_timeout_secs: i32, // Timeout for each upstream API request
+    #[serde(default = "default_collect_concurrency")]
+    pub collect_concurrency: i32, // Pages fetched in parallel during a batch collect; 1 = sequential
+    #[serde(default)]
+    pub collect_window: String, // Scheduled runs only hit this source within "HH:MM-HH:MM" (server local time); "" = any time
+    #[serde(default)]
+    pub collect_proxy: String, // HTTP/SOCKS5 proxy for requests to this source, e.g. "socks5://127.0.0.1:1080"; "" = direct
+    #[serde(default)]
+    pub collect_user_agent: String, // User-Agent sent to this source; "" = a browser-like default
+    #[serde(default)]
+    pub collect_referer: String, // Referer sent to this source; "" = none
     pub collect_status: i32,    // Status: 1=enabled, 0=disabled
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+impl Collection {
+    // Request timeout clamped to the supported range, for records saved before validation existed
+    pub fn timeout_secs(&self) -> u64 {
+        self.collect_timeout_secs
+            .clamp(MIN_COLLECT_TIMEOUT_SECS, MAX_COLLECT_TIMEOUT_SECS) as u64
+    }
+
+    // Page concurrency clamped to the supported range, for records saved before validation existed
+    pub fn concurrency(&self) -> usize {
+        self.collect_concurrency
+            .clamp(MIN_COLLECT_CONCURRENCY, MAX_COLLECT_CONCURRENCY) as usize
+    }
+
+    // Whether a scheduled run may collect from this source at the given minute of the day;
+    // an unparsable window (saved before validation existed) never blocks collection
+    pub fn in_collect_window(&self, minute_of_day: u32) -> bool {
+        match parse_collect_window(&self.collect_window) {
+            Ok(Some((start, end))) if start < end => (start..end).contains(&minute_of_day),
+            Ok(Some((start, end))) => minute_of_day >= start || minute_of_day < end,
+            _ => true,
+        }
+    }
+}
+
 // Collection task model
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CollectTask {
@@ -216,3 +431,135 @@ pub struct CollectTask {
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
+
+// Summary of one start_batch_collect run, written when the run reaches a terminal status
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionRun {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub task_id: String,
+    pub collection_id: String,
+    pub collection_name: String,
+    pub trigger: String, // "manual", "scheduled" or "resume"
+    pub status: String,  // "completed", "failed", "stopped"
+    pub started_at: DateTime,
+    pub finished_at: DateTime,
+    pub total_pages: u32,
+    pub success: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+// Page-level state of a batch collect (collect_task_state collection, keyed by task_id),
+// saved after every page so a failed or interrupted task can be resumed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectTaskState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub task_id: String,
+    pub collection_id: ObjectId,
+    pub collection_name: String,
+    pub hours: Option<String>, // The "h" parameter of the original run
+    pub total_pages: u32,
+    pub next_page: u32,         // First page not yet finished; every page before it succeeded or is in failed_pages
+    pub failed_pages: Vec<u32>, // Pages that failed and have not been collected successfully since
+    pub status: String,         // "running", "completed", "failed", "stopped"
+    pub success: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    #[serde(default)]
+    pub filtered: u32,
+    #[serde(default)]
+    pub filtered_sources: u32,
+    pub updated_at: DateTime,
+}
+
+impl CollectTaskState {
+    // Pages a resume has to collect: the recorded failed pages, then everything from next_page on
+    pub fn remaining_pages(&self) -> Vec<u32> {
+        let mut pages = self.failed_pages.clone();
+        pages.sort_unstable();
+        pages.dedup();
+        pages.retain(|&page| page < self.next_page);
+        pages.extend(self.next_page..=self.total_pages);
+        pages
+    }
+}
+
+// One admin mutation recorded by the audit_log middleware
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLogEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: Option<String>, // Session user id, None for unauthenticated attempts
+    pub action: String,          // HTTP method: POST / PUT / DELETE
+    pub resource: String,        // First path segment under /api/admin, e.g. "types"
+    #[serde(default)]
+    pub target_id: Option<String>, // ObjectId or numeric id found in the path
+    pub target: String,          // Full request path
+    pub status: i32,
+    pub summary: String,         // Resource, id and outcome, e.g. "collections 64f0...: 成功 200 Collection updated
+    pub ip: String,
+    pub timestamp: DateTime,
+}
+
+// Per-user playback position, one document per (user, vod) holding the last watched episode
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchHistory {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub vod_id: ObjectId,
+    pub vod_name: String,
+    #[serde(default)]
+    pub vod_pic: String,
+    pub play_source: i32,       // Index into vod_play_urls
+    pub play_idx: i32,          // Episode index within the source
+    pub episode_name: String,   // Episode name at save time
+    pub position_seconds: f64,  // Playback position within the episode
+    pub updated_at: DateTime,
+}
+
+// User-submitted broken play link report, aggregated per (vod, source, episode)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokenReport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub vod_id: ObjectId,
+    pub vod_name: String,
+    pub play_source: i32,       // Index into vod_play_urls
+    pub source_name: String,    // Play source name at report time
+    pub play_idx: i32,          // Episode index within the source
+    pub report_count: i32,      // Number of reports, one per client per dedup window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reason: Option<String>,
+    pub first_reported_at: DateTime,
+    pub last_reported_at: DateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_year_num_handles_messy_input() {
+        assert_eq!(parse_year_num("2020"), Some(2020));
+        assert_eq!(parse_year_num(" 2020年"), Some(2020));
+        assert_eq!(parse_year_num("2019-2020"), Some(2019));
+        assert_eq!(parse_year_num(""), None);
+        assert_eq!(parse_year_num("未知"), None);
+    }
+
+    #[test]
+    fn parse_year_num_rejects_out_of_range_and_non_year_numbers() {
+        assert_eq!(parse_year_num("1899"), None);
+        assert_eq!(parse_year_num("2101"), None);
+        assert_eq!(parse_year_num("0000"), None);
+        // 5 位数字不是年份，不截取其中的 4 位
+        assert_eq!(parse_year_num("20201"), None);
+        assert_eq!(parse_year_num("第12集"), None);
+        assert_eq!(parse_year_num("1899/2001"), Some(2001));
+    }
+}
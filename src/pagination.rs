@@ -0,0 +1,67 @@
+use crate::site_data::SiteDataManager;
+use mongodb::bson::Document;
+
+pub const DEFAULT_PAGINATION_WINDOW: u64 = 3;
+
+// 页码链接窗口大小：当前页前后各显示多少个页码，取自 pagination_window 配置
+pub async fn pagination_window(site_data: &SiteDataManager) -> u64 {
+    site_data
+        .get_config("pagination_window")
+        .await
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|w| w.min(20))
+        .unwrap_or(DEFAULT_PAGINATION_WINDOW)
+}
+
+// 在排序条件末尾追加 _id 作为最终排序键。主排序字段相同（如同一年份、同一更新时间）时
+// MongoDB 不保证返回顺序，skip/limit 翻页会出现重复或漏掉的条目；加上唯一的 _id 后顺序稳定
+pub fn stable_sort(mut sort: Document) -> Document {
+    if !sort.contains_key("_id") {
+        sort.insert("_id", -1);
+    }
+    sort
+}
+
+// 生成当前页前后 window 个页码（限制在 1..=total 内），
+// 如 current=5, total=20, window=3 -> [2, 3, 4, 5, 6, 7, 8]
+pub fn build_pagination(current: u64, total: u64, window: u64) -> Vec<u64> {
+    let start = current.saturating_sub(window).max(1);
+    let end = current.saturating_add(window).min(total);
+    (start..=end).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_around_middle_page() {
+        assert_eq!(build_pagination(5, 20, 3), vec![2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn first_page() {
+        assert_eq!(build_pagination(1, 20, 3), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn last_page() {
+        assert_eq!(build_pagination(20, 20, 3), vec![17, 18, 19, 20]);
+    }
+
+    #[test]
+    fn total_smaller_than_window() {
+        assert_eq!(build_pagination(2, 3, 5), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn single_page_and_empty_results() {
+        assert_eq!(build_pagination(1, 1, 3), vec![1]);
+        assert_eq!(build_pagination(1, 0, 3), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn zero_window_shows_only_current_page() {
+        assert_eq!(build_pagination(4, 10, 0), vec![4]);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::site_data::SiteDataManager;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use mongodb::bson::oid::ObjectId;
+use rand::Rng;
+use sha2::Sha256;
+use std::sync::OnceLock;
+
+// 播放地址防盗链（protect_play_urls 配置开启时生效）：
+// 播放页与剧集接口不再输出原始地址，而是 /stream/{token}，由 /stream 校验后 302 跳转到真实地址。
+//
+// token 格式：{vod_id}.{source}.{index}.{expires}.{sig}[.扩展名]
+//   sig = base64url(HMAC-SHA256(secret, "{vod_id}.{source}.{index}.{expires}.{client_ip}"))
+// token 只引用视频和剧集序号，不包含真实地址；绑定访客 IP，过期时间由 play_token_ttl 配置。
+// 扩展名（如 .m3u8）仅供播放器识别格式，不参与校验。
+type HmacSha256 = Hmac<Sha256>;
+
+pub const DEFAULT_PLAY_TOKEN_TTL: i64 = 600;
+
+static PLAY_TOKEN_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+// 签名密钥：优先使用 PLAY_TOKEN_SECRET 环境变量，未设置时每次启动随机生成（重启后旧 token 失效）
+fn secret() -> &'static [u8] {
+    PLAY_TOKEN_SECRET.get_or_init(|| match std::env::var("PLAY_TOKEN_SECRET") {
+        Ok(secret) if !secret.trim().is_empty() => secret.into_bytes(),
+        _ => rand::thread_rng().gen::<[u8; 32]>().to_vec(),
+    })
+}
+
+fn sign(payload: &str, client_ip: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac.update(b".");
+    mac.update(client_ip.as_bytes());
+    mac
+}
+
+// 开启防盗链时返回 token 有效期（秒），未开启返回 None
+pub async fn play_token_ttl(site_data: &SiteDataManager) -> Option<i64> {
+    let enabled = site_data
+        .get_config("protect_play_urls")
+        .await
+        .map(|v| matches!(v.trim(), "1" | "true" | "on"))
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let ttl = site_data
+        .get_config("play_token_ttl")
+        .await
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|ttl| *ttl > 0)
+        .unwrap_or(DEFAULT_PLAY_TOKEN_TTL);
+    Some(ttl)
+}
+
+// 生成 /stream 地址，raw_url 只用于保留扩展名
+pub fn stream_url(
+    vod_id: &ObjectId,
+    source: usize,
+    index: usize,
+    raw_url: &str,
+    client_ip: &str,
+    ttl: i64,
+) -> String {
+    let expires = chrono::Utc::now().timestamp() + ttl;
+    let payload = format!("{}.{}.{}.{}", vod_id.to_hex(), source, index, expires);
+    let sig = URL_SAFE_NO_PAD.encode(sign(&payload, client_ip).finalize().into_bytes());
+
+    let path = raw_url.split(['?', '#']).next().unwrap_or("");
+    let ext = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    match ext {
+        Some(ext) => format!("/stream/{}.{}.{}", payload, sig, ext),
+        None => format!("/stream/{}.{}", payload, sig),
+    }
+}
+
+// 校验 token，成功返回 (vod_id, source, index)
+pub fn verify_token(token: &str, client_ip: &str) -> Option<(ObjectId, usize, usize)> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() < 5 || parts.len() > 6 {
+        return None;
+    }
+
+    let vod_id = ObjectId::parse_str(parts[0]).ok()?;
+    let source = parts[1].parse::<usize>().ok()?;
+    let index = parts[2].parse::<usize>().ok()?;
+    let expires = parts[3].parse::<i64>().ok()?;
+    if expires < chrono::Utc::now().timestamp() {
+        return None;
+    }
+
+    let sig = URL_SAFE_NO_PAD.decode(parts[4]).ok()?;
+    let payload = parts[..4].join(".");
+    sign(&payload, client_ip).verify_slice(&sig).ok()?;
+
+    Some((vod_id, source, index))
+}
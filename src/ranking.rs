@@ -0,0 +1,44 @@
+use crate::site_data::SiteDataManager;
+use mongodb::bson::{doc, DateTime, Document};
+
+// 热度排序（sort=trending），参考 Hacker News 排名公式：
+//   trending_score = vod_hits / (age_hours + 2) ^ trending_gravity
+// age_hours 为视频入库至今的小时数（vod_created_at，缺失时用 vod_pubdate）。
+// gravity 越大，旧视频的分数衰减越快；0 表示只按总播放量排序。
+pub const DEFAULT_TRENDING_GRAVITY: f64 = 1.8;
+
+pub async fn trending_gravity(site_data: &SiteDataManager) -> f64 {
+    site_data
+        .get_config("trending_gravity")
+        .await
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|g| g.is_finite())
+        .map(|g| g.clamp(0.0, 10.0))
+        .unwrap_or(DEFAULT_TRENDING_GRAVITY)
+}
+
+// 生成计算 trending_score 的 $addFields 阶段，需放在 $match 之后、$sort 之前
+pub fn trending_score_stage(gravity: f64) -> Document {
+    let age_hours = doc! {
+        "$divide": [
+            { "$subtract": [DateTime::now(), { "$ifNull": ["$vod_created_at", "$vod_pubdate"] }] },
+            3_600_000
+        ]
+    };
+
+    doc! {
+        "$addFields": {
+            "trending_score": {
+                "$divide": [
+                    { "$ifNull": ["$vod_hits", 0] },
+                    { "$pow": [{ "$add": [{ "$max": [age_hours, 0] }, 2] }, gravity] }
+                ]
+            }
+        }
+    }
+}
+
+// 与 trending_score_stage 配套的排序，分数相同时按发布时间倒序
+pub fn trending_sort() -> Document {
+    doc! { "trending_score": -1, "vod_pubdate": -1 }
+}
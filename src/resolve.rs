@@ -0,0 +1,23 @@
+// Helpers return the ready-made error response so handlers can `return` it directly
+#![allow(clippy::result_large_err)]
+
+use actix_web::HttpResponse;
+use mongodb::bson::oid::ObjectId;
+use serde_json::json;
+
+// 解析 API 路径中的 ObjectId，无效时返回统一的 400 JSON 响应
+// kind 用于错误信息，如 "video" -> "Invalid video ID"
+pub fn resolve_object_id(id: &str, kind: &str) -> Result<ObjectId, HttpResponse> {
+    ObjectId::parse_str(id.trim()).map_err(|_| {
+        HttpResponse::BadRequest().json(json!({
+            "success": false,
+            "message": format!("Invalid {} ID", kind)
+        }))
+    })
+}
+
+// 解析前台页面（详情页、播放页）中的视频ID，无效时按页面不存在处理
+// 以后支持 slug 时在这里先按 ObjectId 解析，再按 slug 查找
+pub fn resolve_vod_page_id(id: &str) -> Result<ObjectId, HttpResponse> {
+    ObjectId::parse_str(id.trim()).map_err(|_| HttpResponse::NotFound().body("Invalid video ID"))
+}
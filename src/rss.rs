@@ -0,0 +1,182 @@
+use crate::models::{self, Vod};
+use crate::site_data::SiteDataManager;
+use crate::sitemap::absolute_base_url;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use futures::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::FindOptions;
+use mongodb::Database;
+
+// RSS 2.0 订阅：/rss 为全站最新上架的 50 个视频，/rss/{type_id} 只包含该分类（含子分类）。
+// 查询方式与首页分类区块相同（按 vod_pubdate 倒序），排除的分类见 SiteDataManager::get_feed_excluded_type_ids。
+const FEED_SIZE: i64 = 50;
+// 简介截取的字符数
+const DESCRIPTION_CHARS: usize = 300;
+
+// 去掉 HTML 标签和实体并合并空白，采集的 vod_content 常带 <p>、<br>、&nbsp; 等
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.replace("&nbsp;", " ");
+    // 输出时会重新转义，这里先还原 &amp; 等实体，避免变成 &amp;amp;；无法识别的实体保留原文
+    let text = quick_xml::escape::unescape(&text).map(|t| t.into_owned()).unwrap_or(text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+// enclosure 的 type 按海报扩展名判断，无法判断时按 jpeg 处理
+fn image_mime_type(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    match path.rsplit('.').next() {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "image/jpeg",
+    }
+}
+
+fn escape(value: &str) -> std::borrow::Cow<'_, str> {
+    quick_xml::escape::escape(value)
+}
+
+fn render_item(xml: &mut String, vod: &Vod, base_url: &str) {
+    let Some(id) = vod.id else {
+        return;
+    };
+    let link = format!("{}/detail/{}", base_url, id.to_hex());
+    xml.push_str("<item>");
+    xml.push_str(&format!("<title>{}</title>", escape(&vod.vod_name)));
+    xml.push_str(&format!("<link>{}</link>", escape(&link)));
+    xml.push_str(&format!("<guid isPermaLink=\"true\">{}</guid>", escape(&link)));
+    if let Some(pub_date) = chrono::DateTime::from_timestamp_millis(vod.vod_pubdate.timestamp_millis()) {
+        xml.push_str(&format!("<pubDate>{}</pubDate>", pub_date.to_rfc2822()));
+    }
+    let description = plain_text(vod.vod_content.as_deref().unwrap_or(""));
+    if !description.is_empty() {
+        xml.push_str(&format!(
+            "<description>{}</description>",
+            escape(&truncate_chars(&description, DESCRIPTION_CHARS))
+        ));
+    }
+    if let Some(pic) = vod.vod_pic.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        // 本地保存的海报是站内路径，订阅器需要绝对地址
+        let url = if pic.starts_with('/') && !pic.starts_with("//") {
+            format!("{}{}", base_url, pic)
+        } else {
+            pic.to_string()
+        };
+        xml.push_str(&format!(
+            "<enclosure url=\"{}\" length=\"0\" type=\"{}\"/>",
+            escape(&url),
+            image_mime_type(&url)
+        ));
+    }
+    xml.push_str("</item>\n");
+}
+
+async fn render_feed(
+    req: &HttpRequest,
+    db: &Database,
+    site_data: &SiteDataManager,
+    type_id: Option<i32>,
+) -> HttpResponse {
+    let excluded = site_data.get_feed_excluded_type_ids().await;
+    let base_url = absolute_base_url(req, site_data).await;
+    let site_name = site_data.get_config("site_name").await.unwrap_or_default();
+    let site_description = site_data.get_config("site_description").await.unwrap_or_default();
+
+    let (mut filter, title, link) = match type_id {
+        Some(type_id) => {
+            // 不存在、未启用或被排除的分类没有订阅
+            let category = match site_data.get_category_by_id(type_id).await {
+                Some(c) if c.type_status == 1 && !excluded.contains(&type_id) => c,
+                _ => return HttpResponse::NotFound().body("Category not found"),
+            };
+            let type_ids: Vec<i32> = site_data
+                .get_descendant_type_ids(type_id)
+                .await
+                .into_iter()
+                .filter(|id| !excluded.contains(id))
+                .collect();
+            (
+                models::vod_type_filter(&type_ids),
+                format!("{} - {}", site_name, category.type_name),
+                format!("{}/list/{}", base_url, type_id),
+            )
+        }
+        None => (doc! {}, site_name.clone(), format!("{}/", base_url)),
+    };
+    filter.insert("vod_status", 1);
+    if !excluded.is_empty() {
+        filter.insert("type_id", doc! { "$nin": &excluded });
+    }
+    models::exclude_deleted_vods(&mut filter);
+
+    let find_options = FindOptions::builder()
+        .sort(doc! { "vod_pubdate": -1 })
+        .limit(FEED_SIZE)
+        .build();
+    let vods: Vec<Vod> = match db.collection::<Vod>("vods").find(filter, find_options).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(vods) => vods,
+            Err(e) => {
+                eprintln!("Failed to generate RSS feed: {}", e);
+                return HttpResponse::InternalServerError().body("Failed to generate feed");
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to generate RSS feed: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to generate feed");
+        }
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape(&title)));
+    xml.push_str(&format!("<link>{}</link>\n", escape(&link)));
+    xml.push_str(&format!("<description>{}</description>\n", escape(&site_description)));
+    xml.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", chrono::Utc::now().to_rfc2822()));
+    for vod in &vods {
+        render_item(&mut xml, vod, &base_url);
+    }
+    xml.push_str("</channel>\n</rss>\n");
+
+    HttpResponse::Ok()
+        .content_type("application/rss+xml; charset=utf-8")
+        .body(xml)
+}
+
+// GET /rss
+pub async fn rss_feed(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    render_feed(&req, &db, &site_data_manager, None).await
+}
+
+// GET /rss/{type_id}
+pub async fn category_rss_feed(
+    path: web::Path<i32>,
+    req: HttpRequest,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    render_feed(&req, &db, &site_data_manager, Some(path.into_inner())).await
+}
@@ -5,10 +5,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use std::sync::Arc;
-use chrono::{DateTime as ChronoDateTime};
+use chrono::{DateTime as ChronoDateTime, Local, Timelike};
+use cron::Schedule;
+use std::str::FromStr;
 use tokio::time::{sleep, interval};
 use futures::TryStreamExt;
-use crate::models::Collection;
+use crate::models::{parse_collect_window, Collection};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ScheduledTaskConfig {
@@ -16,14 +18,51 @@ pub struct ScheduledTaskConfig {
     pub id: Option<ObjectId>,
     pub enabled: bool,
     pub interval_hours: i32,
+    // cron 表达式（服务器本地时间），设置后按它计算下次运行时间，未设置时按 interval_hours 间隔运行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
     pub last_run: Option<DateTime>,
     pub next_run: Option<DateTime>,
     pub running_collections: Vec<String>, // 正在运行的采集源ID列表
     pub current_collection_index: usize, // 当前正在执行的采集源索引
+    // 跟随全局调度、因采集时间窗口跳过的采集源在窗口开始时重试：采集源ID(hex) -> 重试时间
+    #[serde(default)]
+    pub window_retries: HashMap<String, DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
+impl ScheduledTaskConfig {
+    // 已到重试时间的采集源ID(hex)
+    fn due_window_retries(&self, now: DateTime) -> Vec<String> {
+        self.window_retries
+            .iter()
+            .filter(|(_, retry_at)| retry_at.timestamp_millis() <= now.timestamp_millis())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+// 单个采集源的定时间隔覆盖（scheduled_source_configs 集合，按 collection_id 唯一）。
+// 没有覆盖的采集源跟随全局配置的 interval_hours / cron 一起运行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledSourceConfig {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub collection_id: ObjectId,
+    pub interval_hours: i32,
+    pub last_run: Option<DateTime>,
+    pub next_run: Option<DateTime>,
+    pub updated_at: DateTime,
+}
+
+impl ScheduledSourceConfig {
+    fn is_due(&self, now: DateTime) -> bool {
+        self.next_run
+            .is_none_or(|next_run| next_run.timestamp_millis() <= now.timestamp_millis())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TaskExecutionLog {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -39,9 +78,68 @@ pub struct TaskExecutionLog {
     pub errors: Option<String>,
 }
 
+pub const DEFAULT_INTERVAL_HOURS: i32 = 12;
+pub const MIN_INTERVAL_HOURS: i32 = 1;
+pub const MAX_INTERVAL_HOURS: i32 = 168;
+
+/// 解析 cron 表达式。支持常见的 5 段格式（分 时 日 月 周，如 "0 3 * * *" 表示每天 3 点），
+/// 也接受 cron crate 的 6/7 段格式（首段为秒）
+pub fn parse_cron(expr: &str) -> Result<Schedule, String> {
+    let expr = expr.trim();
+    let normalized = if expr.split_whitespace().count() == 5 {
+        format!("0 {}", expr)
+    } else {
+        expr.to_string()
+    };
+    let schedule = Schedule::from_str(&normalized).map_err(|e| format!("无效的 cron 表达式 \"{}\": {}", expr, e))?;
+    if schedule.upcoming(Local).next().is_none() {
+        return Err(format!("cron 表达式 \"{}\" 没有未来的执行时间", expr));
+    }
+    Ok(schedule)
+}
+
+/// 计算下次运行时间：有 cron 时取其下一个触发时间，否则为 now + interval_hours
+fn next_run_after(now: DateTime, interval_hours: i32, cron: Option<&str>) -> DateTime {
+    if let Some(schedule) = cron.and_then(|expr| parse_cron(expr).ok()) {
+        let after = ChronoDateTime::from_timestamp_millis(now.timestamp_millis())
+            .unwrap_or_default()
+            .with_timezone(&Local);
+        if let Some(next) = schedule.after(&after).next() {
+            return DateTime::from_millis(next.timestamp_millis());
+        }
+    }
+    DateTime::from_millis(now.timestamp_millis() + (interval_hours as i64) * 3600 * 1000)
+}
+
+// 当前服务器本地时间是一天中的第几分钟，用于判断采集源的采集时间窗口
+fn minute_of_day_now() -> u32 {
+    let now = Local::now();
+    now.hour() * 60 + now.minute()
+}
+
+/// 采集源的采集时间窗口下一次开始的时间（服务器本地时间）；没有窗口时返回 None
+fn next_window_start(collection: &Collection, now: DateTime) -> Option<DateTime> {
+    let (start, _) = parse_collect_window(&collection.collect_window).ok().flatten()?;
+    let now = ChronoDateTime::from_timestamp_millis(now.timestamp_millis())?.with_timezone(&Local);
+    let mut date = now.date_naive();
+    // 今天的开始时间已过时取明天；夏令时跳过的时刻没有对应的本地时间，再往后顺延一天
+    for _ in 0..3 {
+        let start_time = date
+            .and_hms_opt(start / 60, start % 60, 0)?
+            .and_local_timezone(Local)
+            .earliest();
+        if let Some(start_time) = start_time.filter(|t| *t > now) {
+            return Some(DateTime::from_millis(start_time.timestamp_millis()));
+        }
+        date = date.succ_opt()?;
+    }
+    None
+}
+
 pub struct ScheduledTaskManager {
     db: Database,
     config_collection: MongoCollection<ScheduledTaskConfig>,
+    source_config_collection: MongoCollection<ScheduledSourceConfig>,
     log_collection: MongoCollection<TaskExecutionLog>,
     is_running: Arc<RwLock<bool>>,
     current_task: Arc<RwLock<Option<String>>>,
@@ -50,11 +148,13 @@ pub struct ScheduledTaskManager {
 impl ScheduledTaskManager {
     pub fn new(db: Database) -> Self {
         let config_collection = db.collection::<ScheduledTaskConfig>("scheduled_task_configs");
+        let source_config_collection = db.collection::<ScheduledSourceConfig>("scheduled_source_configs");
         let log_collection = db.collection::<TaskExecutionLog>("task_execution_logs");
         
         Self {
             db,
             config_collection,
+            source_config_collection,
             log_collection,
             is_running: Arc::new(RwLock::new(false)),
             current_task: Arc::new(RwLock::new(None)),
@@ -69,17 +169,18 @@ impl ScheduledTaskManager {
         if existing_config.is_none() {
             // 创建默认配置
             let now = DateTime::now();
-            let next_run_millis = now.timestamp_millis() + (12 * 3600 * 1000);
-            let next_run = DateTime::from_millis(next_run_millis);
+            let next_run = next_run_after(now, DEFAULT_INTERVAL_HOURS, None);
             
             let config = ScheduledTaskConfig {
                 id: None,
                 enabled: false,
-                interval_hours: 12,
+                interval_hours: DEFAULT_INTERVAL_HOURS,
+                cron: None,
                 last_run: None,
                 next_run: Some(next_run),
                 running_collections: Vec::new(),
                 current_collection_index: 0,
+                window_retries: HashMap::new(),
                 created_at: now,
                 updated_at: now,
             };
@@ -97,12 +198,19 @@ impl ScheduledTaskManager {
         Ok(config)
     }
 
-    /// 更新配置
-    pub async fn update_config(&self, enabled: bool, interval_hours: Option<i32>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    /// 更新配置。interval_hours / cron 为 None 时保留原有设置，cron 传空字符串表示改回间隔模式
+    pub async fn update_config(&self, enabled: bool, interval_hours: Option<i32>, cron: Option<String>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         let now = DateTime::now();
+        let existing = self.get_config().await?;
+        let interval_hours = interval_hours
+            .or_else(|| existing.as_ref().map(|c| c.interval_hours))
+            .unwrap_or(DEFAULT_INTERVAL_HOURS);
+        let cron = match cron {
+            Some(expr) => Some(expr.trim().to_string()).filter(|e| !e.is_empty()),
+            None => existing.and_then(|c| c.cron),
+        };
         let next_run = if enabled {
-            let next_run_millis = now.timestamp_millis() + ((interval_hours.unwrap_or(12) as i64) * 3600 * 1000);
-            Some(DateTime::from_millis(next_run_millis))
+            Some(next_run_after(now, interval_hours, cron.as_deref()))
         } else {
             None
         };
@@ -110,11 +218,13 @@ impl ScheduledTaskManager {
         let update = doc! {
             "$set": {
                 "enabled": enabled,
-                "interval_hours": interval_hours.unwrap_or(12),
+                "interval_hours": interval_hours,
+                "cron": &cron,
                 "next_run": next_run,
                 "updated_at": now,
                 "running_collections": [],
-                "current_collection_index": 0
+                "current_collection_index": 0,
+                "window_retries": {}
             }
         };
 
@@ -122,6 +232,87 @@ impl ScheduledTaskManager {
         Ok(result.modified_count > 0)
     }
 
+    /// 获取所有采集源的间隔覆盖，按 collection_id 索引
+    pub async fn get_source_configs(&self) -> Result<HashMap<ObjectId, ScheduledSourceConfig>, Box<dyn std::error::Error + Send + Sync>> {
+        let configs: Vec<ScheduledSourceConfig> = self.source_config_collection.find(doc! {}, None).await?.try_collect().await?;
+        Ok(configs.into_iter().map(|c| (c.collection_id, c)).collect())
+    }
+
+    /// 设置单个采集源的定时间隔，下次运行时间从现在起算；interval_hours 为 None 时删除覆盖，改回跟随全局配置
+    pub async fn update_source_config(&self, collection_id: ObjectId, interval_hours: Option<i32>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(interval_hours) = interval_hours else {
+            self.source_config_collection.delete_one(doc! { "collection_id": collection_id }, None).await?;
+            return Ok(());
+        };
+
+        let now = DateTime::now();
+        let update = doc! {
+            "$set": {
+                "interval_hours": interval_hours,
+                "next_run": next_run_after(now, interval_hours, None),
+                "updated_at": now
+            },
+            "$setOnInsert": { "last_run": mongodb::bson::Bson::Null }
+        };
+        let options = mongodb::options::UpdateOptions::builder().upsert(true).build();
+        self.source_config_collection
+            .update_one(doc! { "collection_id": collection_id }, update, options)
+            .await?;
+        Ok(())
+    }
+
+    /// 有间隔覆盖的采集源运行后，按它自己的间隔计算下次运行时间
+    async fn advance_source_schedule(&self, collection: &Collection, source_configs: &HashMap<ObjectId, ScheduledSourceConfig>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(source_config) = collection.id.and_then(|id| source_configs.get(&id)) else {
+            return Ok(());
+        };
+        let now = DateTime::now();
+        let update = doc! {
+            "$set": {
+                "last_run": now,
+                "next_run": next_run_after(now, source_config.interval_hours, None),
+                "updated_at": now
+            }
+        };
+        self.source_config_collection
+            .update_one(doc! { "collection_id": source_config.collection_id }, update, None)
+            .await?;
+        Ok(())
+    }
+
+    /// 采集源因时间窗口跳过时，安排在窗口开始时重试。
+    /// 有间隔覆盖的采集源把下次运行时间改为窗口开始时间；不修改 last_run：按间隔顺延会让间隔为 24 小时倍数的采集源每次都落在窗口外，永远不会采集。
+    /// 跟随全局调度的采集源记录到全局配置的 window_retries，到时单独运行，不用等下一次全局运行
+    async fn defer_source_to_window(&self, collection: &Collection, source_configs: &HashMap<ObjectId, ScheduledSourceConfig>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(collection_id) = collection.id else {
+            return Ok(());
+        };
+        let now = DateTime::now();
+        let Some(source_config) = source_configs.get(&collection_id) else {
+            let Some(retry_at) = next_window_start(collection, now) else {
+                return Ok(());
+            };
+            let mut retry = mongodb::bson::Document::new();
+            retry.insert(format!("window_retries.{}", collection_id.to_hex()), retry_at);
+            self.config_collection
+                .update_one(doc! {}, doc! { "$set": retry }, None)
+                .await?;
+            return Ok(());
+        };
+        let next_run = next_window_start(collection, now)
+            .unwrap_or_else(|| next_run_after(now, source_config.interval_hours, None));
+        let update = doc! {
+            "$set": {
+                "next_run": next_run,
+                "updated_at": now
+            }
+        };
+        self.source_config_collection
+            .update_one(doc! { "collection_id": source_config.collection_id }, update, None)
+            .await?;
+        Ok(())
+    }
+
     /// 启动定时任务
     pub async fn start_scheduled_task(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 检查配置是否已经启用
@@ -144,7 +335,7 @@ impl ScheduledTaskManager {
         
         // 步骤2：更新配置为启用状态
         println!("🔍 步骤2：更新配置为启用状态...");
-        self.update_config(true, None).await?;
+        self.update_config(true, None, None).await?;
         
         // 步骤3：设置内存运行状态
         println!("🔍 步骤3：设置内存运行状态...");
@@ -259,7 +450,7 @@ impl ScheduledTaskManager {
         }
         
         // 无论内存状态如何，都要更新配置为禁用状态
-        self.update_config(false, None).await?;
+        self.update_config(false, None, None).await?;
         
         // 更新内存状态
         *is_running = false;
@@ -282,18 +473,27 @@ impl ScheduledTaskManager {
                 break;
             }
 
-            // 检查是否到了执行时间
+            // 检查是否到了执行时间：全局配置到期时运行所有没有间隔覆盖的采集源，
+            // 有间隔覆盖的采集源按各自的 next_run 单独判断，因时间窗口跳过的采集源按 window_retries 单独重试
             if let Ok(Some(config)) = self.get_config().await {
                 if config.enabled {
-                    if let Some(next_run) = config.next_run {
-                        let now = ChronoDateTime::from_timestamp(DateTime::now().timestamp_millis() as i64 / 1000, 0).unwrap();
-                        let next_run_time = ChronoDateTime::from_timestamp(next_run.timestamp_millis() as i64 / 1000, 0).unwrap();
-                        
-                        if now >= next_run_time {
-                            // 执行采集任务
-                            if let Err(e) = self.execute_scheduled_collection(&config).await {
-                                eprintln!("❌ 执行定时采集任务失败: {}", e);
-                            }
+                    let now = DateTime::now();
+                    let global_due = config
+                        .next_run
+                        .is_some_and(|next_run| next_run.timestamp_millis() <= now.timestamp_millis());
+                    let source_configs = match self.get_source_configs().await {
+                        Ok(source_configs) => source_configs,
+                        Err(e) => {
+                            eprintln!("❌ 获取采集源定时配置失败: {}", e);
+                            HashMap::new()
+                        }
+                    };
+
+                    let retry_due = !config.due_window_retries(now).is_empty();
+                    if global_due || retry_due || source_configs.values().any(|c| c.is_due(now)) {
+                        // 执行采集任务
+                        if let Err(e) = self.execute_scheduled_collection(&config, global_due, &source_configs).await {
+                            eprintln!("❌ 执行定时采集任务失败: {}", e);
                         }
                     }
                 }
@@ -333,6 +533,9 @@ impl ScheduledTaskManager {
             return Ok(());
         }
 
+        // 立即执行会运行所有启用的采集源，有间隔覆盖的采集源也从这次运行起重新计时
+        let source_configs = self.get_source_configs().await?;
+
         // 按顺序执行采集任务
         let total_collections = collections.len();
         let mut total_videos_collected = 0;
@@ -340,10 +543,21 @@ impl ScheduledTaskManager {
         let mut failed_collections = 0;
 
         for (index, collection) in collections.iter().enumerate() {
+            if self.skip_outside_window(collection).await? {
+                self.defer_source_to_window(collection, &source_configs).await?;
+                continue;
+            }
+
+            // 与手动采集共用任务登记，同一采集源已有运行中的任务时本轮跳过
+            let task_id = ObjectId::new().to_hex();
+            if self.skip_busy_source(collection, &task_id).await? {
+                self.advance_source_schedule(collection, &source_configs).await?;
+                continue;
+            }
+
             println!("📥 开始采集第 {}/{} 个采集源: {}", index + 1, total_collections, collection.collect_name);
             
             // 记录任务开始
-            let task_id = ObjectId::new().to_hex();
             let log_entry = TaskExecutionLog {
                 id: None,
                 task_id: task_id.clone(),
@@ -374,7 +588,7 @@ impl ScheduledTaskManager {
             }
 
             // 执行采集（这里需要调用实际的采集逻辑）
-            match self.collect_videos_from_source(&collection).await {
+            match self.collect_videos_from_source(&collection, &task_id).await {
                 Ok(videos_collected) => {
                     total_videos_collected += videos_collected;
                     successful_collections += 1;
@@ -410,6 +624,8 @@ impl ScheduledTaskManager {
                 }
             }
 
+            self.advance_source_schedule(collection, &source_configs).await?;
+
             // 只有当前任务ID匹配时才清除（避免清除立即执行的任务ID）
             let current_task = self.current_task.read().await;
             if let Some(ref current_id) = *current_task {
@@ -429,23 +645,46 @@ impl ScheduledTaskManager {
         Ok(())
     }
 
-    /// 执行定时采集任务
-    async fn execute_scheduled_collection(&self, config: &ScheduledTaskConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// 执行定时采集任务。global_due 为 true 时运行所有没有间隔覆盖的采集源并更新全局的下次运行时间；
+    /// 有间隔覆盖的采集源只在自己的 next_run 到期时运行，window_retries 中到期的采集源也在这次运行
+    async fn execute_scheduled_collection(
+        &self,
+        config: &ScheduledTaskConfig,
+        global_due: bool,
+        source_configs: &HashMap<ObjectId, ScheduledSourceConfig>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("🔄 开始执行定时采集任务");
 
-        // 获取所有启用的采集源
+        // 获取所有启用且已到期的采集源
         let collections_collection = self.db.collection::<Collection>("collections");
         let filter = doc! { "collect_status": 1 };
         let mut cursor = collections_collection.find(filter, None).await?;
         
+        let now = DateTime::now();
+        let due_retries = config.due_window_retries(now);
         let mut collections: Vec<Collection> = Vec::new();
         while let Ok(Some(collection)) = cursor.try_next().await {
-            collections.push(collection);
+            let due = match collection.id.and_then(|id| source_configs.get(&id)) {
+                Some(source_config) => source_config.is_due(now),
+                None => global_due || collection.id.is_some_and(|id| due_retries.contains(&id.to_hex())),
+            };
+            if due {
+                collections.push(collection);
+            }
         }
 
-        if collections.is_empty() {
-            println!("⚠️ 没有找到启用的采集源");
-            println!("🔍 调试信息: 查询条件为 collect_status: 1");
+        // 到期的重试在这次运行中处理，先移除（包括已删除或禁用的采集源）；再次落在窗口外时会重新安排
+        if !due_retries.is_empty() {
+            let mut unset = mongodb::bson::Document::new();
+            for id in &due_retries {
+                unset.insert(format!("window_retries.{}", id), "");
+            }
+            self.config_collection
+                .update_one(doc! {}, doc! { "$unset": unset }, None)
+                .await?;
+        }
+
+        if collections.is_empty() && !global_due {
             return Ok(());
         }
 
@@ -461,11 +700,21 @@ impl ScheduledTaskManager {
                 println!("⏹️ 定时任务已停止，中断采集");
                 break;
             }
+            if self.skip_outside_window(collection).await? {
+                self.defer_source_to_window(collection, source_configs).await?;
+                continue;
+            }
+
+            // 与手动采集共用任务登记，同一采集源已有运行中的任务时本轮跳过
+            let task_id = ObjectId::new().to_hex();
+            if self.skip_busy_source(collection, &task_id).await? {
+                self.advance_source_schedule(collection, source_configs).await?;
+                continue;
+            }
 
             println!("📥 开始采集第 {}/{} 个采集源: {}", index + 1, total_collections, collection.collect_name);
             
             // 记录任务开始
-            let task_id = ObjectId::new().to_hex();
             let log_entry = TaskExecutionLog {
                 id: None,
                 task_id: task_id.clone(),
@@ -496,7 +745,7 @@ impl ScheduledTaskManager {
             }
 
             // 执行采集（这里需要调用实际的采集逻辑）
-            match self.collect_videos_from_source(&collection).await {
+            match self.collect_videos_from_source(&collection, &task_id).await {
                 Ok(videos_collected) => {
                     total_videos_collected += videos_collected;
                     successful_collections += 1;
@@ -532,6 +781,8 @@ impl ScheduledTaskManager {
                 }
             }
 
+            self.advance_source_schedule(collection, source_configs).await?;
+
             // 只有当前任务ID匹配时才清除（避免清除立即执行的任务ID）
             let current_task = self.current_task.read().await;
             if let Some(ref current_id) = *current_task {
@@ -545,19 +796,20 @@ impl ScheduledTaskManager {
             sleep(tokio::time::Duration::from_secs(5)).await;
         }
 
-        // 更新配置中的执行时间
-        let now = DateTime::now();
-        let next_run_millis = now.timestamp_millis() + ((config.interval_hours as i64) * 3600 * 1000);
-        let next_run = DateTime::from_millis(next_run_millis);
+        // 更新配置中的执行时间（只有全局调度到期的这次运行才推进）
+        if global_due {
+            let now = DateTime::now();
+            let next_run = next_run_after(now, config.interval_hours, config.cron.as_deref());
 
-        let update = doc! {
-            "$set": {
-                "last_run": now,
-                "next_run": next_run,
-                "updated_at": now
-            }
-        };
-        self.config_collection.update_one(doc! {}, update, None).await?;
+            let update = doc! {
+                "$set": {
+                    "last_run": now,
+                    "next_run": next_run,
+                    "updated_at": now
+                }
+            };
+            self.config_collection.update_one(doc! {}, update, None).await?;
+        }
 
         println!("🎉 定时采集任务完成: 成功 {}/{}, 共获取 {} 个视频", 
             successful_collections, total_collections, total_videos_collected);
@@ -565,20 +817,80 @@ impl ScheduledTaskManager {
         Ok(())
     }
 
+    /// 采集源设置了采集时间窗口且当前不在窗口内时，记录一条 skipped 日志并返回 true。
+    /// 跳过的采集源由 defer_source_to_window 安排在窗口开始时重试；只在开始采集前检查，已开始的采集源即使超出窗口也会采集完
+    async fn skip_outside_window(&self, collection: &Collection) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        if collection.in_collect_window(minute_of_day_now()) {
+            return Ok(false);
+        }
+
+        let now = DateTime::now();
+        let retry_at = next_window_start(collection, now)
+            .and_then(|t| ChronoDateTime::from_timestamp_millis(t.timestamp_millis()))
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string());
+        let message = match retry_at {
+            Some(retry_at) => format!(
+                "当前不在采集时间窗口 {} 内，推迟到窗口开始时（{}）重试",
+                collection.collect_window.trim(),
+                retry_at
+            ),
+            None => format!("当前不在采集时间窗口 {} 内，推迟到下次定时运行", collection.collect_window.trim()),
+        };
+        println!("⏭️ 跳过采集源 {}: {}", collection.collect_name, message);
+        let log_entry = TaskExecutionLog {
+            id: None,
+            task_id: ObjectId::new().to_hex(),
+            collection_id: collection.id.unwrap_or_default().to_hex(),
+            collection_name: collection.collect_name.clone(),
+            status: "skipped".to_string(),
+            started_at: now,
+            completed_at: Some(now),
+            message: Some(message),
+            videos_collected: None,
+            errors: None,
+        };
+        self.log_collection.insert_one(&log_entry, None).await?;
+        Ok(true)
+    }
+
+    /// 为采集源登记任务（与手动采集相同的 reserve_collect_task），同一采集源已有运行中的任务时
+    /// 记录一条 skipped 日志并返回 true，避免手动和定时任务同时采集同一个源
+    async fn skip_busy_source(&self, collection: &Collection, task_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Err(existing_task_id) = crate::collect_handlers::reserve_collect_task(&collection.collect_name, task_id).await else {
+            return Ok(false);
+        };
+
+        let message = format!("采集源已有运行中的任务 {}，本轮跳过", existing_task_id);
+        println!("⏭️ 跳过采集源 {}: {}", collection.collect_name, message);
+        let now = DateTime::now();
+        let log_entry = TaskExecutionLog {
+            id: None,
+            task_id: task_id.to_string(),
+            collection_id: collection.id.unwrap_or_default().to_hex(),
+            collection_name: collection.collect_name.clone(),
+            status: "skipped".to_string(),
+            started_at: now,
+            completed_at: Some(now),
+            message: Some(message),
+            videos_collected: None,
+            errors: None,
+        };
+        self.log_collection.insert_one(&log_entry, None).await?;
+        Ok(true)
+    }
+
     /// 从指定采集源采集视频（调用真实的采集逻辑）
-    async fn collect_videos_from_source(&self, collection: &Collection) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        use crate::collect_handlers::start_batch_collect;
+    /// task_id 与执行日志相同，采集记录（collection_runs）可据此关联到对应的执行日志
+    async fn collect_videos_from_source(&self, collection: &Collection, task_id: &str) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+        use crate::collect_handlers::{start_batch_collect, TRIGGER_SCHEDULED};
         
         println!("🔄 正在从采集源采集视频: {}", collection.collect_name);
         
-        // 生成任务ID
-        let task_id = ObjectId::new().to_hex();
-        
         // 调用真实的批量采集函数，专门采集当天数据（24小时内）
-        match start_batch_collect(&self.db, collection.clone(), Some("24".to_string()), task_id.clone()).await {
+        match start_batch_collect(&self.db, collection.clone(), Some("24".to_string()), task_id.to_string(), TRIGGER_SCHEDULED).await {
             Ok(_) => {
                 // 获取采集结果
-                let videos_collected = self.get_videos_collected_count(&task_id).await.unwrap_or(0);
+                let videos_collected = self.get_videos_collected_count(task_id).await.unwrap_or(0);
                 println!("✅ 采集完成: {} (获取 {} 个视频)", collection.collect_name, videos_collected);
                 Ok(videos_collected)
             }
@@ -608,6 +920,7 @@ impl ScheduledTaskManager {
         let config_enabled = if let Some(config) = self.get_config().await? {
             status.insert("enabled".to_string(), serde_json::Value::Bool(config.enabled));
             status.insert("interval_hours".to_string(), serde_json::Value::Number(serde_json::Number::from(config.interval_hours)));
+            status.insert("cron".to_string(), config.cron.clone().map_or(serde_json::Value::Null, serde_json::Value::String));
             status.insert("last_run".to_string(), serde_json::Value::String(
                 config.last_run.map_or("从未运行".to_string(), |dt| format!("{}", dt.timestamp_millis()))
             ));
@@ -619,6 +932,17 @@ impl ScheduledTaskManager {
             false
         };
 
+        // 各采集源的间隔覆盖
+        let source_configs = self.get_source_configs().await?;
+        status.insert("source_overrides".to_string(), serde_json::Value::Array(
+            source_configs.into_values().map(|c| serde_json::json!({
+                "collection_id": c.collection_id.to_hex(),
+                "interval_hours": c.interval_hours,
+                "last_run": c.last_run.map(|dt| format!("{}", dt.timestamp_millis())),
+                "next_run": c.next_run.map(|dt| format!("{}", dt.timestamp_millis()))
+            })).collect()
+        ));
+
         // 获取当前运行状态：检查配置状态、内存状态和当前任务
         let memory_is_running = *self.is_running.read().await;
         let current_task = self.current_task.read().await;
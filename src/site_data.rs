@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 use mongodb::Database;
@@ -7,6 +8,14 @@ use crate::models::{Type, Config};
 use futures::stream::TryStreamExt;
 use mongodb::bson::doc;
 
+// 视频没有海报时使用的默认图片，可通过 default_poster 配置修改
+pub const DEFAULT_POSTER: &str = "/static/images/no-poster.png";
+
+// 站点数据缓存的自动刷新间隔（秒），可通过 cache_ttl_seconds 配置修改，0 表示只在启动和手动刷新时加载
+pub const DEFAULT_CACHE_TTL_SECONDS: u64 = 300;
+// 关闭自动刷新时，每隔这么久检查一次配置是否重新开启
+const AUTO_REFRESH_DISABLED_RECHECK: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NavigationCategory {
     pub category: Type,
@@ -20,6 +29,7 @@ pub struct SiteData {
     pub all_categories_map: HashMap<i32, Type>,
     pub configs: HashMap<String, String>,
     pub last_updated: std::time::Instant,
+    pub last_refreshed: chrono::DateTime<chrono::Utc>,
 }
 
 impl SiteData {
@@ -30,6 +40,7 @@ impl SiteData {
             all_categories_map: HashMap::new(),
             configs: HashMap::new(),
             last_updated: std::time::Instant::now(),
+            last_refreshed: chrono::Utc::now(),
         }
     }
 }
@@ -51,92 +62,69 @@ impl SiteDataManager {
     /// 初始化并加载所有数据
     pub async fn initialize(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("🔧 正在初始化站点数据缓存...");
-        
-        // 加载分类数据
-        self.load_categories().await?;
-        
-        // 加载配置数据
-        self.load_configs().await?;
-        
+        self.reload().await?;
         println!("✅ 站点数据缓存初始化完成");
         Ok(())
     }
 
-    /// 加载分类数据
-    async fn load_categories(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let type_collection = self.db.collection::<Type>("types");
-        
+    /// 从数据库读取分类和配置，构建完整的 SiteData；不持有锁
+    async fn load(&self) -> Result<SiteData, Box<dyn std::error::Error + Send + Sync>> {
         // 获取所有分类
-        let all_categories: Vec<Type> = type_collection
+        let all_categories: Vec<Type> = self
+            .db
+            .collection::<Type>("types")
             .find(None, None)
             .await?
             .try_collect()
-            .await
-            .unwrap_or_else(|_| vec![]);
-        
-        // 获取顶级分类用于导航
-        let nav_categories: Vec<Type> = all_categories
+            .await?;
+
+        // 构建导航分类结构：启用的顶级分类及其启用的子分类
+        let navigation_categories = all_categories
             .iter()
             .filter(|cat| cat.type_pid == 0 && cat.type_status == 1)
-            .cloned()
-            .collect();
-        
-        // 构建导航分类结构
-        let mut navigation_categories = Vec::new();
-        let mut all_categories_map = HashMap::new();
-        
-        for category in nav_categories {
-            // 获取子分类
-            let sub_categories: Vec<Type> = all_categories
-                .iter()
-                .filter(|cat| cat.type_pid == category.type_id && cat.type_status == 1)
-                .cloned()
-                .collect();
-            
-            navigation_categories.push(NavigationCategory {
+            .map(|category| NavigationCategory {
                 category: category.clone(),
-                sub_categories,
-            });
-        }
-        
+                sub_categories: all_categories
+                    .iter()
+                    .filter(|cat| cat.type_pid == category.type_id && cat.type_status == 1)
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+
         // 构建分类映射表
-        for category in &all_categories {
-            all_categories_map.insert(category.type_id, category.clone());
-        }
-        
-        // 更新数据
-        let mut data = self.data.write().await;
-        data.navigation_categories = navigation_categories;
-        data.all_categories = all_categories;
-        data.all_categories_map = all_categories_map;
-        data.last_updated = std::time::Instant::now();
-        
-        Ok(())
-    }
+        let all_categories_map = all_categories
+            .iter()
+            .map(|category| (category.type_id, category.clone()))
+            .collect();
 
-    /// 加载配置数据
-    async fn load_configs(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let config_collection = self.db.collection::<Config>("configs");
-        
-        let configs: Vec<Config> = config_collection
+        let configs: Vec<Config> = self
+            .db
+            .collection::<Config>("configs")
             .find(None, None)
             .await?
             .try_collect()
-            .await
-            .unwrap_or_else(|_| vec![]);
-        
-        // 配置加载完成
-        
-        let mut config_map = HashMap::new();
-        for config in configs {
-            config_map.insert(config.config_key, config.config_value);
-        }
-        
-        // 更新数据
-        let mut data = self.data.write().await;
-        data.configs = config_map;
-        data.last_updated = std::time::Instant::now();
-        
+            .await?;
+        let configs = configs
+            .into_iter()
+            .map(|config| (config.config_key, config.config_value))
+            .collect();
+
+        Ok(SiteData {
+            navigation_categories,
+            all_categories,
+            all_categories_map,
+            configs,
+            last_updated: std::time::Instant::now(),
+            last_refreshed: chrono::Utc::now(),
+        })
+    }
+
+    /// 重新加载并整体替换缓存：先在锁外构建新数据，写锁只用于替换，读取方不会看到分类和配置不一致的中间状态。
+    /// 读取数据库失败时保留原有缓存
+    async fn reload(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let data = self.load().await?;
+        *self.data.write().await = data;
         Ok(())
     }
 
@@ -158,12 +146,93 @@ impl SiteDataManager {
         data.all_categories_map.get(&type_id).cloned()
     }
 
+    /// 获取分类面包屑：从顶级分类到指定分类的有序链（如 电影 > 动作片）
+    /// 父分类缺失时在此截断，链上出现重复分类时停止以避免环
+    pub async fn get_category_breadcrumb(&self, type_id: i32) -> Vec<Type> {
+        let data = self.data.read().await;
+        let mut chain = Vec::new();
+        let mut current = data.all_categories_map.get(&type_id);
+
+        while let Some(category) = current {
+            if chain.iter().any(|c: &Type| c.type_id == category.type_id) {
+                break;
+            }
+            chain.push(category.clone());
+            if category.type_pid == 0 {
+                break;
+            }
+            current = data.all_categories_map.get(&category.type_pid);
+        }
+
+        chain.reverse();
+        chain
+    }
+
+    /// 分类本身或任一上级分类标记为成人内容时返回 true
+    pub async fn is_adult_category(&self, type_id: i32) -> bool {
+        self.get_category_breadcrumb(type_id)
+            .await
+            .iter()
+            .any(|c| c.is_adult)
+    }
+
+    /// 获取所有成人分类ID（包含成人分类下的子分类），用于 API 过滤
+    pub async fn get_adult_type_ids(&self) -> Vec<i32> {
+        let data = self.data.read().await;
+        let adult_ids: Vec<i32> = data
+            .all_categories
+            .iter()
+            .filter(|c| c.is_adult)
+            .map(|c| c.type_id)
+            .collect();
+        expand_descendants(&data.all_categories, adult_ids)
+    }
+
+    /// 获取 sitemap、RSS 等对外输出中需要排除的分类ID（包含子分类）：
+    /// feed_excluded_type_ids 中的分类总是排除；成人分类默认排除，feed_include_adult=1 时保留
+    pub async fn get_feed_excluded_type_ids(&self) -> Vec<i32> {
+        let data = self.data.read().await;
+        let mut ids = data
+            .configs
+            .get("feed_excluded_type_ids")
+            .and_then(|v| parse_type_id_list(v).ok())
+            .unwrap_or_default();
+        let include_adult = data
+            .configs
+            .get("feed_include_adult")
+            .map(|v| matches!(v.trim(), "1" | "true" | "on"))
+            .unwrap_or(false);
+        if !include_adult {
+            for category in data.all_categories.iter().filter(|c| c.is_adult) {
+                if !ids.contains(&category.type_id) {
+                    ids.push(category.type_id);
+                }
+            }
+        }
+        expand_descendants(&data.all_categories, ids)
+    }
+
+    /// 获取分类自身及其所有下级分类ID（不区分启用状态）
+    pub async fn get_descendant_type_ids(&self, type_id: i32) -> Vec<i32> {
+        let data = self.data.read().await;
+        expand_descendants(&data.all_categories, vec![type_id])
+    }
+
     /// 获取配置值
     pub async fn get_config(&self, key: &str) -> Option<String> {
         let data = self.data.read().await;
         data.configs.get(key).cloned()
     }
 
+    /// 获取默认海报地址（default_poster 配置，未设置或为空时使用内置图片）
+    pub async fn get_default_poster(&self) -> String {
+        self.get_config("default_poster")
+            .await
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_POSTER.to_string())
+    }
+
     /// 获取所有配置
     pub async fn get_all_configs(&self) -> HashMap<String, String> {
         let data = self.data.read().await;
@@ -173,12 +242,58 @@ impl SiteDataManager {
     /// 刷新数据缓存
     pub async fn refresh(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("🔄 正在刷新站点数据缓存...");
-        self.load_categories().await?;
-        self.load_configs().await?;
+        self.reload().await?;
         println!("✅ 站点数据缓存刷新完成");
         Ok(())
     }
 
+    /// 缓存最近一次成功加载的时间
+    pub async fn last_refreshed(&self) -> chrono::DateTime<chrono::Utc> {
+        self.data.read().await.last_refreshed
+    }
+
+    /// 距最近一次成功加载经过的时间
+    pub async fn cache_age(&self) -> Duration {
+        self.data.read().await.last_updated.elapsed()
+    }
+
+    /// 自动刷新间隔（cache_ttl_seconds），为 0 时不自动刷新
+    async fn auto_refresh_interval(&self) -> Duration {
+        let secs = self
+            .get_config("cache_ttl_seconds")
+            .await
+            .and_then(|v| parse_cache_ttl_seconds(&v).ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECONDS);
+        Duration::from_secs(secs)
+    }
+
+    /// 启动后台任务，缓存存在超过 cache_ttl_seconds 后自动重新加载，后台修改分类和配置后无需手动刷新。
+    /// 手动刷新会重新计时；间隔取自缓存中的配置，修改后在下一次刷新时生效
+    pub fn spawn_auto_refresh(&self) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let interval = manager.auto_refresh_interval().await;
+                if interval.is_zero() {
+                    tokio::time::sleep(AUTO_REFRESH_DISABLED_RECHECK).await;
+                    continue;
+                }
+                let age = manager.cache_age().await;
+                if age < interval {
+                    tokio::time::sleep(interval - age).await;
+                    continue;
+                }
+                match manager.reload().await {
+                    Ok(()) => tracing::debug!("站点数据缓存已自动刷新"),
+                    Err(e) => {
+                        tracing::warn!("站点数据缓存自动刷新失败: {}", e);
+                        tokio::time::sleep(interval).await;
+                    }
+                }
+            }
+        });
+    }
+
     /// 获取缓存统计信息
     pub async fn get_stats(&self) -> HashMap<String, serde_json::Value> {
         let data = self.data.read().await;
@@ -199,4 +314,57 @@ impl SiteDataManager {
         let data = self.data.read().await;
         data.last_updated.elapsed().as_secs() > max_age_seconds
     }
-}
\ No newline at end of file
+}
+
+/// 校验 cache_ttl_seconds 配置：非负整数秒数，0 表示不自动刷新
+pub fn parse_cache_ttl_seconds(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "cache_ttl_seconds 必须是非负整数（秒），0 表示不自动刷新".to_string())
+}
+
+/// 解析逗号分隔的分类ID列表（如 "5,12"），空字符串表示不排除任何分类
+pub fn parse_type_id_list(value: &str) -> Result<Vec<i32>, String> {
+    let mut ids = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let id = part
+            .parse::<i32>()
+            .map_err(|_| format!("无效的分类ID: {}，需为逗号分隔的整数", part))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// 原样输出到前台页面的自定义 HTML 配置（统计代码、自定义 CSS 等），只允许管理员组修改
+pub const CUSTOM_HTML_CONFIG_KEYS: [&str; 2] = ["custom_head_html", "custom_footer_html"];
+pub const MAX_CUSTOM_HTML_BYTES: usize = 64 * 1024;
+
+/// 校验自定义 HTML 配置的大小，避免超大配置拖慢每个页面的渲染
+pub fn validate_custom_html(value: &str) -> Result<(), String> {
+    if value.len() > MAX_CUSTOM_HTML_BYTES {
+        return Err(format!(
+            "自定义 HTML 不能超过 {} KB",
+            MAX_CUSTOM_HTML_BYTES / 1024
+        ));
+    }
+    Ok(())
+}
+
+// 从 roots 逐层向下展开子分类，直到没有新的分类加入；返回结果包含 roots 本身
+fn expand_descendants(categories: &[Type], mut ids: Vec<i32>) -> Vec<i32> {
+    loop {
+        let children: Vec<i32> = categories
+            .iter()
+            .filter(|c| ids.contains(&c.type_pid) && !ids.contains(&c.type_id))
+            .map(|c| c.type_id)
+            .collect();
+        if children.is_empty() {
+            break;
+        }
+        ids.extend(children);
+    }
+    ids
+}
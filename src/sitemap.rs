@@ -0,0 +1,246 @@
+use crate::cache;
+use crate::client_ip::request_scheme_and_host;
+use crate::models;
+use crate::site_data::SiteDataManager;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::options::FindOptions;
+use mongodb::Database;
+use std::time::Duration;
+
+// 站点地图：/sitemap.xml 为索引，列出 /sitemap-1.xml、/sitemap-2.xml……，每个子地图最多 50000 个地址。
+// 子地图依次包含启用分类的 /list/{type_id}（只在第一个）和上架视频的 /detail/{id}（lastmod 为 vod_pubdate），
+// 视频按 _id 排序，新视频总是追加在最后一个子地图，已有子地图的内容保持稳定。
+// 排除的分类见 SiteDataManager::get_feed_excluded_type_ids；配置了 site_url 时生成结果缓存 sitemap_cache_ttl 秒。
+
+// sitemaps.org 协议规定单个地图的地址上限
+const URLS_PER_SITEMAP: u64 = 50_000;
+pub const DEFAULT_SITEMAP_CACHE_TTL: u64 = 3600;
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+const SITEMAP_NS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+// 校验 sitemap_cache_ttl 配置：非负整数秒数，0 表示不缓存
+pub fn parse_sitemap_cache_ttl(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "sitemap_cache_ttl 必须是非负整数（秒），0 表示不缓存".to_string())
+}
+
+// 地址取自请求的域名（未配置 site_url）时不缓存：缓存键不含域名，一个伪造 Host 的请求会让之后所有爬虫拿到指向其他域名的地图
+async fn cache_ttl(site_data: &SiteDataManager) -> Duration {
+    if configured_base_url(site_data).await.is_none() {
+        return Duration::ZERO;
+    }
+    let secs = site_data
+        .get_config("sitemap_cache_ttl")
+        .await
+        .and_then(|v| parse_sitemap_cache_ttl(&v).ok())
+        .unwrap_or(DEFAULT_SITEMAP_CACHE_TTL);
+    Duration::from_secs(secs)
+}
+
+// 网站配置 site_url（不带结尾的 /），未配置时为 None
+async fn configured_base_url(site_data: &SiteDataManager) -> Option<String> {
+    site_data
+        .get_config("site_url")
+        .await
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+// 站点地图、RSS 中的地址前缀（不带结尾的 /）。两者都要求绝对地址，未配置 site_url 时使用本次请求的协议和域名
+// （X-Forwarded-* 只在来自受信任代理时采用，见 client_ip::request_scheme_and_host）
+pub async fn absolute_base_url(req: &HttpRequest, site_data: &SiteDataManager) -> String {
+    match configured_base_url(site_data).await {
+        Some(url) => url,
+        None => {
+            let (scheme, host) = request_scheme_and_host(req);
+            format!("{}://{}", scheme, host)
+        }
+    }
+}
+
+// 生成地图所需的站点数据
+struct SitemapSource {
+    base_url: String,
+    list_type_ids: Vec<i32>,
+    vod_filter: Document,
+}
+
+impl SitemapSource {
+    async fn load(req: &HttpRequest, site_data: &SiteDataManager) -> Self {
+        let base_url = absolute_base_url(req, site_data).await;
+
+        let excluded = site_data.get_feed_excluded_type_ids().await;
+        let list_type_ids = site_data
+            .get_all_categories()
+            .await
+            .into_iter()
+            .filter(|t| t.type_status == 1 && !excluded.contains(&t.type_id))
+            .map(|t| t.type_id)
+            .collect();
+
+        let mut vod_filter = doc! { "vod_status": 1 };
+        if !excluded.is_empty() {
+            vod_filter.insert("type_id", doc! { "$nin": excluded });
+        }
+        models::exclude_deleted_vods(&mut vod_filter);
+
+        SitemapSource { base_url, list_type_ids, vod_filter }
+    }
+
+    async fn page_count(&self, db: &Database) -> mongodb::error::Result<u64> {
+        let vods = db
+            .collection::<Document>("vods")
+            .count_documents(self.vod_filter.clone(), None)
+            .await?;
+        let urls = self.list_type_ids.len() as u64 + vods;
+        Ok(urls.div_ceil(URLS_PER_SITEMAP).max(1))
+    }
+
+    // 第 page 个子地图（从 1 开始）；分类地址排在最前，占用全局序号 [0, 分类数)
+    async fn render_page(&self, db: &Database, page: u64) -> mongodb::error::Result<String> {
+        let start = (page - 1) * URLS_PER_SITEMAP;
+        let end = start + URLS_PER_SITEMAP;
+        let categories = self.list_type_ids.len() as u64;
+
+        let mut xml = format!("{}<urlset xmlns=\"{}\">\n", XML_HEADER, SITEMAP_NS);
+        for type_id in self.list_type_ids.iter().take(end as usize).skip(start as usize) {
+            push_url(&mut xml, &format!("{}/list/{}", self.base_url, type_id), None);
+        }
+
+        let vod_start = start.max(categories);
+        if vod_start < end {
+            let options = FindOptions::builder()
+                .projection(doc! { "_id": 1, "vod_pubdate": 1 })
+                .sort(doc! { "_id": 1 })
+                .skip(Some(vod_start - categories))
+                .limit(Some((end - vod_start) as i64))
+                .build();
+            let mut cursor = db
+                .collection::<Document>("vods")
+                .find(self.vod_filter.clone(), options)
+                .await?;
+            while let Some(vod) = cursor.try_next().await? {
+                let Ok(id) = vod.get_object_id("_id") else {
+                    continue;
+                };
+                let lastmod = vod
+                    .get_datetime("vod_pubdate")
+                    .ok()
+                    .and_then(|t| chrono::DateTime::from_timestamp_millis(t.timestamp_millis()))
+                    .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+                push_url(
+                    &mut xml,
+                    &format!("{}/detail/{}", self.base_url, id.to_hex()),
+                    lastmod.as_deref(),
+                );
+            }
+        }
+
+        xml.push_str("</urlset>\n");
+        Ok(xml)
+    }
+
+    fn render_index(&self, pages: u64) -> String {
+        let mut xml = format!("{}<sitemapindex xmlns=\"{}\">\n", XML_HEADER, SITEMAP_NS);
+        for page in 1..=pages {
+            let loc = format!("{}/sitemap-{}.xml", self.base_url, page);
+            xml.push_str(&format!(
+                "<sitemap><loc>{}</loc></sitemap>\n",
+                quick_xml::escape::escape(loc.as_str())
+            ));
+        }
+        xml.push_str("</sitemapindex>\n");
+        xml
+    }
+}
+
+fn push_url(xml: &mut String, loc: &str, lastmod: Option<&str>) {
+    xml.push_str("<url><loc>");
+    xml.push_str(&quick_xml::escape::escape(loc));
+    xml.push_str("</loc>");
+    if let Some(lastmod) = lastmod {
+        xml.push_str("<lastmod>");
+        xml.push_str(lastmod);
+        xml.push_str("</lastmod>");
+    }
+    xml.push_str("</url>\n");
+}
+
+fn xml_response(xml: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .body(xml)
+}
+
+// GET /sitemap.xml
+pub async fn sitemap_index(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let ttl = cache_ttl(&site_data_manager).await;
+    if !ttl.is_zero() {
+        if let Some(xml) = cache::get_cached(cache::SCOPE_SITEMAP, "index").await {
+            return xml_response(xml);
+        }
+    }
+
+    let source = SitemapSource::load(&req, &site_data_manager).await;
+    let xml = match source.page_count(&db).await {
+        Ok(pages) => source.render_index(pages),
+        Err(e) => {
+            eprintln!("Failed to generate sitemap index: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to generate sitemap");
+        }
+    };
+    if !ttl.is_zero() {
+        cache::set_cached(cache::SCOPE_SITEMAP, "index", xml.clone(), ttl).await;
+    }
+    xml_response(xml)
+}
+
+// GET /sitemap-{page}.xml
+pub async fn sitemap_page(
+    path: web::Path<u64>,
+    req: HttpRequest,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    let page = path.into_inner();
+    let cache_key = page.to_string();
+    let ttl = cache_ttl(&site_data_manager).await;
+    if !ttl.is_zero() {
+        if let Some(xml) = cache::get_cached(cache::SCOPE_SITEMAP, &cache_key).await {
+            return xml_response(xml);
+        }
+    }
+
+    let source = SitemapSource::load(&req, &site_data_manager).await;
+    let pages = match source.page_count(&db).await {
+        Ok(pages) => pages,
+        Err(e) => {
+            eprintln!("Failed to generate sitemap {}: {}", page, e);
+            return HttpResponse::InternalServerError().body("Failed to generate sitemap");
+        }
+    };
+    if page == 0 || page > pages {
+        return HttpResponse::NotFound().body("Sitemap not found");
+    }
+
+    let xml = match source.render_page(&db, page).await {
+        Ok(xml) => xml,
+        Err(e) => {
+            eprintln!("Failed to generate sitemap {}: {}", page, e);
+            return HttpResponse::InternalServerError().body("Failed to generate sitemap");
+        }
+    };
+    if !ttl.is_zero() {
+        cache::set_cached(cache::SCOPE_SITEMAP, &cache_key, xml.clone(), ttl).await;
+    }
+    xml_response(xml)
+}
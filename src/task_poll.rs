@@ -0,0 +1,54 @@
+use crate::site_data::SiteDataManager;
+use serde::Serialize;
+
+pub const DEFAULT_TASK_POLL_INTERVAL_MS: u64 = 3000;
+// 低于 500ms 的轮询对大任务量的部署没有意义，只会增加进度接口压力
+pub const MIN_TASK_POLL_INTERVAL_MS: u64 = 500;
+pub const MAX_TASK_POLL_INTERVAL_MS: u64 = 60_000;
+
+// 后台任务页面的轮询设置，注入到页面上下文供脚本使用
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskPollSettings {
+    pub interval_ms: u64,
+    pub auto_refresh: bool,
+}
+
+// 校验 task_poll_interval_ms 配置：500-60000 的整数（毫秒）
+pub fn parse_task_poll_interval_ms(value: &str) -> Result<u64, String> {
+    let ms = value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "task_poll_interval_ms 必须是整数（毫秒）".to_string())?;
+    if !(MIN_TASK_POLL_INTERVAL_MS..=MAX_TASK_POLL_INTERVAL_MS).contains(&ms) {
+        return Err(format!(
+            "task_poll_interval_ms 必须在 {}-{} 之间",
+            MIN_TASK_POLL_INTERVAL_MS, MAX_TASK_POLL_INTERVAL_MS
+        ));
+    }
+    Ok(ms)
+}
+
+// 校验 task_auto_refresh 配置：1/0（也接受 true/false、on/off）
+pub fn parse_task_auto_refresh(value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => Err("task_auto_refresh 必须是 1 或 0".to_string()),
+    }
+}
+
+// 读取轮询设置；数据库中已有的越界值按上下限截断，无法解析时使用默认值
+pub async fn task_poll_settings(site_data: &SiteDataManager) -> TaskPollSettings {
+    let interval_ms = site_data
+        .get_config("task_poll_interval_ms")
+        .await
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|ms| ms.clamp(MIN_TASK_POLL_INTERVAL_MS, MAX_TASK_POLL_INTERVAL_MS))
+        .unwrap_or(DEFAULT_TASK_POLL_INTERVAL_MS);
+    let auto_refresh = site_data
+        .get_config("task_auto_refresh")
+        .await
+        .and_then(|v| parse_task_auto_refresh(&v).ok())
+        .unwrap_or(true);
+    TaskPollSettings { interval_ms, auto_refresh }
+}
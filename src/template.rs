@@ -4,22 +4,30 @@ use std::collections::HashMap;
 
 lazy_static! {
     pub static ref TERA: Tera = {
-        // Adjust the path to be relative to the project root where Cargo.toml is.
-        let mut tera = match Tera::new("templates/**/*.html") {
+        match load_templates() {
             Ok(t) => t,
             Err(e) => {
                 println!("Tera parsing error(s): {}", e);
                 ::std::process::exit(1);
             }
-        };
-        
-        // Register custom filters
-        tera.register_filter("json", json_filter);
-        
-        tera
+        }
     };
 }
 
+// Parse every template under templates/ and register the custom filters.
+// TERA is built once at startup; the admin template preview calls this again
+// so edits on disk show up without a restart.
+pub fn load_templates() -> TeraResult<Tera> {
+    // Adjust the path to be relative to the project root where Cargo.toml is.
+    let mut tera = Tera::new("templates/**/*.html")?;
+
+    // Register custom filters
+    tera.register_filter("json", json_filter);
+    tera.register_filter("asset_url", asset_url_filter);
+
+    Ok(tera)
+}
+
 // Custom json filter function
 fn json_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
     match serde_json::to_string(value) {
@@ -28,3 +36,25 @@ fn json_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
     }
 }
 
+
+// Prefix root-relative asset paths (e.g. /static/images/...) with the CDN base URL.
+// Usage: {{ vod.vod_pic | asset_url(base=ASSET_URL) }}
+// Absolute URLs, protocol-relative URLs and an empty base are passed through unchanged.
+fn asset_url_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let path = match value.as_str() {
+        Some(path) => path,
+        None => return Ok(value.clone()),
+    };
+
+    let base = args
+        .get("base")
+        .and_then(|v| v.as_str())
+        .map(|b| b.trim().trim_end_matches('/'))
+        .unwrap_or("");
+
+    if base.is_empty() || !path.starts_with('/') || path.starts_with("//") {
+        return Ok(value.clone());
+    }
+
+    Ok(Value::String(format!("{}{}", base, path)))
+}
@@ -0,0 +1,136 @@
+use crate::api_handlers::{build_play_strings, vod_api_entry};
+use crate::dto::VodId;
+use crate::models::{self, Type, Vod};
+use actix_web::web::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use mongodb::bson::{doc, DateTime, Document};
+use mongodb::options::FindOptions;
+use mongodb::Database;
+use std::collections::HashMap;
+
+// 导出视频库（GET /api/admin/vods/export）：默认每行一个 Vod 文档（NDJSON），format=maccms 时输出
+// 与 /api/provide/vod 相同的 {"code":1,"list":[…]} 结构（带完整播放地址），可直接作为另一站点的采集源数据。
+// 用游标逐条序列化后流式写出，不把整个视频库读进内存；回收站中的视频不导出。
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Ndjson,
+    Maccms,
+}
+
+impl ExportFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("json") | Some("ndjson") => Ok(ExportFormat::Ndjson),
+            Some("maccms") => Ok(ExportFormat::Maccms),
+            Some(other) => Err(format!("不支持的导出格式: {}（可选 json、maccms）", other)),
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson; charset=utf-8",
+            ExportFormat::Maccms => "application/json; charset=utf-8",
+        }
+    }
+
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Maccms => "json",
+        }
+    }
+}
+
+// since 参数：Unix 时间戳（秒）或 RFC3339 时间，如 "2024-01-01T00:00:00+08:00"
+pub fn parse_since(value: &str) -> Result<DateTime, String> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<i64>() {
+        return chrono::DateTime::from_timestamp(secs, 0)
+            .map(|t| DateTime::from_millis(t.timestamp_millis()))
+            .ok_or_else(|| "since 时间戳超出范围".to_string());
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| DateTime::from_millis(t.timestamp_millis()))
+        .map_err(|_| "since 必须是 Unix 时间戳（秒）或 RFC3339 时间".to_string())
+}
+
+// type_id 与前台分类页一致，同时匹配主分类和附加分类；since 按 vod_pubdate（采集更新和后台编辑时刷新）过滤
+pub fn export_filter(type_id: Option<i32>, since: Option<DateTime>) -> Document {
+    let mut filter = match type_id {
+        Some(type_id) => models::vod_type_filter(&[type_id]),
+        None => doc! {},
+    };
+    if let Some(since) = since {
+        filter.insert("vod_pubdate", doc! { "$gte": since });
+    }
+    models::exclude_deleted_vods(&mut filter);
+    filter
+}
+
+// 按 _id 顺序遍历，导出过程中新采集的视频排在末尾，不会打乱已写出的部分
+pub async fn export_stream(
+    db: &Database,
+    filter: Document,
+    format: ExportFormat,
+    types: Vec<Type>,
+) -> mongodb::error::Result<impl Stream<Item = Result<Bytes, std::io::Error>>> {
+    let collection = db.collection::<Vod>("vods");
+    // maccms 格式的 total 需要在写出列表前确定
+    let total = match format {
+        ExportFormat::Maccms => collection.count_documents(filter.clone(), None).await?,
+        ExportFormat::Ndjson => 0,
+    };
+    let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+    let cursor = collection.find(filter, options).await?;
+
+    let type_names: HashMap<i32, String> =
+        types.into_iter().map(|t| (t.type_id, t.type_name)).collect();
+
+    let (prefix, suffix) = match format {
+        ExportFormat::Ndjson => (String::new(), String::new()),
+        ExportFormat::Maccms => (
+            format!(
+                "{{\"code\":1,\"msg\":\"success\",\"page\":1,\"pagecount\":1,\"limit\":{},\"total\":{},\"list\":[",
+                total, total
+            ),
+            "]}".to_string(),
+        ),
+    };
+
+    let items = cursor.enumerate().map(move |(index, vod)| {
+        let vod = vod.map_err(|e| {
+            eprintln!("Failed to export vods: {}", e);
+            std::io::Error::other(e.to_string())
+        })?;
+        let line = match format {
+            ExportFormat::Ndjson => serde_json::to_string(&vod).map(|json| json + "\n"),
+            ExportFormat::Maccms => {
+                let separator = if index > 0 { "," } else { "" };
+                serde_json::to_string(&maccms_entry(vod, &type_names))
+                    .map(|json| format!("{}{}", separator, json))
+            }
+        };
+        line.map(Bytes::from).map_err(std::io::Error::other)
+    });
+
+    Ok(stream::once(async move { Ok(Bytes::from(prefix)) })
+        .chain(items)
+        .chain(stream::once(async move { Ok(Bytes::from(suffix)) })))
+}
+
+// provide-vod 列表项只带播放源名称，导出时补上 $$$ 分隔的完整播放地址和分类名称，供采集时重新入库
+fn maccms_entry(vod: Vod, type_names: &HashMap<i32, String>) -> crate::dto::VodApiListEntry {
+    let (play_from, play_url) = build_play_strings(&vod.vod_play_urls);
+    let type_name = type_names.get(&vod.type_id).cloned();
+    // provide 接口的 vod_id 取 ObjectId 的时间戳秒数，同一秒写入的视频会重复；导出用完整的 ObjectId，保证唯一且稳定
+    let vod_id = vod.id.map(|id| VodId::String(id.to_hex()));
+    let mut entry = vod_api_entry(vod, &None);
+    if let Some(vod_id) = vod_id {
+        entry.vod_id = vod_id;
+    }
+    entry.type_name = type_name;
+    entry.vod_play_from = play_from;
+    entry.vod_play_url = Some(play_url);
+    entry
+}
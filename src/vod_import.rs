@@ -0,0 +1,406 @@
+use crate::admin_handlers::{purge_vod_count_caches, VodRequest};
+use crate::collect_handlers::{vod_dedup_filter, DedupKey};
+use crate::models::{Type, Vod};
+use crate::site_data::SiteDataManager;
+use actix_multipart::Multipart;
+use actix_web::web::Bytes;
+use futures::StreamExt;
+use mongodb::bson::{doc, Bson};
+use mongodb::options::{CountOptions, InsertManyOptions};
+use mongodb::Database;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+// 批量导入视频（POST /api/admin/vods/import）：解析 JSON 数组或 CSV 文件，逐行校验后每 500 行 insert_many 一次。
+// 名称+年份与已有视频（或同一文件中前面的行）相同的行跳过，与采集默认的 name_year 去重方式一致。
+const IMPORT_BATCH_SIZE: usize = 500;
+// 上传文件（或 JSON 请求体）大小上限
+const MAX_IMPORT_BYTES: usize = 50 * 1024 * 1024;
+// 进度中最多保留的错误行数，其余只计入 error_count
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+// CSV 表头可用的列，与 VodRequest 字段同名
+const CSV_COLUMNS: &[&str] = &[
+    "vod_name",
+    "type_id",
+    "vod_status",
+    "vod_class",
+    "vod_pic",
+    "vod_actor",
+    "vod_director",
+    "vod_remarks",
+    "vod_area",
+    "vod_lang",
+    "vod_year",
+    "vod_content",
+    "vod_extra_types",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VodImportProgress {
+    pub status: String, // running / completed / failed
+    pub total: usize,
+    pub processed: usize,
+    pub inserted: u64,
+    pub skipped: u64,
+    pub error_count: u64,
+    pub errors: Vec<ImportRowError>, // 最多保留 MAX_REPORTED_ERRORS 条
+    pub log: String,
+}
+
+impl VodImportProgress {
+    fn add_error(&mut self, row: usize, reason: String) {
+        self.error_count += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(ImportRowError { row, reason });
+        }
+    }
+}
+
+// 待导入的一行；row 为 JSON 数组中的序号（从 1 开始）或 CSV 文件中的行号（表头为第 1 行）
+pub struct ImportRow {
+    pub row: usize,
+    pub request: Result<VodRequest, String>,
+}
+
+// 全局导入任务进度（附最后更新时间），任务结束后保留 IMPORT_PROGRESS_TTL 以便查询结果
+type ImportProgressMap = HashMap<String, (VodImportProgress, Instant)>;
+
+static VOD_IMPORT_PROGRESS: std::sync::OnceLock<tokio::sync::RwLock<ImportProgressMap>> =
+    std::sync::OnceLock::new();
+
+// 已结束的导入任务进度保留的时间，超过后在下次更新进度时清理
+const IMPORT_PROGRESS_TTL: Duration = Duration::from_secs(3600);
+
+fn is_expired(progress: &VodImportProgress, updated_at: Instant) -> bool {
+    progress.status != "running" && updated_at.elapsed() >= IMPORT_PROGRESS_TTL
+}
+
+fn get_import_progress_store() -> &'static tokio::sync::RwLock<ImportProgressMap> {
+    VOD_IMPORT_PROGRESS.get_or_init(|| tokio::sync::RwLock::new(HashMap::new()))
+}
+
+pub async fn get_import_progress(task_id: &str) -> Option<VodImportProgress> {
+    get_import_progress_store()
+        .read()
+        .await
+        .get(task_id)
+        .filter(|(progress, updated_at)| !is_expired(progress, *updated_at))
+        .map(|(progress, _)| progress.clone())
+}
+
+async fn update_import_progress(task_id: &str, progress: &VodImportProgress) {
+    let mut store = get_import_progress_store().write().await;
+    store.retain(|_, (progress, updated_at)| !is_expired(progress, *updated_at));
+    store.insert(task_id.to_string(), (progress.clone(), Instant::now()));
+}
+
+// 读取 JSON 请求体，超过 MAX_IMPORT_BYTES 时报错
+pub async fn read_body(mut payload: actix_web::web::Payload) -> Result<Bytes, String> {
+    let mut body = actix_web::web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| format!("读取请求体失败: {}", e))?;
+        if body.len() + chunk.len() > MAX_IMPORT_BYTES {
+            return Err(format!("导入数据不能超过 {} MB", MAX_IMPORT_BYTES / 1024 / 1024));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+// 读取 multipart 中名为 file 的文件字段
+pub async fn read_csv_upload(mut multipart: Multipart) -> Result<Bytes, String> {
+    while let Some(field) = multipart.next().await {
+        let mut field = field.map_err(|e| format!("读取上传文件失败: {}", e))?;
+        if field.name() != Some("file") {
+            continue;
+        }
+        let mut body = actix_web::web::BytesMut::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(|e| format!("读取上传文件失败: {}", e))?;
+            if body.len() + chunk.len() > MAX_IMPORT_BYTES {
+                return Err(format!("导入文件不能超过 {} MB", MAX_IMPORT_BYTES / 1024 / 1024));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        return Ok(body.freeze());
+    }
+    Err("缺少 file 字段（CSV 文件）".to_string())
+}
+
+// 解析 VodRequest 结构的 JSON 数组；单个元素格式错误只影响该行
+pub fn parse_json_rows(body: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let items: Vec<serde_json::Value> = serde_json::from_slice(body)
+        .map_err(|e| format!("请求体必须是视频对象组成的 JSON 数组: {}", e))?;
+    Ok(items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| ImportRow {
+            row: i + 1,
+            request: serde_json::from_value::<VodRequest>(item).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+// 解析首行为字段名的 CSV；表头必须包含 vod_name 和 type_id，不认识的列直接报错，避免列名拼错时静默丢数据
+pub fn parse_csv_rows(body: &[u8]) -> Result<Vec<ImportRow>, String> {
+    // Excel 另存的 UTF-8 CSV 带 BOM
+    let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body);
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("读取 CSV 表头失败: {}", e))?
+        .iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+    if let Some(unknown) = headers.iter().find(|h| !CSV_COLUMNS.contains(&h.as_str())) {
+        return Err(format!("CSV 表头包含未知列: {}，可用列: {}", unknown, CSV_COLUMNS.join(",")));
+    }
+    for required in ["vod_name", "type_id"] {
+        if !headers.iter().any(|h| h == required) {
+            return Err(format!("CSV 表头缺少 {} 列", required));
+        }
+    }
+
+    Ok(reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            let row = record
+                .as_ref()
+                .ok()
+                .and_then(|r| r.position())
+                .map(|p| p.line() as usize)
+                .unwrap_or(i + 2);
+            let request = record
+                .map_err(|e| e.to_string())
+                .and_then(|record| csv_row_to_request(&headers, &record));
+            ImportRow { row, request }
+        })
+        .collect())
+}
+
+fn csv_row_to_request(headers: &[String], record: &csv::StringRecord) -> Result<VodRequest, String> {
+    let get = |name: &str| -> Option<String> {
+        headers
+            .iter()
+            .position(|h| h == name)
+            .and_then(|i| record.get(i))
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let parse_int = |name: &str, value: &str| -> Result<i32, String> {
+        value.parse::<i32>().map_err(|_| format!("{} 必须是整数: {}", name, value))
+    };
+
+    let type_id = match get("type_id") {
+        Some(v) => parse_int("type_id", &v)?,
+        None => return Err("缺少 type_id".to_string()),
+    };
+    let vod_status = match get("vod_status") {
+        Some(v) => parse_int("vod_status", &v)?,
+        None => 1,
+    };
+    // 附加分类在单元格中用 | 或逗号分隔，如 "3|5"
+    let vod_extra_types = match get("vod_extra_types") {
+        Some(v) => Some(
+            v.split(['|', ',', '，'])
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| parse_int("vod_extra_types", id))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(VodRequest {
+        vod_name: get("vod_name").unwrap_or_default(),
+        type_id,
+        vod_status,
+        vod_class: get("vod_class"),
+        vod_pic: get("vod_pic"),
+        vod_actor: get("vod_actor"),
+        vod_director: get("vod_director"),
+        vod_remarks: get("vod_remarks"),
+        vod_area: get("vod_area"),
+        vod_lang: get("vod_lang"),
+        vod_year: get("vod_year"),
+        vod_content: get("vod_content"),
+        vod_extra_types,
+    })
+}
+
+// 校验单行：名称不能为空，主分类和附加分类必须存在
+fn validate_row(request: &mut VodRequest, type_ids: &HashSet<i32>) -> Result<(), String> {
+    request.vod_name = request.vod_name.trim().to_string();
+    if request.vod_name.is_empty() {
+        return Err("vod_name 不能为空".to_string());
+    }
+    if !type_ids.contains(&request.type_id) {
+        return Err(format!("分类 {} 不存在", request.type_id));
+    }
+    if let Some(missing) = request
+        .vod_extra_types
+        .iter()
+        .flatten()
+        .find(|id| !type_ids.contains(id))
+    {
+        return Err(format!("附加分类 {} 不存在", missing));
+    }
+    Ok(())
+}
+
+// 登记导入任务并在后台执行，返回任务ID
+pub async fn start_import(db: Database, site_data_manager: SiteDataManager, rows: Vec<ImportRow>) -> String {
+    let task_id = uuid::Uuid::new_v4().to_string();
+    let progress = VodImportProgress {
+        status: "running".to_string(),
+        total: rows.len(),
+        processed: 0,
+        inserted: 0,
+        skipped: 0,
+        error_count: 0,
+        errors: Vec::new(),
+        log: format!("开始导入，共 {} 行", rows.len()),
+    };
+    update_import_progress(&task_id, &progress).await;
+
+    let task_id_for_task = task_id.clone();
+    tokio::spawn(async move {
+        let mut progress = progress;
+        if let Err(e) = run_import(&db, &task_id_for_task, rows, &mut progress).await {
+            tracing::error!("批量导入视频失败 {}: {}", task_id_for_task, e);
+            progress.status = "failed".to_string();
+            progress.log = format!("导入失败: {}（已导入 {} 条）", e, progress.inserted);
+            update_import_progress(&task_id_for_task, &progress).await;
+        }
+        if progress.inserted > 0 {
+            purge_vod_count_caches(&site_data_manager).await;
+        }
+    });
+
+    task_id
+}
+
+async fn run_import(
+    db: &Database,
+    task_id: &str,
+    rows: Vec<ImportRow>,
+    progress: &mut VodImportProgress,
+) -> Result<(), mongodb::error::Error> {
+    let type_ids: HashSet<i32> = db
+        .collection::<Type>("types")
+        .distinct("type_id", None, None)
+        .await?
+        .into_iter()
+        .filter_map(|id| match id {
+            Bson::Int32(id) => Some(id),
+            Bson::Int64(id) => i32::try_from(id).ok(),
+            _ => None,
+        })
+        .collect();
+    let vods = db.collection::<Vod>("vods");
+    // 同一文件中名称+年份重复的行只导入第一行
+    let mut seen: HashSet<(String, Option<String>)> = HashSet::new();
+
+    let mut rows = rows.into_iter();
+    loop {
+        let chunk: Vec<ImportRow> = rows.by_ref().take(IMPORT_BATCH_SIZE).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        let chunk_len = chunk.len();
+        let mut batch: Vec<(usize, Vod)> = Vec::new();
+        for import_row in chunk {
+            let mut request = match import_row.request {
+                Ok(request) => request,
+                Err(reason) => {
+                    progress.add_error(import_row.row, reason);
+                    continue;
+                }
+            };
+            if let Err(reason) = validate_row(&mut request, &type_ids) {
+                progress.add_error(import_row.row, reason);
+                continue;
+            }
+
+            let year = request
+                .vod_year
+                .as_deref()
+                .map(str::trim)
+                .filter(|y| !y.is_empty())
+                .map(str::to_string);
+            if !seen.insert((request.vod_name.clone(), year.clone())) {
+                progress.skipped += 1;
+                continue;
+            }
+            let filter = vod_dedup_filter(DedupKey::NameYear, &request.vod_name, year.as_deref(), None);
+            let count_options = CountOptions::builder().limit(1).build();
+            if vods.count_documents(filter, count_options).await? > 0 {
+                progress.skipped += 1;
+                continue;
+            }
+            batch.push((import_row.row, request.to_new_vod()));
+        }
+
+        if !batch.is_empty() {
+            insert_batch(db, &batch, progress).await?;
+        }
+        progress.processed += chunk_len;
+        progress.log = format!(
+            "已处理 {}/{} 行：导入 {}，跳过 {}，错误 {}",
+            progress.processed, progress.total, progress.inserted, progress.skipped, progress.error_count
+        );
+        update_import_progress(task_id, progress).await;
+    }
+
+    progress.status = "completed".to_string();
+    progress.log = format!(
+        "导入完成：导入 {}，跳过 {}（已存在或重复），错误 {}",
+        progress.inserted, progress.skipped, progress.error_count
+    );
+    update_import_progress(task_id, progress).await;
+    Ok(())
+}
+
+// 写入一批视频；ordered(false) 时单条失败不影响其他行，失败的行记入 errors
+async fn insert_batch(
+    db: &Database,
+    batch: &[(usize, Vod)],
+    progress: &mut VodImportProgress,
+) -> Result<(), mongodb::error::Error> {
+    let options = InsertManyOptions::builder().ordered(false).build();
+    let result = db
+        .collection::<Vod>("vods")
+        .insert_many(batch.iter().map(|(_, vod)| vod), options)
+        .await;
+
+    match result {
+        Ok(result) => {
+            progress.inserted += result.inserted_ids.len() as u64;
+            Ok(())
+        }
+        Err(e) => match *e.kind {
+            mongodb::error::ErrorKind::BulkWrite(failure) => {
+                let write_errors = failure.write_errors.unwrap_or_default();
+                progress.inserted += (batch.len() - write_errors.len()) as u64;
+                for write_error in write_errors {
+                    let row = batch.get(write_error.index).map(|(row, _)| *row).unwrap_or(0);
+                    progress.add_error(row, format!("写入失败: {}", write_error.message));
+                }
+                Ok(())
+            }
+            _ => Err(e),
+        },
+    }
+}
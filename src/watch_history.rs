@@ -0,0 +1,100 @@
+use crate::site_data::SiteDataManager;
+use mongodb::bson::oid::ObjectId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// 播放页上报播放进度的间隔（秒），0 表示不记录进度
+pub const DEFAULT_WATCH_PROGRESS_INTERVAL: u64 = 15;
+// 低于 5 秒的上报只会增加数据库写入，续播精度没有明显提升
+pub const MIN_WATCH_PROGRESS_INTERVAL: u64 = 5;
+pub const MAX_WATCH_PROGRESS_INTERVAL: u64 = 300;
+
+// 单集播放位置上限（秒），超出视为无效数据
+pub const MAX_POSITION_SECONDS: f64 = 86_400.0;
+
+pub const DEFAULT_HISTORY_LIMIT: i64 = 20;
+pub const MAX_HISTORY_LIMIT: i64 = 100;
+
+// 校验 watch_progress_interval 配置：0 或 5-300 的整数（秒）
+pub fn parse_watch_progress_interval(value: &str) -> Result<u64, String> {
+    let secs = value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "watch_progress_interval 必须是整数（秒）".to_string())?;
+    if secs != 0 && !(MIN_WATCH_PROGRESS_INTERVAL..=MAX_WATCH_PROGRESS_INTERVAL).contains(&secs) {
+        return Err(format!(
+            "watch_progress_interval 必须为 0 或 {}-{} 之间",
+            MIN_WATCH_PROGRESS_INTERVAL, MAX_WATCH_PROGRESS_INTERVAL
+        ));
+    }
+    Ok(secs)
+}
+
+// 读取上报间隔；无法解析时使用默认值
+pub async fn watch_progress_interval(site_data: &SiteDataManager) -> u64 {
+    site_data
+        .get_config("watch_progress_interval")
+        .await
+        .and_then(|v| parse_watch_progress_interval(&v).ok())
+        .unwrap_or(DEFAULT_WATCH_PROGRESS_INTERVAL)
+}
+
+// 校验播放位置：非负有限数且不超过 MAX_POSITION_SECONDS
+pub fn validate_position(position_seconds: f64) -> Result<f64, String> {
+    if !position_seconds.is_finite() || !(0.0..=MAX_POSITION_SECONDS).contains(&position_seconds) {
+        return Err("position_seconds 无效".to_string());
+    }
+    Ok(position_seconds)
+}
+
+// 每个用户每部视频的写入令牌：每 watch_progress_interval 秒补充 1 个，最多攒 2 个。
+// 正常播放时按间隔上报，多出的 1 个留给暂停时的立即上报；绕过播放器高频提交的请求被拒绝，不写数据库
+const PROGRESS_WRITE_BURST: f64 = 2.0;
+
+// (user_id, vod_id) -> (剩余令牌, 上次计算时间)
+type ProgressWriteTokens = HashMap<(ObjectId, ObjectId), (f64, Instant)>;
+
+static PROGRESS_WRITE_TOKENS: std::sync::OnceLock<tokio::sync::RwLock<ProgressWriteTokens>> =
+    std::sync::OnceLock::new();
+
+// 服务端按上报间隔限制写入频率，返回 false 时本次不写入
+pub async fn allow_progress_write(user_id: ObjectId, vod_id: ObjectId, interval_secs: u64) -> bool {
+    let interval = Duration::from_secs(interval_secs.max(1));
+    let mut tokens = PROGRESS_WRITE_TOKENS
+        .get_or_init(|| tokio::sync::RwLock::new(HashMap::new()))
+        .write()
+        .await;
+    let now = Instant::now();
+
+    // 超过补满所需时间的记录与新记录等价，顺带清理，避免内存无限增长
+    let full_after = interval.mul_f64(PROGRESS_WRITE_BURST);
+    tokens.retain(|_, (_, last)| now.duration_since(*last) < full_after);
+
+    let (available, last) = tokens.entry((user_id, vod_id)).or_insert((PROGRESS_WRITE_BURST, now));
+    let refilled = now.duration_since(*last).as_secs_f64() / interval.as_secs_f64();
+    *available = (*available + refilled).min(PROGRESS_WRITE_BURST);
+    *last = now;
+    if *available < 1.0 {
+        return false;
+    }
+    *available -= 1.0;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn progress_writes_are_throttled_per_user_and_vod() {
+        let (user_id, vod_id) = (ObjectId::new(), ObjectId::new());
+
+        // 一次定时上报加一次暂停上报，之后需等下一个间隔
+        assert!(allow_progress_write(user_id, vod_id, 60).await);
+        assert!(allow_progress_write(user_id, vod_id, 60).await);
+        assert!(!allow_progress_write(user_id, vod_id, 60).await);
+
+        // 其他视频不受影响
+        assert!(allow_progress_write(user_id, ObjectId::new(), 60).await);
+    }
+}
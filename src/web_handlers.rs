@@ -1,9 +1,14 @@
+use crate::cache;
+use crate::client_ip::client_ip;
 use crate::models::{Type, User, Vod};
+use crate::pagination::{build_pagination, pagination_window};
+use crate::resolve::resolve_vod_page_id;
 use crate::template::TERA;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::cookie::{time::Duration as CookieDuration, Cookie, SameSite};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use chrono::Datelike;
 use futures::stream::TryStreamExt;
-use mongodb::{bson::doc, options::FindOptions, Database};
+use mongodb::{bson::{doc, Document}, options::FindOptions, Database};
 use regex::Regex;
 
 // Helper function to get play URL and episode name
@@ -23,6 +28,24 @@ fn get_play_info(
     }
 }
 
+// Helper function to pick the play source when the play index has no source prefix.
+// Walks the comma-separated `play_source_priority` config and returns the first source
+// that has the requested episode; falls back to source 0 otherwise.
+fn select_default_play_source(video: &Vod, play_idx: usize, priority: Option<&str>) -> usize {
+    if let Some(priority) = priority {
+        for name in priority.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            if let Some(pos) = video
+                .vod_play_urls
+                .iter()
+                .position(|source| source.source_name == name && play_idx < source.urls.len())
+            {
+                return pos;
+            }
+        }
+    }
+    0
+}
+
 // Helper function to extract line and column information from error messages
 fn extract_line_info(error_str: &str) -> Option<String> {
     // 尝试匹配各种可能的行号格式
@@ -66,6 +89,7 @@ fn extract_line_info(error_str: &str) -> Option<String> {
 }
 use crate::dto::ListPageParams;
 use crate::init_data;
+use crate::login_lockout::{self, LockoutSettings};
 use crate::site_data::SiteDataManager;
 use actix_session::Session;
 use actix_web_flash_messages::FlashMessage;
@@ -75,23 +99,218 @@ use std::error::Error;
 #[derive(Serialize)]
 struct CategorizedVideos {
     category: Type,
-    videos: Vec<Vod>,
+    videos: Vec<serde_json::Value>,
 }
 
-// 辅助函数：获取站点数据并添加到模板上下文
-async fn with_site_data<F, R>(
-    db: web::Data<Database>,
-    site_data_manager: web::Data<SiteDataManager>,
-    template_handler: F,
-) -> Result<HttpResponse, Box<dyn std::error::Error>>
-where
-    F: FnOnce(tera::Context, SiteDataManager) -> R,
-    R: std::future::Future<Output = Result<String, Box<dyn std::error::Error>>>,
-{
+// 读取 hot_hits_threshold 配置，未配置或 <= 0 时不标记热门
+async fn get_hot_threshold(site_data: &SiteDataManager) -> Option<i32> {
+    site_data
+        .get_config("hot_hits_threshold")
+        .await
+        .and_then(|v| v.trim().parse::<i32>().ok())
+        .filter(|threshold| *threshold > 0)
+}
+
+const DEFAULT_NEW_BADGE_DAYS: i64 = 3;
+
+// 读取 new_badge_days 配置，返回“新”角标的起始时间；配置为 0 时不标记
+async fn get_new_since(site_data: &SiteDataManager) -> Option<mongodb::bson::DateTime> {
+    let days = site_data
+        .get_config("new_badge_days")
+        .await
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_NEW_BADGE_DAYS)
+        .clamp(0, 365);
+    if days == 0 {
+        return None;
+    }
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    Some(mongodb::bson::DateTime::from_millis(since.timestamp_millis()))
+}
+
+// 卡片角标规则（热 / 新），每个请求读取一次配置
+struct VodBadges {
+    hot_threshold: Option<i32>,
+    new_since: Option<mongodb::bson::DateTime>,
+}
+
+impl VodBadges {
+    async fn load(site_data: &SiteDataManager) -> Self {
+        Self {
+            hot_threshold: get_hot_threshold(site_data).await,
+            new_since: get_new_since(site_data).await,
+        }
+    }
+
+    fn is_hot(&self, vod: &Vod) -> bool {
+        match (self.hot_threshold, vod.vod_hits_week) {
+            (Some(threshold), Some(hits)) => hits >= threshold,
+            _ => false,
+        }
+    }
+
+    // 入库时间（vod_created_at，旧数据缺失时用 vod_pubdate）在窗口内即为新片
+    fn is_new(&self, vod: &Vod) -> bool {
+        match self.new_since {
+            Some(since) => vod.vod_created_at.unwrap_or(vod.vod_pubdate) >= since,
+            None => false,
+        }
+    }
+
+    // Serialize vods for templates with `is_hot` / `is_new` flags, so cards can show the "热" / "新" badges
+    fn apply(&self, vods: &[Vod]) -> Vec<serde_json::Value> {
+        vods.iter()
+            .filter_map(|vod| {
+                let mut value = serde_json::to_value(vod).ok()?;
+                if let Some(obj) = value.as_object_mut() {
+                    obj.insert("is_hot".to_string(), self.is_hot(vod).into());
+                    obj.insert("is_new".to_string(), self.is_new(vod).into());
+                }
+                Some(value)
+            })
+            .collect()
+    }
+}
+
+// 读取推荐数量配置（detail_related_count / player_recommend_count），限制在 0..=50
+async fn get_related_count(site_data: &SiteDataManager, key: &str, default: i64) -> i64 {
+    site_data
+        .get_config(key)
+        .await
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .map(|count| count.clamp(0, 50))
+        .unwrap_or(default)
+}
+
+// 与当前视频有相同主演的推荐最多取这么多条，其余按 related_strategy 补齐
+const MAX_CAST_RELATED: i64 = 10;
+
+// 按 filter 查询推荐视频，排除 seen 中的视频（当前视频和已选中的推荐），并把结果加入 seen
+async fn find_related(
+    vod_collection: &mongodb::Collection<Vod>,
+    mut filter: Document,
+    sort: Document,
+    limit: i64,
+    seen: &mut Vec<mongodb::bson::oid::ObjectId>,
+) -> Vec<Vod> {
+    filter.insert("_id", doc! { "$nin": seen.clone() });
+    crate::models::exclude_deleted_vods(&mut filter);
+    let find_options = FindOptions::builder().sort(sort).limit(limit).build();
+    let vods: Vec<Vod> = match vod_collection.find(filter, find_options).await {
+        Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+        Err(_) => vec![],
+    };
+    seen.extend(vods.iter().filter_map(|v| v.id));
+    vods
+}
+
+// 获取相关视频：
+//   1. 视频有主演时，先取至少有一位相同主演的视频（vod_actor_list 上的 $in 查询，最多 10 条，按播放量）
+//   2. related_strategy 配置为 smart 时，再匹配相同类型标签
+//   3. 不足 count 条时用同分类最新视频补齐；没有相同主演的视频时即只按分类推荐
+// 结果按 _id 去重
+async fn fetch_related_videos(
+    vod_collection: &mongodb::Collection<Vod>,
+    video: &Vod,
+    count: i64,
+    strategy: &str,
+) -> Vec<Vod> {
+    if count <= 0 {
+        return vec![];
+    }
+    let mut seen: Vec<_> = video.id.into_iter().collect();
+    let mut related: Vec<Vod> = Vec::new();
+
+    if !video.vod_actor_list.is_empty() {
+        let same_cast = find_related(
+            vod_collection,
+            doc! { "vod_actor_list": { "$in": &video.vod_actor_list } },
+            doc! { "vod_hits": -1, "vod_pubdate": -1 },
+            count.min(MAX_CAST_RELATED),
+            &mut seen,
+        )
+        .await;
+        related.extend(same_cast);
+    }
+
+    let remaining = count - related.len() as i64;
+    if strategy == "smart" && remaining > 0 {
+        let conditions: Vec<Document> = video
+            .vod_class
+            .as_deref()
+            .unwrap_or("")
+            .split([',', '，', '/', ' '])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .take(3)
+            .map(|class| doc! { "vod_class": { "$regex": regex::escape(class) } })
+            .collect();
+
+        if !conditions.is_empty() {
+            let same_class = find_related(
+                vod_collection,
+                doc! { "$or": conditions },
+                doc! { "vod_hits": -1, "vod_pubdate": -1 },
+                remaining,
+                &mut seen,
+            )
+            .await;
+            related.extend(same_class);
+        }
+    }
+
+    let remaining = count - related.len() as i64;
+    if remaining > 0 {
+        let same_category = find_related(
+            vod_collection,
+            doc! { "type_id": video.type_id },
+            doc! { "vod_pubdate": -1 },
+            remaining,
+            &mut seen,
+        )
+        .await;
+        related.extend(same_category);
+    }
+
+    related
+}
+
+// 成人分类年龄确认 cookie，值为 "1" 表示访客已确认年满18周岁
+const AGE_GATE_COOKIE: &str = "age_verified";
+const AGE_GATE_COOKIE_DAYS: i64 = 30;
+const DEFAULT_AGE_GATE_TEXT: &str =
+    "本分类包含仅限成年人观看的内容。继续访问即表示您确认已年满18周岁，且所在地区法律允许观看此类内容。";
+
+fn age_confirmed(req: &HttpRequest) -> bool {
+    req.cookie(AGE_GATE_COOKIE).is_some_and(|c| c.value() == "1")
+}
+
+// 渲染年龄确认页，确认后跳回 return_to；提示文字取自 age_gate_text 配置
+async fn render_age_gate(
+    mut context: tera::Context,
+    site_data: &SiteDataManager,
+    return_to: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let text = site_data
+        .get_config("age_gate_text")
+        .await
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_AGE_GATE_TEXT.to_string());
+    context.insert("age_gate_text", &text);
+    context.insert("return_to", return_to);
+
+    TERA.render("age_gate.html", &context).map_err(|e| {
+        handle_template_rendering_error("age_gate.html", &e, Some("Age confirmation page"), None);
+        Box::new(e) as Box<dyn std::error::Error>
+    })
+}
+
+// 所有前台页面共用的全局模板变量（分类、配置、SITENAME、ASSET_URL 等）
+async fn site_context(site_data: &SiteDataManager) -> tera::Context {
     let mut context = tera::Context::new();
 
     // 获取导航分类数据
-    let nav_categories = site_data_manager.get_navigation_categories().await;
+    let nav_categories = site_data.get_navigation_categories().await;
     let categories: Vec<Type> = nav_categories
         .iter()
         .map(|nav| nav.category.clone())
@@ -102,10 +321,10 @@ where
         .collect();
 
     // 获取所有分类
-    let all_categories = site_data_manager.get_all_categories().await;
+    let all_categories = site_data.get_all_categories().await;
 
     // 获取配置数据
-    let configs = site_data_manager.get_all_configs().await;
+    let configs = site_data.get_all_configs().await;
 
     // 获取网站名称
     let sitename = configs
@@ -119,6 +338,8 @@ where
     context.insert("categories_with_subs", &categories_with_subs);
     context.insert("configs", &configs);
     context.insert("SITENAME", &sitename);
+    // 视频没有海报时模板使用的默认图片
+    context.insert("DEFAULT_POSTER", &site_data.get_default_poster().await);
 
     // 为方便模板使用，添加一些常用的配置项
     if let Some(site_url) = configs.get("site_url") {
@@ -131,6 +352,37 @@ where
         context.insert("SITEDESCRIPTION", site_description);
     }
 
+    // 静态资源/海报的CDN地址，未配置时为空，asset_url 过滤器保持相对路径
+    let asset_url = configs
+        .get("asset_base_url")
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    context.insert("ASSET_URL", &asset_url);
+
+    // 运营配置的自定义 HTML，由 base.html 原样输出到 <head> 和 </body> 之前；空值不输出任何内容
+    for (key, name) in [
+        ("custom_head_html", "CUSTOM_HEAD_HTML"),
+        ("custom_footer_html", "CUSTOM_FOOTER_HTML"),
+    ] {
+        let html = configs.get(key).map(|v| v.trim()).unwrap_or_default();
+        context.insert(name, html);
+    }
+
+    context
+}
+
+// 辅助函数：获取站点数据并添加到模板上下文
+async fn with_site_data<F, R>(
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+    template_handler: F,
+) -> Result<HttpResponse, Box<dyn std::error::Error>>
+where
+    F: FnOnce(tera::Context, SiteDataManager) -> R,
+    R: std::future::Future<Output = Result<String, Box<dyn std::error::Error>>>,
+{
+    let context = site_context(&site_data_manager).await;
+
     let rendered = template_handler(context, site_data_manager.as_ref().clone()).await?;
 
     Ok(HttpResponse::Ok().content_type("text/html").body(rendered))
@@ -138,21 +390,24 @@ where
 
 
 
-// 通用模板渲染错误处理器
-fn handle_template_rendering_error(
+// 生成模板渲染错误的详细诊断信息（错误类型、错误链、位置、调试建议）
+fn template_error_report(
     template_name: &str,
     error: &tera::Error,
     context_info: Option<&str>,
     context_variables: Option<&str>,
-) {
-    eprintln!("🚨 Template Rendering Error");
-    eprintln!("");
-    eprintln!("=== Template Rendering Error ===");
-    eprintln!("");
-    eprintln!("Template: {}", template_name);
-    eprintln!("");
-    eprintln!("Error: {}", error);
-    eprintln!("");
+) -> String {
+    use std::fmt::Write as _;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "🚨 Template Rendering Error");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "=== Template Rendering Error ===");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Template: {}", template_name);
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Error: {}", error);
+    let _ = writeln!(report);
     
     // 确定错误类型
     let error_type = match &error.kind {
@@ -164,25 +419,25 @@ fn handle_template_rendering_error(
         tera::ErrorKind::Io(_) => "IO Error",
         _ => "Other",
     };
-    eprintln!("Error Type: {}", error_type);
-    eprintln!("");
+    let _ = writeln!(report, "Error Type: {}", error_type);
+    let _ = writeln!(report);
     
     // 输出详细的调试信息
-    eprintln!("Debug Info: {:?}", error);
-    eprintln!("");
+    let _ = writeln!(report, "Debug Info: {:?}", error);
+    let _ = writeln!(report);
     
     // 输出上下文信息
     if let Some(info) = context_info {
-        eprintln!("Context Info: {}", info);
-        eprintln!("");
+        let _ = writeln!(report, "Context Info: {}", info);
+        let _ = writeln!(report);
     }
     
     // 输出错误链
     let mut level = 1;
     let mut current_error = error.source();
     while let Some(err) = current_error {
-        eprintln!("Error Chain Level {}: {}", level, err);
-        eprintln!("");
+        let _ = writeln!(report, "Error Chain Level {}: {}", level, err);
+        let _ = writeln!(report);
         current_error = err.source();
         level += 1;
     }
@@ -190,48 +445,60 @@ fn handle_template_rendering_error(
     // 输出位置信息
     let error_str = format!("{}", error);
     if let Some(line_info) = extract_line_info(&error_str) {
-        eprintln!("Error Location: {}", line_info);
-        eprintln!("");
+        let _ = writeln!(report, "Error Location: {}", line_info);
+        let _ = writeln!(report);
     }
     
     // 输出上下文变量信息
     if let Some(variables) = context_variables {
-        eprintln!("Context Variables: {}", variables);
-        eprintln!("");
+        let _ = writeln!(report, "Context Variables: {}", variables);
+        let _ = writeln!(report);
     }
     
     // 输出调试建议
-    eprintln!("=== Debugging Suggestions ===");
-    eprintln!("");
-    eprintln!("1. Check if all variables used in the template are properly passed in the context");
-    eprintln!("");
-    eprintln!("2. Verify template syntax and variable names");
-    eprintln!("");
-    eprintln!("3. Ensure all required template files exist");
-    eprintln!("");
-    eprintln!("4. Check for typos in variable names or template");
-    eprintln!("");
+    let _ = writeln!(report, "=== Debugging Suggestions ===");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "1. Check if all variables used in the template are properly passed in the context");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "2. Verify template syntax and variable names");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "3. Ensure all required template files exist");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "4. Check for typos in variable names or template");
+    let _ = writeln!(report);
     
     // 根据错误类型提供特定建议
     match &error.kind {
         tera::ErrorKind::TemplateNotFound(name) => {
-            eprintln!("5. Template '{}' not found - check file path and name", name);
-            eprintln!("");
+            let _ = writeln!(report, "5. Template '{}' not found - check file path and name", name);
+            let _ = writeln!(report);
         }
         tera::ErrorKind::Msg(msg) if msg.contains("Variable") && msg.contains("not found") => {
-            eprintln!("5. Variable not found error - ensure all template variables are provided in context");
-            eprintln!("");
+            let _ = writeln!(report, "5. Variable not found error - ensure all template variables are provided in context");
+            let _ = writeln!(report);
         }
         tera::ErrorKind::CallFunction(func_name) => {
-            eprintln!("5. Function '{}' call failed - check function implementation and parameters", func_name);
-            eprintln!("");
+            let _ = writeln!(report, "5. Function '{}' call failed - check function implementation and parameters", func_name);
+            let _ = writeln!(report);
         }
         tera::ErrorKind::CallFilter(filter_name) => {
-            eprintln!("5. Filter '{}' call failed - check filter implementation and input data", filter_name);
-            eprintln!("");
+            let _ = writeln!(report, "5. Filter '{}' call failed - check filter implementation and input data", filter_name);
+            let _ = writeln!(report);
         }
         _ => {}
     }
+
+    report
+}
+
+// 通用模板渲染错误处理器
+fn handle_template_rendering_error(
+    template_name: &str,
+    error: &tera::Error,
+    context_info: Option<&str>,
+    context_variables: Option<&str>,
+) {
+    eprint!("{}", template_error_report(template_name, error, context_info, context_variables));
 }
 
 // 创建包装函数来处理Actix Web路由的参数传递
@@ -254,44 +521,110 @@ pub async fn home_page_wrapper(
 }
 
 pub async fn video_detail_handler_wrapper(
+    req: HttpRequest,
     path: web::Path<String>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
-    video_detail_handler(path, db, site_data_manager).await
+    video_detail_handler(req, path, db, site_data_manager).await
 }
 
 pub async fn video_player_handler_wrapper(
+    req: HttpRequest,
     path: web::Path<(String, String)>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
-    video_player_handler(path, db, site_data_manager).await
+    video_player_handler(req, path, db, site_data_manager).await
 }
 
 pub async fn list_page_handler_wrapper(
+    req: HttpRequest,
     path: web::Path<i32>,
     query: web::Query<ListPageParams>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
-    list_page_handler(path, query, db, site_data_manager).await
+    list_page_handler(req, path, query, db, site_data_manager).await
 }
 
 pub async fn search_page_handler_wrapper(
+    req: HttpRequest,
     query: web::Query<crate::dto::ApiParams>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
-    search_page_handler(query, db, site_data_manager).await
+    search_page_handler(req, query, db, site_data_manager).await
 }
 
 // --- Frontend Web Handlers ---
 
+pub const DEFAULT_HOME_SECTION_SIZE: i64 = 12;
+const MAX_HOME_SECTION_SIZE: i64 = 60;
+
+// 解析 home_section_size：首页每个分类区块显示的视频数，1 - 60
+pub fn parse_home_section_size(value: &str) -> Result<i64, String> {
+    match value.trim().parse::<i64>() {
+        Ok(n) if (1..=MAX_HOME_SECTION_SIZE).contains(&n) => Ok(n),
+        _ => Err(format!("首页每个分类的视频数必须是 1-{} 之间的整数: {}", MAX_HOME_SECTION_SIZE, value.trim())),
+    }
+}
+
+// 首页分类区块：(分类, 查询的分类ID)。
+// home_sections 为空时按导航顺序显示所有顶级分类（含子分类视频）；
+// 否则按配置的顺序显示，不存在或已禁用的分类跳过
+async fn home_sections(site_data: &SiteDataManager) -> Vec<(Type, Vec<i32>)> {
+    let configured = site_data
+        .get_config("home_sections")
+        .await
+        .and_then(|v| crate::site_data::parse_type_id_list(&v).ok())
+        .filter(|ids| !ids.is_empty());
+
+    let Some(ids) = configured else {
+        return site_data
+            .get_navigation_categories()
+            .await
+            .into_iter()
+            .map(|nav_category| {
+                // Include both top-level category and its sub-categories
+                let mut type_ids = vec![nav_category.category.type_id];
+                for sub_cat in &nav_category.sub_categories {
+                    type_ids.push(sub_cat.type_id);
+                }
+                (nav_category.category, type_ids)
+            })
+            .collect();
+    };
+
+    let mut sections = Vec::new();
+    for type_id in ids {
+        match site_data.get_category_by_id(type_id).await {
+            Some(category) if category.type_status == 1 => {
+                let type_ids = site_data.get_descendant_type_ids(type_id).await;
+                sections.push((category, type_ids));
+            }
+            _ => eprintln!("home_sections: category {} not found or disabled, skipped", type_id),
+        }
+    }
+    sections
+}
+
 pub async fn home_page(
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
+    // 首页渲染结果缓存，home_cache_ttl 为缓存秒数，0 表示不缓存
+    let cache_ttl = site_data_manager
+        .get_config("home_cache_ttl")
+        .await
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(60);
+    if cache_ttl > 0 {
+        if let Some(html) = cache::get_cached(cache::SCOPE_HOME, "/").await {
+            return HttpResponse::Ok().content_type("text/html").body(html);
+        }
+    }
+
     match with_site_data(
         db.clone(),
         site_data_manager.clone(),
@@ -299,24 +632,26 @@ pub async fn home_page(
             let vod_collection = db.collection::<Vod>("vods");
             let mut categorized_videos_list = Vec::new();
 
-            // 获取导航分类数据
-            let nav_categories = site_data.get_navigation_categories().await;
+            // 首页分类区块及每个区块的视频数
+            let sections = home_sections(&site_data).await;
+            let section_size = site_data
+                .get_config("home_section_size")
+                .await
+                .and_then(|v| parse_home_section_size(&v).ok())
+                .unwrap_or(DEFAULT_HOME_SECTION_SIZE);
+            let badges = VodBadges::load(&site_data).await;
 
-            // Fetch videos for each top-level category (include sub-categories)
-            for nav_category in nav_categories {
+            // Fetch videos for each section (include sub-categories)
+            for (category, type_ids) in sections {
                 let find_options = FindOptions::builder()
                     .sort(doc! { "vod_pubdate": -1 })
-                    .limit(12)
+                    .limit(section_size)
                     .build();
 
-                // Build filter to include both top-level category and its sub-categories
-                let mut type_ids = vec![nav_category.category.type_id];
-                for sub_cat in &nav_category.sub_categories {
-                    type_ids.push(sub_cat.type_id);
-                }
-
+                let mut filter = crate::models::vod_type_filter(&type_ids);
+                crate::models::exclude_deleted_vods(&mut filter);
                 let videos = match vod_collection
-                    .find(doc! { "type_id": { "$in": type_ids } }, find_options)
+                    .find(filter, find_options)
                     .await
                 {
                     Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
@@ -324,14 +659,14 @@ pub async fn home_page(
                 };
 
                 categorized_videos_list.push(CategorizedVideos {
-                    category: nav_category.category,
-                    videos,
+                    category,
+                    videos: badges.apply(&videos),
                 });
             }
 
             context.insert("categorized_videos", &categorized_videos_list);
 
-            TERA.render("index.html", &context)
+            let html = TERA.render("index.html", &context)
                 .map_err(|e| {
                     handle_template_rendering_error(
                         "index.html",
@@ -340,7 +675,19 @@ pub async fn home_page(
                         Some(&format!("categorized_videos: {} categories", categorized_videos_list.len()))
                     );
                     Box::new(e) as Box<dyn std::error::Error>
-                })
+                })?;
+
+            if cache_ttl > 0 {
+                cache::set_cached(
+                    cache::SCOPE_HOME,
+                    "/",
+                    html.clone(),
+                    std::time::Duration::from_secs(cache_ttl),
+                )
+                .await;
+            }
+
+            Ok(html)
         },
     )
     .await
@@ -479,8 +826,31 @@ pub async fn terms_page(
     }
 }
 
+// robots.txt handler
+// 配置了 robots_txt 时原样输出，否则使用默认规则（禁止抓取后台，指向站点地图）
+pub async fn robots_txt_handler(site_data_manager: web::Data<SiteDataManager>) -> impl Responder {
+    let body = match site_data_manager.get_config("robots_txt").await {
+        Some(robots) if !robots.trim().is_empty() => robots,
+        _ => {
+            let site_url = site_data_manager
+                .get_config("site_url")
+                .await
+                .unwrap_or_default();
+            format!(
+                "User-agent: *\nDisallow: /admin\nDisallow: /api/admin\n\nSitemap: {}/sitemap.xml\n",
+                site_url.trim().trim_end_matches('/')
+            )
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(body)
+}
+
 // Video detail page handler
 pub async fn video_detail_handler(
+    req: HttpRequest,
     path: web::Path<String>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
@@ -488,9 +858,9 @@ pub async fn video_detail_handler(
     let vod_id = path.into_inner();
 
     // Parse ObjectId from string
-    let object_id = match mongodb::bson::oid::ObjectId::parse_str(&vod_id) {
+    let object_id = match resolve_vod_page_id(&vod_id) {
         Ok(id) => id,
-        Err(_) => return HttpResponse::NotFound().body("Invalid video ID"),
+        Err(response) => return response,
     };
 
     match with_site_data(
@@ -500,11 +870,16 @@ pub async fn video_detail_handler(
             let vod_collection = db.collection::<Vod>("vods");
 
             // 1. Fetch video details
-            let video = match vod_collection.find_one(doc! {"_id": object_id}, None).await {
+            let video = match vod_collection.find_one(doc! {"_id": object_id, "vod_deleted_at": {"$exists": false}}, None).await {
                 Ok(Some(v)) => v,
                 _ => return Err("Video not found".into()),
             };
 
+            // 成人分类需要先确认年龄
+            if !age_confirmed(&req) && site_data.is_adult_category(video.type_id).await {
+                return render_age_gate(context, &site_data, &req.uri().to_string()).await;
+            }
+
             // Convert MongoDB DateTime to timestamp for template
             let pubdate_timestamp = video.vod_pubdate.timestamp_millis() / 1000;
             context.insert("vod_pubdate_timestamp", &pubdate_timestamp);
@@ -514,30 +889,32 @@ pub async fn video_detail_handler(
             if let Some(category) = site_data.get_category_by_id(video.type_id).await {
                 context.insert("category", &category);
             }
+            let breadcrumb = site_data.get_category_breadcrumb(video.type_id).await;
+            context.insert("breadcrumb", &breadcrumb);
+            context.insert(
+                "watch_progress_interval",
+                &crate::watch_history::watch_progress_interval(&site_data).await,
+            );
 
-            // 3. Fetch related videos (same category)
-            let find_options = FindOptions::builder()
-                .sort(doc! { "vod_pubdate": -1 })
-                .limit(10)
-                .build();
-
-            let related_videos: Vec<Vod> = match vod_collection
-                .find(
-                    doc! { "type_id": video.type_id, "_id": { "$ne": object_id } },
-                    find_options,
-                )
+            // 3. Fetch related videos
+            let related_count = get_related_count(&site_data, "detail_related_count", 10).await;
+            let related_strategy = site_data
+                .get_config("related_strategy")
                 .await
-            {
-                Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
-                Err(_) => vec![],
-            };
+                .unwrap_or_else(|| "category".to_string());
+            let related_videos =
+                fetch_related_videos(&vod_collection, &video, related_count, related_strategy.trim())
+                    .await;
 
             // Convert related videos dates to timestamps
             let related_timestamps: Vec<i64> = related_videos
                 .iter()
                 .map(|v| v.vod_pubdate.timestamp_millis() / 1000)
                 .collect();
-            context.insert("related_videos", &related_videos);
+            let badges = VodBadges::load(&site_data).await;
+            context.insert("is_hot", &badges.is_hot(&video));
+            context.insert("is_new", &badges.is_new(&video));
+            context.insert("related_videos", &badges.apply(&related_videos));
             context.insert("related_pubdate_timestamps", &related_timestamps);
 
             TERA.render("detail.html", &context)
@@ -571,6 +948,7 @@ pub async fn video_detail_handler(
 
 // Video player page handler
 pub async fn video_player_handler(
+    req: HttpRequest,
     path: web::Path<(String, String)>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
@@ -578,9 +956,9 @@ pub async fn video_player_handler(
     let (vod_id, play_index) = path.into_inner();
 
     // Parse ObjectId from string
-    let object_id = match mongodb::bson::oid::ObjectId::parse_str(&vod_id) {
+    let object_id = match resolve_vod_page_id(&vod_id) {
         Ok(id) => id,
-        Err(_) => return HttpResponse::NotFound().body("Invalid video ID"),
+        Err(response) => return response,
     };
 
     // Parse play index (format: "source-index" or "index")
@@ -598,14 +976,14 @@ pub async fn video_player_handler(
             Ok(idx) => idx,
             Err(_) => return HttpResponse::NotFound().body("Invalid episode index"),
         };
-        (source_idx, episode_idx)
+        (Some(source_idx), episode_idx)
     } else {
-        // Format: "index" (backward compatibility, default to source 0)
+        // Format: "index" (backward compatibility, source resolved after the video is loaded)
         let episode_idx = match play_index.parse() {
             Ok(idx) => idx,
             Err(_) => return HttpResponse::NotFound().body("Invalid play index"),
         };
-        (0, episode_idx)
+        (None, episode_idx)
     };
 
     match with_site_data(
@@ -615,11 +993,16 @@ pub async fn video_player_handler(
             let vod_collection = db.collection::<Vod>("vods");
 
             // 1. Fetch video details and increment hit count
-            let video = match vod_collection.find_one(doc! {"_id": object_id}, None).await {
+            let video = match vod_collection.find_one(doc! {"_id": object_id, "vod_deleted_at": {"$exists": false}}, None).await {
                 Ok(Some(v)) => v,
                 _ => return Err("Video not found".into()),
             };
 
+            // 成人分类需要先确认年龄，未确认时不计入播放次数
+            if !age_confirmed(&req) && site_data.is_adult_category(video.type_id).await {
+                return render_age_gate(context, &site_data, &req.uri().to_string()).await;
+            }
+
             // Increment hit count
             let current_hits = video.vod_hits.unwrap_or(0);
             let current_hits_day = video.vod_hits_day.unwrap_or(0);
@@ -648,6 +1031,15 @@ pub async fn video_player_handler(
             context.insert("vod_pubdate_timestamp", &pubdate_timestamp);
             context.insert("video", &video);
 
+            // Resolve the default source from play_source_priority when none was given
+            let play_source = match play_source {
+                Some(source_idx) => source_idx,
+                None => {
+                    let priority = site_data.get_config("play_source_priority").await;
+                    select_default_play_source(&video, play_idx, priority.as_deref())
+                }
+            };
+
             // 2. Get play URL and episode name
             let (play_url, current_episode_name) =
                 match get_play_info(&video, play_source, play_idx) {
@@ -655,27 +1047,41 @@ pub async fn video_player_handler(
                     Err(e) => return Err(e),
                 };
 
+            // 开启防盗链时输出带签名的 /stream 地址，不暴露原始播放地址
+            let play_url = match (crate::play_token::play_token_ttl(&site_data).await, video.id) {
+                (Some(ttl), Some(vod_id)) => crate::play_token::stream_url(
+                    &vod_id,
+                    play_source,
+                    play_idx,
+                    &play_url,
+                    &client_ip(&req),
+                    ttl,
+                ),
+                _ => play_url,
+            };
+
             context.insert("play_url", &play_url);
             context.insert("play_index", &play_idx);
             context.insert("play_source", &play_source);
             context.insert("current_episode_name", &current_episode_name);
+            context.insert(
+                "watch_progress_interval",
+                &crate::watch_history::watch_progress_interval(&site_data).await,
+            );
 
-            // 3. Get recommended movies (same category, excluding current video)
-            let find_options = FindOptions::builder()
-                .sort(doc! { "vod_pubdate": -1 })
-                .limit(6)
-                .build();
-
-            let recommended_movies: Vec<Vod> = match vod_collection
-                .find(
-                    doc! { "type_id": video.type_id, "_id": { "$ne": object_id } },
-                    find_options,
-                )
+            // 3. Get recommended movies (excluding current video)
+            let recommend_count = get_related_count(&site_data, "player_recommend_count", 6).await;
+            let related_strategy = site_data
+                .get_config("related_strategy")
                 .await
-            {
-                Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
-                Err(_) => vec![],
-            };
+                .unwrap_or_else(|| "category".to_string());
+            let recommended_movies = fetch_related_videos(
+                &vod_collection,
+                &video,
+                recommend_count,
+                related_strategy.trim(),
+            )
+            .await;
 
             // Convert recommended videos dates to timestamps
             let recommended_timestamps: Vec<i64> = recommended_movies
@@ -683,7 +1089,8 @@ pub async fn video_player_handler(
                 .map(|v| v.vod_pubdate.timestamp_millis() / 1000)
                 .collect();
 
-            context.insert("recommended_movies", &recommended_movies);
+            let badges = VodBadges::load(&site_data).await;
+            context.insert("recommended_movies", &badges.apply(&recommended_movies));
             context.insert("recommended_timestamps", &recommended_timestamps);
 
             TERA.render("player.html", &context).map_err(|e| {
@@ -730,6 +1137,7 @@ struct PaginationInfo {
 }
 
 pub async fn list_page_handler(
+    req: HttpRequest,
     path: web::Path<i32>,
     query: web::Query<ListPageParams>,
     db: web::Data<Database>,
@@ -746,6 +1154,11 @@ pub async fn list_page_handler(
                 Some(cat) => cat,
                 None => return Err("Category not found".into()),
             };
+
+            // 成人分类需要先确认年龄
+            if !age_confirmed(&req) && site_data.is_adult_category(type_id).await {
+                return render_age_gate(context, &site_data, &req.uri().to_string()).await;
+            }
             context.insert("category", &main_category);
 
             // Determine the actual category for filtering options (use parent if this is a sub-category)
@@ -791,28 +1204,29 @@ pub async fn list_page_handler(
 
             let vod_collection = db.collection::<Vod>("vods");
 
-            // Build filter for videos
-            let mut filter = doc! {};
-
             // Handle sub_type filtering - if sub_type is provided, use it instead of main type_id
             let mut display_category = main_category.clone();
-            if let Some(sub_type) = query.sub_type {
+            let type_ids = if let Some(sub_type) = query.sub_type {
                 context.insert("current_sub_type", &sub_type);
-                filter.insert("type_id", sub_type);
 
                 // Fetch subcategory info for SEO and display
                 if let Some(sub_cat) = site_data.get_category_by_id(sub_type).await {
                     display_category = sub_cat;
                     context.insert("subcategory", &display_category);
                 }
+                vec![sub_type]
             } else {
                 // If no sub_type is selected, include main category and all its sub-categories
                 let mut type_ids = vec![type_id];
                 for sub_cat in &sub_categories {
                     type_ids.push(sub_cat.type_id);
                 }
-                filter.insert("type_id", doc! { "$in": type_ids });
-            }
+                type_ids
+            };
+
+            // Build filter for videos; vods listing the category in vod_extra_types are included too
+            let mut filter = crate::models::vod_type_filter(&type_ids);
+            crate::models::exclude_deleted_vods(&mut filter);
 
             // Always insert the display category (either main category or subcategory)
             context.insert("display_category", &display_category);
@@ -825,7 +1239,11 @@ pub async fn list_page_handler(
             }
             if let Some(year) = &query.year {
                 if !year.is_empty() {
-                    filter.insert("vod_year", year);
+                    // 能解析出年份时按数值字段筛选，否则按原始字符串匹配
+                    match crate::models::parse_year_num(year) {
+                        Some(year_num) => filter.insert("vod_year_num", year_num),
+                        None => filter.insert("vod_year", year),
+                    };
                     context.insert("current_year", year);
                 }
             }
@@ -850,43 +1268,59 @@ pub async fn list_page_handler(
             // Build sort options based on query parameter
             let sort_doc = match query.sort.as_deref() {
                 Some("hits") => doc! { "vod_hits": -1 },      // Most played
-                Some("score") => doc! { "vod_score": -1 },    // Highest rated
-                Some("year_desc") => doc! { "vod_year": -1 }, // Newest year
-                Some("year_asc") => doc! { "vod_year": 1 },   // Oldest year
+                Some("score") => doc! { "vod_score_num": -1, "vod_pubdate": -1 }, // Highest rated
+                Some("year_desc") => doc! { "vod_year_num": -1, "vod_pubdate": -1 }, // Newest year
+                Some("year_asc") => doc! { "vod_year_num": 1, "vod_pubdate": -1 },   // Oldest year
                 Some("name_asc") => doc! { "vod_name": 1 },   // Name A-Z
                 Some("name_desc") => doc! { "vod_name": -1 }, // Name Z-A
+                Some("trending") => crate::ranking::trending_sort(), // Hits decayed by age
                 _ => doc! { "vod_pubdate": -1 },              // Default: latest published
             };
+            let sort_doc = crate::pagination::stable_sort(sort_doc);
 
             // Fetch videos based on filter with pagination
-            let find_options = FindOptions::builder()
-                .skip(Some(skip as u64))
-                .limit(Some(limit as i64))
-                .sort(sort_doc)
-                .build();
-
-            let vods: Vec<Vod> = match vod_collection.find(filter, find_options).await {
-                Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
-                Err(_) => vec![],
+            let vods: Vec<Vod> = if query.sort.as_deref() == Some("trending") {
+                // 热度分数需在查询时计算，使用聚合管道
+                let gravity = crate::ranking::trending_gravity(&site_data).await;
+                let pipeline = vec![
+                    doc! { "$match": filter },
+                    crate::ranking::trending_score_stage(gravity),
+                    doc! { "$sort": sort_doc },
+                    doc! { "$skip": skip as i64 },
+                    doc! { "$limit": limit as i64 },
+                ];
+                match vod_collection.aggregate(pipeline, None).await {
+                    Ok(cursor) => cursor
+                        .try_collect::<Vec<Document>>()
+                        .await
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|d| mongodb::bson::from_document(d).ok())
+                        .collect(),
+                    Err(_) => vec![],
+                }
+            } else {
+                let find_options = FindOptions::builder()
+                    .skip(Some(skip as u64))
+                    .limit(Some(limit as i64))
+                    .sort(sort_doc)
+                    .build();
+
+                match vod_collection.find(filter, find_options).await {
+                    Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+                    Err(_) => vec![],
+                }
             };
-            context.insert("vods", &vods);
+            let badges = VodBadges::load(&site_data).await;
+            context.insert("vods", &badges.apply(&vods));
 
             // Add total items count to context
             context.insert("total_items", &total_items);
 
             // Add pagination info to context
             if total_pages > 1 {
-                let mut pages = Vec::new();
-                let start_page = if page > 3 { page - 3 } else { 1 };
-                let end_page = if page + 3 < total_pages {
-                    page + 3
-                } else {
-                    total_pages
-                };
-
-                for p in start_page..=end_page {
-                    pages.push(p);
-                }
+                let window = pagination_window(&site_data).await;
+                let pages = build_pagination(page, total_pages, window);
 
                 let pagination = PaginationInfo {
                     current_page: page,
@@ -912,7 +1346,76 @@ pub async fn list_page_handler(
 }
 
 // Search page handler
+// 关键词中含有正则/全文检索语法字符（引号短语、通配符、排除词等）时走正则查询
+fn is_plain_search_keyword(keyword: &str) -> bool {
+    !keyword.chars().any(|c| "\\^$.|?*+()[]{}\"-".contains(c))
+}
+
+// 前台搜索：普通关键词使用全文索引 $text 查询并按相关度排序，search_score 为相关度；
+// 短语/通配符查询、全文索引查不到结果（中文片名不按空格分词，部分匹配只能靠正则）或索引尚未创建时，
+// 回退到按片名/演员/导演的正则查询，search_score 为 None
+// excluded_type_ids 中的分类（未确认年龄时的成人分类）不出现在结果中
+async fn search_vods(db: &Database, keyword: &str, excluded_type_ids: &[i32]) -> (Vec<Vod>, Vec<Option<f64>>) {
+    const SEARCH_LIMIT: i64 = 50;
+
+    if is_plain_search_keyword(keyword) {
+        let find_options = FindOptions::builder()
+            .projection(doc! { "search_score": { "$meta": "textScore" } })
+            .sort(doc! { "search_score": { "$meta": "textScore" }, "vod_pubdate": -1 })
+            .limit(SEARCH_LIMIT)
+            .build();
+        let mut text_filter = doc! { "$text": { "$search": keyword } };
+        crate::models::exclude_deleted_vods(&mut text_filter);
+        crate::models::exclude_vod_types(&mut text_filter, excluded_type_ids);
+        let text_results: Vec<Document> = match db
+            .collection::<Document>("vods")
+            .find(text_filter, find_options)
+            .await
+        {
+            Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+            Err(e) => {
+                eprintln!("Text search failed, falling back to regex: {}", e);
+                vec![]
+            }
+        };
+
+        if !text_results.is_empty() {
+            return text_results
+                .into_iter()
+                .filter_map(|mut doc| {
+                    let score = doc.remove("search_score").and_then(|s| s.as_f64());
+                    let vod = mongodb::bson::from_document::<Vod>(doc).ok()?;
+                    Some((vod, score))
+                })
+                .unzip();
+        }
+    }
+
+    let mut search_filter = doc! {
+        "$or": [
+            { "vod_name": doc! { "$regex": keyword, "$options": "i" } },
+            { "vod_actor": doc! { "$regex": keyword, "$options": "i" } },
+            { "vod_director": doc! { "$regex": keyword, "$options": "i" } }
+        ],
+        "vod_deleted_at": { "$exists": false }
+    };
+    crate::models::exclude_vod_types(&mut search_filter, excluded_type_ids);
+
+    let find_options = FindOptions::builder()
+        .sort(doc! { "vod_pubdate": -1 })
+        .limit(SEARCH_LIMIT)
+        .build();
+
+    let vods: Vec<Vod> = match db.collection::<Vod>("vods").find(search_filter, find_options).await {
+        Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+        Err(_) => vec![],
+    };
+    let scores = vec![None; vods.len()];
+    (vods, scores)
+}
+
 pub async fn search_page_handler(
+    req: HttpRequest,
     query: web::Query<crate::dto::ApiParams>,
     db: web::Data<Database>,
     site_data_manager: web::Data<SiteDataManager>,
@@ -920,31 +1423,29 @@ pub async fn search_page_handler(
     match with_site_data(
         db.clone(),
         site_data_manager.clone(),
-        |mut context, _site_data| async move {
-            let vod_collection = db.collection::<Vod>("vods");
-            let search_results = if let Some(ref keyword) = query.wd {
-                let search_filter = doc! {
-                    "$or": [
-                        { "vod_name": doc! { "$regex": keyword, "$options": "i" } },
-                        { "vod_actor": doc! { "$regex": keyword, "$options": "i" } },
-                        { "vod_director": doc! { "$regex": keyword, "$options": "i" } }
-                    ]
-                };
-
-                let find_options = FindOptions::builder()
-                    .sort(doc! { "vod_pubdate": -1 })
-                    .limit(50)
-                    .build();
-
-                match vod_collection.find(search_filter, find_options).await {
-                    Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
-                    Err(_) => vec![],
-                }
+        |mut context, site_data| async move {
+            let keyword = query.wd.as_deref().map(str::trim).unwrap_or("");
+            // 空关键词直接返回空列表，不查询数据库
+            let (search_results, search_scores) = if keyword.is_empty() {
+                (vec![], vec![])
             } else {
-                vec![]
+                // 未通过年龄确认的访客搜索不到成人分类的视频
+                let adult_ids = if age_confirmed(&req) {
+                    vec![]
+                } else {
+                    site_data.get_adult_type_ids().await
+                };
+                search_vods(&db, keyword, &adult_ids).await
             };
 
-            context.insert("search_results", &search_results);
+            let badges = VodBadges::load(&site_data).await;
+            let mut results = badges.apply(&search_results);
+            for (result, score) in results.iter_mut().zip(&search_scores) {
+                if let Some(obj) = result.as_object_mut() {
+                    obj.insert("search_score".to_string(), (*score).into());
+                }
+            }
+            context.insert("search_results", &results);
             context.insert("search_keyword", &query.wd);
 
             TERA.render("search.html", &context)
@@ -975,12 +1476,75 @@ pub async fn search_page_handler(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AgeGateForm {
+    return_to: Option<String>,
+}
+
+// POST /age-gate: 记录年龄确认并跳回原页面
+pub async fn age_gate_confirm(form: web::Form<AgeGateForm>) -> impl Responder {
+    // 只允许站内相对路径，避免被用作开放重定向
+    let return_to = form
+        .return_to
+        .as_deref()
+        .filter(|p| p.starts_with('/') && !p.starts_with("//") && !p.starts_with("/\\"))
+        .unwrap_or("/");
+
+    let cookie = Cookie::build(AGE_GATE_COOKIE, "1")
+        .path("/")
+        .max_age(CookieDuration::days(AGE_GATE_COOKIE_DAYS))
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .finish();
+
+    HttpResponse::SeeOther()
+        .cookie(cookie)
+        .append_header(("Location", return_to))
+        .finish()
+}
+
+// GET /stream/{token}: 校验播放 token 后跳转到真实播放地址（protect_play_urls）
+pub async fn stream_redirect(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let (vod_id, play_source, play_idx) =
+        match crate::play_token::verify_token(&path.into_inner(), &client_ip(&req)) {
+            Some(target) => target,
+            None => return HttpResponse::Forbidden().body("Invalid or expired play token"),
+        };
+
+    let video = match db
+        .collection::<Vod>("vods")
+        .find_one(doc! {"_id": vod_id, "vod_status": 1, "vod_deleted_at": {"$exists": false}}, None)
+        .await
+    {
+        Ok(Some(v)) => v,
+        Ok(None) => return HttpResponse::NotFound().body("Video not found"),
+        Err(e) => {
+            eprintln!("Stream lookup failed: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to fetch video");
+        }
+    };
+
+    match get_play_info(&video, play_source, play_idx) {
+        Ok((play_url, _)) => HttpResponse::Found()
+            .append_header(("Location", play_url))
+            .append_header(("Cache-Control", "no-store"))
+            .finish(),
+        Err(_) => HttpResponse::NotFound().body("Play URL not found"),
+    }
+}
+
 // --- Admin Web Handlers ---
 
 #[derive(Debug, Deserialize)]
 pub struct LoginForm {
     username: String,
     password: String,
+    #[serde(rename = "remember-me")]
+    remember_me: Option<String>, // checkbox, present ("on") when checked
 }
 
 pub async fn login_page() -> impl Responder {
@@ -1000,15 +1564,30 @@ pub async fn login_page() -> impl Responder {
 }
 
 pub async fn login_post(
+    req: HttpRequest,
     db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
     form: web::Form<LoginForm>,
     session: Session,
 ) -> impl Responder {
-    println!(
-        "[DEBUG] Login attempt - Username: '{}', Password length: {}",
-        form.username,
-        form.password.len()
-    );
+    // 尝试次数过多时直接拒绝，不查询用户也不校验密码；未超过时先计入本次尝试
+    let ip = crate::client_ip::client_ip(&req);
+    let lockout = LockoutSettings::load(&site_data_manager).await;
+    if let Err(wait) = login_lockout::reserve_attempt(lockout, &form.username, &ip).await {
+        // 登录页直接显示错误，不再发送 flash，否则下次打开页面时会重复显示
+        let mut context = tera::Context::new();
+        context.insert("error", login_lockout::LOCKOUT_MESSAGE);
+        let body = TERA
+            .render("admin/login.html", &context)
+            .unwrap_or_else(|_| login_lockout::LOCKOUT_MESSAGE.to_string());
+        return HttpResponse::TooManyRequests()
+            .insert_header((
+                actix_web::http::header::RETRY_AFTER,
+                login_lockout::retry_after_secs(wait),
+            ))
+            .content_type("text/html")
+            .body(body);
+    }
 
     let user_collection = db.collection::<User>("users");
 
@@ -1016,15 +1595,8 @@ pub async fn login_post(
         .find_one(doc! {"user_name": &form.username}, None)
         .await
     {
-        Ok(Some(u)) => {
-            println!("[DEBUG] User found in database: {}", u.user_name);
-            u
-        }
+        Ok(Some(u)) => u,
         Ok(None) => {
-            println!(
-                "[DEBUG] User not found in database for username: {}",
-                form.username
-            );
             FlashMessage::error("Invalid username or password.").send();
             return HttpResponse::Found()
                 .append_header(("Location", "/admin/login"))
@@ -1039,21 +1611,16 @@ pub async fn login_post(
         }
     };
 
-    println!("[DEBUG] Stored password hash: {}", user.user_pwd);
     let password_valid = bcrypt::verify(&form.password, &user.user_pwd).unwrap_or(false);
-    println!("[DEBUG] Password verification result: {}", password_valid);
 
     if password_valid {
+        login_lockout::clear(&form.username, &ip).await;
         let user_id_str = user.id.unwrap().to_string();
-        println!("[DEBUG] Setting session user_id: {}", user_id_str);
 
-        match session.insert("user_id", user_id_str) {
-            Ok(_) => {
-                println!("[DEBUG] Session set successfully, redirecting to /admin");
-                HttpResponse::Found()
-                    .append_header(("Location", "/admin"))
-                    .finish()
-            }
+        match crate::auth::start_session(&session, user_id_str, form.remember_me.is_some()) {
+            Ok(_) => HttpResponse::Found()
+                .append_header(("Location", "/admin"))
+                .finish(),
             Err(e) => {
                 println!("[DEBUG] Failed to set session: {}", e);
                 FlashMessage::error("Login failed due to session error.").send();
@@ -1063,7 +1630,6 @@ pub async fn login_post(
             }
         }
     } else {
-        println!("[DEBUG] Password verification failed, redirecting back to login");
         FlashMessage::error("Invalid username or password.").send();
         HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
@@ -1071,8 +1637,12 @@ pub async fn login_post(
     }
 }
 
-pub async fn admin_dashboard(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+pub async fn admin_dashboard(
+    session: Session,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<SiteDataManager>,
+) -> impl Responder {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1081,67 +1651,15 @@ pub async fn admin_dashboard(session: Session, db: web::Data<Database>) -> impl
     let mut context = tera::Context::new();
     context.insert("SITENAME", "maccms-rust");
 
-    // 获取统计数据
-    let mut total_videos = 0;
-    let mut total_categories = 0;
-    let mut total_collections = 0;
-    let mut total_configs = 0;
-    let mut total_bindings = 0;
-    let mut total_users = 0;
-
-    // 获取视频数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("vods")
-        .count_documents(None, None)
-        .await
-    {
-        total_videos = count as i32;
-    }
-
-    // 获取分类数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("types")
-        .count_documents(None, None)
-        .await
-    {
-        total_categories = count as i32;
-    }
-
-    // 获取采集源数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("collections")
-        .count_documents(None, None)
-        .await
-    {
-        total_collections = count as i32;
-    }
-
-    // 获取配置数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("configs")
-        .count_documents(None, None)
-        .await
-    {
-        total_configs = count as i32;
-    }
-
-    // 获取绑定数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("bindings")
-        .count_documents(None, None)
-        .await
-    {
-        total_bindings = count as i32;
-    }
-
-    // 获取用户数量
-    if let Ok(count) = db
-        .collection::<mongodb::bson::Document>("users")
-        .count_documents(None, None)
-        .await
-    {
-        total_users = count as i32;
-    }
+    // 获取统计数据（计数缓存 stats_cache_ttl 秒）
+    let ttl = cache::count_cache_ttl(&site_data_manager).await;
+    let counts = cache::SiteCounts::load(&db, ttl).await;
+    let total_videos = counts.vods as i32;
+    let total_categories = counts.types as i32;
+    let total_collections = counts.collections as i32;
+    let total_configs = counts.configs as i32;
+    let total_bindings = counts.bindings as i32;
+    let total_users = counts.users as i32;
 
     // 插入统计数据到模板上下文
     context.insert("total_videos", &total_videos);
@@ -1178,7 +1696,7 @@ pub async fn logout(session: Session) -> impl Responder {
 }
 
 pub async fn admin_types_page(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1210,7 +1728,7 @@ pub async fn admin_types_page(session: Session, db: web::Data<Database>) -> impl
 
 pub async fn init_data_handler(session: Session, db: web::Data<Database>) -> impl Responder {
     // Check if user is logged in
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1233,29 +1751,44 @@ pub async fn init_data_handler(session: Session, db: web::Data<Database>) -> imp
     }
 }
 
-pub async fn admin_vods_page(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+pub async fn admin_vods_page(
+    session: Session,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<crate::site_data::SiteDataManager>,
+    query: web::Query<crate::admin_handlers::VodsQuery>,
+) -> impl Responder {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
     }
 
-    let vod_collection = db.collection::<Vod>("vods");
-    let find_options = FindOptions::builder()
-        .sort(doc! {"vod_pubdate": -1})
-        .limit(50)
-        .build();
-    let vods: Vec<Vod> = match vod_collection.find(None, find_options).await {
-        Ok(cursor) => cursor.try_collect().await.unwrap_or_else(|_| vec![]),
+    // 与 /api/admin/vods 使用同一查询，筛选/排序/分页参数来自地址栏，收藏或分享的筛选链接打开即生效
+    let initial_data = match crate::admin_handlers::query_admin_vods(&db, &site_data_manager, &query).await {
+        Ok(data) => data,
         Err(e) => {
             eprintln!("Failed to fetch vods: {}", e);
-            vec![]
+            serde_json::json!({"code": 0, "msg": "Failed to fetch videos"})
         }
     };
 
     let mut context = tera::Context::new();
     context.insert("SITENAME", "maccms-rust");
-    context.insert("vods", &vods);
+    // 嵌入 <script> 中，转义 </ 防止视频名称等内容提前结束脚本
+    context.insert("initial_data", &initial_data.to_string().replace("</", "<\\/"));
+    context.insert(
+        "task_poll",
+        &crate::task_poll::task_poll_settings(&site_data_manager).await,
+    );
+    context.insert(
+        "filters",
+        &serde_json::json!({
+            "search": query.search.clone().unwrap_or_default(),
+            "type_id": query.type_id,
+            "status": query.status,
+            "sort": query.sort.clone().unwrap_or_default(),
+        }),
+    );
 
     match TERA.render("admin/vods.html", &context) {
         Ok(s) => HttpResponse::Ok().content_type("text/html").body(s),
@@ -1271,8 +1804,12 @@ pub async fn admin_vods_page(session: Session, db: web::Data<Database>) -> impl
     }
 }
 
-pub async fn admin_collect_page(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+pub async fn admin_collect_page(
+    session: Session,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<crate::site_data::SiteDataManager>,
+) -> impl Responder {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1287,16 +1824,28 @@ pub async fn admin_collect_page(session: Session, db: web::Data<Database>) -> im
             }
         };
 
+    // 首屏直接带上运行中的采集任务，页面脚本之后按原有方式轮询刷新
+    let running_tasks = crate::collect_handlers::get_all_running_tasks().await;
+
     let mut context = tera::Context::new();
     context.insert("SITENAME", "maccms-rust");
     context.insert("collections", &collections);
+    context.insert("running_tasks", &running_tasks);
+    context.insert(
+        "task_poll",
+        &crate::task_poll::task_poll_settings(&site_data_manager).await,
+    );
 
     // println!("collections: {:?}", collections);
 
     match TERA.render("admin/collect.html", &context) {
         Ok(s) => HttpResponse::Ok().content_type("text/html").body(s),
         Err(e) => {
-            let context_variables = format!("collections count: {}", collections.len());
+            let context_variables = format!(
+                "collections count: {}, running tasks: {}",
+                collections.len(),
+                running_tasks.len()
+            );
             
             handle_template_rendering_error(
                 "admin/collect.html",
@@ -1310,7 +1859,7 @@ pub async fn admin_collect_page(session: Session, db: web::Data<Database>) -> im
 }
 
 pub async fn admin_bindings_page(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1346,7 +1895,7 @@ pub async fn admin_bindings_page(session: Session, db: web::Data<Database>) -> i
 }
 
 pub async fn admin_config_page(session: Session, db: web::Data<Database>) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1381,9 +1930,13 @@ pub async fn admin_config_page(session: Session, db: web::Data<Database>) -> imp
     }
 }
 
-pub async fn admin_collect_vod_page(session: Session, db: web::Data<Database>) -> impl Responder {
+pub async fn admin_collect_vod_page(
+    session: Session,
+    db: web::Data<Database>,
+    site_data_manager: web::Data<crate::site_data::SiteDataManager>,
+) -> impl Responder {
     // Check if user is logged in
-    if session.get::<String>("user_id").unwrap_or(None).is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1415,6 +1968,10 @@ pub async fn admin_collect_vod_page(session: Session, db: web::Data<Database>) -
         Err(_) => vec![],
     };
     context.insert("types", &types);
+    context.insert(
+        "task_poll",
+        &crate::task_poll::task_poll_settings(&site_data_manager).await,
+    );
 
     match TERA.render("admin/collect_vod.html", &context) {
         Ok(rendered) => HttpResponse::Ok().content_type("text/html").body(rendered),
@@ -1436,7 +1993,7 @@ pub async fn admin_collect_vod_page(session: Session, db: web::Data<Database>) -
 }
 
 pub async fn admin_indexes_page(session: Session) -> impl Responder {
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Found()
             .append_header(("Location", "/admin/login"))
             .finish();
@@ -1465,24 +2022,35 @@ pub async fn refresh_cache_handler(
     site_data_manager: web::Data<SiteDataManager>,
 ) -> impl Responder {
     // 检查用户是否登录
-    if session.get::<String>("user_id").ok().flatten().is_none() {
+    if crate::auth::session_user_id(&session).is_none() {
         return HttpResponse::Unauthorized().json(serde_json::json!({
             "success": false,
             "message": "未登录或会话已过期"
         }));
     }
 
+    // 同时清除页面与计数缓存（即 /api/admin/cache/purge 的 "all"）
+    cache::purge(cache::SCOPE_HOME, None).await;
+    cache::purge(cache::SCOPE_COUNTS, None).await;
+    cache::purge(cache::SCOPE_SITEMAP, None).await;
+
+    // 刷新前站点数据缓存已存在的秒数（自动刷新后的数据最多存在 cache_ttl_seconds 秒）
+    let cache_age = site_data_manager.cache_age().await.as_secs();
     match site_data_manager.refresh().await {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
             "success": true,
             "message": "缓存刷新成功",
-            "timestamp": chrono::Utc::now().timestamp()
+            "timestamp": chrono::Utc::now().timestamp(),
+            "cache_age_seconds": cache_age,
+            "last_refreshed": site_data_manager.last_refreshed().await.to_rfc3339()
         })),
         Err(e) => {
             eprintln!("Cache refresh failed: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
                 "success": false,
-                "message": format!("缓存刷新失败: {}", e)
+                "message": format!("缓存刷新失败: {}", e),
+                "cache_age_seconds": cache_age,
+                "last_refreshed": site_data_manager.last_refreshed().await.to_rfc3339()
             }))
         }
     }
@@ -1518,3 +2086,102 @@ pub async fn user_profile_page(
         }
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TemplatePreviewRequest {
+    pub template_name: String,
+    #[serde(default)]
+    pub context_json: serde_json::Value,
+}
+
+// 模板预览：用提交的示例数据渲染 templates/ 下的指定模板，便于主题开发时快速调试。
+// 每次预览都从磁盘重新加载模板，修改后无需重启；示例数据会覆盖同名的全局变量（SITENAME 等）。
+// 渲染失败时返回与服务器日志相同的详细诊断信息。
+pub async fn preview_template(
+    session: Session,
+    site_data_manager: web::Data<SiteDataManager>,
+    req: web::Json<TemplatePreviewRequest>,
+) -> impl Responder {
+    if crate::auth::session_user_id(&session).is_none() {
+        return HttpResponse::Unauthorized().json(serde_json::json!({
+            "success": false,
+            "message": "未登录或会话已过期"
+        }));
+    }
+
+    // 只接受 templates/ 目录内的相对路径，如 "list.html"、"admin/types.html"
+    let template_name = req.template_name.trim();
+    let traversal = template_name.is_empty()
+        || template_name.starts_with('/')
+        || template_name.contains('\\')
+        || template_name.split('/').any(|part| part.is_empty() || part == "." || part == "..");
+    if traversal || !template_name.ends_with(".html") {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "message": "无效的模板名称，应为 templates 目录下的 .html 文件"
+        }));
+    }
+
+    let sample_context = match &req.context_json {
+        serde_json::Value::Null => tera::Context::new(),
+        value @ serde_json::Value::Object(_) => match tera::Context::from_value(value.clone()) {
+            Ok(context) => context,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "success": false,
+                    "message": format!("context_json 无效: {}", e)
+                }));
+            }
+        },
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "success": false,
+                "message": "context_json 必须是 JSON 对象"
+            }));
+        }
+    };
+
+    let tera = match crate::template::load_templates() {
+        Ok(tera) => tera,
+        Err(e) => {
+            let report = template_error_report(template_name, &e, Some("Template preview (parse)"), None);
+            eprint!("{}", report);
+            return HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "success": false,
+                "message": "模板解析失败",
+                "error": report
+            }));
+        }
+    };
+
+    if !tera.get_template_names().any(|name| name == template_name) {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "success": false,
+            "message": format!("模板不存在: {}", template_name)
+        }));
+    }
+
+    let mut context = site_context(&site_data_manager).await;
+    context.extend(sample_context);
+
+    match tera.render(template_name, &context) {
+        Ok(html) => HttpResponse::Ok().content_type("text/html; charset=utf-8").body(html),
+        Err(e) => {
+            let variables = req.context_json.as_object().map(|obj| {
+                obj.keys().cloned().collect::<Vec<_>>().join(", ")
+            });
+            let report = template_error_report(
+                template_name,
+                &e,
+                Some("Template preview"),
+                variables.as_deref(),
+            );
+            eprint!("{}", report);
+            HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "success": false,
+                "message": "模板渲染失败",
+                "error": report
+            }))
+        }
+    }
+}